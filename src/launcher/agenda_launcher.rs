@@ -0,0 +1,423 @@
+use chrono::{
+    DateTime, Days, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone,
+};
+use gpui::SharedString;
+use rusqlite::{Connection, OpenFlags, params};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::launcher::Launcher;
+use crate::launcher::children::RenderableChild;
+use crate::loader::utils::{AppData, construct_search};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+
+/// Where the events are read from
+#[derive(Clone, Debug)]
+pub enum AgendaSource {
+    /// ICS files and directories of them, like the vdirs of vdirsyncer
+    Ics(Vec<PathBuf>),
+    /// The database of khal, which has its recurring events expanded already
+    Khal(PathBuf),
+}
+
+/// Today's and upcoming events of local calendars
+#[derive(Clone, Debug)]
+pub struct AgendaLauncher {
+    pub source: AgendaSource,
+    /// Days ahead of today that are listed
+    pub days: u64,
+    /// Calendar client that opens an event, with `{date}` and `{file}` in place of its day and
+    /// ICS file
+    pub client: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AgendaEvent {
+    pub summary: String,
+    pub location: String,
+    /// Local wall clock times
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub all_day: bool,
+    pub file: Option<PathBuf>,
+}
+
+impl AgendaLauncher {
+    pub const DEFAULT_DAYS: u64 = 7;
+    pub const DEFAULT_KHAL_DATABASE: &str = "~/.local/share/khal/khal.db";
+
+    /// Events that did not end yet and start before the last listed day ends, sorted by start
+    pub fn events(&self, now: NaiveDateTime) -> Result<Vec<AgendaEvent>, SherlockError> {
+        let until = now
+            .date()
+            .checked_add_days(Days::new(self.days + 1))
+            .unwrap_or(now.date())
+            .and_time(NaiveTime::MIN);
+        let mut events = match &self.source {
+            AgendaSource::Ics(paths) => ics_events(paths, now, until),
+            AgendaSource::Khal(database) => khal_events(database, now, until)?,
+        };
+        events.retain(|event| event.end > now && event.start < until);
+        events.sort_by_key(|event| event.start);
+        Ok(events)
+    }
+
+    /// Tiles of the events in the order they take place
+    pub fn children(
+        &self,
+        launcher: &Arc<Launcher>,
+        events: &[AgendaEvent],
+        today: NaiveDate,
+    ) -> Vec<RenderableChild> {
+        events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let mut inner = self.app_data(event, today);
+                inner.priority = Some(launcher.priority as f32 + i as f32 / events.len() as f32);
+                RenderableChild::AppLike {
+                    launcher: Arc::clone(launcher),
+                    inner,
+                }
+            })
+            .collect()
+    }
+
+    fn app_data(&self, event: &AgendaEvent, today: NaiveDate) -> AppData {
+        let file = event
+            .file
+            .as_ref()
+            .map(|file| file.to_string_lossy().replace('\'', r"'\''"))
+            .unwrap_or_default();
+        let mut inner = AppData {
+            name: Some(SharedString::from(event.summary.clone())),
+            generic_name: Some(SharedString::from(
+                [when(event, today), event.location.clone()]
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" · "),
+            )),
+            search_string: construct_search(Some(&event.summary), &event.location, true),
+            exec: Some(
+                self.client
+                    .replace("{date}", &event.start.format("%Y-%m-%d").to_string())
+                    .replace("{file}", &file),
+            ),
+            ..AppData::new()
+        };
+        inner.defer_icon("x-office-calendar");
+        inner
+    }
+}
+
+/// The start of the event, like `Today 14:00–15:00` or `Fri 6 Jun, all day`
+fn when(event: &AgendaEvent, today: NaiveDate) -> String {
+    let day = event.start.date();
+    let day = match (day - today).num_days() {
+        ..=0 if event.all_day && event.end.date() > today => String::from("Today"),
+        0 => String::from("Today"),
+        1 => String::from("Tomorrow"),
+        _ => day.format("%a %-d %b").to_string(),
+    };
+    if event.all_day {
+        return format!("{day}, all day");
+    }
+    let start = event.start.format("%H:%M");
+    if event.end.date() == event.start.date() && event.end > event.start {
+        format!("{day} {start}–{}", event.end.format("%H:%M"))
+    } else {
+        format!("{day} {start}")
+    }
+}
+
+fn ics_events(paths: &[PathBuf], now: NaiveDateTime, until: NaiveDateTime) -> Vec<AgendaEvent> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let pattern = path.join("**").join("*.ics");
+            files.extend(
+                glob::glob(&pattern.to_string_lossy())
+                    .into_iter()
+                    .flatten()
+                    .flatten(),
+            );
+        } else {
+            files.push(path.clone());
+        }
+    }
+    let mut events = Vec::new();
+    for file in files {
+        let content = match std::fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) => {
+                let e = sherlock_error!(SherlockErrorType::FileReadError(file), e.to_string());
+                eprintln!("{e}");
+                continue;
+            }
+        };
+        for (event, rule) in parse_calendar(&content) {
+            let duration = event.end - event.start;
+            for start in occurrences(event.start, rule.as_deref(), now - duration, until) {
+                events.push(AgendaEvent {
+                    start,
+                    end: start + duration,
+                    file: Some(file.clone()),
+                    ..event.clone()
+                });
+            }
+        }
+    }
+    events
+}
+
+fn khal_events(
+    database: &Path,
+    now: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Result<Vec<AgendaEvent>, SherlockError> {
+    let error = |e: rusqlite::Error| {
+        sherlock_error!(
+            SherlockErrorType::SqlConnectionError(),
+            format!("{}: {e}", database.display())
+        )
+    };
+    let conn =
+        Connection::open_with_flags(database, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(error)?;
+    // Floating times are stored as if they were UTC, a day of margin covers every offset
+    let from = now.and_utc().timestamp() - 86_400;
+    let until = until.and_utc().timestamp() + 86_400;
+    let mut stmt = conn
+        .prepare(
+            "SELECT r.dtstart, r.dtend, e.item, 0 FROM recs_loc r
+                JOIN events e ON r.href = e.href AND r.calendar = e.calendar
+                WHERE r.dtend > ?1 AND r.dtstart < ?2
+            UNION ALL
+            SELECT r.dtstart, r.dtend, e.item, 1 FROM recs_float r
+                JOIN events e ON r.href = e.href AND r.calendar = e.calendar
+                WHERE r.dtend > ?1 AND r.dtstart < ?2",
+        )
+        .map_err(error)?;
+    let rows = stmt
+        .query_map(params![from, until], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, bool>(3)?,
+            ))
+        })
+        .map_err(error)?;
+
+    let wall_clock = |timestamp: i64, floating: bool| {
+        let time = DateTime::from_timestamp(timestamp, 0)?;
+        Some(match floating {
+            true => time.naive_utc(),
+            false => time.with_timezone(&Local).naive_local(),
+        })
+    };
+    let mut events = Vec::new();
+    for (start, end, item, floating) in rows.flatten() {
+        let (Some(start), Some(end)) = (wall_clock(start, floating), wall_clock(end, floating))
+        else {
+            continue;
+        };
+        // Summary and location are only part of the event itself
+        let Some((event, _)) = parse_calendar(&item).into_iter().next() else {
+            continue;
+        };
+        events.push(AgendaEvent {
+            start,
+            end,
+            ..event
+        });
+    }
+    Ok(events)
+}
+
+/// Events of an ICS document along with their recurrence rule. Cancelled events are left out.
+fn parse_calendar(content: &str) -> Vec<(AgendaEvent, Option<String>)> {
+    // Lines starting with a space or tab continue the previous one
+    let unfolded = content
+        .replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+    let mut events = Vec::new();
+    let mut current: Option<(AgendaEvent, Option<String>)> = None;
+    let mut end: Option<NaiveDateTime> = None;
+    let mut cancelled = false;
+    // Alarms inside of events have properties of their own
+    let mut nested = 0;
+    for line in unfolded.lines() {
+        let Some((property, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut params = property.split(';');
+        let name = params.next().unwrap_or_default().to_uppercase();
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => {
+                current = Some((AgendaEvent::default(), None));
+                (end, cancelled, nested) = (None, false, 0);
+            }
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", "VEVENT") => {
+                let Some((mut event, rule)) = current.take() else {
+                    continue;
+                };
+                if cancelled || event.start == NaiveDateTime::default() {
+                    continue;
+                }
+                // Without an end, all day events last their day and others no time at all
+                event.end = match (end, event.all_day) {
+                    (Some(end), _) => end,
+                    (None, true) => event.start + TimeDelta::days(1),
+                    (None, false) => event.start,
+                };
+                events.push((event, rule));
+            }
+            ("END", _) if nested > 0 => nested -= 1,
+            _ if nested > 0 => {}
+            (name, value) => {
+                let Some((event, rule)) = current.as_mut() else {
+                    continue;
+                };
+                match name {
+                    "SUMMARY" => event.summary = unescape(value),
+                    "LOCATION" => event.location = unescape(value),
+                    "STATUS" => cancelled = value.eq_ignore_ascii_case("cancelled"),
+                    "RRULE" => *rule = Some(value.to_string()),
+                    "DTSTART" => {
+                        if let Some((start, all_day)) = parse_time(value) {
+                            (event.start, event.all_day) = (start, all_day);
+                        }
+                    }
+                    "DTEND" => end = parse_time(value).map(|(end, _)| end),
+                    _ => {}
+                }
+            }
+        }
+    }
+    events
+}
+
+/// A date or date time as local wall clock time, along with whether it is a date. Times in
+/// other time zones than UTC are taken as local.
+fn parse_time(value: &str) -> Option<(NaiveDateTime, bool)> {
+    let value = value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((date.and_time(NaiveTime::MIN), true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        let local = Local.from_utc_datetime(&time).naive_local();
+        return Some((local, false));
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((time, false))
+}
+
+/// Starts of an event from `from` to `until`. Rules are followed by their frequency, interval,
+/// count and end, rules with parts like `BYDAY` only yield the first occurrence.
+fn occurrences(
+    start: NaiveDateTime,
+    rule: Option<&str>,
+    from: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Vec<NaiveDateTime> {
+    let parts: Vec<(&str, &str)> = rule
+        .unwrap_or_default()
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .collect();
+    let part = |key: &str| parts.iter().find(|(name, _)| *name == key).map(|(_, v)| *v);
+    let frequency = part("FREQ").filter(|_| !parts.iter().any(|(key, _)| key.starts_with("BY")));
+    let Some(frequency) = frequency else {
+        return [start]
+            .into_iter()
+            .filter(|start| (from..until).contains(start))
+            .collect();
+    };
+    let interval: u32 = part("INTERVAL")
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1)
+        .max(1);
+    let count: Option<usize> = part("COUNT").and_then(|n| n.parse().ok());
+    let last = part("UNTIL")
+        .and_then(parse_time)
+        .map_or(until, |(last, _)| last.min(until));
+
+    let mut found = Vec::new();
+    let mut current = Some(start);
+    let mut n = 0;
+    while let Some(time) = current
+        && time <= last
+        && count.is_none_or(|count| n < count)
+    {
+        if time >= from && time < until {
+            found.push(time);
+        }
+        n += 1;
+        // Monthly and yearly steps count from the first start, so the 31st stays the 31st
+        current = match frequency {
+            "DAILY" => time.checked_add_days(Days::new(interval.into())),
+            "WEEKLY" => time.checked_add_days(Days::new(7 * u64::from(interval))),
+            "MONTHLY" => start.checked_add_months(Months::new(n as u32 * interval)),
+            "YEARLY" => start.checked_add_months(Months::new(n as u32 * interval * 12)),
+            _ => None,
+        };
+    }
+    found
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .trim()
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+#[test]
+fn test_parse_calendar() {
+    let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Stand-up\r\nLOCATION:Room 2\\, first\r\n  \
+floor\r\nDTSTART;TZID=Europe/Berlin:20250602T093000\r\nDTEND;TZID=Europe/Berlin:20250602T094500\r\n\
+RRULE:FREQ=WEEKLY;COUNT=3\r\nBEGIN:VALARM\r\nSUMMARY:Reminder\r\nEND:VALARM\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20250609\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nSUMMARY:Cancelled\r\nSTATUS:CANCELLED\r\nDTSTART:20250609T100000\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+    let events = parse_calendar(ics);
+    assert_eq!(events.len(), 2);
+    let (standup, rule) = &events[0];
+    assert_eq!(standup.summary, "Stand-up");
+    assert_eq!(standup.location, "Room 2, first floor");
+
+    let time = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+    let starts = occurrences(
+        standup.start,
+        rule.as_deref(),
+        time("2025-06-05 00:00"),
+        time("2025-06-30 00:00"),
+    );
+    // The third and last one is on the 16th
+    assert_eq!(
+        starts,
+        vec![time("2025-06-09 09:30"), time("2025-06-16 09:30")]
+    );
+
+    let today = NaiveDate::from_ymd_opt(2025, 6, 9).unwrap();
+    let standup = AgendaEvent {
+        start: starts[0],
+        end: starts[0] + (standup.end - standup.start),
+        ..standup.clone()
+    };
+    assert_eq!(when(&standup, today), "Today 09:30–09:45");
+    assert_eq!(when(&events[1].0, today), "Today, all day");
+    assert_eq!(
+        when(&events[1].0, today - TimeDelta::days(3)),
+        "Mon 9 Jun, all day"
+    );
+}
@@ -0,0 +1,219 @@
+use gpui::SharedString;
+use serde::Deserialize;
+use serde_json::Value;
+use std::process::Command;
+
+use crate::loader::utils::AppData;
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+
+/// Whether a device plays or records audio
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceKind {
+    #[serde(alias = "output")]
+    Sink,
+    #[serde(alias = "input")]
+    Source,
+}
+impl DeviceKind {
+    /// Name of the object type in `pactl` commands
+    fn noun(self) -> &'static str {
+        match self {
+            Self::Sink => "sink",
+            Self::Source => "source",
+        }
+    }
+    /// Streams that are routed to devices of this kind
+    fn streams(self) -> &'static str {
+        match self {
+            Self::Sink => "sink-input",
+            Self::Source => "source-output",
+        }
+    }
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Sink => "Output",
+            Self::Source => "Input",
+        }
+    }
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Sink => "audio-speakers",
+            Self::Source => "audio-input-microphone",
+        }
+    }
+}
+
+/// Lists the output and input devices and makes the selected one the default. PipeWire is
+/// reached through its PulseAudio server, so `pactl` covers both.
+#[derive(Clone, Debug)]
+pub struct AudioDeviceLauncher {
+    pub kinds: Vec<DeviceKind>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioDevice {
+    pub kind: DeviceKind,
+    pub name: String,
+    pub description: String,
+    pub is_default: bool,
+}
+impl AudioDevice {
+    /// The device as it is stored in the `exec` of its tile, e.g. `sink:alsa_output.pci`
+    pub fn exec(&self) -> String {
+        format!("{}:{}", self.kind.noun(), self.name)
+    }
+    /// Reverses [`AudioDevice::exec`]
+    pub fn parse_exec(exec: &str) -> Option<(DeviceKind, &str)> {
+        match exec.split_once(':')? {
+            ("sink", name) => Some((DeviceKind::Sink, name)),
+            ("source", name) => Some((DeviceKind::Source, name)),
+            _ => None,
+        }
+    }
+}
+
+/// Runs the command and returns its output, failed commands are reported with their stderr
+pub(super) fn command_output(program: &str, args: &[&str]) -> Result<String, SherlockError> {
+    let mut command = Command::new(program);
+    command.args(args);
+    let output = command.output().map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::CommandExecutionError(format!("{command:?}")),
+            e.to_string()
+        )
+    })?;
+    if !output.status.success() {
+        return Err(sherlock_error!(
+            SherlockErrorType::CommandExecutionError(format!("{command:?}")),
+            String::from_utf8_lossy(&output.stderr).trim().to_string()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn pactl(args: &[&str]) -> Result<String, SherlockError> {
+    command_output("pactl", args)
+}
+
+impl AudioDeviceLauncher {
+    pub fn devices(&self) -> Result<Vec<AudioDevice>, SherlockError> {
+        let mut devices = Vec::new();
+        for kind in &self.kinds {
+            let default = pactl(&[&format!("get-default-{}", kind.noun())])?;
+            let list = pactl(&["--format=json", "list", &format!("{}s", kind.noun())])?;
+            let json: Value = serde_json::from_str(&list).map_err(|e| {
+                sherlock_error!(
+                    SherlockErrorType::DeserializationError,
+                    format!("pactl list {}s: {e}", kind.noun())
+                )
+            })?;
+            devices.extend(parse_devices(*kind, &json, default.trim()));
+        }
+        Ok(devices)
+    }
+
+    /// Makes the device the default and moves the streams that are currently playing or
+    /// recording over to it. Streams that cannot be moved are logged and stay where they are.
+    pub fn set_default(kind: DeviceKind, name: &str) -> Result<(), SherlockError> {
+        pactl(&[&format!("set-default-{}", kind.noun()), name])?;
+        let streams = pactl(&["list", "short", &format!("{}s", kind.streams())])?;
+        for id in stream_ids(&streams) {
+            if let Err(e) = pactl(&[&format!("move-{}", kind.streams()), id, name]) {
+                eprintln!("{e}");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn app_data(device: &AudioDevice) -> AppData {
+        let mut inner = AppData::new();
+        let label = if device.is_default {
+            format!("{} · Default", device.kind.label())
+        } else {
+            device.kind.label().to_string()
+        };
+        inner.search_string = format!("{};{}", device.description, device.kind.label());
+        inner.name = Some(SharedString::from(device.description.clone()));
+        inner.generic_name = Some(SharedString::from(label));
+        inner.exec = Some(device.exec());
+        inner.defer_icon(device.kind.icon());
+        inner
+    }
+
+    /// Shown until the devices were listed
+    pub fn placeholder() -> AppData {
+        let mut inner = AppData::new();
+        inner.name = Some(SharedString::from("Listing audio devices…"));
+        inner.search_string = String::from("audio;output;input");
+        inner.defer_icon(DeviceKind::Sink.icon());
+        inner
+    }
+}
+
+fn parse_devices(kind: DeviceKind, json: &Value, default: &str) -> Vec<AudioDevice> {
+    json.as_array()
+        .into_iter()
+        .flatten()
+        // Monitors only mirror the output of a sink
+        .filter(|device| {
+            device["monitor_of_sink"]
+                .as_str()
+                .is_none_or(|m| m == "n/a")
+        })
+        .filter_map(|device| {
+            let name = device["name"].as_str()?;
+            Some(AudioDevice {
+                kind,
+                name: name.to_string(),
+                description: device["description"].as_str().unwrap_or(name).to_string(),
+                is_default: name == default,
+            })
+        })
+        .collect()
+}
+
+/// Ids of the streams in the output of `pactl list short sink-inputs`
+fn stream_ids(short: &str) -> impl Iterator<Item = &str> {
+    short
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|id| id.parse::<u32>().is_ok())
+}
+
+#[test]
+fn test_parse_devices() {
+    let sources = serde_json::json!([
+        {
+            "name": "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor",
+            "description": "Monitor of Built-in Audio",
+            "monitor_of_sink": "alsa_output.pci-0000_00_1f.3.analog-stereo"
+        },
+        {
+            "name": "alsa_input.usb-Blue_Yeti-00.analog-stereo",
+            "description": "Yeti Stereo Microphone",
+            "monitor_of_sink": null
+        }
+    ]);
+    let devices = parse_devices(
+        DeviceKind::Source,
+        &sources,
+        "alsa_input.usb-Blue_Yeti-00.analog-stereo",
+    );
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].description, "Yeti Stereo Microphone");
+    assert!(devices[0].is_default);
+
+    let exec = devices[0].exec();
+    assert_eq!(
+        AudioDevice::parse_exec(&exec),
+        Some((
+            DeviceKind::Source,
+            "alsa_input.usb-Blue_Yeti-00.analog-stereo"
+        ))
+    );
+
+    let streams = "42\t58\t61\tPipeWire\tfloat32le 2ch 48000Hz\n57\t58\t70\tPipeWire\ts16le\n";
+    assert_eq!(stream_ids(streams).collect::<Vec<_>>(), ["42", "57"]);
+}
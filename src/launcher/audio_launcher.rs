@@ -1,148 +1,226 @@
-use bytes::Bytes;
-use gpui::ImageSource;
-use std::env;
-use std::fs::{self, File};
-use std::io::{Read, Write};
-use std::path::PathBuf;
-use zbus::blocking::{Connection, Proxy};
+use base64::prelude::{BASE64_STANDARD, Engine};
+use gpui::SharedString;
+use image::ImageFormat;
+use percent_encoding::percent_decode_str;
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+use zbus::MatchRule;
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::Type as MessageType;
 
+use crate::loader::utils::ApplicationAction;
 use crate::sherlock_error;
 use crate::utils::config::ConfigGuard;
 use crate::utils::errors::{SherlockError, SherlockErrorType};
+use crate::utils::paths::get_cache_dir;
+use crate::utils::watch::{STOP_CHECK_INTERVAL, StopToken};
 
-use super::utils::MprisData;
+use super::utils::{MprisData, to_title_case};
 
 #[derive(Debug, Clone, Default)]
 pub struct MusicPlayerLauncher {
     pub player: String,
     pub mpris: MprisData,
+    /// All running players, offered by the picker of the tile
+    pub players: Vec<String>,
+    pub art: Option<AlbumArt>,
 }
-impl MusicPlayerLauncher {
-    /// Get current image
-    /// Return:
-    /// image: Pixbuf
-    /// was_cached: bool
-    pub async fn get_image(&self) -> Option<(ImageSource, bool)> {
-        let art_url = self.mpris.metadata.art.as_ref()?;
-        let loc = art_url.split("/").last()?.to_string();
-        let mut was_cached = true;
-        let _bytes = match MusicPlayerLauncher::read_cached_cover(&loc) {
-            Ok(b) => b,
-            Err(_) => {
-                if art_url.starts_with("file") {
-                    MusicPlayerLauncher::read_image_file(art_url).ok()?
-                } else {
-                    let response = reqwest::get(art_url).await.ok()?;
-                    let bytes = response.bytes().await.ok()?;
-                    let _ = MusicPlayerLauncher::cache_cover(&bytes, &loc);
-                    was_cached = false;
-                    bytes
-                }
-            }
-        };
 
-        // let loader = PixbufLoader::new();
-        // loader.write(&bytes).ok()?;
-        // loader.close().ok()?;
-        // loader.pixbuf().and_then(|i| Some((i, was_cached)));
-        None
-    }
-    fn cache_cover(image: &Bytes, loc: &str) -> Result<(), SherlockError> {
-        // Create dir and parents
-        let home = env::var("HOME").map_err(|e| {
-            sherlock_error!(
-                SherlockErrorType::EnvVarNotFoundError("HOME".to_string()),
-                e.to_string()
-            )
-        })?;
+/// Album art of a track, converted to png in the image cache
+#[derive(Debug, Clone)]
+pub struct AlbumArt {
+    /// The `mpris:artUrl` the art was loaded from
+    pub url: String,
+    pub cover: Arc<Path>,
+    /// Blurred copy of the cover, used as the background of the tile
+    pub background: Arc<Path>,
+}
+impl AlbumArt {
+    /// Covers are only shown small, so they are scaled down before they are stored
+    const SIZE: u32 = 256;
+    const BLUR_SIGMA: f32 = 16.0;
+    /// Covers kept in the cache, the oldest ones are removed once more are stored
+    const CACHE_LIMIT: usize = 100;
 
-        let home_dir = PathBuf::from(home);
-        let path = home_dir.join(".cache/sherlock/mpris-cache/").join(loc);
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|e| sherlock_error!(
-                SherlockErrorType::DirCreateError(
-                    "~/.cache/sherlock/mpris-cache/".to_string(),
-                ),
-                e.to_string()
-            ))?;
+    fn paths(url: &str) -> Option<(PathBuf, PathBuf)> {
+        let mut hasher = DefaultHasher::new();
+        url.hash(&mut hasher);
+        let key = format!("{:x}", hasher.finish());
+        let dir = get_cache_dir().ok()?.join("mpris-cache");
+        Some((
+            dir.join(format!("{key}.png")),
+            dir.join(format!("{key}-blurred.png")),
+        ))
+    }
+    pub fn cached(url: &str) -> Option<Self> {
+        let (cover, background) = Self::paths(url)?;
+        (cover.is_file() && background.is_file()).then(|| Self {
+            url: url.to_string(),
+            cover: Arc::from(cover.into_boxed_path()),
+            background: Arc::from(background.into_boxed_path()),
+        })
+    }
+    /// Downloads the art of `http(s)://` urls, `None` for other urls. Runs on the Tokio runtime
+    /// of the main thread, so it is awaited on the foreground.
+    pub async fn download(url: &str) -> Option<Vec<u8>> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return None;
+        }
+        Some(reqwest::get(url).await.ok()?.bytes().await.ok()?.to_vec())
+    }
+    /// Loads the art from the cache, otherwise from the downloaded bytes or a `file://` or
+    /// `data:` url. Decoding and blurring blocks, so it is meant for the background executor.
+    pub fn load(url: &str, downloaded: Option<Vec<u8>>) -> Option<Self> {
+        if let Some(art) = Self::cached(url) {
+            return Some(art);
+        }
+        let bytes = if let Some(bytes) = downloaded {
+            bytes
+        } else if let Some(path) = url.strip_prefix("file://") {
+            let path = percent_decode_str(path).decode_utf8().ok()?;
+            fs::read(path.as_ref()).ok()?
+        } else if url.starts_with("data:") {
+            decode_data_url(url)?
+        } else {
+            return None;
         };
+        Self::store(url, &bytes)
+    }
+    fn store(url: &str, bytes: &[u8]) -> Option<Self> {
+        let (cover, background) = Self::paths(url)?;
+        let dir = cover.parent()?;
+        fs::create_dir_all(dir).ok()?;
+        let image = image::load_from_memory(bytes)
+            .ok()?
+            .thumbnail(Self::SIZE, Self::SIZE);
+        image.save_with_format(&cover, ImageFormat::Png).ok()?;
+        image
+            .blur(Self::BLUR_SIGMA)
+            .save_with_format(&background, ImageFormat::Png)
+            .ok()?;
+        evict(dir, Self::CACHE_LIMIT);
+        Self::cached(url)
+    }
+}
 
-        let mut file = if path.exists() {
-            File::open(&path)
-        } else {
-            File::create(&path)
+/// Removes the oldest covers of the cache along with their blurred copies, keeping `limit`
+fn evict(dir: &Path, limit: usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut covers: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().is_some_and(|extension| extension == "png")
+                && !path.to_string_lossy().ends_with("-blurred.png")
+        })
+        .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+        .collect();
+    if covers.len() <= limit {
+        return;
+    }
+    covers.sort_by(|a, b| b.0.cmp(&a.0));
+    for (_, cover) in covers.into_iter().skip(limit) {
+        if let Some(key) = cover.file_stem() {
+            let _ = fs::remove_file(dir.join(format!("{}-blurred.png", key.to_string_lossy())));
         }
-        .map_err(|e| {
-            sherlock_error!(
-                SherlockErrorType::FileExistError(path.clone()),
-                e.to_string()
-            )
-        })?;
-
-        file.write_all(&image).map_err(|e| {
-            sherlock_error!(
-                SherlockErrorType::FileExistError(path.clone()),
-                e.to_string()
-            )
-        })?;
-        // if file not exist, create and write it
-        Ok(())
+        let _ = fs::remove_file(&cover);
     }
-    fn read_cached_cover(loc: &str) -> Result<Bytes, SherlockError> {
-        let home = env::var("HOME").map_err(|e| {
-            sherlock_error!(
-                SherlockErrorType::EnvVarNotFoundError("HOME".to_string()),
-                e.to_string()
-            )
-        })?;
-        let home_dir = PathBuf::from(home);
-        let path = home_dir.join(".cache/sherlock/mpris-cache/").join(loc);
+}
 
-        let mut file = File::open(&path).map_err(|e| {
-            sherlock_error!(
-                SherlockErrorType::FileExistError(path.clone()),
-                e.to_string()
-            )
-        })?;
-        let mut buffer = vec![];
-        file.read_to_end(&mut buffer).map_err(|e| {
-            sherlock_error!(
-                SherlockErrorType::FileReadError(path.clone()),
-                e.to_string()
-            )
-        })?;
-        Ok(buffer.into())
+/// Decodes the payload of a `data:` url, e.g. `data:image/png;base64,iVBORw0KGgo=`
+fn decode_data_url(url: &str) -> Option<Vec<u8>> {
+    let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+    if header.ends_with(";base64") {
+        BASE64_STANDARD.decode(data.trim()).ok()
+    } else {
+        Some(percent_decode_str(data).collect())
     }
-    fn read_image_file(loc: &str) -> Result<Bytes, SherlockError> {
-        let path = PathBuf::from(loc.trim_start_matches("file://"));
-
-        let mut file = File::open(&path).map_err(|e| {
-            sherlock_error!(
-                SherlockErrorType::FileExistError(path.clone()),
-                e.to_string()
-            )
-        })?;
-        let mut buffer = vec![];
-        file.read_to_end(&mut buffer).map_err(|e| {
-            sherlock_error!(
-                SherlockErrorType::FileReadError(path.clone()),
-                e.to_string()
-            )
-        })?;
-        Ok(buffer.into())
+}
+impl MusicPlayerLauncher {
+    /// Album art of the current track, if it has been cached already
+    pub fn cached_art(&self) -> Option<AlbumArt> {
+        AlbumArt::cached(self.mpris.metadata.art.as_deref()?)
     }
     pub fn playpause(player: &str) -> Result<(), SherlockError> {
-        Self::player_method(player, "PlayPause")
+        Self::player_method(player, "PlayPause", &())
     }
     pub fn next(player: &str) -> Result<(), SherlockError> {
-        Self::player_method(player, "Next")
+        Self::player_method(player, "Next", &())
     }
     pub fn previous(player: &str) -> Result<(), SherlockError> {
-        Self::player_method(player, "Previous")
+        Self::player_method(player, "Previous", &())
+    }
+    /// Moves the playback position by the offset, in microseconds
+    pub fn seek(player: &str, offset: i64) -> Result<(), SherlockError> {
+        Self::player_method(player, "Seek", &(offset,))
+    }
+    pub fn control(player: &str, command: MprisCommand) -> Result<(), SherlockError> {
+        match command {
+            MprisCommand::PlayPause => Self::playpause(player),
+            MprisCommand::Next => Self::next(player),
+            MprisCommand::Previous => Self::previous(player),
+            MprisCommand::Seek(offset) => Self::seek(player, offset),
+            MprisCommand::Select => {
+                if let Ok(events) = PLAYER_EVENTS.lock()
+                    && let Some(events) = events.as_ref()
+                {
+                    let _ = events.send(PlayerEvent::Select(player.to_string()));
+                }
+                Ok(())
+            }
+        }
+    }
+    /// Context menu entries of the music tile
+    pub fn actions(&self) -> Arc<[Arc<ApplicationAction>]> {
+        let action = |name: &str, method: &str| {
+            Arc::new(ApplicationAction {
+                name: Some(SharedString::from(name.to_string())),
+                exec: Some(self.player.clone()),
+                exit: false,
+                ..ApplicationAction::new(method)
+            })
+        };
+        let playpause = if self.mpris.playback_status == "Playing" {
+            "Pause"
+        } else {
+            "Play"
+        };
+        let controls = [
+            action(playpause, "mpris_playpause"),
+            action("Next", "mpris_next"),
+            action("Previous", "mpris_previous"),
+            action("Forward 10 Seconds", "mpris_seek_forward"),
+            action("Back 10 Seconds", "mpris_seek_backward"),
+        ];
+        // Picker for the other running players
+        let picker = self
+            .players
+            .iter()
+            .filter(|player| **player != self.player)
+            .map(|player| {
+                Arc::new(ApplicationAction {
+                    name: Some(SharedString::from(format!(
+                        "Switch to {}",
+                        player_name(player)
+                    ))),
+                    exec: Some(player.clone()),
+                    exit: false,
+                    ..ApplicationAction::new("mpris_select")
+                })
+            });
+        controls.into_iter().chain(picker).collect()
     }
-    fn player_method(player: &str, method: &str) -> Result<(), SherlockError> {
-        let conn = Connection::session()
-            .map_err(|e| sherlock_error!(SherlockErrorType::DBusConnectionError, e.to_string()))?;
+    fn player_method<B>(player: &str, method: &str, body: &B) -> Result<(), SherlockError>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+    {
+        let conn = control_connection()?;
         let proxy = Proxy::new(
             &conn,
             player,
@@ -151,13 +229,13 @@ impl MusicPlayerLauncher {
         )
         .map_err(|e| {
             sherlock_error!(
-                SherlockErrorType::DBusMessageConstructError(format!("PlayPause for {}", player)),
+                SherlockErrorType::DBusMessageConstructError(format!("{method} for {player}")),
                 e.to_string()
             )
         })?;
-        proxy.call_method(method, &()).map_err(|e| {
+        proxy.call_method(method, body).map_err(|e| {
             sherlock_error!(
-                SherlockErrorType::DBusMessageSendError(format!("PlayPause to {}", player)),
+                SherlockErrorType::DBusMessageSendError(format!("{method} to {player}")),
                 e.to_string()
             )
         })?;
@@ -174,6 +252,199 @@ impl MusicPlayerLauncher {
     }
 }
 
+/// Session bus connection the controls of the music tile are sent over, opened on first use
+static CONTROL_CONNECTION: Mutex<Option<Connection>> = Mutex::new(None);
+
+fn control_connection() -> Result<Connection, SherlockError> {
+    let mut shared = CONTROL_CONNECTION
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(conn) = shared.as_ref() {
+        return Ok(conn.clone());
+    }
+    let conn = Connection::session()
+        .map_err(|e| sherlock_error!(SherlockErrorType::DBusConnectionError, e.to_string()))?;
+    *shared = Some(conn.clone());
+    Ok(conn)
+}
+
+/// Commands the music tile sends to the player
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MprisCommand {
+    PlayPause,
+    Next,
+    Previous,
+    /// Offset in microseconds
+    Seek(i64),
+    /// Controls the player from now on
+    Select,
+}
+impl MprisCommand {
+    const SEEK_STEP: i64 = 10_000_000;
+
+    /// Maps the method of a context action to its command
+    pub fn from_method(method: &str) -> Option<Self> {
+        let command = match method {
+            "mpris_playpause" => Self::PlayPause,
+            "mpris_next" => Self::Next,
+            "mpris_previous" => Self::Previous,
+            "mpris_seek_forward" => Self::Seek(Self::SEEK_STEP),
+            "mpris_seek_backward" => Self::Seek(-Self::SEEK_STEP),
+            "mpris_select" => Self::Select,
+            _ => return None,
+        };
+        Some(command)
+    }
+}
+
+/// What the watcher of the music tile reacts to
+enum PlayerEvent {
+    /// A player changed its properties, carries the unique bus name of the sender
+    Changed(Option<String>),
+    /// Players appeared or quit
+    NamesChanged,
+    /// The user picked a player from the tile
+    Select(String),
+}
+
+/// Lets the picker of the music tile reach its watcher
+static PLAYER_EVENTS: Mutex<Option<Sender<PlayerEvent>>> = Mutex::new(None);
+
+/// Forwards the signals matching the rule to the watcher, until the connection is closed
+fn forward_signals(
+    conn: &Connection,
+    rule: zbus::Result<MatchRule<'static>>,
+    events: Sender<PlayerEvent>,
+) {
+    let Ok(messages) = rule.and_then(|rule| MessageIterator::for_match_rule(rule, conn, None))
+    else {
+        return;
+    };
+    let is_properties = |msg: &zbus::Message| {
+        msg.header().member().map(|m| m.as_str()) == Some("PropertiesChanged")
+    };
+    for msg in messages {
+        let Ok(msg) = msg else {
+            return;
+        };
+        let event = if is_properties(&msg) {
+            PlayerEvent::Changed(msg.header().sender().map(|s| s.to_string()))
+        } else {
+            PlayerEvent::NamesChanged
+        };
+        if events.send(event).is_err() {
+            return;
+        }
+    }
+}
+
+/// Calls `on_change` with the state of the controlled player whenever a player reports changed
+/// properties, players come and go or another player is picked, until `on_change` returns
+/// `false` or the watcher is stopped. Blocks the calling thread.
+pub fn watch_players(stop: &StopToken, mut on_change: impl FnMut(MusicPlayerLauncher) -> bool) {
+    let Some(functions) = AudioLauncherFunctions::new() else {
+        return;
+    };
+    let (tx, rx) = mpsc::channel();
+    // The picker of the newest tile reaches the newest watcher
+    if let Ok(mut events) = PLAYER_EVENTS.lock() {
+        *events = Some(tx.clone());
+    }
+
+    let properties = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface("org.freedesktop.DBus.Properties")
+        .and_then(|rule| rule.member("PropertiesChanged"))
+        .and_then(|rule| rule.path("/org/mpris/MediaPlayer2"))
+        .map(|rule| rule.build());
+    let names = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface("org.freedesktop.DBus")
+        .and_then(|rule| rule.member("NameOwnerChanged"))
+        .and_then(|rule| rule.arg0ns("org.mpris.MediaPlayer2"))
+        .map(|rule| rule.build());
+    for rule in [properties, names] {
+        let conn = functions.conn.clone();
+        let tx = tx.clone();
+        std::thread::spawn(move || forward_signals(&conn, rule, tx));
+    }
+    // Fills in the picker right away
+    let _ = tx.send(PlayerEvent::NamesChanged);
+    drop(tx);
+
+    let mut pinned: Option<String> = None;
+    let mut recent: Vec<String> = Vec::new();
+    while !stop.is_stopped() {
+        let event = match rx.recv_timeout(STOP_CHECK_INTERVAL) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let players = functions.players();
+        match event {
+            PlayerEvent::Select(player) => pinned = Some(player),
+            PlayerEvent::Changed(Some(sender)) => {
+                // Players that start playing become the most recently active one
+                let playing = players.iter().find(|state| {
+                    state.mpris.playback_status == "Playing"
+                        && functions.name_owner(&state.player).as_deref() == Some(sender.as_str())
+                });
+                if let Some(state) = playing {
+                    recent.retain(|name| name != &state.player);
+                    recent.insert(0, state.player.clone());
+                }
+            }
+            PlayerEvent::Changed(None) | PlayerEvent::NamesChanged => {}
+        }
+
+        let names: Vec<String> = players.iter().map(|state| state.player.clone()).collect();
+        let preferred = ConfigGuard::read()
+            .ok()
+            .and_then(|config| config.default_apps.mpris.clone());
+        let Some(chosen) = choose_player(&names, pinned.as_deref(), preferred.as_deref(), &recent)
+        else {
+            continue;
+        };
+        let Some(mut state) = players.into_iter().find(|state| state.player == chosen) else {
+            continue;
+        };
+        state.players = names;
+        if !on_change(state) {
+            break;
+        }
+    }
+    // Ends the threads forwarding the signals, which wait for the next message otherwise
+    let _ = functions.conn.close();
+}
+
+/// Picks the player the music tile controls:
+/// 1. the player picked from the tile
+/// 2. the player of `default_apps.mpris`
+/// 3. the most recently active player
+/// 4. the first player
+fn choose_player<'a>(
+    players: &'a [String],
+    pinned: Option<&str>,
+    preferred: Option<&str>,
+    recent: &[String],
+) -> Option<&'a str> {
+    let running = |name: &str| players.iter().find(|player| player.as_str() == name);
+    pinned
+        .and_then(running)
+        .or_else(|| preferred.and_then(|m| players.iter().find(|player| player.contains(m))))
+        .or_else(|| recent.iter().find_map(|name| running(name)))
+        .or_else(|| players.first())
+        .map(String::as_str)
+}
+
+/// Name of a player for the picker, e.g. `Firefox` for
+/// `org.mpris.MediaPlayer2.firefox.instance_1_52`
+pub fn player_name(player: &str) -> String {
+    let name = player.trim_start_matches("org.mpris.MediaPlayer2.");
+    let name = name.split('.').next().unwrap_or(name);
+    to_title_case(name)
+}
+
 pub struct AudioLauncherFunctions {
     conn: Connection,
 }
@@ -183,26 +454,46 @@ impl AudioLauncherFunctions {
         let conn = Connection::session().ok()?;
         Some(AudioLauncherFunctions { conn })
     }
-    pub fn get_current_player(&self) -> Option<String> {
-        let proxy = Proxy::new(
+    fn bus(&self) -> Option<Proxy<'_>> {
+        Proxy::new(
             &self.conn,
             "org.freedesktop.DBus",
             "/",
             "org.freedesktop.DBus",
         )
-        .ok()?;
-        let mut names: Vec<String> = proxy.call("ListNames", &()).ok()?;
-        names.retain(|n| n.starts_with("org.mpris.MediaPlayer2."));
-        let first = names.first().cloned();
-        if let Ok(config) = ConfigGuard::read() {
-            if let Some(m) = config.default_apps.mpris.as_ref() {
-                let preferred = names.into_iter().find(|name| name.contains(m));
-                if preferred.is_some() {
-                    return preferred;
-                }
-            }
-        }
-        first
+        .ok()
+    }
+    /// States of the running players
+    pub fn players(&self) -> Vec<MusicPlayerLauncher> {
+        let Some(names) = self
+            .bus()
+            .and_then(|bus| bus.call::<_, _, Vec<String>>("ListNames", &()).ok())
+        else {
+            return Vec::new();
+        };
+        names
+            .iter()
+            .filter(|name| name.starts_with("org.mpris.MediaPlayer2."))
+            .filter_map(|name| self.get_metadata(name))
+            .collect()
+    }
+    /// Unique bus name of a player, which is what its signals are sent from
+    fn name_owner(&self, player: &str) -> Option<String> {
+        self.bus()?.call("GetNameOwner", &(player,)).ok()
+    }
+    /// The player of `default_apps.mpris`, otherwise a playing one or the first one
+    pub fn get_current_player(&self) -> Option<String> {
+        let players = self.players();
+        let names: Vec<String> = players.iter().map(|state| state.player.clone()).collect();
+        let playing: Vec<String> = players
+            .into_iter()
+            .filter(|state| state.mpris.playback_status == "Playing")
+            .map(|state| state.player)
+            .collect();
+        let preferred = ConfigGuard::read()
+            .ok()
+            .and_then(|config| config.default_apps.mpris.clone());
+        choose_player(&names, None, preferred.as_deref(), &playing).map(str::to_string)
     }
     pub fn get_metadata(&self, player: &str) -> Option<MusicPlayerLauncher> {
         let proxy = Proxy::new(
@@ -221,6 +512,82 @@ impl AudioLauncherFunctions {
         Some(MusicPlayerLauncher {
             player: player.to_string(),
             mpris: mpris_data,
+            players: Vec::new(),
+            art: None,
         })
     }
 }
+
+#[test]
+fn test_choose_player() {
+    let players = [
+        String::from("org.mpris.MediaPlayer2.firefox.instance_1_52"),
+        String::from("org.mpris.MediaPlayer2.spotify"),
+        String::from("org.mpris.MediaPlayer2.mpv"),
+    ];
+    let recent = [String::from("org.mpris.MediaPlayer2.mpv")];
+    let choose = |pinned, preferred| choose_player(&players, pinned, preferred, &recent);
+
+    assert_eq!(choose(None, None), Some("org.mpris.MediaPlayer2.mpv"));
+    assert_eq!(
+        choose(None, Some("spotify")),
+        Some("org.mpris.MediaPlayer2.spotify")
+    );
+    assert_eq!(
+        choose(
+            Some("org.mpris.MediaPlayer2.firefox.instance_1_52"),
+            Some("spotify")
+        ),
+        Some("org.mpris.MediaPlayer2.firefox.instance_1_52")
+    );
+    // Players that quit are skipped
+    assert_eq!(
+        choose(Some("org.mpris.MediaPlayer2.vlc"), Some("vlc")),
+        Some("org.mpris.MediaPlayer2.mpv")
+    );
+    assert_eq!(
+        choose_player(&players, None, None, &[]),
+        Some(players[0].as_str())
+    );
+    assert_eq!(player_name(&players[0]), "Firefox");
+}
+
+#[test]
+fn test_decode_data_url() {
+    assert_eq!(
+        decode_data_url("data:image/png;base64,iVBORw0KGgo=").as_deref(),
+        Some(&b"\x89PNG\r\n\x1a\n"[..])
+    );
+    assert_eq!(
+        decode_data_url("data:image/svg+xml,%3Csvg%3E").as_deref(),
+        Some(&b"<svg>"[..])
+    );
+    assert_eq!(decode_data_url("data:image/png;base64"), None);
+    assert_eq!(decode_data_url("https://example.org/cover.png"), None);
+}
+
+#[test]
+fn test_evict_album_art() {
+    let dir = std::env::temp_dir().join(format!("sherlock-{}-mpris", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let now = SystemTime::now();
+    for (i, key) in ["old", "mid", "new"].into_iter().enumerate() {
+        for name in [format!("{key}.png"), format!("{key}-blurred.png")] {
+            let file = fs::File::create(dir.join(name)).unwrap();
+            file.set_modified(now - std::time::Duration::from_secs(60 * (3 - i as u64)))
+                .unwrap();
+        }
+    }
+    evict(&dir, 2);
+    let mut left: Vec<String> = fs::read_dir(&dir)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    left.sort();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(
+        left,
+        ["mid-blurred.png", "mid.png", "new-blurred.png", "new.png"]
+    );
+}
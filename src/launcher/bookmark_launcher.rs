@@ -1,7 +1,8 @@
 use gpui::SharedString;
 use rusqlite::Connection;
+use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::launcher::Launcher;
@@ -10,6 +11,7 @@ use crate::loader::resolve_icon_path;
 use crate::loader::utils::{AppData, construct_search};
 use crate::utils::cache::BinaryCache;
 use crate::utils::errors::{SherlockError, SherlockErrorType};
+use crate::utils::favicon::{FaviconDatabase, FaviconSchema, FaviconSource};
 use crate::utils::files::home_dir;
 use crate::utils::paths::get_cache_dir;
 use crate::{sher_log, sherlock_error};
@@ -17,22 +19,33 @@ use crate::{sher_log, sherlock_error};
 #[derive(Clone, Debug)]
 pub struct BookmarkLauncher {
     pub target_browser: String,
+    pub favicons: FaviconSource,
+    /// Names of the Firefox or Zen profiles to read, the default profile if empty
+    pub profiles: Vec<String>,
 }
 impl BookmarkLauncher {
     pub fn find_bookmarks(
         browser: &str,
+        profiles: &[String],
         launcher: Arc<Launcher>,
     ) -> Result<Vec<AppData>, SherlockError> {
-        match browser.to_lowercase().as_str() {
-            "zen" | "zen-browser" | "/opt/zen-browser-bin/zen-bin %u" => {
-                BookmarkParser::zen(launcher)
+        match supported_browser(browser) {
+            Some("zen") => {
+                BookmarkParser::mozilla(launcher, &home_dir()?.join(".zen"), "zen", profiles)
             }
-            "brave" | "brave %u" => BookmarkParser::brave(launcher),
-            "firefox" | "/usr/lib/firefox/firefox %u" => BookmarkParser::firefox(launcher),
-            "chrome" | "google-chrome" | "/usr/bin/google-chrome-stable %u" => {
-                BookmarkParser::chrome(launcher)
-            }
-            "thorium" | "/usr/bin/thorium-browser %u" => BookmarkParser::thorium(launcher),
+            Some("brave") => BookmarkParser::brave(launcher),
+            Some("firefox") => BookmarkParser::mozilla(
+                launcher,
+                &home_dir()?.join(".mozilla/firefox"),
+                "firefox",
+                profiles,
+            ),
+            Some("chrome") => BookmarkParser::chrome(launcher),
+            Some("thorium") => BookmarkParser::thorium(launcher),
+            Some("vivaldi") => BookmarkParser::vivaldi(launcher),
+            Some("opera") => BookmarkParser::opera(launcher),
+            Some("edge") => BookmarkParser::edge(launcher),
+            Some("qutebrowser") => BookmarkParser::qutebrowser(launcher),
             _ => {
                 sher_log!(format!(
                     r#"Failed to gather bookmarks for browser: "{}""#,
@@ -52,6 +65,214 @@ impl BookmarkLauncher {
             }
         }
     }
+
+    /// Copies the favicon database of the browser into the cache and returns the copy
+    pub fn favicon_database(browser: &str) -> Option<FaviconDatabase> {
+        let home = home_dir().ok()?;
+        let prefix = supported_browser(browser)?;
+        let (file, schema) = match prefix {
+            "zen" => (default_profile(&home.join(".zen"))?, FaviconSchema::Mozilla),
+            "firefox" => (
+                default_profile(&home.join(".mozilla/firefox"))?,
+                FaviconSchema::Mozilla,
+            ),
+            "brave" => (
+                home.join(".config/BraveSoftware/Brave-Browser/Default"),
+                FaviconSchema::Chromium,
+            ),
+            "chrome" => (
+                home.join(".config/google-chrome/Default"),
+                FaviconSchema::Chromium,
+            ),
+            "thorium" => (
+                home.join(".config/thorium/Default"),
+                FaviconSchema::Chromium,
+            ),
+            "vivaldi" => (
+                home.join(".config/vivaldi/Default"),
+                FaviconSchema::Chromium,
+            ),
+            "opera" => (home.join(".config/opera"), FaviconSchema::Chromium),
+            "edge" => (
+                home.join(".config/microsoft-edge/Default"),
+                FaviconSchema::Chromium,
+            ),
+            _ => return None,
+        };
+        let file = match schema {
+            FaviconSchema::Mozilla => file.join("favicons.sqlite"),
+            FaviconSchema::Chromium => file.join("Favicons"),
+        };
+        if !file.exists() {
+            return None;
+        }
+
+        let path = get_cache_dir()
+            .ok()?
+            .join(format!("bookmarks/{}-favicons.sqlite", prefix));
+        MozillaSqliteParser::copy_if_needed(&file, &path);
+        Some(FaviconDatabase { path, schema })
+    }
+}
+
+/// Maps the configured browser onto one of the supported ones
+fn supported_browser(browser: &str) -> Option<&'static str> {
+    match browser.to_lowercase().as_str() {
+        "zen" | "zen-browser" | "/opt/zen-browser-bin/zen-bin %u" => Some("zen"),
+        "brave" | "brave %u" => Some("brave"),
+        "firefox" | "/usr/lib/firefox/firefox %u" => Some("firefox"),
+        "chrome" | "google-chrome" | "/usr/bin/google-chrome-stable %u" => Some("chrome"),
+        "thorium" | "/usr/bin/thorium-browser %u" => Some("thorium"),
+        "vivaldi" | "vivaldi-stable" | "/usr/bin/vivaldi-stable %u" => Some("vivaldi"),
+        "opera" | "opera %u" | "/usr/bin/opera %u" => Some("opera"),
+        "edge"
+        | "microsoft-edge"
+        | "microsoft-edge-stable"
+        | "/usr/bin/microsoft-edge-stable %u" => Some("edge"),
+        "qutebrowser" | "qutebrowser %u" | "/usr/bin/qutebrowser %u" => Some("qutebrowser"),
+        _ => None,
+    }
+}
+
+/// A profile listed in the `profiles.ini` of a Mozilla based browser
+#[derive(Debug, PartialEq)]
+struct MozillaProfile {
+    name: String,
+    path: PathBuf,
+    is_default: bool,
+}
+
+/// Parses the `profiles.ini` of a Mozilla based browser, the default profile comes first. The
+/// `[Install…]` sections name the profile the browser actually opens and take precedence over
+/// the `Default=1` flag of older versions.
+fn parse_profiles_ini(root: &Path, content: &str) -> Vec<MozillaProfile> {
+    let mut profiles: Vec<(MozillaProfile, String)> = Vec::new();
+    let mut install_default: Option<String> = None;
+    let mut section = String::new();
+    let mut is_relative = true;
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].to_string();
+            is_relative = true;
+            if section.starts_with("Profile") {
+                profiles.push((
+                    MozillaProfile {
+                        name: String::new(),
+                        path: PathBuf::new(),
+                        is_default: false,
+                    },
+                    String::new(),
+                ));
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if section.starts_with("Install") {
+            if key == "Default" && install_default.is_none() {
+                install_default = Some(value.to_string());
+            }
+            continue;
+        }
+        let Some((profile, raw_path)) = profiles
+            .last_mut()
+            .filter(|_| section.starts_with("Profile"))
+        else {
+            continue;
+        };
+        match key {
+            "Name" => profile.name = value.to_string(),
+            "Default" => profile.is_default = value == "1",
+            "IsRelative" => is_relative = value == "1",
+            "Path" => *raw_path = value.to_string(),
+            _ => {}
+        }
+        if matches!(key, "Path" | "IsRelative") {
+            profile.path = if is_relative {
+                root.join(&*raw_path)
+            } else {
+                PathBuf::from(&*raw_path)
+            };
+        }
+    }
+
+    let mut profiles: Vec<MozillaProfile> = profiles
+        .into_iter()
+        .filter(|(_, raw_path)| !raw_path.is_empty())
+        .map(|(mut profile, raw_path)| {
+            if let Some(default) = &install_default {
+                profile.is_default = *default == raw_path;
+            }
+            profile
+        })
+        .collect();
+    profiles.sort_by_key(|profile| !profile.is_default);
+    profiles
+}
+
+/// Lists the profiles of a Mozilla based browser that contain bookmarks. Installations without a
+/// `profiles.ini` fall back to the profile directories found in the root.
+fn mozilla_profiles(root: &Path) -> Vec<MozillaProfile> {
+    let listed = fs::read_to_string(root.join("profiles.ini"))
+        .map(|content| parse_profiles_ini(root, &content))
+        .unwrap_or_default();
+    let profiles: Vec<MozillaProfile> = if listed.is_empty() {
+        fs::read_dir(root)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                Some(MozillaProfile {
+                    name: path.file_name()?.to_string_lossy().to_string(),
+                    path,
+                    is_default: false,
+                })
+            })
+            .collect()
+    } else {
+        listed
+    };
+    profiles
+        .into_iter()
+        .filter(|profile| profile.path.join("places.sqlite").exists())
+        .collect()
+}
+
+/// Directory of the profile a Mozilla based browser opens by default
+fn default_profile(root: &Path) -> Option<PathBuf> {
+    mozilla_profiles(root)
+        .into_iter()
+        .next()
+        .map(|profile| profile.path)
+}
+
+/// Creates the tile of a bookmark. Its folder is shown below the title, folder and tags are
+/// searchable just like the url.
+fn bookmark_data(
+    launcher: &Launcher,
+    title: &str,
+    url: String,
+    folder: Option<String>,
+    tags: &[&str],
+) -> AppData {
+    let folder = folder.filter(|folder| !folder.is_empty());
+    let keywords = std::iter::once(url.as_str())
+        .chain(folder.as_deref())
+        .chain(tags.iter().copied())
+        .collect::<Vec<_>>()
+        .join(";");
+    AppData {
+        name: Some(SharedString::from(title.to_string())),
+        generic_name: folder.map(SharedString::from),
+        icon: resolve_icon_path("sherlock-bookmark"),
+        search_string: construct_search(Some(title), &keywords, true),
+        exec: Some(url),
+        priority: Some(launcher.priority as f32 + 1.0),
+        ..AppData::new()
+    }
 }
 
 struct BookmarkParser;
@@ -75,56 +296,75 @@ impl BookmarkParser {
             .map_err(|e| sherlock_error!(SherlockErrorType::FileReadError(path), e.to_string()))?;
         ChromeParser::parse(launcher, data)
     }
+    fn vivaldi(launcher: Arc<Launcher>) -> Result<Vec<AppData>, SherlockError> {
+        let path = home_dir()?.join(".config/vivaldi/Default/Bookmarks");
+        let data = fs::read_to_string(&path)
+            .map_err(|e| sherlock_error!(SherlockErrorType::FileReadError(path), e.to_string()))?;
+        ChromeParser::parse(launcher, data)
+    }
+    fn opera(launcher: Arc<Launcher>) -> Result<Vec<AppData>, SherlockError> {
+        // Opera keeps its profile directly in the config directory
+        let path = home_dir()?.join(".config/opera/Bookmarks");
+        let data = fs::read_to_string(&path)
+            .map_err(|e| sherlock_error!(SherlockErrorType::FileReadError(path), e.to_string()))?;
+        ChromeParser::parse(launcher, data)
+    }
+    fn edge(launcher: Arc<Launcher>) -> Result<Vec<AppData>, SherlockError> {
+        let path = home_dir()?.join(".config/microsoft-edge/Default/Bookmarks");
+        let data = fs::read_to_string(&path)
+            .map_err(|e| sherlock_error!(SherlockErrorType::FileReadError(path), e.to_string()))?;
+        ChromeParser::parse(launcher, data)
+    }
+    fn qutebrowser(launcher: Arc<Launcher>) -> Result<Vec<AppData>, SherlockError> {
+        let config = home_dir()?.join(".config/qutebrowser");
+        let path = config.join("bookmarks/urls");
+        let bookmarks = fs::read_to_string(&path)
+            .map_err(|e| sherlock_error!(SherlockErrorType::FileReadError(path), e.to_string()))?;
+        // Quickmarks are optional
+        let quickmarks = fs::read_to_string(config.join("quickmarks")).unwrap_or_default();
+        Ok(QutebrowserParser::parse(&launcher, &bookmarks, &quickmarks))
+    }
 
-    fn zen(launcher: Arc<Launcher>) -> Result<Vec<AppData>, SherlockError> {
-        fn get_path() -> Option<PathBuf> {
-            let zen_root = home_dir().ok()?.join(".zen");
-            fs::read_dir(&zen_root)
-                .ok()?
-                .filter_map(|entry| {
-                    let path = entry.ok()?.path();
-                    if path.is_dir() && path.join("places.sqlite").exists() {
-                        Some(path.join("places.sqlite"))
-                    } else {
-                        None
-                    }
-                })
-                .next()
+    /// Reads the bookmarks of the given profiles, or of the default profile if none are given.
+    /// Bookmarks found in several profiles are only listed once.
+    fn mozilla(
+        launcher: Arc<Launcher>,
+        root: &Path,
+        prefix: &str,
+        profiles: &[String],
+    ) -> Result<Vec<AppData>, SherlockError> {
+        let available = mozilla_profiles(root);
+        let selected: Vec<&MozillaProfile> = if profiles.is_empty() {
+            available.iter().take(1).collect()
+        } else {
+            available
+                .iter()
+                .filter(|profile| profiles.iter().any(|name| name == &profile.name))
+                .collect()
+        };
+        if selected.is_empty() {
+            return Err(sherlock_error!(
+                SherlockErrorType::FileExistError(root.join("profiles.ini")),
+                "No profile with bookmarks found"
+            ));
         }
-        let path = get_path().ok_or_else(|| {
-            sherlock_error!(
-                SherlockErrorType::FileExistError(PathBuf::from("~/.zen/../places.sqlite")),
-                "File does not exist"
-            )
-        })?;
-        let parser = MozillaSqliteParser::new(path, "zen");
-        parser.read(launcher, "zen")
-    }
-    fn firefox(launcher: Arc<Launcher>) -> Result<Vec<AppData>, SherlockError> {
-        fn get_path() -> Option<PathBuf> {
-            let zen_root = home_dir().ok()?.join(".mozilla/firefox/");
-            fs::read_dir(&zen_root)
-                .ok()?
-                .filter_map(|entry| {
-                    let path = entry.ok()?.path();
-                    if path.is_dir() && path.join("places.sqlite").exists() {
-                        Some(path.join("places.sqlite"))
-                    } else {
-                        None
-                    }
-                })
-                .next()
+
+        let mut seen = HashSet::new();
+        let mut bookmarks = Vec::new();
+        for profile in selected {
+            let Some(dir) = profile.path.file_name() else {
+                continue;
+            };
+            let key = format!("{prefix}-{}", dir.to_string_lossy());
+            let parser = MozillaSqliteParser::new(profile.path.join("places.sqlite"), &key);
+            bookmarks.extend(
+                parser
+                    .read(Arc::clone(&launcher), &key)?
+                    .into_iter()
+                    .filter(|bookmark| seen.insert(bookmark.exec.clone())),
+            );
         }
-        let path = get_path().ok_or_else(|| {
-            sherlock_error!(
-                SherlockErrorType::FileExistError(PathBuf::from(
-                    "~/.mozilla/firefox/../places.sqlite",
-                )),
-                "File does not exist"
-            )
-        })?;
-        let parser = MozillaSqliteParser::new(path, "firefox");
-        parser.read(launcher, "firefox")
+        Ok(bookmarks)
     }
 }
 struct MozillaSqliteParser {
@@ -162,10 +402,28 @@ impl MozillaSqliteParser {
     }
     fn read_new(&self, launcher: Arc<Launcher>) -> Result<Vec<AppData>, SherlockError> {
         let mut res: Vec<AppData> = Vec::new();
+        // Folders directly below the root (menu, toolbar, ...) are left out of the path. Tags are
+        // folders below the tags root that hold a second, untitled bookmark of the same place.
         let query = "
-            SELECT b.title, p.url
+            WITH RECURSIVE folders(id, path) AS (
+                SELECT id, '' FROM moz_bookmarks
+                WHERE parent = (SELECT id FROM moz_bookmarks WHERE guid = 'root________')
+                UNION ALL
+                SELECT b.id, CASE WHEN f.path = '' THEN b.title ELSE f.path || ' / ' || b.title END
+                FROM moz_bookmarks b
+                JOIN folders f ON b.parent = f.id
+                WHERE b.type = 2
+            )
+            SELECT b.title, p.url, f.path, (
+                SELECT group_concat(t.title, ';')
+                FROM moz_bookmarks tb
+                JOIN moz_bookmarks t ON tb.parent = t.id
+                WHERE tb.fk = b.fk
+                AND t.parent = (SELECT id FROM moz_bookmarks WHERE guid = 'tags________')
+            )
             FROM moz_bookmarks b
             JOIN moz_places p ON b.fk = p.id
+            LEFT JOIN folders f ON b.parent = f.id
             WHERE b.type = 1
             AND b.title IS NOT NULL
             AND p.url IS NOT NULL
@@ -178,24 +436,18 @@ impl MozillaSqliteParser {
             let event_iter = stmt.query_map([], |row| {
                 let title: String = row.get(0)?;
                 let url: String = row.get(1)?;
+                let folder: Option<String> = row.get(2)?;
+                let tags: Option<String> = row.get(3)?;
 
-                Ok((title, url))
+                Ok((title, url, folder, tags))
             });
 
             if let Ok(rows) = event_iter {
-                for row in rows.flatten() {
-                    let bookmark = AppData {
-                        name: Some(SharedString::from(&row.0)),
-                        icon: resolve_icon_path("sherlock-bookmark"),
-                        search_string: construct_search(Some(&row.0), &row.1, true),
-                        exec: Some(row.1),
-                        desktop_file: None,
-                        priority: Some(launcher.priority as f32 + 1.0),
-                        actions: Arc::new([]),
-                        vars: vec![],
-                        terminal: false,
-                    };
-                    res.push(bookmark);
+                for (title, url, folder, tags) in rows.flatten() {
+                    let tags: Vec<&str> = tags
+                        .as_deref()
+                        .map_or(Vec::new(), |t| t.split(';').collect());
+                    res.push(bookmark_data(&launcher, &title, url, folder, &tags));
                 }
             }
         }
@@ -259,41 +511,128 @@ impl ChromeParser {
             .map_err(|e| sherlock_error!(SherlockErrorType::FlagLoadError, e.to_string()))?;
 
         fn process_bookmark(
-            launcher: Arc<Launcher>,
+            launcher: &Launcher,
             bookmarks: &mut Vec<AppData>,
             bookmark: parser::ChromeBookmark,
+            folder: Option<String>,
         ) {
             match bookmark.r#type.as_ref() {
                 "folder" => {
-                    if let Some(children) = bookmark.children {
-                        for child in children {
-                            process_bookmark(Arc::clone(&launcher), bookmarks, child);
-                        }
+                    let path = match &folder {
+                        Some(parent) => format!("{parent} / {}", bookmark.name),
+                        None => bookmark.name.clone(),
+                    };
+                    for child in bookmark.children.into_iter().flatten() {
+                        process_bookmark(launcher, bookmarks, child, Some(path.clone()));
                     }
                 }
                 "url" => {
                     if let Some(url) = bookmark.url {
-                        bookmarks.push(AppData {
-                            name: Some(SharedString::from(&bookmark.name)),
-                            icon: resolve_icon_path("sherlock-bookmark"),
-                            exec: Some(url.clone()),
-                            search_string: construct_search(Some(&bookmark.name), &url, true),
-                            desktop_file: None,
-                            priority: Some(launcher.priority as f32 + 1.0),
-                            actions: Arc::new([]),
-                            vars: vec![],
-                            terminal: false,
-                        });
+                        bookmarks.push(bookmark_data(launcher, &bookmark.name, url, folder, &[]));
                     }
                 }
                 _ => {}
             };
         }
 
-        for (_name, bookmark) in file.roots {
-            process_bookmark(launcher.clone(), &mut bookmarks, bookmark);
+        // The roots (bookmark bar, other bookmarks, ...) are left out of the folder path
+        for (_name, root) in file.roots {
+            for child in root.children.into_iter().flatten() {
+                process_bookmark(&launcher, &mut bookmarks, child, None);
+            }
         }
 
         Ok(bookmarks)
     }
 }
+
+/// qutebrowser stores bookmarks as plain text, one `url title` per line, and quickmarks as
+/// `name url`.
+struct QutebrowserParser;
+impl QutebrowserParser {
+    fn parse(launcher: &Launcher, bookmarks: &str, quickmarks: &str) -> Vec<AppData> {
+        let bookmarks = bookmarks.lines().filter_map(|line| {
+            let line = line.trim();
+            let (url, title) = line.split_once(' ').unwrap_or((line, line));
+            (!url.is_empty()).then(|| (title.trim(), url))
+        });
+        // The url is the last field, as quickmark names may contain spaces
+        let quickmarks = quickmarks
+            .lines()
+            .filter_map(|line| line.trim().rsplit_once(' '));
+
+        let mut seen = HashSet::new();
+        bookmarks
+            .map(|(title, url)| (title, url, None))
+            .chain(quickmarks.map(|(name, url)| (name.trim(), url, Some("Quickmarks".to_string()))))
+            .filter(|(_, url, _)| seen.insert(*url))
+            .map(|(title, url, folder)| {
+                bookmark_data(launcher, title, url.to_string(), folder, &[])
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_parse_profiles_ini() {
+    let ini = "[Profile1]\n\
+        Name=work\n\
+        IsRelative=1\n\
+        Path=abcd.work\n\
+        Default=1\n\
+        \n\
+        [Profile0]\n\
+        Name=default-release\n\
+        IsRelative=0\n\
+        Path=/data/firefox/efgh.default-release\n\
+        \n\
+        [Install4F96D1932A9F858E]\n\
+        Default=/data/firefox/efgh.default-release\n\
+        Locked=1\n\
+        \n\
+        [General]\n\
+        StartWithLastProfile=1\n";
+    let root = Path::new("/home/user/.mozilla/firefox");
+    let profiles = parse_profiles_ini(root, ini);
+
+    // The install default wins over the legacy flag
+    assert_eq!(profiles.len(), 2);
+    assert_eq!(profiles[0].name, "default-release");
+    assert_eq!(
+        profiles[0].path,
+        PathBuf::from("/data/firefox/efgh.default-release")
+    );
+    assert!(profiles[0].is_default);
+    assert_eq!(profiles[1].path, root.join("abcd.work"));
+    assert!(!profiles[1].is_default);
+}
+
+#[test]
+fn test_parse_qutebrowser() {
+    let launcher = Launcher::default();
+    let bookmarks = "https://docs.rs/ Docs.rs\nhttps://example.org\n\n";
+    let quickmarks = "rust book https://doc.rust-lang.org/book/\ndocs https://docs.rs/\n";
+    let parsed = QutebrowserParser::parse(&launcher, bookmarks, quickmarks);
+
+    let entries: Vec<(&str, &str)> = parsed
+        .iter()
+        .map(|b| {
+            (
+                b.name.as_ref().map_or("", |n| n.as_str()),
+                b.exec.as_deref().unwrap_or_default(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("Docs.rs", "https://docs.rs/"),
+            ("https://example.org", "https://example.org"),
+            ("rust book", "https://doc.rust-lang.org/book/"),
+        ]
+    );
+    assert_eq!(
+        parsed[2].generic_name.as_ref().map(|f| f.as_str()),
+        Some("Quickmarks")
+    );
+}
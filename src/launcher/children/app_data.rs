@@ -1,19 +1,24 @@
 use std::sync::Arc;
 
 use gpui::{
-    AnyElement, Image, ImageSource, IntoElement, ParentElement, SharedString, Styled, div, img, px,
-    rgb,
+    AnyElement, App, Image, ImageSource, InteractiveElement, IntoElement, ParentElement,
+    SharedString, StatefulInteractiveElement, Styled, div, img, px, rgb,
 };
 
 use crate::{
     launcher::{ExecMode, Launcher, children::RenderableChildImpl},
-    loader::utils::AppData,
-    utils::errors::SherlockError,
+    loader::utils::{AppData, CounterReader, IgnoreList},
+    ui::typography::monospace_family,
+    utils::{
+        config::ConfigGuard,
+        errors::SherlockError,
+        windows::{OpenWindow, find_window, focus_window},
+    },
 };
 
 impl<'a> RenderableChildImpl<'a> for AppData {
     fn render(&self, launcher: &Arc<Launcher>, is_selected: bool) -> AnyElement {
-        div()
+        let row = div()
             .px_4()
             .py_2()
             .w_full()
@@ -34,21 +39,46 @@ impl<'a> RenderableChildImpl<'a> for AppData {
                     .items_center()
                     .child(
                         div()
-                            .text_sm()
-                            .text_color(if is_selected {
-                                rgb(0xffffff)
-                            } else {
-                                rgb(0xcccccc)
-                            })
-                            .overflow_hidden()
-                            .text_ellipsis()
-                            .whitespace_nowrap()
-                            .children(
-                                self.name
-                                    .as_ref()
-                                    .or(launcher.display_name.as_ref())
-                                    .map(|name| div().child(name.clone())),
-                            ),
+                            .flex()
+                            .gap_2()
+                            .items_center()
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(if is_selected {
+                                        rgb(0xffffff)
+                                    } else {
+                                        rgb(0xcccccc)
+                                    })
+                                    .overflow_hidden()
+                                    .text_ellipsis()
+                                    .whitespace_nowrap()
+                                    .children(
+                                        self.name
+                                            .as_ref()
+                                            .or(launcher.display_name.as_ref())
+                                            .map(|name| div().child(name.clone())),
+                                    ),
+                            )
+                            // Origin badge for sandboxed apps
+                            .children(self.origin.as_ref().map(|origin| {
+                                div()
+                                    .px_1()
+                                    .rounded_sm()
+                                    .bg(rgb(0x2a2a2a))
+                                    .text_xs()
+                                    .text_color(rgb(0x888888))
+                                    .child(origin.label())
+                            }))
+                            .children(self.badges.iter().map(|badge| {
+                                div()
+                                    .px_1()
+                                    .rounded_sm()
+                                    .bg(rgb(0x2a2a2a))
+                                    .text_xs()
+                                    .text_color(rgb(0x888888))
+                                    .child(badge.clone())
+                            })),
                     )
                     .child(
                         div()
@@ -58,8 +88,47 @@ impl<'a> RenderableChildImpl<'a> for AppData {
                             } else {
                                 rgb(0x666666)
                             })
-                            .children(launcher.name.as_ref().map(|name| div().child(name.clone()))),
+                            .children(match self.generic_name.as_ref() {
+                                Some(generic_name) => Some(div().child(generic_name.clone())),
+                                None => {
+                                    launcher.name.as_ref().map(|name| div().child(name.clone()))
+                                }
+                            }),
                     ),
+            );
+
+        let Some(output) = self.output.as_ref() else {
+            return row.into_any_element();
+        };
+        // Output of `show_output` commands, scrolls on its own once it gets long
+        let id = self
+            .exec
+            .as_deref()
+            .or(self.name.as_ref().map(|name| name.as_str()))
+            .unwrap_or_default();
+        let output_box = div().id(SharedString::from(format!("output-{id}")));
+        let output_box = match monospace_family() {
+            Some(family) => output_box.font_family(family),
+            None => output_box,
+        };
+        div()
+            .w_full()
+            .flex()
+            .flex_col()
+            .child(row)
+            .child(
+                output_box
+                    .mx_4()
+                    .mb_2()
+                    .px_2()
+                    .py_1()
+                    .max_h(px(240.))
+                    .overflow_y_scroll()
+                    .rounded_sm()
+                    .bg(rgb(0x1a1a1a))
+                    .text_xs()
+                    .text_color(rgb(0xcccccc))
+                    .child(output.clone()),
             )
             .into_any_element()
     }
@@ -68,9 +137,22 @@ impl<'a> RenderableChildImpl<'a> for AppData {
         launcher: &Arc<Launcher>,
         keyword: &str,
         variables: &[(SharedString, SharedString)],
+        cx: &App,
     ) -> Result<bool, SherlockError> {
         let attrs = ExecMode::from_appdata(self, launcher);
-        launcher.execute(&attrs, keyword, variables)
+        let executed = launcher.execute(&attrs, keyword, variables, cx)?;
+        // Launches of apps, commands, bookmarks and searches move them up next time
+        if matches!(
+            attrs,
+            ExecMode::App { .. }
+                | ExecMode::Commmand { .. }
+                | ExecMode::Chain { .. }
+                | ExecMode::Type { .. }
+                | ExecMode::Web { .. }
+        ) {
+            self.increment_count(launcher);
+        }
+        Ok(executed)
     }
     fn priority(&self, launcher: &Arc<Launcher>) -> f32 {
         self.priority.unwrap_or(launcher.priority as f32)
@@ -79,3 +161,43 @@ impl<'a> RenderableChildImpl<'a> for AppData {
         &self.search_string
     }
 }
+
+impl AppData {
+    /// Switches to the open window of the app instead of launching it again, if the config allows
+    /// it. `windows` is the snapshot taken when the window was opened, and focusing goes through
+    /// the compositor's IPC, so it runs on a thread of its own.
+    pub fn focus_open_window(&self, launcher: &Arc<Launcher>, windows: &[OpenWindow]) -> bool {
+        let switch = ConfigGuard::read().map_or(true, |c| c.behavior.switch_to_window);
+        let Some(window) = self
+            .window_class()
+            .filter(|_| switch)
+            .and_then(|class| find_window(windows, class))
+            .cloned()
+        else {
+            return false;
+        };
+        std::thread::spawn(move || {
+            if let Err(e) = focus_window(&window) {
+                eprintln!("{e}");
+            }
+        });
+        self.increment_count(launcher);
+        true
+    }
+
+    fn increment_count(&self, launcher: &Arc<Launcher>) {
+        if self.is_counted(launcher, &uncounted_items())
+            && let Ok(count_reader) = CounterReader::new()
+        {
+            let _ = count_reader.increment(&self.count_key(launcher));
+        }
+    }
+}
+
+/// Items of the `uncounted` option of the config
+fn uncounted_items() -> IgnoreList {
+    ConfigGuard::read().map_or_else(
+        |_| IgnoreList::default(),
+        |config| IgnoreList::from_lines(&config.behavior.uncounted),
+    )
+}
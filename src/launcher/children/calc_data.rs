@@ -3,9 +3,13 @@ use std::{
     sync::{Arc, RwLock},
 };
 
-use gpui::{IntoElement, ParentElement, SharedString, Styled, div, px, rgb};
+use gpui::{IntoElement, ParentElement, SharedString, Styled, div, rgb};
 
-use crate::{launcher::children::RenderableChildImpl, utils::intent::Intent};
+use crate::{
+    launcher::children::RenderableChildImpl,
+    ui::typography::{monospace_family, text_px},
+    utils::intent::Intent,
+};
 
 #[derive(Clone)]
 pub struct CalcData {
@@ -67,6 +71,7 @@ impl<'a> RenderableChildImpl<'a> for CalcData {
         _launcher: &std::sync::Arc<crate::launcher::Launcher>,
         _keyword: &str,
         _variables: &[(SharedString, SharedString)],
+        _cx: &gpui::App,
     ) -> Result<bool, crate::utils::errors::SherlockError> {
         Ok(false)
     }
@@ -86,6 +91,11 @@ impl<'a> RenderableChildImpl<'a> for CalcData {
             res.clone()
         };
 
+        let value = div().text_size(text_px(24.0));
+        let value = match monospace_family() {
+            Some(family) => value.font_family(family),
+            None => value,
+        };
         div()
             .px_4()
             .py_7()
@@ -95,8 +105,7 @@ impl<'a> RenderableChildImpl<'a> for CalcData {
             .items_center()
             .justify_center()
             .child(
-                div()
-                    .text_size(px(24.0))
+                value
                     .text_color(if is_selected {
                         rgb(0xDDD5D0)
                     } else {
@@ -1,15 +1,34 @@
-use std::sync::Arc;
+use std::{collections::HashMap, path::Path, sync::Arc};
 
-use gpui::{AnyElement, SharedString};
+use gpui::{AnyElement, App, SharedString};
+use smallvec::{SmallVec, smallvec};
 
 pub mod app_data;
 pub mod calc_data;
+pub mod mpris_data;
+pub mod volume_data;
 pub mod weather_data;
 
 use crate::{
-    launcher::{ExecMode, Launcher, LauncherType, weather_launcher::WeatherData},
-    loader::utils::{AppData, ApplicationAction, ExecVariable},
-    utils::{config::HomeType, errors::SherlockError},
+    launcher::{
+        ExecMode, Launcher, LauncherType,
+        audio_launcher::{MprisCommand, MusicPlayerLauncher},
+        control_in_background,
+        volume_launcher::{VolumeCommand, VolumeData, VolumeLauncher},
+        weather_launcher::WeatherData,
+    },
+    loader::{
+        LauncherIcons,
+        utils::{ActionTable, AppData, ApplicationAction, ExecVariable},
+    },
+    utils::{
+        config::HomeType,
+        errors::SherlockError,
+        favicon::{FaviconPage, FaviconSource, url_host},
+        watch::StopToken,
+        websearch::engine_url,
+        windows::{OpenWindow, find_window},
+    },
 };
 
 use calc_data::CalcData;
@@ -47,17 +66,17 @@ macro_rules! renderable_enum {
                 }
             }
 
-            fn execute(&self, keyword: &str, variables: &[(SharedString, SharedString)]) -> Result<bool, SherlockError> {
+            fn execute(&self, keyword: &str, variables: &[(SharedString, SharedString)], cx: &App) -> Result<bool, SherlockError> {
                 match self {
-                    $(Self::$variant {inner, launcher} => inner.execute(launcher, keyword, variables)),*
+                    $(Self::$variant {inner, launcher} => inner.execute(launcher, keyword, variables, cx)),*
                 }
             }
 
-            fn execute_action(&self, action: &'a ApplicationAction) -> Result<bool, SherlockError> {
+            fn execute_action(&self, action: &'a ApplicationAction, keyword: &str, cx: &App) -> Result<bool, SherlockError> {
                 match self {
                     $(Self::$variant {launcher, ..} => {
                         let what = ExecMode::from_app_action(action, launcher);
-                        launcher.execute(&what, "", &[])
+                        launcher.execute(&what, keyword, &[], cx)
                     }),*
                 }
             }
@@ -79,6 +98,8 @@ macro_rules! renderable_enum {
             fn actions(&self) -> Option<Arc<[Arc<ApplicationAction>]>> {
                 match self {
                     Self::AppLike { inner, ..} => Some(inner.actions.clone()),
+                    Self::MusicLike { inner, ..} => Some(inner.actions()),
+                    Self::VolumeLike { inner, ..} => Some(inner.actions()),
                     _ => None
                 }
             }
@@ -111,6 +132,10 @@ macro_rules! renderable_enum {
                 }
             }
 
+            fn weight(&self) -> f32 {
+                self.launcher().weight
+            }
+
             fn launcher_type(&'a self) -> &'a LauncherType {
                 &self.launcher().launcher_type
             }
@@ -132,6 +157,12 @@ macro_rules! renderable_enum {
                     _ => None
                 }
             }
+
+            /// Whether [`based_show`](Self::based_show) can turn this child visible
+            /// independently of the search string
+            pub fn has_based_show(&self) -> bool {
+                matches!(self, Self::CalcLike { .. })
+            }
         }
     };
 }
@@ -140,26 +171,369 @@ renderable_enum! {
         AppLike(AppData),
         WeatherLike(WeatherData),
         CalcLike(CalcData),
+        MusicLike(MusicPlayerLauncher),
+        VolumeLike(VolumeData),
     }
 }
 
 impl RenderableChild {
-    pub fn get_exec(&self) -> Option<String> {
+    /// Name of an icon whose lookup has been deferred during loading
+    pub fn pending_icon(&self) -> Option<&str> {
+        match self {
+            Self::AppLike { inner, .. } => inner.icon_name.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Patches icons resolved in the background into the child
+    pub fn apply_icons(&mut self, resolved: &HashMap<String, Option<Arc<Path>>>) {
+        let Self::AppLike { inner, .. } = self else {
+            return;
+        };
+        if let Some(icon) = inner.icon_name.as_ref().and_then(|n| resolved.get(n)) {
+            inner.set_icon(icon.clone());
+        }
+    }
+
+    /// Gives precedence to the icons the launcher brings along over the global icon theme
+    pub fn apply_launcher_icons(&mut self, icons: &LauncherIcons) {
+        let Self::AppLike { inner, .. } = self else {
+            return;
+        };
+        let name = inner
+            .name
+            .as_deref()
+            .and_then(|item| icons.override_for(item))
+            .map(str::to_string)
+            .or_else(|| inner.icon_name.clone());
+        let Some(name) = name else {
+            return;
+        };
+        match icons.lookup(&name) {
+            Some(icon) => inner.set_icon(Some(icon)),
+            None => inner.defer_icon(&name),
+        }
+    }
+
+    /// Adds the keyword boosts a launcher declares for the child in its `boosts` argument, e.g.
+    /// `"boosts": { "Thunderbird": { "mail": 2 } }`
+    pub fn apply_launcher_boosts(&mut self, boosts: &HashMap<String, HashMap<String, f32>>) {
+        let Self::AppLike { inner, .. } = self else {
+            return;
+        };
+        if let Some(boosts) = inner
+            .name
+            .as_ref()
+            .and_then(|name| boosts.get(name.as_ref()))
+        {
+            inner.add_boosts(boosts);
+        }
+    }
+
+    /// How far the child moves up for the lowercase query
+    pub fn boost(&self, query: &str) -> f32 {
+        match self {
+            Self::AppLike { inner, .. } => inner.boost(query),
+            _ => 0.0,
+        }
+    }
+
+    /// Adds the actions of sherlock_actions.json and "Run as root" to the context menu
+    pub fn add_declared_actions(&mut self, table: &ActionTable) {
+        let Self::AppLike { inner, launcher } = self else {
+            return;
+        };
+        let kind = launcher.launcher_type.kind();
+        let name = inner.name.as_ref().map(|name| name.as_str());
+        let mut declared = table.actions_for(kind, name, inner.exec.as_deref());
+        declared.extend(table.root_action(kind, name, inner.exec.as_deref(), inner.terminal));
+        if declared.is_empty() {
+            return;
+        }
+        inner.actions = inner
+            .actions
+            .iter()
+            .cloned()
+            .chain(declared.into_iter().map(|mut action| {
+                if action.icon.is_none() {
+                    action.icon = inner.icon.clone();
+                }
+                Arc::new(action)
+            }))
+            .collect();
+    }
+
+    /// Page whose favicon replaces the icon of the tile
+    pub fn favicon_page(&self) -> Option<FaviconPage> {
+        let Self::AppLike { inner, launcher } = self else {
+            return None;
+        };
+        match &launcher.launcher_type {
+            LauncherType::Bookmark(bkm) => {
+                let (browser, network) = match bkm.favicons {
+                    FaviconSource::Disabled => return None,
+                    FaviconSource::Browser => (Some(bkm.target_browser.clone()), false),
+                    FaviconSource::Network => (None, true),
+                };
+                let url = inner.exec.as_deref()?;
+                Some(FaviconPage {
+                    host: url_host(url)?,
+                    url: url.to_string(),
+                    browser,
+                    network,
+                })
+            }
+            // Configured icons take precedence over the engine's favicon
+            LauncherType::Web(web)
+                if web.favicons != FaviconSource::Disabled
+                    && inner.icon.is_none()
+                    && inner.icon_name.is_none() =>
+            {
+                let url = engine_url(&web.engine);
+                let network = web.favicons == FaviconSource::Network;
+                Some(FaviconPage {
+                    host: url_host(url)?,
+                    url: url.to_string(),
+                    browser: web.browser.clone().filter(|_| !network),
+                    network,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Patches favicons resolved in the background into the child
+    pub fn apply_favicons(&mut self, resolved: &HashMap<String, Option<Arc<Path>>>) {
+        let Some(page) = self.favicon_page() else {
+            return;
+        };
+        let Self::AppLike { inner, .. } = self else {
+            return;
+        };
+        if let Some(Some(icon)) = resolved.get(&page.host) {
+            inner.set_icon(Some(icon.clone()));
+        }
+    }
+
+    /// Switches to the open window of an app, see [`AppData::focus_open_window`]
+    pub fn focus_open_window(&self, windows: &[OpenWindow]) -> bool {
+        let Self::AppLike { inner, launcher } = self else {
+            return false;
+        };
+        matches!(launcher.launcher_type, LauncherType::App(_))
+            && inner.focus_open_window(launcher, windows)
+    }
+
+    /// Action that starts another instance of an app whose window is already open
+    pub fn new_instance_action(&self, windows: &[OpenWindow]) -> Option<ApplicationAction> {
+        let Self::AppLike { inner, launcher } = self else {
+            return None;
+        };
+        if !matches!(launcher.launcher_type, LauncherType::App(_)) {
+            return None;
+        }
+        find_window(windows, inner.window_class()?)?;
+        inner.new_instance_action()
+    }
+
+    /// Labels of the launcher's `tag_start` and `tag_end`, with `{keyword}` standing for the
+    /// query. Tags that end up empty are left out.
+    pub fn tags(&self, keyword: &str) -> (Option<SharedString>, Option<SharedString>) {
+        let launcher = self.launcher();
+        let label = |tag: &Option<String>| {
+            let text = tag.as_deref()?.replace("{keyword}", keyword);
+            let text = text.trim();
+            (!text.is_empty()).then(|| SharedString::from(text.to_string()))
+        };
+        (label(&launcher.tag_start), label(&launcher.tag_end))
+    }
+
+    /// Whether the child can be run with the shortcut of its row, see
+    /// [`Shortcuts`](crate::ui::keybinds::Shortcuts)
+    pub fn has_shortcut(&self) -> bool {
+        self.launcher().shortcut
+    }
+
+    /// Question to answer before the child runs, see [`AppData::confirm`]
+    pub fn confirm(&self) -> Option<&str> {
+        match self {
+            Self::AppLike { inner, .. } => inner.confirm.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Whether the child opens in place on execution instead of running anything, like the
+    /// weather forecast
+    pub fn is_expandable(&self) -> bool {
+        matches!(self, Self::WeatherLike { inner, .. } if !inner.forecast.is_empty())
+    }
+
+    /// Expands or collapses the child, see [`is_expandable`](Self::is_expandable)
+    pub fn toggle_expanded(&mut self) {
+        if let Self::WeatherLike { inner, .. } = self {
+            inner.expanded = !inner.expanded;
+        }
+    }
+
+    /// Whether running the child shows the output of its command in the tile, see
+    /// [`SHOW_OUTPUT_METHOD`]
+    pub fn shows_output(&self) -> bool {
+        matches!(self, Self::AppLike { launcher, .. }
+            if launcher.method == SHOW_OUTPUT_METHOD
+                && matches!(launcher.launcher_type, LauncherType::Command(_)))
+    }
+
+    /// The output shown in the tile, `None` while it is collapsed
+    pub fn output(&self) -> Option<&SharedString> {
+        match self {
+            Self::AppLike { inner, .. } => inner.output.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn set_output(&mut self, output: Option<SharedString>) {
+        if let Self::AppLike { inner, .. } = self {
+            inner.output = output;
+        }
+    }
+
+    /// Runs the command of the child and waits for what it prints, until `stop` is stopped
+    pub fn capture_output(
+        &self,
+        keyword: &str,
+        variables: &[(SharedString, SharedString)],
+        stop: &StopToken,
+    ) -> Option<Result<String, SherlockError>> {
+        match self {
+            Self::AppLike { inner, .. } => Some(inner.capture_output(keyword, variables, stop)),
+            _ => None,
+        }
+    }
+
+    /// Context menu entry that copies the output shown in the tile
+    pub fn copy_output_action(&self) -> Option<ApplicationAction> {
+        self.output()?;
+        Some(ApplicationAction {
+            name: Some(SharedString::from("Copy Output")),
+            exit: false,
+            ..ApplicationAction::new(COPY_OUTPUT_METHOD)
+        })
+    }
+
+    /// Reacts to the left and right arrow keys, returns `false` for children that do not use
+    /// them, so the keys move the cursor of the search bar instead
+    pub fn arrow_key(&self, forward: bool, cx: &App) -> bool {
         match self {
-            Self::AppLike { inner, launcher } => inner.get_exec(launcher),
+            Self::MusicLike { inner, .. } => {
+                let command = if forward {
+                    MprisCommand::Next
+                } else {
+                    MprisCommand::Previous
+                };
+                let player = inner.player.clone();
+                control_in_background(cx, move || MusicPlayerLauncher::control(&player, command));
+                true
+            }
+            Self::VolumeLike { launcher, .. } => {
+                let step = VolumeLauncher::step_of(launcher);
+                let percent = if forward { step } else { -step };
+                control_in_background(cx, move || {
+                    VolumeData::control(VolumeCommand::Change(percent))
+                });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the child has been created by the given launcher
+    pub fn is_from(&self, launcher: &Arc<Launcher>) -> bool {
+        std::ptr::eq(self.launcher(), Arc::as_ptr(launcher))
+    }
+
+    /// Stops the watchers of the child's launcher once it was replaced
+    pub fn stop_watchers(&self) {
+        self.launcher().watchers.stop();
+    }
+
+    /// The command the child runs, the url for bookmarks
+    pub fn exec(&self) -> Option<&str> {
+        match self {
+            Self::AppLike { inner, .. } => inner.exec.as_deref(),
             _ => None,
         }
     }
+
+    /// Identifies the child among the pins and the `home_order`, the command it runs
+    pub fn pin_key(&self) -> Option<&str> {
+        self.exec()
+    }
+
+    /// Position at the top of the home view. Pins come first, followed by the entries of
+    /// `home_order`, which match either the title or the command. `None` for all other children.
+    pub fn home_rank(&self, pins: &[String], home_order: &[String]) -> Option<usize> {
+        let key = self.pin_key();
+        if let Some(i) = pins.iter().position(|pin| Some(pin.as_str()) == key) {
+            return Some(i);
+        }
+        let title = self.title();
+        home_order
+            .iter()
+            .position(|entry| {
+                Some(entry.as_str()) == key
+                    || title.is_some_and(|title| title.eq_ignore_ascii_case(entry))
+            })
+            .map(|i| pins.len() + i)
+    }
+
+    /// Context menu entry that pins the child to the home view or unpins it again
+    pub fn pin_action(&self, pins: &[String]) -> Option<ApplicationAction> {
+        let key = self.pin_key()?;
+        let name = if pins.iter().any(|pin| pin == key) {
+            "Unpin from Home"
+        } else {
+            "Pin to Home"
+        };
+        Some(ApplicationAction {
+            name: Some(SharedString::from(name)),
+            exit: false,
+            ..ApplicationAction::new(PIN_METHOD)
+        })
+    }
+
+    /// The name shown on the tile
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            Self::AppLike { inner, launcher } => {
+                inner.name.as_ref().or(launcher.display_name.as_ref())
+            }
+            _ => self.launcher().display_name.as_ref(),
+        }
+        .map(|name| name.as_ref())
+    }
 }
 
+/// Method of the context action that pins a child, handled by the window itself
+pub const PIN_METHOD: &str = "pin_home";
+/// `on_return` of command launchers whose commands print into their tile instead of detaching
+pub const SHOW_OUTPUT_METHOD: &str = "show_output";
+/// Method of the context action that copies the output of a command, handled by the window
+pub const COPY_OUTPUT_METHOD: &str = "copy_output";
+
 pub trait RenderableChildDelegate<'a> {
     fn render(&self, is_selected: bool) -> AnyElement;
     fn execute(
         &self,
         keyword: &str,
         variables: &[(SharedString, SharedString)],
+        cx: &App,
+    ) -> Result<bool, SherlockError>;
+    fn execute_action(
+        &self,
+        action: &'a ApplicationAction,
+        keyword: &str,
+        cx: &App,
     ) -> Result<bool, SherlockError>;
-    fn execute_action(&self, action: &'a ApplicationAction) -> Result<bool, SherlockError>;
     fn search(&'a self) -> &'a str;
     fn vars(&self) -> Option<&[ExecVariable]>;
     fn actions(&self) -> Option<Arc<[Arc<ApplicationAction>]>>;
@@ -171,6 +545,7 @@ pub trait LauncherValues<'a> {
     fn display_name(&self) -> Option<SharedString>;
     fn alias(&'a self) -> Option<&'a str>;
     fn priority(&self) -> f32;
+    fn weight(&self) -> f32;
     fn is_async(&self) -> bool;
     fn home(&self) -> HomeType;
     fn launcher_type(&'a self) -> &'a LauncherType;
@@ -183,45 +558,156 @@ pub trait RenderableChildImpl<'a> {
         launcher: &Arc<Launcher>,
         keyword: &str,
         variables: &[(SharedString, SharedString)],
+        cx: &App,
     ) -> Result<bool, SherlockError>;
     fn priority(&self, launcher: &Arc<Launcher>) -> f32;
     fn search(&'a self, launcher: &Arc<Launcher>) -> &'a str;
 }
 
+/// How well a search string matches the query. Later variants rank higher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchQuality {
+    /// Pattern only matches after tolerating a few typos
+    Typo,
+    /// Pattern is a subsequence somewhere in the search string
+    Fuzzy,
+    /// Fuzzy match starting at the beginning of a word
+    WordStart,
+    /// Pattern matches consecutive word initials of the name (`vsc` → Visual Studio Code)
+    Acronym,
+    /// Name starts with the pattern
+    Prefix,
+    /// Name equals the pattern
+    Exact,
+}
+
 pub trait SherlockSearch {
-    /// Both self and substring should already be lowercased to increase performance
-    fn fuzzy_match<'a>(&'a self, substring: &'a str) -> bool;
+    /// Rates the match of the pattern against the search string. Returns `None` if it does not
+    /// match at all. Both should already be lowercased.
+    fn match_quality(&self, pattern: &str) -> Option<MatchQuality>;
+    /// Fallback for failed matches: checks whether the pattern is within `max_typos` edits of the
+    /// text starting at any word of the search string.
+    fn typo_match(&self, pattern: &str, max_typos: usize) -> bool;
+}
+
+/// Number of typos tolerated for a query. Queries shorter than four characters have to match
+/// exactly, every four characters allow one more edit, capped by `tolerance`.
+pub fn allowed_typos(query: &str, tolerance: usize) -> usize {
+    (query.chars().count() / 4).min(tolerance)
 }
 
 impl<T: AsRef<str>> SherlockSearch for T {
-    fn fuzzy_match(&self, pattern: &str) -> bool {
-        let t_bytes = self.as_ref().as_bytes();
+    fn match_quality(&self, pattern: &str) -> Option<MatchQuality> {
+        let target = self.as_ref();
+        if pattern.is_empty() {
+            return Some(MatchQuality::Fuzzy);
+        }
+
+        // The name is everything in front of the first keyword separator
+        let name = target.split(';').next().unwrap_or(target);
+        if name == pattern {
+            return Some(MatchQuality::Exact);
+        }
+        if name.starts_with(pattern) {
+            return Some(MatchQuality::Prefix);
+        }
+        if acronym_match(name, pattern) {
+            return Some(MatchQuality::Acronym);
+        }
+
+        let t_bytes = target.as_bytes();
         let p_bytes = pattern.as_bytes();
+        let mut best = None;
+        let mut offset = 0;
+        while let Some(pos) = memchr::memchr(p_bytes[0], &t_bytes[offset..]) {
+            let pos = offset + pos;
+            if sequential_check(p_bytes, &t_bytes[pos..], 5) {
+                if is_word_start(t_bytes, pos) {
+                    return Some(MatchQuality::WordStart);
+                }
+                best = Some(MatchQuality::Fuzzy);
+            }
+            offset = pos + 1;
+            if offset >= t_bytes.len() {
+                break;
+            }
+        }
 
-        // Early return for empty bytes
-        if p_bytes.is_empty() {
-            return true;
+        // Queries of several words match if every word does, in any order ("recipes pasta")
+        if best.is_none() && pattern.contains(char::is_whitespace) {
+            let mut words = pattern.split_whitespace().peekable();
+            if words.peek().is_some() && words.all(|word| target.match_quality(word).is_some()) {
+                best = Some(MatchQuality::Fuzzy);
+            }
         }
-        if t_bytes.is_empty() {
+
+        best
+    }
+
+    fn typo_match(&self, pattern: &str, max_typos: usize) -> bool {
+        if max_typos == 0 || pattern.is_empty() {
             return false;
         }
 
-        let mut current_target = t_bytes;
+        let t_bytes = self.as_ref().as_bytes();
+        let p_bytes = pattern.as_bytes();
+        (0..t_bytes.len())
+            .filter(|pos| is_word_start(t_bytes, *pos) && t_bytes[*pos].is_ascii_alphanumeric())
+            .any(|pos| prefix_distance(p_bytes, &t_bytes[pos..], max_typos).is_some())
+    }
+}
 
-        // memchr find first search byte
-        while let Some(pos) = memchr::memchr(p_bytes[0], current_target) {
-            if sequential_check(p_bytes, &current_target[pos..], 5) {
-                return true;
-            }
-            // Move past the current match to find the next possible start
-            if pos + 1 >= current_target.len() {
-                break;
+/// Smallest edit distance (insertions, deletions, substitutions and transpositions) between the
+/// pattern and any prefix of the target. Returns `None` as soon as it has to exceed `max`.
+fn prefix_distance(pattern: &[u8], target: &[u8], max: usize) -> Option<usize> {
+    // Prefixes longer than this can never be within reach
+    let n = target.len().min(pattern.len() + max);
+    let target = &target[..n];
+
+    let mut prev2: SmallVec<[usize; 32]> = smallvec![0; n + 1];
+    let mut prev: SmallVec<[usize; 32]> = (0..=n).collect();
+    let mut curr: SmallVec<[usize; 32]> = smallvec![0; n + 1];
+
+    for i in 1..=pattern.len() {
+        curr[0] = i;
+        let mut row_min = i;
+        for j in 1..=n {
+            let cost = usize::from(pattern[i - 1] != target[j - 1]);
+            let mut dist = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && pattern[i - 1] == target[j - 2] && pattern[i - 2] == target[j - 1]
+            {
+                dist = dist.min(prev2[j - 2] + 1);
             }
-            current_target = &current_target[pos + 1..];
+            curr[j] = dist;
+            row_min = row_min.min(dist);
+        }
+        if row_min > max {
+            return None;
         }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev.iter().copied().min().filter(|dist| *dist <= max)
+}
+
+#[inline]
+fn is_word_start(target: &[u8], pos: usize) -> bool {
+    pos == 0 || !target[pos - 1].is_ascii_alphanumeric()
+}
 
-        false
+/// Checks whether the pattern appears within the initials of the words in `name`
+fn acronym_match(name: &str, pattern: &str) -> bool {
+    let pattern: SmallVec<[char; 16]> = pattern.chars().collect();
+    let initials: SmallVec<[char; 16]> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter_map(|word| word.chars().next())
+        .collect();
+
+    if pattern.len() > initials.len() {
+        return false;
     }
+    initials.windows(pattern.len()).any(|w| w == &pattern[..])
 }
 
 fn sequential_check(pattern: &[u8], target: &[u8], window_size: usize) -> bool {
@@ -251,3 +737,129 @@ fn sequential_check(pattern: &[u8], target: &[u8], window_size: usize) -> bool {
 
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_quality() {
+        let gimp = "gnu image manipulation program;gimp";
+        let vscode = "visual studio code;editor";
+
+        assert_eq!(gimp.match_quality("gimp"), Some(MatchQuality::Acronym));
+        assert_eq!(vscode.match_quality("vsc"), Some(MatchQuality::Acronym));
+        assert_eq!(
+            vscode.match_quality("studio"),
+            Some(MatchQuality::WordStart)
+        );
+        assert_eq!(vscode.match_quality("tud"), Some(MatchQuality::Fuzzy));
+        assert_eq!(vscode.match_quality("xyz"), None);
+        assert_eq!("firefox".match_quality(""), Some(MatchQuality::Fuzzy));
+
+        assert_eq!(
+            "code;editor".match_quality("code"),
+            Some(MatchQuality::Exact)
+        );
+        assert_eq!(vscode.match_quality("visual"), Some(MatchQuality::Prefix));
+        assert_eq!(
+            "pasta carbonara;https://example.org;recipes".match_quality("recipes pasta"),
+            Some(MatchQuality::Fuzzy)
+        );
+        assert_eq!(vscode.match_quality("code gimp"), None);
+
+        assert!(MatchQuality::Exact > MatchQuality::Prefix);
+        assert!(MatchQuality::Prefix > MatchQuality::Acronym);
+        assert!(MatchQuality::Acronym > MatchQuality::WordStart);
+        assert!(MatchQuality::WordStart > MatchQuality::Fuzzy);
+        assert!(MatchQuality::Fuzzy > MatchQuality::Typo);
+    }
+
+    #[test]
+    fn test_typo_match() {
+        let firefox = "firefox;browser;web";
+
+        assert_eq!(allowed_typos("fir", 1), 0);
+        assert_eq!(allowed_typos("fierfox", 1), 1);
+        assert_eq!(allowed_typos("fierfox browser", 2), 2);
+
+        assert!(firefox.typo_match("fierfox", 1));
+        assert!(firefox.typo_match("frefox", 1));
+        assert!(firefox.typo_match("browsr", 1));
+        assert!(firefox.typo_match("fierf", 1));
+        assert!(!firefox.typo_match("fierfox", 0));
+        assert!(!firefox.typo_match("chromium", 1));
+        assert!("visual studio code".typo_match("visual stduio", 1));
+    }
+
+    #[test]
+    fn test_home_rank() {
+        let launcher = Arc::new(Launcher::default());
+        let app = |name: &str, exec: &str| {
+            let mut inner = AppData::new();
+            inner.name = Some(SharedString::from(name.to_string()));
+            inner.exec = Some(exec.to_string());
+            RenderableChild::AppLike {
+                launcher: Arc::clone(&launcher),
+                inner,
+            }
+        };
+        let pins = vec![String::from("kitty")];
+        let home_order = vec![String::from("firefox"), String::from("Gimp")];
+
+        assert_eq!(app("Kitty", "kitty").home_rank(&pins, &home_order), Some(0));
+        assert_eq!(
+            app("Firefox", "firefox %u").home_rank(&pins, &home_order),
+            Some(1)
+        );
+        assert_eq!(app("GIMP", "gimp").home_rank(&pins, &home_order), Some(2));
+        assert_eq!(
+            app("Inkscape", "inkscape").home_rank(&pins, &home_order),
+            None
+        );
+
+        let action = app("Kitty", "kitty").pin_action(&pins).unwrap();
+        assert_eq!(
+            action.name.as_ref().map(|name| name.as_ref()),
+            Some("Unpin from Home")
+        );
+        assert_eq!(action.method, PIN_METHOD);
+    }
+
+    #[test]
+    fn test_keyword_boosts() {
+        let mut inner = AppData::new();
+        inner.name = Some(SharedString::from("Thunderbird"));
+        let mut child = RenderableChild::AppLike {
+            launcher: Arc::new(Launcher::default()),
+            inner,
+        };
+        let boosts: HashMap<String, HashMap<String, f32>> = serde_json::from_str(
+            r#"{"Thunderbird": {"Mail": 2, "calendar": 1}, "Firefox": {"web": 3}}"#,
+        )
+        .unwrap();
+        child.apply_launcher_boosts(&boosts);
+
+        assert_eq!(child.boost("mail"), 2.0);
+        assert_eq!(child.boost("mail calendar"), 3.0);
+        assert_eq!(child.boost("thunder"), 0.0);
+        assert_eq!(child.boost("web"), 0.0);
+    }
+
+    #[test]
+    fn test_tags() {
+        let child = RenderableChild::AppLike {
+            launcher: Arc::new(Launcher {
+                tag_start: Some(String::from("{keyword}")),
+                tag_end: Some(String::from("Search for {keyword}")),
+                ..Default::default()
+            }),
+            inner: AppData::new(),
+        };
+
+        let (start, end) = child.tags("rust");
+        assert_eq!(start, Some(SharedString::from("rust")));
+        assert_eq!(end, Some(SharedString::from("Search for rust")));
+        assert_eq!(child.tags("").0, None);
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use gpui::{
+    AnyElement, App, IntoElement, ObjectFit, ParentElement, SharedString, Styled, StyledImage, div,
+    img, px, rgb,
+};
+
+use crate::{
+    launcher::{
+        Launcher, audio_launcher::MusicPlayerLauncher, children::RenderableChildImpl,
+        control_in_background,
+    },
+    ui::typography::text_px,
+    utils::errors::SherlockError,
+};
+
+impl<'a> RenderableChildImpl<'a> for MusicPlayerLauncher {
+    fn execute(
+        &self,
+        _launcher: &Arc<Launcher>,
+        _keyword: &str,
+        _variables: &[(SharedString, SharedString)],
+        cx: &App,
+    ) -> Result<bool, SherlockError> {
+        // The tile is updated through the player's signals, so Sherlock stays open
+        let player = self.player.clone();
+        control_in_background(cx, move || MusicPlayerLauncher::playpause(&player));
+        Ok(false)
+    }
+    fn priority(&self, launcher: &Arc<Launcher>) -> f32 {
+        launcher.priority as f32
+    }
+    fn search(&self, _launcher: &Arc<Launcher>) -> &'a str {
+        ""
+    }
+    fn render(&self, _launcher: &Arc<Launcher>, is_selected: bool) -> AnyElement {
+        let metadata = &self.mpris.metadata;
+        let status = if self.mpris.playback_status == "Playing" {
+            "⏸"
+        } else {
+            "▶"
+        };
+        let artists = metadata
+            .artists
+            .as_ref()
+            .map(|artists| artists.join(", "))
+            .unwrap_or_default();
+
+        let status = div()
+            .text_size(text_px(24.))
+            .flex_none()
+            .child(status)
+            .into_any_element();
+        // The cover takes the place of the status, which moves to the end of the tile
+        let (background, leading, trailing) = match &self.art {
+            Some(art) => (
+                Some(
+                    div().absolute().inset_0().opacity(0.35).child(
+                        img(Arc::clone(&art.background))
+                            .size_full()
+                            .object_fit(ObjectFit::Cover),
+                    ),
+                ),
+                img(Arc::clone(&art.cover))
+                    .size(px(48.))
+                    .rounded_md()
+                    .flex_none()
+                    .into_any_element(),
+                Some(status),
+            ),
+            None => (None, status, None),
+        };
+
+        div()
+            .relative()
+            .overflow_hidden()
+            .px_4()
+            .py_2()
+            .w_full()
+            .flex()
+            .gap_5()
+            .items_center()
+            .children(background)
+            .child(leading)
+            .child(
+                div()
+                    .flex_col()
+                    .overflow_hidden()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(if is_selected {
+                                rgb(0xffffff)
+                            } else {
+                                rgb(0xcccccc)
+                            })
+                            .text_ellipsis()
+                            .whitespace_nowrap()
+                            .child(metadata.title.clone().unwrap_or_default()),
+                    )
+                    .flex_1()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(if is_selected {
+                                rgb(0x999999)
+                            } else {
+                                rgb(0x666666)
+                            })
+                            .text_ellipsis()
+                            .whitespace_nowrap()
+                            .child(match metadata.album.as_deref() {
+                                Some(album) if !album.is_empty() => format!("{artists} · {album}"),
+                                _ => artists,
+                            }),
+                    ),
+            )
+            .children(trailing)
+            .into_any_element()
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use gpui::{
+    AnyElement, App, InteractiveElement, IntoElement, ParentElement, SharedString, Styled, div, px,
+    relative, rgb,
+};
+
+use crate::{
+    launcher::{
+        Launcher,
+        children::RenderableChildImpl,
+        control_in_background,
+        volume_launcher::{VolumeCommand, VolumeData, VolumeLauncher},
+    },
+    ui::typography::text_px,
+    utils::errors::SherlockError,
+};
+
+impl<'a> RenderableChildImpl<'a> for VolumeData {
+    fn execute(
+        &self,
+        _launcher: &Arc<Launcher>,
+        _keyword: &str,
+        _variables: &[(SharedString, SharedString)],
+        cx: &App,
+    ) -> Result<bool, SherlockError> {
+        // The tile is updated once the mixer reports the change, so Sherlock stays open
+        control_in_background(cx, || VolumeData::control(VolumeCommand::ToggleMute));
+        Ok(false)
+    }
+    fn priority(&self, launcher: &Arc<Launcher>) -> f32 {
+        launcher.priority as f32
+    }
+    fn search(&self, _launcher: &Arc<Launcher>) -> &'a str {
+        ""
+    }
+    fn render(&self, launcher: &Arc<Launcher>, is_selected: bool) -> AnyElement {
+        let step = VolumeLauncher::step_of(launcher);
+        let percent = (self.level * 100.0).round();
+        let glyph = if self.muted {
+            "🔇"
+        } else if percent == 0.0 {
+            "🔈"
+        } else if percent < 50.0 {
+            "🔉"
+        } else {
+            "🔊"
+        };
+
+        div()
+            .px_4()
+            .py_2()
+            .w_full()
+            .flex()
+            .gap_5()
+            .items_center()
+            .on_scroll_wheel(move |event, _window, cx| {
+                let delta = event.delta.pixel_delta(px(16.)).y;
+                if delta == px(0.) {
+                    return;
+                }
+                let percent = if delta > px(0.) { step } else { -step };
+                // The mixer is run in the background, the watcher updates the tile
+                control_in_background(cx, move || {
+                    VolumeData::control(VolumeCommand::Change(percent))
+                });
+                // Keeps the list from scrolling along
+                cx.stop_propagation();
+            })
+            .child(div().text_size(text_px(24.)).flex_none().child(glyph))
+            .child(
+                div()
+                    .flex_1()
+                    .h(px(6.))
+                    .rounded_full()
+                    .bg(rgb(0x2a2a2a))
+                    .child(
+                        div()
+                            .h_full()
+                            .rounded_full()
+                            .w(relative(self.level.clamp(0.0, 1.0)))
+                            .bg(match (self.muted, is_selected) {
+                                (true, _) => rgb(0x555555),
+                                (false, true) => rgb(0xffffff),
+                                (false, false) => rgb(0xcccccc),
+                            }),
+                    ),
+            )
+            .child(
+                div()
+                    .w(px(40.))
+                    .flex_none()
+                    .text_sm()
+                    .text_color(if is_selected {
+                        rgb(0xffffff)
+                    } else {
+                        rgb(0xcccccc)
+                    })
+                    .child(format!("{percent}%")),
+            )
+            .into_any_element()
+    }
+}
@@ -1,13 +1,18 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use gpui::{
-    AnyElement, Image, ImageSource, IntoElement, ParentElement, SharedString, Styled, div, img,
-    linear_gradient, px,
+    AnyElement, App, Image, ImageSource, IntoElement, ParentElement, SharedString, Styled, div,
+    img, linear_gradient, px,
 };
 
 use crate::{
-    launcher::{Launcher, children::RenderableChildImpl, weather_launcher::WeatherData},
-    utils::errors::SherlockError,
+    launcher::{
+        Launcher, LauncherType,
+        children::RenderableChildImpl,
+        weather_launcher::{WeatherData, WeatherUnits},
+    },
+    ui::typography::text_px,
+    utils::{config::ConfigUnits, errors::SherlockError},
 };
 
 impl<'a> RenderableChildImpl<'a> for WeatherData {
@@ -16,6 +21,7 @@ impl<'a> RenderableChildImpl<'a> for WeatherData {
         _launcher: &Arc<Launcher>,
         _keyword: &str,
         _variables: &[(SharedString, SharedString)],
+        _cx: &App,
     ) -> Result<bool, SherlockError> {
         Ok(false)
     }
@@ -25,7 +31,51 @@ impl<'a> RenderableChildImpl<'a> for WeatherData {
     fn search(&self, _launcher: &Arc<Launcher>) -> &'a str {
         ""
     }
-    fn render(&self, _launcher: &Arc<Launcher>, _is_selected: bool) -> AnyElement {
+    fn render(&self, launcher: &Arc<Launcher>, _is_selected: bool) -> AnyElement {
+        let units = match &launcher.launcher_type {
+            LauncherType::Weather(weather) => weather.units,
+            _ => WeatherUnits::from_config(&ConfigUnits::default()),
+        };
+        let icon = |icon: Option<&Arc<Path>>, size: f32| match icon {
+            Some(icon) => img(Arc::clone(icon)).size(px(size)),
+            None => img(ImageSource::Image(Arc::new(Image::empty()))).size(px(size)),
+        };
+        let forecast = self.expanded.then(|| {
+            let hours = self.upcoming_hours(8).map(|hour| {
+                div()
+                    .flex()
+                    .flex_col()
+                    .items_center()
+                    .gap_1()
+                    .child(hour.time.format("%H:%M").to_string())
+                    .child(icon(hour.icon.as_ref(), 24.))
+                    .child(units.temperature(hour.temperature))
+            });
+            let days = self.forecast.iter().map(|day| {
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_5()
+                    .child(
+                        div()
+                            .w(px(80.))
+                            .child(day.date.format("%a, %d.%m.").to_string()),
+                    )
+                    .child(icon(day.icon.as_ref(), 24.))
+                    .child(format!(
+                        "{} / {}",
+                        units.temperature(day.max),
+                        units.temperature(day.min)
+                    ))
+            });
+            div()
+                .flex()
+                .flex_col()
+                .gap_3()
+                .child(div().flex().justify_between().children(hours))
+                .child(div().flex().flex_col().gap_1().children(days))
+        });
+
         div()
             .px_4()
             .py_2()
@@ -37,20 +87,25 @@ impl<'a> RenderableChildImpl<'a> for WeatherData {
             .flex_col()
             .gap_5()
             .items_center()
-            .text_size(px(12.0))
-            .child(self.format_str.clone())
+            .text_size(text_px(12.0))
+            .children(self.init.then(|| self.summary(&units)))
             .child(
                 div()
                     .flex()
                     .items_center()
                     .gap_5()
-                    .child(if let Some(icon) = self.icon.as_ref() {
-                        img(Arc::clone(&icon)).size(px(48.))
+                    .child(if self.icon.is_some() {
+                        icon(self.icon.as_ref(), 48.)
                     } else {
-                        img(ImageSource::Image(Arc::new(Image::empty()))).size(px(24.))
+                        icon(None, 24.)
                     })
-                    .child(div().text_size(px(40.0)).child(self.temperature.clone())),
+                    .children(self.init.then(|| {
+                        div()
+                            .text_size(text_px(40.0))
+                            .child(units.temperature(self.temperature))
+                    })),
             )
+            .children(forecast)
             .into_any_element()
     }
 }
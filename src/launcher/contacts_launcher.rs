@@ -0,0 +1,233 @@
+use gpui::SharedString;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::loader::utils::{AppData, ApplicationAction, construct_search};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+
+/// Contacts of vCard directories, as kept by khard or synced by vdirsyncer
+#[derive(Clone, Debug)]
+pub struct ContactsLauncher {
+    /// Directories searched for `.vcf` files, including their subdirectories
+    pub directories: Vec<PathBuf>,
+    /// Command that calls a number, with `{phone}` in place of it
+    pub call: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Contact {
+    pub name: String,
+    pub emails: Vec<String>,
+    pub phones: Vec<String>,
+    pub organization: String,
+}
+
+impl ContactsLauncher {
+    pub const DEFAULT_DIRECTORY: &str = "~/.contacts";
+    pub const DEFAULT_CALL: &str = "xdg-open 'tel:{phone}'";
+
+    /// Reads every vCard file of the directories, sorted by name
+    pub fn contacts(&self) -> Result<Vec<Contact>, SherlockError> {
+        let mut contacts = Vec::new();
+        for directory in &self.directories {
+            let pattern = directory.join("**").join("*.vcf");
+            let files = glob::glob(&pattern.to_string_lossy()).map_err(|e| {
+                sherlock_error!(
+                    SherlockErrorType::DirReadError(directory.display().to_string()),
+                    e.to_string()
+                )
+            })?;
+            for file in files.flatten() {
+                match std::fs::read_to_string(&file) {
+                    Ok(content) => contacts.extend(parse_vcards(&content)),
+                    Err(e) => eprintln!(
+                        "{}",
+                        sherlock_error!(
+                            SherlockErrorType::FileReadError(file.clone()),
+                            e.to_string()
+                        )
+                    ),
+                }
+            }
+        }
+        contacts.sort_by_key(|contact| contact.name.to_lowercase());
+        Ok(contacts)
+    }
+
+    /// The tile of a contact, which composes a mail or, without an address, calls. Every
+    /// address and number gets actions to use or copy it.
+    pub fn app_data(&self, contact: &Contact) -> AppData {
+        let mut inner = AppData::new();
+        inner.name = Some(SharedString::from(contact.name.clone()));
+        inner.generic_name = Some(SharedString::from(
+            [
+                contact.emails.first().map(String::as_str),
+                contact.phones.first().map(String::as_str),
+                Some(contact.organization.as_str()),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" · "),
+        ));
+        let keywords = contact
+            .emails
+            .iter()
+            .chain(&contact.phones)
+            .chain([&contact.organization])
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(";");
+        inner.search_string = construct_search(Some(&contact.name), &keywords, true);
+        inner.defer_icon("x-office-address-book");
+
+        let action = |label: String, exec: String| {
+            Arc::new(ApplicationAction {
+                name: Some(SharedString::from(label)),
+                exec: Some(exec),
+                ..ApplicationAction::new("command")
+            })
+        };
+        let mut actions = Vec::new();
+        for email in &contact.emails {
+            let quoted = email.replace('\'', r"'\''");
+            actions.push(action(
+                format!("Mail {email}"),
+                format!("xdg-open 'mailto:{quoted}'"),
+            ));
+            actions.push(action(
+                format!("Copy {email}"),
+                format!("wl-copy '{quoted}'"),
+            ));
+        }
+        for phone in &contact.phones {
+            let number = dial_number(phone);
+            actions.push(action(
+                format!("Call {phone}"),
+                self.call.replace("{phone}", &number),
+            ));
+            actions.push(action(
+                format!("Copy {phone}"),
+                format!("wl-copy '{number}'"),
+            ));
+        }
+        // The first address is mailed, or the first number called if there is none
+        inner.exec = actions.first().and_then(|action| action.exec.clone());
+        inner.actions = Arc::from(actions);
+        inner
+    }
+}
+
+/// The number without the characters a dialer does not understand, which also keeps it safe
+/// to put in a command
+fn dial_number(phone: &str) -> String {
+    phone
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '+' | '*' | '#'))
+        .collect()
+}
+
+/// Contacts of a vCard file, which may hold several of them
+fn parse_vcards(content: &str) -> Vec<Contact> {
+    // Lines starting with a space or tab continue the previous one
+    let unfolded = content
+        .replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+    let mut contacts = Vec::new();
+    let mut current: Option<Contact> = None;
+    for line in unfolded.lines() {
+        let Some((property, value)) = line.split_once(':') else {
+            continue;
+        };
+        // `item1.EMAIL;TYPE=work` is an email address
+        let name = property.split(';').next().unwrap_or_default();
+        let name = name.rsplit('.').next().unwrap_or_default().to_uppercase();
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("vcard") => {
+                current = Some(Contact::default())
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("vcard") => {
+                contacts.extend(current.take().filter(|contact| !contact.name.is_empty()))
+            }
+            ("FN", Some(contact)) => contact.name = unescape(value),
+            // `Doe;Jane;;;` as fallback for cards without a formatted name
+            ("N", Some(contact)) if contact.name.is_empty() => {
+                let parts: Vec<String> = value.split(';').map(unescape).collect();
+                contact.name = parts
+                    .iter()
+                    .take(2)
+                    .rev()
+                    .filter(|part| !part.is_empty())
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+            }
+            ("EMAIL", Some(contact)) if !value.is_empty() => contact.emails.push(unescape(value)),
+            // vCard 4 allows numbers as `tel:` uri
+            ("TEL", Some(contact)) if !value.is_empty() => {
+                let value = value.strip_prefix("tel:").unwrap_or(value);
+                contact.phones.push(unescape(value));
+            }
+            ("ORG", Some(contact)) => {
+                contact.organization = value.split(';').next().map(unescape).unwrap_or_default()
+            }
+            _ => {}
+        }
+    }
+    contacts
+}
+
+/// Resolves the escaped `\,`, `\;`, `\\` and `\n` of a value
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.trim().chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(escaped) => text.push(escaped),
+            None => {}
+        }
+    }
+    text
+}
+
+#[test]
+fn test_parse_vcards() {
+    let content = "BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Jane Doe\r\nORG:Example\\, Inc.;Research\r\n\
+item1.EMAIL;TYPE=work:jane@exam\r\n ple.com\r\nTEL;VALUE=uri:tel:+49 (30) 1234-5678\r\n\
+END:VCARD\r\nBEGIN:VCARD\r\nVERSION:3.0\r\nN:Roe;Richard;;;\r\nEND:VCARD\r\n";
+    let contacts = parse_vcards(content);
+    assert_eq!(contacts.len(), 2);
+    assert_eq!(contacts[0].emails, vec!["jane@example.com"]);
+    assert_eq!(contacts[0].phones, vec!["+49 (30) 1234-5678"]);
+    assert_eq!(contacts[0].organization, "Example, Inc.");
+    assert_eq!(contacts[1].name, "Richard Roe");
+
+    let launcher = ContactsLauncher {
+        directories: Vec::new(),
+        call: String::from(ContactsLauncher::DEFAULT_CALL),
+    };
+    let inner = launcher.app_data(&contacts[0]);
+    assert_eq!(
+        inner.exec.as_deref(),
+        Some("xdg-open 'mailto:jane@example.com'")
+    );
+    assert_eq!(
+        inner.generic_name,
+        Some(SharedString::from(
+            "jane@example.com · +49 (30) 1234-5678 · Example, Inc."
+        ))
+    );
+    assert_eq!(inner.actions.len(), 4);
+    assert_eq!(
+        inner.actions[2].exec.as_deref(),
+        Some("xdg-open 'tel:+493012345678'")
+    );
+}
@@ -0,0 +1,254 @@
+use gpui::SharedString;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::audio_device_launcher::command_output;
+use crate::loader::utils::{AppData, construct_search};
+
+/// Display layouts, from the profiles of kanshi and layouts saved as `wlr-randr` arguments.
+/// The layout whose outputs are in place is marked as current.
+#[derive(Clone, Debug)]
+pub struct DisplayLauncher {
+    /// Config of kanshi, read if it exists
+    pub kanshi_config: PathBuf,
+    /// Names and `wlr-randr` arguments, like `--output eDP-1 --off --output DP-1 --on`
+    pub layouts: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DisplayLayout {
+    pub name: String,
+    pub exec: String,
+    /// Outputs, by name, description or `*`, and whether the layout enables them
+    pub outputs: Vec<(String, bool)>,
+    /// Profiles of kanshi only apply while exactly their outputs are connected
+    pub exhaustive: bool,
+}
+
+/// An output as reported by `wlr-randr --json`
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Output {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub make: String,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub serial: String,
+    pub enabled: bool,
+}
+impl Output {
+    /// Whether the output is the one a layout refers to, by name or description
+    fn matches(&self, criteria: &str) -> bool {
+        criteria == "*"
+            || criteria == self.name
+            || criteria == self.description
+            || criteria == format!("{} {} {}", self.make, self.model, self.serial)
+    }
+}
+
+impl DisplayLauncher {
+    pub const DEFAULT_KANSHI_CONFIG: &str = "~/.config/kanshi/config";
+
+    pub fn layouts(&self) -> Vec<DisplayLayout> {
+        let mut layouts = std::fs::read_to_string(&self.kanshi_config)
+            .map(|config| parse_kanshi(&config))
+            .unwrap_or_default();
+        layouts.extend(
+            self.layouts
+                .iter()
+                .map(|(name, args)| parse_wlr_randr(name, args)),
+        );
+        layouts
+    }
+
+    /// Outputs as they are now, empty without `wlr-randr`
+    pub fn outputs() -> Vec<Output> {
+        command_output("wlr-randr", &["--json"])
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// The tile of the layout, marked as current once the outputs were read
+    pub fn app_data(layout: &DisplayLayout) -> AppData {
+        let mut inner = AppData {
+            name: Some(SharedString::from(layout.name.clone())),
+            generic_name: Some(SharedString::from(
+                layout
+                    .outputs
+                    .iter()
+                    .map(|(output, enabled)| match enabled {
+                        true => output.clone(),
+                        false => format!("{output} off"),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" · "),
+            )),
+            search_string: construct_search(Some(&layout.name), "display;layout", true),
+            exec: Some(layout.exec.clone()),
+            ..AppData::new()
+        };
+        inner.defer_icon("video-display");
+        inner
+    }
+
+    pub fn mark_current(inner: &mut AppData) {
+        inner.badges = Arc::from([SharedString::from("Current")]);
+    }
+}
+
+/// Whether the outputs are laid out as the layout wants them
+pub fn is_current(layout: &DisplayLayout, outputs: &[Output]) -> bool {
+    if outputs.is_empty() || layout.outputs.is_empty() {
+        return false;
+    }
+    let in_place = layout.outputs.iter().all(|(criteria, enabled)| {
+        outputs
+            .iter()
+            .any(|output| output.matches(criteria) && output.enabled == *enabled)
+    });
+    let covered = !layout.exhaustive
+        || outputs.iter().all(|output| {
+            layout
+                .outputs
+                .iter()
+                .any(|(criteria, _)| output.matches(criteria))
+        });
+    in_place && covered
+}
+
+/// Named profiles of a kanshi config, which are switched to through `kanshictl`
+fn parse_kanshi(config: &str) -> Vec<DisplayLayout> {
+    let mut layouts = Vec::new();
+    let mut current: Option<DisplayLayout> = None;
+    for line in config.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let words = split_words(line);
+        match words.first().map(String::as_str) {
+            // Profiles without a name cannot be switched to
+            Some("profile") => {
+                current = words
+                    .get(1)
+                    .filter(|name| *name != "{")
+                    .map(|name| DisplayLayout {
+                        name: name.clone(),
+                        exec: format!("kanshictl switch '{}'", name.replace('\'', r"'\''")),
+                        outputs: Vec::new(),
+                        exhaustive: true,
+                    });
+            }
+            Some("output") => {
+                if let (Some(layout), Some(criteria)) = (current.as_mut(), words.get(1)) {
+                    let enabled = !words.iter().any(|word| word == "disable");
+                    layout.outputs.push((criteria.clone(), enabled));
+                }
+            }
+            Some("}") => layouts.extend(current.take()),
+            _ => {}
+        }
+    }
+    layouts
+}
+
+/// A layout saved as arguments of `wlr-randr`
+fn parse_wlr_randr(name: &str, args: &str) -> DisplayLayout {
+    let mut outputs: Vec<(String, bool)> = Vec::new();
+    let words = split_words(args);
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        match word.as_str() {
+            "--output" => outputs.extend(words.next().map(|output| (output.clone(), true))),
+            "--off" | "--on" => {
+                if let Some(output) = outputs.last_mut() {
+                    output.1 = word == "--on";
+                }
+            }
+            _ => {}
+        }
+    }
+    DisplayLayout {
+        name: name.to_string(),
+        exec: format!("wlr-randr {args}"),
+        outputs,
+        exhaustive: false,
+    }
+}
+
+/// Words of the line, where double quotes keep words with spaces together
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+#[test]
+fn test_parse_layouts() {
+    let config = r#"
+profile {
+    output eDP-1 enable
+}
+# Both screens at the desk
+profile docked {
+    output eDP-1 disable
+    output "Dell Inc. DELL U2720Q 8XYZ123" mode 3840x2160 position 0,0
+}
+profile laptop {
+    output eDP-1 enable scale 1.5
+}
+"#;
+    let layouts = parse_kanshi(config);
+    assert_eq!(layouts.len(), 2);
+    assert_eq!(layouts[0].exec, "kanshictl switch 'docked'");
+    assert_eq!(
+        layouts[0].outputs,
+        vec![
+            (String::from("eDP-1"), false),
+            (String::from("Dell Inc. DELL U2720Q 8XYZ123"), true)
+        ]
+    );
+
+    let outputs: Vec<Output> = serde_json::from_str(
+        r#"[
+            { "name": "eDP-1", "make": "BOE", "model": "0x095F", "serial": "", "enabled": false },
+            { "name": "DP-3", "make": "Dell Inc.", "model": "DELL U2720Q", "serial": "8XYZ123",
+              "enabled": true }
+        ]"#,
+    )
+    .unwrap();
+    assert!(is_current(&layouts[0], &outputs));
+    assert!(!is_current(&layouts[1], &outputs));
+
+    let laptop = parse_wlr_randr("Laptop only", "--output eDP-1 --on --output DP-3 --off");
+    assert_eq!(
+        laptop.exec,
+        "wlr-randr --output eDP-1 --on --output DP-3 --off"
+    );
+    assert!(!is_current(&laptop, &outputs));
+    let mut inner = DisplayLauncher::app_data(&laptop);
+    assert_eq!(
+        inner.generic_name,
+        Some(SharedString::from("eDP-1 · DP-3 off"))
+    );
+    assert!(inner.badges.is_empty());
+    DisplayLauncher::mark_current(&mut inner);
+    assert_eq!(inner.badges.len(), 1);
+}
@@ -0,0 +1,279 @@
+use gpui::SharedString;
+use std::sync::Arc;
+use std::time::Duration;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedValue, Value};
+
+use crate::loader::utils::{AppData, construct_search};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+use crate::utils::watch::StopToken;
+
+/// Notification daemons whose do-not-disturb state is toggled through their own interface
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationDaemon {
+    SwayNc,
+    /// Pauses notifications through its `paused` property
+    Dunst,
+    /// Enables a mode of its config that hides notifications
+    Mako,
+}
+impl NotificationDaemon {
+    const ALL: [Self; 3] = [Self::SwayNc, Self::Dunst, Self::Mako];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::SwayNc => "SwayNC",
+            Self::Dunst => "dunst",
+            Self::Mako => "mako",
+        }
+    }
+    /// Bus name, object path and interface of the daemon
+    fn destination(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Self::SwayNc => (
+                "org.erikreider.swaync.cc",
+                "/org/erikreider/swaync/cc",
+                "org.erikreider.swaync.cc",
+            ),
+            Self::Dunst => (
+                "org.freedesktop.Notifications",
+                "/org/freedesktop/Notifications",
+                "org.dunstproject.cmd0",
+            ),
+            Self::Mako => (
+                "org.freedesktop.Notifications",
+                "/fr/emersion/Mako",
+                "fr.emersion.Mako",
+            ),
+        }
+    }
+}
+
+/// Toggles do-not-disturb of the running notification daemon
+#[derive(Clone, Debug)]
+pub struct DndLauncher {
+    /// Mode of the mako config that hides notifications, like `[mode=do-not-disturb]
+    /// invisible=1`
+    pub mako_mode: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DndState {
+    pub daemon: NotificationDaemon,
+    pub enabled: bool,
+}
+
+impl DndLauncher {
+    pub const DEFAULT_MAKO_MODE: &str = "do-not-disturb";
+
+    /// The state of the first daemon that answers
+    pub fn state(&self) -> Result<DndState, SherlockError> {
+        let conn = session()?;
+        self.state_on(&conn)
+    }
+
+    fn state_on(&self, conn: &Connection) -> Result<DndState, SherlockError> {
+        let mut error = None;
+        for daemon in NotificationDaemon::ALL {
+            match self.enabled(conn, daemon) {
+                Ok(enabled) => return Ok(DndState { daemon, enabled }),
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(error.unwrap_or_else(|| {
+            sherlock_error!(
+                SherlockErrorType::DBusMessageSendError(String::from("notification daemon")),
+                ""
+            )
+        }))
+    }
+
+    fn enabled(
+        &self,
+        conn: &Connection,
+        daemon: NotificationDaemon,
+    ) -> Result<bool, SherlockError> {
+        let (_, _, interface) = daemon.destination();
+        match daemon {
+            NotificationDaemon::SwayNc => call(conn, daemon, None, "GetDnd", &()),
+            NotificationDaemon::Dunst => {
+                let paused: OwnedValue = call(
+                    conn,
+                    daemon,
+                    Some("org.freedesktop.DBus.Properties"),
+                    "Get",
+                    &(interface, "paused"),
+                )?;
+                bool::try_from(paused).map_err(|e| {
+                    sherlock_error!(
+                        SherlockErrorType::DBusMessageSendError(String::from("Get to dunst")),
+                        e.to_string()
+                    )
+                })
+            }
+            NotificationDaemon::Mako => {
+                let modes: Vec<String> = call(conn, daemon, None, "ListModes", &())?;
+                Ok(modes.contains(&self.mako_mode))
+            }
+        }
+    }
+
+    /// Turns do-not-disturb off if it is on and on otherwise
+    pub fn toggle(&self) -> Result<(), SherlockError> {
+        let conn = session()?;
+        let state = self.state_on(&conn)?;
+        let (daemon, enabled) = (state.daemon, !state.enabled);
+        let (_, _, interface) = daemon.destination();
+        match daemon {
+            NotificationDaemon::SwayNc => call(&conn, daemon, None, "SetDnd", &(enabled,)),
+            NotificationDaemon::Dunst => call(
+                &conn,
+                daemon,
+                Some("org.freedesktop.DBus.Properties"),
+                "Set",
+                &(interface, "paused", Value::from(enabled)),
+            ),
+            NotificationDaemon::Mako => {
+                let modes: Vec<String> = call(&conn, daemon, None, "ListModes", &())?;
+                let modes = with_mode(modes, &self.mako_mode, enabled);
+                call(&conn, daemon, None, "SetModes", &(modes,))
+            }
+        }
+    }
+
+    pub fn app_data(state: DndState) -> AppData {
+        let mut inner = Self::placeholder();
+        Self::apply_state(&mut inner, state);
+        inner
+    }
+
+    /// Sets the parts of the tile that change when do-not-disturb is toggled
+    pub fn apply_state(inner: &mut AppData, state: DndState) {
+        let label = if state.enabled { "On" } else { "Off" };
+        inner.generic_name = Some(SharedString::from(format!(
+            "{label} · {}",
+            state.daemon.label()
+        )));
+        inner.badges = match state.enabled {
+            true => Arc::from([SharedString::from("Silenced")]),
+            false => Arc::from([]),
+        };
+        if state.enabled {
+            inner.defer_icon("notifications-disabled");
+        } else {
+            inner.defer_icon("notifications");
+        }
+    }
+
+    /// Calls `on_change` with the state, then again whenever it changed, until it returns
+    /// `false` or the watcher is stopped. Not every daemon announces changes, so the state is
+    /// polled. Blocks the calling thread, fails if no daemon answers.
+    pub fn watch(
+        &self,
+        stop: &StopToken,
+        mut on_change: impl FnMut(DndState) -> bool,
+    ) -> Result<(), SherlockError> {
+        let conn = session()?;
+        let mut last = self.state_on(&conn)?;
+        if !on_change(last) {
+            return Ok(());
+        }
+        while !stop.is_stopped() {
+            std::thread::sleep(Duration::from_secs(2));
+            match self.state_on(&conn) {
+                Ok(state) if state != last => {
+                    last = state;
+                    if !on_change(state) {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Shown until a daemon answered
+    pub fn placeholder() -> AppData {
+        let mut inner = AppData {
+            name: Some(SharedString::from("Do Not Disturb")),
+            search_string: construct_search(
+                Some("Do Not Disturb"),
+                "dnd;notifications;silence",
+                true,
+            ),
+            ..AppData::new()
+        };
+        inner.defer_icon("notifications");
+        inner
+    }
+}
+
+fn session() -> Result<Connection, SherlockError> {
+    Connection::session()
+        .map_err(|e| sherlock_error!(SherlockErrorType::DBusConnectionError, e.to_string()))
+}
+
+/// Calls a method of the daemon, on `interface` instead of its own one if given
+fn call<B, R>(
+    conn: &Connection,
+    daemon: NotificationDaemon,
+    interface: Option<&str>,
+    method: &str,
+    body: &B,
+) -> Result<R, SherlockError>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+    R: serde::de::DeserializeOwned + zbus::zvariant::Type,
+{
+    let (destination, path, own) = daemon.destination();
+    let proxy = Proxy::new(conn, destination, path, interface.unwrap_or(own)).map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::DBusMessageConstructError(format!(
+                "{method} for {}",
+                daemon.label()
+            )),
+            e.to_string()
+        )
+    })?;
+    proxy.call(method, body).map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::DBusMessageSendError(format!("{method} to {}", daemon.label())),
+            e.to_string()
+        )
+    })
+}
+
+/// The active modes of mako with `mode` added or removed
+fn with_mode(mut modes: Vec<String>, mode: &str, enabled: bool) -> Vec<String> {
+    modes.retain(|active| active != mode);
+    if enabled {
+        modes.push(mode.to_string());
+    }
+    modes
+}
+
+#[test]
+fn test_dnd_state() {
+    let modes = vec![String::from("default")];
+    let modes = with_mode(modes, "do-not-disturb", true);
+    assert_eq!(modes, vec!["default", "do-not-disturb"]);
+    assert_eq!(with_mode(modes, "do-not-disturb", false), vec!["default"]);
+
+    let mut inner = DndLauncher::app_data(DndState {
+        daemon: NotificationDaemon::Dunst,
+        enabled: false,
+    });
+    assert_eq!(inner.generic_name, Some(SharedString::from("Off · dunst")));
+    assert!(inner.badges.is_empty());
+    DndLauncher::apply_state(
+        &mut inner,
+        DndState {
+            daemon: NotificationDaemon::Dunst,
+            enabled: true,
+        },
+    );
+    assert_eq!(inner.generic_name, Some(SharedString::from("On · dunst")));
+    assert_eq!(inner.badges.len(), 1);
+}
@@ -0,0 +1,331 @@
+use chrono::DateTime;
+use futures::future::join_all;
+use gpui::SharedString;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::loader::utils::{AppData, construct_search};
+use crate::sherlock_error;
+use crate::utils::cache::BinaryCache;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+use crate::utils::paths::get_cache_dir;
+
+const ICON: &str = "application-rss+xml";
+
+/// Set while the feeds are fetched, so reloads do not start a second fetch
+static FETCHING: AtomicBool = AtomicBool::new(false);
+
+/// Recent headlines of RSS and Atom feeds. The headlines of the last fetch are shown while the
+/// feeds are fetched again in the background.
+#[derive(Clone, Debug)]
+pub struct FeedLauncher {
+    pub feeds: Vec<String>,
+    /// Headlines shown across all feeds
+    pub limit: usize,
+    /// Minutes until the feeds are fetched again
+    pub update_interval: u64,
+    pub browser: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedEntry {
+    pub title: String,
+    pub url: String,
+    /// Title of the feed the entry belongs to
+    pub source: String,
+    /// Unix timestamp of the publication
+    pub published: Option<i64>,
+}
+
+impl FeedLauncher {
+    pub const DEFAULT_LIMIT: usize = 30;
+    pub const DEFAULT_UPDATE_INTERVAL: u64 = 30;
+
+    fn cache_file(&self) -> Result<PathBuf, SherlockError> {
+        let key = crc32fast::hash(self.feeds.join("\n").as_bytes());
+        Ok(get_cache_dir()?.join(format!("feeds-{key:08x}.bin")))
+    }
+
+    /// The headlines of the last fetch, `None` if there was none. The flag tells whether they
+    /// are due for a new fetch.
+    pub fn cached_entries(&self) -> (Option<Vec<FeedEntry>>, bool) {
+        let Ok(cache) = self.cache_file() else {
+            return (None, false);
+        };
+        let lifetime = Duration::from_secs(self.update_interval * 60);
+        let outdated = std::fs::metadata(&cache)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_none_or(|age| age > lifetime);
+        (BinaryCache::read(&cache).ok(), outdated)
+    }
+
+    /// Fetches all feeds at once and caches their newest headlines, `None` if another fetch is
+    /// running already. Feeds that fail are skipped, unless all of them do.
+    pub async fn fetch_entries(&self) -> Option<Result<Vec<FeedEntry>, SherlockError>> {
+        if FETCHING.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        let fetched = join_all(self.feeds.iter().map(|url| fetch_feed(url))).await;
+        let mut entries = Vec::new();
+        let mut errors = Vec::new();
+        for result in fetched {
+            match result {
+                Ok(feed) => entries.extend(feed),
+                Err(e) => errors.push(e),
+            }
+        }
+        let result = if entries.is_empty() && !errors.is_empty() {
+            Err(errors.remove(0))
+        } else {
+            errors.iter().for_each(|e| eprintln!("{e}"));
+            newest(&mut entries, self.limit);
+            self.cache_file()
+                .and_then(|cache| BinaryCache::write(cache, &entries))
+                .map(|_| entries)
+        };
+        FETCHING.store(false, Ordering::Release);
+        Some(result)
+    }
+
+    /// The tile of a headline, which opens the article
+    pub fn app_data(entry: &FeedEntry, now: i64) -> AppData {
+        let age = entry.published.map(|published| age(now - published));
+        let mut inner = AppData {
+            name: Some(SharedString::from(entry.title.clone())),
+            generic_name: Some(SharedString::from(
+                [Some(entry.source.as_str()), age.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" · "),
+            )),
+            search_string: construct_search(Some(&entry.title), &entry.source, true),
+            exec: Some(entry.url.clone()),
+            ..AppData::new()
+        };
+        inner.defer_icon(ICON);
+        inner
+    }
+
+    /// Shown until the first fetch finished
+    pub fn placeholder() -> AppData {
+        let mut inner = AppData {
+            name: Some(SharedString::from("Fetching feeds…")),
+            search_string: String::from("feeds"),
+            ..AppData::new()
+        };
+        inner.defer_icon(ICON);
+        inner
+    }
+}
+
+/// Current time as unix timestamp
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs() as i64)
+}
+
+async fn fetch_feed(url: &str) -> Result<Vec<FeedEntry>, SherlockError> {
+    let error =
+        |e: String| sherlock_error!(SherlockErrorType::HttpRequestError(format!("GET {url}")), e);
+    // Some feeds refuse requests without a user agent
+    let response = reqwest::Client::new()
+        .get(url)
+        .header("User-Agent", "sherlock")
+        .send()
+        .await
+        .map_err(|e| error(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(error(response.status().to_string()));
+    }
+    let body = response.text().await.map_err(|e| error(e.to_string()))?;
+    Ok(parse_feed(&body))
+}
+
+/// Sorts the entries from newest to oldest and keeps the first `limit` ones. Entries without a
+/// date go last.
+fn newest(entries: &mut Vec<FeedEntry>, limit: usize) {
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.published));
+    entries.truncate(limit);
+}
+
+/// Entries of an RSS or Atom document. Lenient on purpose, as feeds in the wild often are not
+/// valid XML.
+fn parse_feed(xml: &str) -> Vec<FeedEntry> {
+    let mut items = elements(xml, "item");
+    let atom = items.is_empty();
+    if atom {
+        items = elements(xml, "entry");
+    }
+    // The first title outside of the entries names the feed
+    let head = items
+        .first()
+        .and_then(|(tag, _)| xml.find(tag))
+        .map_or(xml, |start| &xml[..start]);
+    let source = first_text(head, "title").unwrap_or_default();
+
+    items
+        .iter()
+        .filter_map(|(_, body)| {
+            let url = if atom {
+                elements(body, "link")
+                    .into_iter()
+                    .find(|(tag, _)| attribute(tag, "rel").is_none_or(|rel| rel == "alternate"))
+                    .and_then(|(tag, _)| attribute(tag, "href"))
+                    .map(decode)
+            } else {
+                first_text(body, "link")
+            }?;
+            let published = ["pubDate", "published", "updated", "dc:date"]
+                .iter()
+                .find_map(|name| first_text(body, name))
+                .and_then(|date| {
+                    DateTime::parse_from_rfc2822(&date)
+                        .or_else(|_| DateTime::parse_from_rfc3339(&date))
+                        .ok()
+                })
+                .map(|date| date.timestamp());
+            Some(FeedEntry {
+                title: first_text(body, "title").unwrap_or_else(|| url.clone()),
+                url,
+                source: source.clone(),
+                published,
+            })
+        })
+        .collect()
+}
+
+/// Opening tags and contents of the `<name>` elements in the xml
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<(&'a str, &'a str)> {
+    let close = format!("</{name}>");
+    let mut found = Vec::new();
+    let mut pos = 0;
+    while let Some((start, end)) = open_tag(xml, name, pos) {
+        let tag = &xml[start..end];
+        if tag.ends_with("/>") {
+            found.push((tag, ""));
+            pos = end;
+            continue;
+        }
+        let Some(len) = xml[end..].find(&close) else {
+            break;
+        };
+        found.push((tag, &xml[end..end + len]));
+        pos = end + len + close.len();
+    }
+    found
+}
+
+/// Start and end of the next opening tag `<name …>` from `from` on
+fn open_tag(xml: &str, name: &str, from: usize) -> Option<(usize, usize)> {
+    let pattern = format!("<{name}");
+    let mut pos = from;
+    loop {
+        let start = pos + xml[pos..].find(&pattern)?;
+        let after = start + pattern.len();
+        // `<link>` must not match `<linked>`
+        match xml[after..].chars().next() {
+            Some(c) if c == '>' || c == '/' || c.is_whitespace() => {
+                let end = after + xml[after..].find('>')? + 1;
+                return Some((start, end));
+            }
+            _ => pos = after,
+        }
+    }
+}
+
+fn first_text(xml: &str, name: &str) -> Option<String> {
+    let (_, content) = elements(xml, name).into_iter().next()?;
+    let content = content.trim();
+    let text = match content
+        .strip_prefix("<![CDATA[")
+        .and_then(|inner| inner.strip_suffix("]]>"))
+    {
+        Some(raw) => raw.trim().to_string(),
+        None => decode(content),
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    ['"', '\''].into_iter().find_map(|quote| {
+        let pattern = format!(" {name}={quote}");
+        let start = tag.find(&pattern)? + pattern.len();
+        let len = tag[start..].find(quote)?;
+        Some(&tag[start..start + len])
+    })
+}
+
+/// Resolves the predefined entities of XML, `&amp;` last so `&amp;lt;` stays `&lt;`
+fn decode(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Seconds since the publication, e.g. `3h ago`
+pub(super) fn age(seconds: i64) -> String {
+    match seconds {
+        ..60 => String::from("just now"),
+        60..3_600 => format!("{}m ago", seconds / 60),
+        3_600..86_400 => format!("{}h ago", seconds / 3_600),
+        _ => format!("{}d ago", seconds / 86_400),
+    }
+}
+
+#[test]
+fn test_parse_feed() {
+    let rss = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel>
+  <title>This Week in Rust</title>
+  <link>https://this-week-in-rust.org/</link>
+  <item>
+    <title><![CDATA[This Week in Rust 600]]></title>
+    <link>https://this-week-in-rust.org/blog/600/</link>
+    <pubDate>Wed, 04 Jun 2025 04:00:00 +0000</pubDate>
+  </item>
+  <item>
+    <title>Tom &amp; Jerry</title>
+    <link>https://this-week-in-rust.org/blog/599/</link>
+  </item>
+</channel></rss>"#;
+    let entries = parse_feed(rss);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].title, "This Week in Rust 600");
+    assert_eq!(entries[0].source, "This Week in Rust");
+    assert_eq!(entries[0].published, Some(1_749_009_600));
+    assert_eq!(entries[1].title, "Tom & Jerry");
+
+    let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <title type="text">Sherlock Releases</title>
+  <entry>
+    <title>v0.2.0</title>
+    <link rel="replies" href="https://example.com/comments"/>
+    <link rel="alternate" href="https://example.com/releases/v0.2.0"/>
+    <updated>2025-06-05T04:00:00Z</updated>
+  </entry>
+</feed>"#;
+    let mut merged = parse_feed(atom);
+    assert_eq!(merged[0].url, "https://example.com/releases/v0.2.0");
+    assert_eq!(merged[0].source, "Sherlock Releases");
+
+    merged.extend(entries);
+    newest(&mut merged, 2);
+    assert_eq!(merged[0].title, "v0.2.0");
+    assert_eq!(merged[1].title, "This Week in Rust 600");
+
+    let inner = FeedLauncher::app_data(&merged[1], 1_749_009_600 + 3 * 3_600);
+    assert_eq!(
+        inner.generic_name,
+        Some(SharedString::from("This Week in Rust · 3h ago"))
+    );
+}
@@ -0,0 +1,215 @@
+use gpui::SharedString;
+use percent_encoding::percent_decode_str;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::loader::utils::{AppData, construct_search};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+use crate::utils::files::home_dir;
+
+/// Recently used files and the files of some directories, opened with their default application
+#[derive(Clone, Debug)]
+pub struct FileLauncher {
+    /// Directories whose files are listed, without their subdirectories
+    pub directories: Vec<PathBuf>,
+    /// Whether the recently used files of `recently-used.xbel` are listed
+    pub recent: bool,
+    /// Maximum number of files
+    pub limit: usize,
+}
+
+impl FileLauncher {
+    pub const DEFAULT_LIMIT: usize = 50;
+
+    /// Recent files first, newest first, then the files of the directories by name. Sources that
+    /// cannot be read are logged and skipped.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let recent = self.recent.then(recent_files);
+        let listed = self
+            .directories
+            .iter()
+            .map(|directory| directory_files(directory));
+        for found in recent.into_iter().chain(listed) {
+            match found {
+                Ok(found) => files.extend(found),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+
+        let mut seen = HashSet::new();
+        files.retain(|path| seen.insert(path.clone()));
+        files.truncate(self.limit);
+        files
+    }
+
+    /// Opens the file with `xdg-open`
+    pub fn exec(path: &Path) -> String {
+        let quoted = path.to_string_lossy().replace('\'', r"'\''");
+        format!("xdg-open '{quoted}'")
+    }
+
+    /// The tile of a file, showing its directory below the name. The mime icon is replaced by
+    /// the thumbnail once it is loaded.
+    pub fn app_data(path: &Path) -> AppData {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        let mut directory = path
+            .parent()
+            .map(|parent| parent.display().to_string())
+            .unwrap_or_default();
+        if let Ok(home) = home_dir() {
+            let home = home.display().to_string();
+            if let Some(rest) = directory.strip_prefix(&home) {
+                directory = format!("~{rest}");
+            }
+        }
+
+        let mut inner = AppData::new();
+        inner.search_string = construct_search(Some(&name), &directory, true);
+        inner.name = Some(SharedString::from(name));
+        inner.generic_name = Some(SharedString::from(directory));
+        inner.exec = Some(Self::exec(path));
+        inner.defer_icon(mime_icon(path));
+        inner
+    }
+}
+
+/// Generic icon of the file type, by the extension
+fn mime_icon(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "svg" | "tif" | "tiff" | "ico" => {
+            "image-x-generic"
+        }
+        "pdf" => "application-pdf",
+        "mp3" | "flac" | "ogg" | "opus" | "wav" | "m4a" => "audio-x-generic",
+        "mp4" | "mkv" | "webm" | "avi" | "mov" => "video-x-generic",
+        "zip" | "tar" | "gz" | "xz" | "zst" | "7z" | "rar" => "package-x-generic",
+        "odt" | "doc" | "docx" | "rtf" => "x-office-document",
+        "ods" | "xls" | "xlsx" | "csv" => "x-office-spreadsheet",
+        "odp" | "ppt" | "pptx" => "x-office-presentation",
+        _ => "text-x-generic",
+    }
+}
+
+/// Files of a directory by name, without hidden files and subdirectories
+fn directory_files(directory: &Path) -> Result<Vec<PathBuf>, SherlockError> {
+    let entries = fs::read_dir(directory).map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::DirReadError(directory.display().to_string()),
+            e.to_string()
+        )
+    })?;
+    let mut listed: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .map(|entry| entry.path())
+        .collect();
+    listed.sort_by_key(|path| path.file_name().map(|name| name.to_ascii_lowercase()));
+    Ok(listed)
+}
+
+/// Existing local files of `$XDG_DATA_HOME/recently-used.xbel`, newest first
+fn recent_files() -> Result<Vec<PathBuf>, SherlockError> {
+    let Some(path) = xdg::BaseDirectories::new()
+        .get_data_home()
+        .map(|data| data.join("recently-used.xbel"))
+    else {
+        return Ok(Vec::new());
+    };
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| sherlock_error!(SherlockErrorType::FileReadError(path), e.to_string()))?;
+    Ok(parse_xbel(&content)
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect())
+}
+
+/// Local files of the bookmarks of an xbel document, newest first
+fn parse_xbel(content: &str) -> Vec<PathBuf> {
+    let mut bookmarks: Vec<(String, PathBuf)> = content
+        .split("<bookmark ")
+        .skip(1)
+        .filter_map(|bookmark| {
+            // Keeps the space before the first attribute, which the split removed
+            let tag = format!(" {}", &bookmark[..bookmark.find('>')?]);
+            let tag = tag.as_str();
+            let href = attribute(tag, "href")?;
+            let path = href.strip_prefix("file://")?;
+            let path = percent_decode_str(path).decode_utf8().ok()?;
+            // Timestamps are ISO 8601 in UTC, so they sort as text
+            let time = attribute(tag, "visited")
+                .into_iter()
+                .chain(attribute(tag, "modified"))
+                .max()
+                .unwrap_or_default();
+            Some((time, PathBuf::from(path.as_ref())))
+        })
+        .collect();
+    bookmarks.sort_by(|a, b| b.0.cmp(&a.0));
+    bookmarks.into_iter().map(|(_, path)| path).collect()
+}
+
+/// The unescaped value of an attribute of a tag
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&format!(" {name}=\""))? + name.len() + 3;
+    let value = &tag[start..start + tag[start..].find('"')?];
+    Some(
+        value
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
+
+#[test]
+fn test_parse_xbel() {
+    let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0">
+  <bookmark href="file:///tmp/old.txt" added="2024-01-01T10:00:00.000000Z" modified="2024-01-01T10:00:00.000000Z" visited="2024-01-01T10:00:00.000000Z">
+  </bookmark>
+  <bookmark href="https://example.org" modified="2025-01-01T10:00:00Z" visited="2025-01-01T10:00:00Z"/>
+  <bookmark href="file:///tmp/Scan%201%20&amp;%202.pdf" added="2024-03-01T10:00:00Z" modified="2024-03-02T10:00:00Z" visited="2024-03-01T10:00:00Z">
+  </bookmark>
+</xbel>"#;
+    assert_eq!(
+        parse_xbel(content),
+        vec![
+            PathBuf::from("/tmp/Scan 1 & 2.pdf"),
+            PathBuf::from("/tmp/old.txt")
+        ]
+    );
+    assert_eq!(
+        FileLauncher::exec(Path::new("/tmp/it's.pdf")),
+        r"xdg-open '/tmp/it'\''s.pdf'"
+    );
+    assert_eq!(mime_icon(Path::new("/tmp/a.JPG")), "image-x-generic");
+}
+
+#[test]
+fn test_unreadable_directory() {
+    let listed = std::env::temp_dir().join(format!("sherlock-files-{}", std::process::id()));
+    fs::create_dir_all(&listed).unwrap();
+    fs::write(listed.join("notes.txt"), "").unwrap();
+    let launcher = FileLauncher {
+        directories: vec![listed.join("missing"), listed.clone()],
+        recent: false,
+        limit: FileLauncher::DEFAULT_LIMIT,
+    };
+    assert_eq!(launcher.files(), vec![listed.join("notes.txt")]);
+    let _ = fs::remove_dir_all(&listed);
+}
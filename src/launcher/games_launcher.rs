@@ -0,0 +1,337 @@
+use gpui::SharedString;
+use rusqlite::{Connection, OpenFlags};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::loader::utils::{AppData, construct_search};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+use crate::utils::files::home_dir;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameSource {
+    Steam,
+    Lutris,
+    Heroic,
+}
+impl GameSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Steam => "Steam",
+            Self::Lutris => "Lutris",
+            Self::Heroic => "Heroic",
+        }
+    }
+    fn icon_name(&self) -> &'static str {
+        match self {
+            Self::Steam => "steam",
+            Self::Lutris => "lutris",
+            Self::Heroic => "com.heroicgameslauncher.hgl",
+        }
+    }
+}
+
+/// Installed games of Steam, Lutris and Heroic, which are started through the URI handlers of
+/// their clients
+#[derive(Clone, Debug)]
+pub struct GamesLauncher {
+    pub sources: Vec<GameSource>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Game {
+    pub name: String,
+    pub uri: String,
+    pub source: GameSource,
+    /// Cover art of the library grid
+    pub grid: Option<PathBuf>,
+}
+
+impl GamesLauncher {
+    pub const DEFAULT_SOURCES: &[GameSource] =
+        &[GameSource::Steam, GameSource::Lutris, GameSource::Heroic];
+
+    /// Games of every source, sources that are not installed have none
+    pub fn games(&self) -> Result<Vec<Game>, SherlockError> {
+        let home = home_dir()?;
+        let mut games = Vec::new();
+        for source in &self.sources {
+            let found = match source {
+                GameSource::Steam => Ok(steam_games(&home)),
+                GameSource::Lutris => lutris_games(&home),
+                GameSource::Heroic => Ok(heroic_games(&home)),
+            };
+            match found {
+                Ok(found) => games.extend(found),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        Ok(games)
+    }
+
+    pub fn app_data(game: &Game) -> AppData {
+        let uri = game.uri.replace('\'', r"'\''");
+        let mut inner = AppData {
+            name: Some(SharedString::from(game.name.clone())),
+            generic_name: Some(SharedString::from(game.source.label())),
+            search_string: construct_search(Some(&game.name), game.source.label(), true),
+            exec: Some(format!("xdg-open '{uri}'")),
+            ..AppData::new()
+        };
+        match &game.grid {
+            Some(grid) => inner.set_icon(Some(Arc::from(grid.as_path()))),
+            None => inner.defer_icon(game.source.icon_name()),
+        }
+        inner
+    }
+}
+
+/// The first of the paths that exists
+fn existing(paths: impl IntoIterator<Item = PathBuf>) -> Option<PathBuf> {
+    paths.into_iter().find(|path| path.exists())
+}
+
+fn steam_games(home: &Path) -> Vec<Game> {
+    // Native and Flatpak installs
+    let Some(root) = existing([
+        home.join(".local/share/Steam"),
+        home.join(".steam/steam"),
+        home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+    ]) else {
+        return Vec::new();
+    };
+    let folders = root.join("steamapps/libraryfolders.vdf");
+    let mut libraries = vec![root.clone()];
+    if let Ok(content) = std::fs::read_to_string(&folders) {
+        libraries.extend(vdf_values(&content, "path").into_iter().map(PathBuf::from));
+    }
+    // The root is usually listed too, though often behind a symlink
+    let mut libraries: Vec<PathBuf> = libraries
+        .into_iter()
+        .map(|library| library.canonicalize().unwrap_or(library))
+        .collect();
+    libraries.sort();
+    libraries.dedup();
+
+    let mut games = Vec::new();
+    for library in libraries {
+        let pattern = library.join("steamapps/appmanifest_*.acf");
+        for manifest in glob::glob(&pattern.to_string_lossy())
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            // Steam rewrites the manifests of games it updates
+            let Ok(content) = std::fs::read_to_string(&manifest) else {
+                continue;
+            };
+            let Some(game) = parse_manifest(&content) else {
+                continue;
+            };
+            let cache = root.join("appcache/librarycache");
+            games.push(Game {
+                uri: format!("steam://rungameid/{}", game.0),
+                source: GameSource::Steam,
+                // Newer clients keep the images of a game in a directory of its own
+                grid: existing([
+                    cache.join(format!("{}_library_600x900.jpg", game.0)),
+                    cache.join(&game.0).join("library_600x900.jpg"),
+                ]),
+                name: game.1,
+            });
+        }
+    }
+    games
+}
+
+/// App id and name of an app manifest, `None` for Proton and the other tools Steam installs
+/// like games
+fn parse_manifest(content: &str) -> Option<(String, String)> {
+    let id = vdf_values(content, "appid").into_iter().next()?;
+    let name = vdf_values(content, "name").into_iter().next()?;
+    let tool = ["Proton", "Steam Linux Runtime", "Steamworks Common"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix));
+    (!tool).then_some((id, name))
+}
+
+/// Values of the `"key"  "value"` lines of a Valve KeyValues file
+fn vdf_values(content: &str, key: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('"').skip(1).step_by(2);
+            if parts.next()? != key {
+                return None;
+            }
+            parts.next().map(|value| value.replace(r"\\", r"\"))
+        })
+        .collect()
+}
+
+fn lutris_games(home: &Path) -> Result<Vec<Game>, SherlockError> {
+    let data = home.join(".local/share/lutris");
+    let database = data.join("pga.db");
+    if !database.exists() {
+        return Ok(Vec::new());
+    }
+    let error = |e: rusqlite::Error| {
+        sherlock_error!(
+            SherlockErrorType::SqlConnectionError(),
+            format!("{}: {e}", database.display())
+        )
+    };
+    let conn =
+        Connection::open_with_flags(&database, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(error)?;
+    let mut stmt = conn
+        .prepare("SELECT name, slug FROM games WHERE installed = 1")
+        .map_err(error)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(error)?;
+
+    // Older versions keep the cover art next to the database, newer ones in the cache
+    let cache = home.join(".cache/lutris");
+    Ok(rows
+        .flatten()
+        .map(|(name, slug)| Game {
+            name,
+            uri: format!("lutris:rungame/{slug}"),
+            source: GameSource::Lutris,
+            grid: existing(
+                [&data, &cache]
+                    .into_iter()
+                    .flat_map(|dir| ["coverart", "banners"].map(|kind| dir.join(kind)))
+                    .map(|dir| dir.join(format!("{slug}.jpg"))),
+            ),
+        })
+        .collect())
+}
+
+fn heroic_games(home: &Path) -> Vec<Game> {
+    let Some(config) = existing([
+        home.join(".config/heroic"),
+        home.join(".var/app/com.heroicgameslauncher.hgl/config/heroic"),
+    ]) else {
+        return Vec::new();
+    };
+    let read = |path: &str| -> Value {
+        std::fs::read_to_string(config.join(path))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    };
+    parse_heroic(
+        &read("legendaryConfig/legendary/installed.json"),
+        &read("gog_store/installed.json"),
+        &read("gog_store/library.json"),
+        &read("sideload_apps/library.json"),
+    )
+}
+
+/// Installed games of Epic, through legendary, of GOG and those added by hand. GOG only
+/// lists the ids of installed games, their titles are part of the library.
+fn parse_heroic(
+    epic: &Value,
+    gog_installed: &Value,
+    gog_library: &Value,
+    sideload: &Value,
+) -> Vec<Game> {
+    let game = |runner: &str, id: &str, title: &str| Game {
+        name: title.to_string(),
+        uri: format!("heroic://launch/{runner}/{id}"),
+        source: GameSource::Heroic,
+        grid: None,
+    };
+    let library = |json: &Value| -> HashMap<String, String> {
+        json["games"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|game| {
+                let id = game["app_name"].as_str()?;
+                Some((id.to_string(), game["title"].as_str()?.to_string()))
+            })
+            .collect()
+    };
+
+    let mut games: Vec<Game> = epic
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(id, info)| Some(game("legendary", id, info["title"].as_str()?)))
+        .collect();
+    let gog_titles = library(gog_library);
+    games.extend(
+        gog_installed["installed"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|info| {
+                let id = info["appName"].as_str()?;
+                Some(game("gog", id, gog_titles.get(id)?))
+            }),
+    );
+    games.extend(
+        library(sideload)
+            .iter()
+            .map(|(id, title)| game("sideload", id, title)),
+    );
+    games
+}
+
+#[test]
+fn test_parse_libraries() {
+    let folders = r#""libraryfolders"
+{
+	"0"
+	{
+		"path"		"/home/user/.local/share/Steam"
+		"apps"
+		{
+			"1145360"		"11026612"
+		}
+	}
+	"1"
+	{
+		"path"		"D:\\Games"
+	}
+}"#;
+    assert_eq!(
+        vdf_values(folders, "path"),
+        vec!["/home/user/.local/share/Steam", r"D:\Games"]
+    );
+    let manifest = "\"AppState\"\n{\n\t\"appid\"\t\t\"1145360\"\n\t\"name\"\t\t\"Hades\"\n}";
+    assert_eq!(
+        parse_manifest(manifest),
+        Some((String::from("1145360"), String::from("Hades")))
+    );
+    let proton =
+        "\"AppState\"\n{\n\t\"appid\"\t\t\"1493710\"\n\t\"name\"\t\t\"Proton Experimental\"\n}";
+    assert_eq!(parse_manifest(proton), None);
+
+    let epic = serde_json::json!({ "Fortnite": { "app_name": "Fortnite", "title": "Fortnite" } });
+    let gog_installed = serde_json::json!({ "installed": [{ "appName": "1207658924" }] });
+    let gog_library = serde_json::json!({
+        "games": [
+            { "app_name": "1207658924", "title": "Unreal Tournament 2004" },
+            { "app_name": "1207658691", "title": "Not Installed" }
+        ]
+    });
+    let games = parse_heroic(&epic, &gog_installed, &gog_library, &Value::Null);
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].uri, "heroic://launch/legendary/Fortnite");
+    assert_eq!(games[1].name, "Unreal Tournament 2004");
+
+    let inner = GamesLauncher::app_data(&games[1]);
+    assert_eq!(
+        inner.exec.as_deref(),
+        Some("xdg-open 'heroic://launch/gog/1207658924'")
+    );
+}
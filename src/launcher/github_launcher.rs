@@ -0,0 +1,271 @@
+use gpui::SharedString;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::launcher::Launcher;
+use crate::launcher::children::RenderableChild;
+use crate::loader::resolve_icon_path;
+use crate::loader::utils::{AppData, construct_search};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+
+const API: &str = "https://api.github.com";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GithubKind {
+    Repositories,
+    Issues,
+    PullRequests,
+}
+
+/// Searches GitHub for the query while the mode of the launcher is active. Without a token,
+/// GitHub allows ten searches a minute.
+#[derive(Clone, Debug)]
+pub struct GithubLauncher {
+    pub token: Option<String>,
+    pub kinds: Vec<GithubKind>,
+    /// Results per kind, at most 100
+    pub limit: u32,
+    pub browser: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GithubItem {
+    pub title: String,
+    pub url: String,
+    /// `owner/name` of the repository
+    pub repo: String,
+    pub description: String,
+    /// Stars and language of repositories, the state of issues and pull requests
+    pub badges: Vec<String>,
+}
+
+impl GithubLauncher {
+    pub const DEFAULT_KINDS: &[GithubKind] = &[
+        GithubKind::Repositories,
+        GithubKind::Issues,
+        GithubKind::PullRequests,
+    ];
+    pub const DEFAULT_LIMIT: u32 = 10;
+
+    /// Searches every kind of the launcher, one request each
+    pub async fn search(&self, query: &str) -> Result<Vec<GithubItem>, SherlockError> {
+        let mut items = Vec::new();
+        for kind in &self.kinds {
+            let (endpoint, query) = match kind {
+                GithubKind::Repositories => ("repositories", query.to_string()),
+                GithubKind::Issues => ("issues", format!("{query} is:issue")),
+                GithubKind::PullRequests => ("issues", format!("{query} is:pr")),
+            };
+            let json = self.get(endpoint, &query).await?;
+            match kind {
+                GithubKind::Repositories => items.extend(parse_repositories(&json)),
+                _ => items.extend(parse_issues(&json)),
+            }
+        }
+        Ok(items)
+    }
+
+    async fn get(&self, endpoint: &str, query: &str) -> Result<Value, SherlockError> {
+        let url = format!(
+            "{API}/search/{endpoint}?q={}&per_page={}",
+            utf8_percent_encode(query, NON_ALPHANUMERIC),
+            self.limit.min(100)
+        );
+        let error = |e: String| {
+            sherlock_error!(
+                SherlockErrorType::HttpRequestError(format!("GET {API}/search/{endpoint}")),
+                e
+            )
+        };
+        // GitHub rejects requests without a user agent
+        let mut request = reqwest::Client::new()
+            .get(&url)
+            .header("User-Agent", "sherlock")
+            .header("Accept", "application/vnd.github+json");
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(|e| error(e.to_string()))?;
+        let status = response.status();
+        let body = response.text().await.map_err(|e| error(e.to_string()))?;
+        let json: Value = serde_json::from_str(&body)
+            .map_err(|e| sherlock_error!(SherlockErrorType::DeserializationError, e.to_string()))?;
+        if !status.is_success() {
+            let message = json["message"].as_str().unwrap_or_default();
+            return Err(error(format!("{status} {message}")));
+        }
+        Ok(json)
+    }
+
+    /// Tiles of the results, in the order GitHub ranked them. The query is part of their search
+    /// string, as results do not always contain it literally.
+    pub fn children(
+        launcher: &Arc<Launcher>,
+        items: &[GithubItem],
+        query: &str,
+    ) -> Vec<RenderableChild> {
+        if items.is_empty() {
+            return vec![Self::placeholder(launcher)];
+        }
+        let icon = resolve_icon_path("github");
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let mut inner = Self::app_data(item, query);
+                inner.icon = icon.clone();
+                inner.priority = Some(launcher.priority as f32 + i as f32 / items.len() as f32);
+                RenderableChild::AppLike {
+                    launcher: Arc::clone(launcher),
+                    inner,
+                }
+            })
+            .collect()
+    }
+
+    fn app_data(item: &GithubItem, query: &str) -> AppData {
+        let keywords = [query, item.repo.as_str(), item.description.as_str()].join(";");
+        AppData {
+            name: Some(SharedString::from(item.title.clone())),
+            generic_name: Some(SharedString::from(item.description.clone()))
+                .filter(|description| !description.is_empty()),
+            search_string: construct_search(Some(&item.title), &keywords, true),
+            exec: Some(item.url.clone()),
+            badges: item
+                .badges
+                .iter()
+                .cloned()
+                .map(SharedString::from)
+                .collect(),
+            ..AppData::new()
+        }
+    }
+
+    /// Stands in for the results until the first search, which keeps the launcher in its mode
+    pub fn placeholder(launcher: &Arc<Launcher>) -> RenderableChild {
+        let inner = AppData {
+            name: Some(SharedString::from("Search GitHub")),
+            generic_name: Some(SharedString::from(
+                "Type to search repositories, issues and pull requests",
+            )),
+            search_string: String::from("github"),
+            exec: Some(String::from("https://github.com")),
+            icon: resolve_icon_path("github"),
+            ..AppData::new()
+        };
+        RenderableChild::AppLike {
+            launcher: Arc::clone(launcher),
+            inner,
+        }
+    }
+}
+
+fn parse_repositories(json: &Value) -> Vec<GithubItem> {
+    json["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|repo| {
+            let name = repo["full_name"].as_str()?;
+            let stars = repo["stargazers_count"].as_u64().unwrap_or_default();
+            let mut badges = vec![format!("★ {}", short_count(stars))];
+            badges.extend(repo["language"].as_str().map(str::to_string));
+            Some(GithubItem {
+                title: name.to_string(),
+                url: repo["html_url"].as_str()?.to_string(),
+                repo: name.to_string(),
+                description: repo["description"].as_str().unwrap_or_default().to_string(),
+                badges,
+            })
+        })
+        .collect()
+}
+
+/// Issues and pull requests, which share the search endpoint
+fn parse_issues(json: &Value) -> Vec<GithubItem> {
+    json["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|issue| {
+            // `https://api.github.com/repos/owner/name`
+            let repo = issue["repository_url"]
+                .as_str()?
+                .split('/')
+                .rev()
+                .take(2)
+                .collect::<Vec<_>>();
+            let repo = format!("{}/{}", repo.get(1)?, repo.first()?);
+            let number = issue["number"].as_u64()?;
+            let pull = issue["pull_request"].as_object();
+            let state = match issue["state"].as_str()? {
+                _ if pull.is_some_and(|pull| !pull["merged_at"].is_null()) => "Merged",
+                "open" if issue["draft"].as_bool() == Some(true) => "Draft",
+                "open" => "Open",
+                _ => "Closed",
+            };
+            let kind = if pull.is_some() { "PR" } else { "Issue" };
+            Some(GithubItem {
+                title: issue["title"].as_str()?.to_string(),
+                url: issue["html_url"].as_str()?.to_string(),
+                description: format!("{repo} #{number}"),
+                repo,
+                badges: vec![kind.to_string(), state.to_string()],
+            })
+        })
+        .collect()
+}
+
+/// Counts like `12345` as `12.3k`
+fn short_count(count: u64) -> String {
+    match count {
+        0..1_000 => count.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", count as f64 / 1e3),
+        _ => format!("{:.1}M", count as f64 / 1e6),
+    }
+}
+
+#[test]
+fn test_parse_results() {
+    let repos = serde_json::json!({
+        "items": [{
+            "full_name": "zed-industries/zed",
+            "html_url": "https://github.com/zed-industries/zed",
+            "description": "Code at the speed of thought",
+            "stargazers_count": 61234,
+            "language": "Rust"
+        }]
+    });
+    let repos = parse_repositories(&repos);
+    assert_eq!(repos[0].badges, vec!["★ 61.2k", "Rust"]);
+
+    let issues = serde_json::json!({
+        "items": [
+            {
+                "title": "Crash on startup",
+                "html_url": "https://github.com/Skxxtz/sherlock/issues/12",
+                "repository_url": "https://api.github.com/repos/Skxxtz/sherlock",
+                "number": 12,
+                "state": "open"
+            },
+            {
+                "title": "Add a GitHub launcher",
+                "html_url": "https://github.com/Skxxtz/sherlock/pull/13",
+                "repository_url": "https://api.github.com/repos/Skxxtz/sherlock",
+                "number": 13,
+                "state": "closed",
+                "pull_request": { "merged_at": "2025-01-01T00:00:00Z" }
+            }
+        ]
+    });
+    let issues = parse_issues(&issues);
+    assert_eq!(issues[0].description, "Skxxtz/sherlock #12");
+    assert_eq!(issues[0].badges, vec!["Issue", "Open"]);
+    assert_eq!(issues[1].badges, vec!["PR", "Merged"]);
+    assert_eq!(short_count(999), "999");
+    assert_eq!(short_count(1_500_000), "1.5M");
+}
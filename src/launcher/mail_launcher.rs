@@ -0,0 +1,405 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::DateTime;
+use gpui::SharedString;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::audio_device_launcher::command_output;
+use super::feed_launcher::{age, unix_now};
+use crate::launcher::Launcher;
+use crate::launcher::children::RenderableChild;
+use crate::loader::resolve_icon_path;
+use crate::loader::utils::{AppData, construct_search};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+
+const ICON: &str = "mail-unread";
+
+/// Searches the subjects and senders of mails while the mode of the launcher is active, through
+/// notmuch or, if a maildir is given, by reading the headers of its messages
+#[derive(Clone, Debug)]
+pub struct MailLauncher {
+    pub maildir: Option<PathBuf>,
+    /// Command that opens a message, with `{file}` standing for the message file and `{id}`
+    /// for its Message-ID
+    pub client: String,
+    pub limit: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MailMessage {
+    /// Message-ID without angle brackets
+    pub id: String,
+    pub file: PathBuf,
+    pub subject: String,
+    pub from: String,
+    /// Unix timestamp of the `Date` header
+    pub timestamp: Option<i64>,
+}
+
+impl MailLauncher {
+    pub const DEFAULT_CLIENT: &str = "{terminal} less '{file}'";
+    pub const DEFAULT_LIMIT: usize = 20;
+
+    /// The newest messages whose subject or sender contain every word of the query
+    pub fn search(&self, query: &str) -> Result<Vec<MailMessage>, SherlockError> {
+        match &self.maildir {
+            Some(maildir) => Ok(scan_maildir(maildir, query, self.limit)),
+            None => self.notmuch(query),
+        }
+    }
+
+    fn notmuch(&self, query: &str) -> Result<Vec<MailMessage>, SherlockError> {
+        let limit = format!("--limit={}", self.limit);
+        let ids = command_output(
+            "notmuch",
+            &[
+                "search",
+                "--output=messages",
+                "--sort=newest-first",
+                &limit,
+                &notmuch_query(query),
+            ],
+        )?;
+        let ids: Vec<&str> = ids.lines().filter(|id| !id.is_empty()).collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let shown = command_output(
+            "notmuch",
+            &["show", "--format=json", "--body=false", &ids.join(" or ")],
+        )?;
+        let json: Value = serde_json::from_str(&shown).map_err(|e| {
+            sherlock_error!(
+                SherlockErrorType::DeserializationError,
+                format!("notmuch show: {e}")
+            )
+        })?;
+        let mut messages = Vec::new();
+        collect_shown(&json, &mut messages);
+        messages.sort_by_key(|message| std::cmp::Reverse(message.timestamp));
+        Ok(messages)
+    }
+
+    /// Tiles of the messages. The query is part of their search string, as notmuch also
+    /// matches word stems the titles do not contain.
+    pub fn children(
+        &self,
+        launcher: &Arc<Launcher>,
+        messages: &[MailMessage],
+        query: &str,
+    ) -> Vec<RenderableChild> {
+        if messages.is_empty() {
+            return vec![Self::placeholder(launcher)];
+        }
+        let icon = resolve_icon_path(ICON);
+        let now = unix_now();
+        messages
+            .iter()
+            .enumerate()
+            .map(|(i, message)| {
+                let mut inner = self.app_data(message, query, now);
+                inner.icon = icon.clone();
+                inner.priority = Some(launcher.priority as f32 + i as f32 / messages.len() as f32);
+                RenderableChild::AppLike {
+                    launcher: Arc::clone(launcher),
+                    inner,
+                }
+            })
+            .collect()
+    }
+
+    fn app_data(&self, message: &MailMessage, query: &str, now: i64) -> AppData {
+        let subject = if message.subject.is_empty() {
+            "(no subject)"
+        } else {
+            &message.subject
+        };
+        let age = message.timestamp.map(|timestamp| age(now - timestamp));
+        let file = message.file.to_string_lossy().replace('\'', r"'\''");
+        AppData {
+            name: Some(SharedString::from(subject.to_string())),
+            generic_name: Some(SharedString::from(
+                [Some(message.from.as_str()), age.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(" · "),
+            )),
+            search_string: construct_search(
+                Some(subject),
+                &[query, message.from.as_str()].join(";"),
+                true,
+            ),
+            exec: Some(
+                self.client
+                    .replace("{file}", &file)
+                    .replace("{id}", &message.id),
+            ),
+            ..AppData::new()
+        }
+    }
+
+    /// Stands in for the messages until the first search, which keeps the launcher in its mode
+    pub fn placeholder(launcher: &Arc<Launcher>) -> RenderableChild {
+        let inner = AppData {
+            name: Some(SharedString::from("Search Mail")),
+            generic_name: Some(SharedString::from("Type to search subjects and senders")),
+            search_string: String::from("mail"),
+            icon: resolve_icon_path(ICON),
+            ..AppData::new()
+        };
+        RenderableChild::AppLike {
+            launcher: Arc::clone(launcher),
+            inner,
+        }
+    }
+}
+
+/// Matches every word of the query against subject or sender, e.g.
+/// `(subject:"invoice" or from:"invoice")`
+fn notmuch_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| {
+            let word = word.replace('"', "");
+            format!(r#"(subject:"{word}" or from:"{word}")"#)
+        })
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+/// Messages in the nested threads of `notmuch show --format=json`
+fn collect_shown(json: &Value, messages: &mut Vec<MailMessage>) {
+    match json {
+        Value::Array(items) => items.iter().for_each(|item| collect_shown(item, messages)),
+        Value::Object(message) if message.contains_key("headers") => {
+            let headers = &message["headers"];
+            let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+            // Newer versions list every file of the message
+            let file = match &message["filename"] {
+                Value::Array(files) => files.first().map(text),
+                file => Some(text(file)),
+            };
+            messages.push(MailMessage {
+                id: text(&message["id"]),
+                file: PathBuf::from(file.unwrap_or_default()),
+                subject: text(&headers["Subject"]),
+                from: sender(&text(&headers["From"])),
+                timestamp: message["timestamp"].as_i64(),
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Reads the headers of the messages in the `cur` and `new` folders below the maildir
+fn scan_maildir(maildir: &Path, query: &str, limit: usize) -> Vec<MailMessage> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut messages = Vec::new();
+    let mut dirs = vec![maildir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let is_mail_dir = dir.ends_with("cur") || dir.ends_with("new");
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if is_mail_dir
+                && let Some(message) = read_headers(&path)
+                && matches_words(&message, &words)
+            {
+                messages.push(message);
+            }
+        }
+    }
+    messages.sort_by_key(|message| std::cmp::Reverse(message.timestamp));
+    messages.truncate(limit);
+    messages
+}
+
+fn matches_words(message: &MailMessage, words: &[String]) -> bool {
+    let haystack = format!("{} {}", message.subject, message.from).to_lowercase();
+    words.iter().all(|word| haystack.contains(word.as_str()))
+}
+
+fn read_headers(path: &Path) -> Option<MailMessage> {
+    let reader = BufReader::new(File::open(path).ok()?);
+    let mut lines = Vec::new();
+    for line in reader.split(b'\n') {
+        let line = String::from_utf8_lossy(&line.ok()?).trim_end().to_string();
+        if line.is_empty() {
+            break;
+        }
+        lines.push(line);
+    }
+    let mut message = parse_headers(&lines.join("\n"));
+    message.file = path.to_path_buf();
+    Some(message)
+}
+
+fn parse_headers(headers: &str) -> MailMessage {
+    // Folded lines continue the header above
+    let mut unfolded: Vec<String> = Vec::new();
+    for line in headers.lines() {
+        match unfolded.last_mut() {
+            Some(last) if line.starts_with([' ', '\t']) => {
+                last.push(' ');
+                last.push_str(line.trim());
+            }
+            _ => unfolded.push(line.to_string()),
+        }
+    }
+    let mut message = MailMessage::default();
+    for line in unfolded {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "subject" => message.subject = decode_words(value),
+            "from" => message.from = sender(&decode_words(value)),
+            "message-id" => {
+                message.id = value.trim_matches(['<', '>']).to_string();
+            }
+            "date" => {
+                message.timestamp = DateTime::parse_from_rfc2822(value)
+                    .ok()
+                    .map(|date| date.timestamp());
+            }
+            _ => {}
+        }
+    }
+    message
+}
+
+/// The name of `Jane Doe <jane@example.com>`, the address if there is none
+fn sender(from: &str) -> String {
+    match from.split_once('<') {
+        Some((name, _)) if !name.trim().is_empty() => name.trim().trim_matches('"').to_string(),
+        _ => from.trim().trim_matches(['<', '>']).to_string(),
+    }
+}
+
+/// Decodes the encoded words of RFC 2047, like `=?UTF-8?B?SGVsbG8=?=`
+fn decode_words(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("=?") {
+        let word = &rest[start + 2..];
+        let mut parts = word.splitn(3, '?');
+        let (Some(charset), Some(encoding), Some(tail)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            break;
+        };
+        let Some(end) = tail.find("?=") else {
+            break;
+        };
+        let text = &tail[..end];
+        let bytes = match encoding {
+            "B" | "b" => STANDARD.decode(text).ok(),
+            "Q" | "q" => Some(decode_q(text)),
+            _ => None,
+        };
+        let Some(bytes) = bytes else {
+            break;
+        };
+        // Whitespace between adjacent encoded words is dropped
+        let before = &rest[..start];
+        if !before.trim().is_empty() || decoded.is_empty() {
+            decoded.push_str(before);
+        }
+        if charset.eq_ignore_ascii_case("iso-8859-1") || charset.eq_ignore_ascii_case("latin1") {
+            decoded.extend(bytes.iter().map(|&b| b as char));
+        } else {
+            decoded.push_str(&String::from_utf8_lossy(&bytes));
+        }
+        rest = &tail[end + 2..];
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_q(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'_' => decoded.push(b' '),
+            b'=' => match bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'='),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    decoded
+}
+
+#[test]
+fn test_parse_headers() {
+    let message = parse_headers(
+        "From: \"Jane Doe\" <jane@example.com>\n\
+         Subject: =?UTF-8?B?UmVjaG51bmc=?= =?UTF-8?Q?_f=C3=BCr_Juni?=\n\
+         Message-ID: <1234@example.com>\n\
+         Date: Wed, 04 Jun 2025 04:00:00 +0000",
+    );
+    assert_eq!(message.from, "Jane Doe");
+    assert_eq!(message.subject, "Rechnung für Juni");
+    assert_eq!(message.id, "1234@example.com");
+    assert_eq!(message.timestamp, Some(1_749_009_600));
+    assert!(matches_words(
+        &message,
+        &[String::from("jane"), String::from("juni")]
+    ));
+    assert!(!matches_words(&message, &[String::from("july")]));
+
+    assert_eq!(
+        notmuch_query("invoice jane"),
+        r#"(subject:"invoice" or from:"invoice") and (subject:"jane" or from:"jane")"#
+    );
+
+    let shown = serde_json::json!([[[{
+        "id": "1234@example.com",
+        "filename": ["/home/jane/Mail/INBOX/cur/1234"],
+        "timestamp": 1_749_009_600,
+        "headers": { "Subject": "Hello", "From": "Jane Doe <jane@example.com>" }
+    }, []]]]);
+    let mut messages = Vec::new();
+    collect_shown(&shown, &mut messages);
+    assert_eq!(
+        messages[0].file,
+        PathBuf::from("/home/jane/Mail/INBOX/cur/1234")
+    );
+
+    let launcher = MailLauncher {
+        maildir: None,
+        client: String::from("thunderbird mid:{id}"),
+        limit: MailLauncher::DEFAULT_LIMIT,
+    };
+    let inner = launcher.app_data(&messages[0], "hello", 1_749_009_600 + 120);
+    assert_eq!(
+        inner.exec.as_deref(),
+        Some("thunderbird mid:1234@example.com")
+    );
+    assert_eq!(
+        inner.generic_name,
+        Some(SharedString::from("Jane Doe · 2m ago"))
+    );
+}
@@ -1,20 +1,35 @@
+pub mod agenda_launcher;
 pub mod app_launcher;
+pub mod audio_device_launcher;
 pub mod audio_launcher;
 pub mod bookmark_launcher;
 pub mod calc_launcher;
 pub mod category_launcher;
 pub mod children;
+pub mod contacts_launcher;
+pub mod display_launcher;
+pub mod dnd_launcher;
 pub mod event_launcher;
+pub mod feed_launcher;
+pub mod file_launcher;
+pub mod games_launcher;
+pub mod github_launcher;
+pub mod mail_launcher;
+pub mod nix_launcher;
+pub mod pacman_launcher;
+pub mod record_launcher;
 pub mod system_cmd_launcher;
 pub mod utils;
+pub mod volume_launcher;
+pub mod vpn_launcher;
 pub mod weather_launcher;
+pub mod weather_provider;
 pub mod web_launcher;
 // Integrate later: TODO
 // pub mod clipboard_launcher;
 // pub mod bulk_text_launcher;
 // pub mod pipe_launcher;
 // pub mod emoji_picker;
-// pub mod file_launcher;
 // pub mod pomodoro_launcher;
 // pub mod process_launcher;
 // pub mod theme_picker;
@@ -22,6 +37,7 @@ pub mod web_launcher;
 use serde::de::IntoDeserializer;
 use std::{
     collections::{HashMap, HashSet},
+    path::Path,
     sync::Arc,
     vec,
 };
@@ -34,26 +50,49 @@ use crate::{
     loader::{
         Loader,
         application_loader::parse_priority,
-        resolve_icon_path,
-        utils::{
-            AppData, ApplicationAction, CounterReader, RawLauncher, deserialize_named_appdata,
-        },
+        is_loaded, reload_launcher, replace_children,
+        utils::{AppData, ApplicationAction, ChainStep, RawLauncher, deserialize_named_appdata},
+        watch_applications,
     },
     utils::{
-        command_launch::spawn_detached, config::HomeType, errors::SherlockError,
-        websearch::websearch,
+        command_launch::{
+            FieldCodes, ProcessEnv, discrete_gpu_env, hold_in_terminal, run_chain, spawn_detached,
+            spawn_detached_in, type_text,
+        },
+        config::HomeType,
+        errors::SherlockError,
+        thumbnails::thumbnail,
+        watch::Watchers,
+        websearch::{BrowserContext, install_web_app, websearch},
     },
 };
 
+use agenda_launcher::AgendaLauncher;
 use app_launcher::AppLauncher;
-use audio_launcher::MusicPlayerLauncher;
+use audio_device_launcher::{AudioDevice, AudioDeviceLauncher, DeviceKind};
+use audio_launcher::{AlbumArt, MprisCommand, MusicPlayerLauncher, watch_players};
 use bookmark_launcher::BookmarkLauncher;
 use calc_launcher::CalculatorLauncher;
 use category_launcher::CategoryLauncher;
+use chrono::Local;
+use contacts_launcher::ContactsLauncher;
+use display_launcher::DisplayLauncher;
+use dnd_launcher::DndLauncher;
 use event_launcher::EventLauncher;
+use feed_launcher::{FeedLauncher, unix_now};
+use file_launcher::FileLauncher;
+use futures::{StreamExt, future::join_all};
+use games_launcher::GamesLauncher;
+use github_launcher::GithubLauncher;
 use gpui::{App, AsyncApp, Entity, SharedString};
+use mail_launcher::MailLauncher;
+use nix_launcher::NixLauncher;
+use pacman_launcher::PacmanLauncher;
+use record_launcher::{RecordCommand, RecordLauncher, watch_recording};
 use serde_json::Value;
 use system_cmd_launcher::CommandLauncher;
+use volume_launcher::{VolumeCommand, VolumeData, VolumeLauncher, watch_volume};
+use vpn_launcher::VpnLauncher;
 use weather_launcher::WeatherLauncher;
 use web_launcher::WebLauncher;
 
@@ -61,20 +100,34 @@ use web_launcher::WebLauncher;
 // use bulk_text_launcher::BulkTextLauncher;
 // use clipboard_launcher::ClipboardLauncher;
 // use emoji_picker::EmojiPicker;
-// use file_launcher::FileLauncher;
 // use pomodoro_launcher::Pomodoro;
 // use process_launcher::ProcessLauncher;
 // use theme_picker::ThemePicker;
 
 #[derive(Clone, Debug, Default)]
 pub enum LauncherType {
+    Agenda(AgendaLauncher),
     App(AppLauncher),
+    AudioDevices(AudioDeviceLauncher),
     Bookmark(BookmarkLauncher),
     Calc(CalculatorLauncher),
     Category(CategoryLauncher),
     Command(CommandLauncher),
+    Contacts(ContactsLauncher),
+    Display(DisplayLauncher),
+    Dnd(DndLauncher),
     Event(EventLauncher),
+    Feed(FeedLauncher),
+    Files(FileLauncher),
+    Games(GamesLauncher),
+    Github(GithubLauncher),
+    Mail(MailLauncher),
     MusicPlayer(MusicPlayerLauncher),
+    Nix(NixLauncher),
+    Pacman(PacmanLauncher),
+    Record(RecordLauncher),
+    Volume(VolumeLauncher),
+    Vpn(VpnLauncher),
     Weather(WeatherLauncher),
     Web(WebLauncher),
     #[default]
@@ -84,24 +137,105 @@ pub enum LauncherType {
     // Api(BulkTextLauncher),
     // Clipboard(ClipboardLauncher),
     // Emoji(EmojiPicker),
-    // File(FileLauncher),
     // Pomodoro(Pomodoro),
     // Process(ProcessLauncher),
     // Theme(ThemePicker),
 }
 
 impl LauncherType {
+    /// Kinds of launchers, which restrict patterns of the sherlockignore file to them
+    pub const KINDS: &[&str] = &[
+        "agenda",
+        "app",
+        "audio_device",
+        "bookmark",
+        "calc",
+        "category",
+        "command",
+        "contacts",
+        "display",
+        "dnd",
+        "event",
+        "feed",
+        "files",
+        "games",
+        "github",
+        "mail",
+        "music",
+        "nix",
+        "pacman",
+        "record",
+        "volume",
+        "vpn",
+        "weather",
+        "web",
+    ];
+    /// Whether the items depend on the query, which the main window then searches with the
+    /// launcher while its mode is active
+    pub fn searches_query(&self) -> bool {
+        matches!(self, Self::Github(_) | Self::Mail(_) | Self::Nix(_))
+    }
+    /// Whether loading the launcher starts watchers that keep its tiles up to date, so it is
+    /// not reloaded periodically. The types of the config are listed in `WATCHED_TYPES`.
+    pub fn watches_changes(&self) -> bool {
+        matches!(
+            self,
+            Self::App(_) | Self::Dnd(_) | Self::MusicPlayer(_) | Self::Volume(_) | Self::Vpn(_)
+        )
+    }
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Agenda(_) => "agenda",
+            Self::App(_) => "app",
+            Self::AudioDevices(_) => "audio_device",
+            Self::Bookmark(_) => "bookmark",
+            Self::Calc(_) => "calc",
+            Self::Category(_) => "category",
+            Self::Command(_) => "command",
+            Self::Contacts(_) => "contacts",
+            Self::Display(_) => "display",
+            Self::Dnd(_) => "dnd",
+            Self::Event(_) => "event",
+            Self::Feed(_) => "feed",
+            Self::Files(_) => "files",
+            Self::Games(_) => "games",
+            Self::Github(_) => "github",
+            Self::Mail(_) => "mail",
+            Self::MusicPlayer(_) => "music",
+            Self::Nix(_) => "nix",
+            Self::Pacman(_) => "pacman",
+            Self::Record(_) => "record",
+            Self::Volume(_) => "volume",
+            Self::Vpn(_) => "vpn",
+            Self::Weather(_) => "weather",
+            Self::Web(_) => "web",
+            Self::Empty => "",
+        }
+    }
     pub fn get_render_obj(
         &self,
         launcher: Arc<Launcher>,
         opts: Arc<Value>,
-        counts: &HashMap<String, u32>,
+        counts: &HashMap<String, f32>,
         decimals: i32,
         cx: &mut App,
         data_handle: Entity<Arc<Vec<RenderableChild>>>,
     ) -> Option<Vec<RenderableChild>> {
         match self {
+            Self::Agenda(agenda) => {
+                let now = Local::now().naive_local();
+                let events = agenda.events(now).map_err(|e| eprintln!("{e}")).ok()?;
+                Some(agenda.children(&launcher, &events, now.date()))
+            }
+
             Self::App(app) => {
+                watch_applications(
+                    cx,
+                    Arc::clone(&launcher),
+                    app.use_keywords,
+                    data_handle.clone(),
+                    launcher.watchers.start(),
+                );
                 Loader::load_applications(Arc::clone(&launcher), counts, decimals, app.use_keywords)
                     .map(|ad| {
                         ad.into_iter()
@@ -114,19 +248,63 @@ impl LauncherType {
                     .ok()
             }
 
-            Self::Bookmark(bkm) => {
-                BookmarkLauncher::find_bookmarks(&bkm.target_browser, Arc::clone(&launcher))
-                    .map(|ad| {
-                        ad.into_iter()
-                            .map(|inner| RenderableChild::AppLike {
-                                launcher: Arc::clone(&launcher),
-                                inner,
+            Self::AudioDevices(devices) => {
+                // Listed in the background, as every kind takes two calls to pactl
+                let (lister, devices_launcher) = (devices.clone(), Arc::clone(&launcher));
+                cx.spawn(move |cx: &mut AsyncApp| {
+                    let cx = cx.clone();
+                    async move {
+                        let listed = cx
+                            .background_executor()
+                            .spawn(async move { lister.devices() })
+                            .await;
+                        if !is_loaded(&cx, &data_handle, &devices_launcher) {
+                            return;
+                        }
+                        let children = listed
+                            .map_err(|e| eprintln!("{e}"))
+                            .unwrap_or_default()
+                            .iter()
+                            .map(|device| RenderableChild::AppLike {
+                                launcher: Arc::clone(&devices_launcher),
+                                inner: AudioDeviceLauncher::app_data(device),
                             })
-                            .collect()
-                    })
-                    .ok()
+                            .collect();
+                        let _ = cx.update(|cx| {
+                            replace_children(cx, &devices_launcher, &opts, &data_handle, children)
+                        });
+                    }
+                })
+                .detach();
+
+                let inner = AudioDeviceLauncher::placeholder();
+                Some(vec![RenderableChild::AppLike { launcher, inner }])
             }
 
+            Self::Bookmark(bkm) => BookmarkLauncher::find_bookmarks(
+                &bkm.target_browser,
+                &bkm.profiles,
+                Arc::clone(&launcher),
+            )
+            .map(|ad| {
+                ad.into_iter()
+                    .map(|mut inner| {
+                        // Bookmarks rank a band below the launcher priority
+                        let count = inner.count(&launcher, counts);
+                        inner.priority = Some(parse_priority(
+                            launcher.priority as f32 + 1.0,
+                            count,
+                            decimals,
+                        ));
+                        RenderableChild::AppLike {
+                            launcher: Arc::clone(&launcher),
+                            inner,
+                        }
+                    })
+                    .collect()
+            })
+            .ok(),
+
             Self::Calc(_) => {
                 let capabilities: HashSet<String> = match opts.get("capabilities") {
                     Some(Value::Array(arr)) => arr
@@ -147,82 +325,630 @@ impl LauncherType {
                 let children: Vec<RenderableChild> = app_data
                     .into_iter()
                     .map(|mut inner| {
-                        let count = inner
-                            .exec
-                            .as_deref()
-                            .and_then(|exec| counts.get(exec))
-                            .copied()
-                            .unwrap_or(0u32);
-                        inner.icon = inner
-                            .icon
-                            .and_then(|i| i.to_str().and_then(resolve_icon_path));
-                        inner.priority =
-                            Some(parse_priority(launcher.priority as f32, count, decimals));
-                        RenderableChild::AppLike {
-                            launcher: Arc::clone(&launcher),
-                            inner,
+                        if let Some(icon) = inner.icon.take() {
+                            inner.defer_icon(&icon.to_string_lossy());
                         }
+                        app_like(&launcher, inner, counts, decimals)
+                    })
+                    .collect();
+
+                Some(children)
+            }
+
+            Self::Contacts(contacts) => {
+                let contacts_list = contacts.contacts().map_err(|e| eprintln!("{e}")).ok()?;
+                let children = contacts_list
+                    .iter()
+                    .map(|contact| {
+                        app_like(&launcher, contacts.app_data(contact), counts, decimals)
                     })
                     .collect();
+                Some(children)
+            }
+
+            Self::Display(display) => {
+                let layouts = display.layouts();
+                // The current layout is marked once wlr-randr reported the outputs
+                let pending = layouts.clone();
+                let display_launcher = Arc::clone(&launcher);
+                cx.spawn(|cx: &mut AsyncApp| {
+                    let cx = cx.clone();
+                    async move {
+                        let outputs = cx
+                            .background_executor()
+                            .spawn(async { DisplayLauncher::outputs() })
+                            .await;
+                        let current: Vec<String> = pending
+                            .into_iter()
+                            .filter(|layout| display_launcher::is_current(layout, &outputs))
+                            .map(|layout| layout.exec)
+                            .collect();
+                        if current.is_empty() || !is_loaded(&cx, &data_handle, &display_launcher) {
+                            return;
+                        }
+                        let _ = cx.update(|cx| {
+                            data_handle.update(cx, |items_arc, cx| {
+                                let items = Arc::make_mut(items_arc);
+                                for item in items.iter_mut() {
+                                    if item.is_from(&display_launcher)
+                                        && let RenderableChild::AppLike { inner, .. } = item
+                                        && inner.exec.as_ref().is_some_and(|e| current.contains(e))
+                                    {
+                                        DisplayLauncher::mark_current(inner);
+                                    }
+                                }
+                                cx.notify();
+                            });
+                        });
+                    }
+                })
+                .detach();
 
+                let children = layouts
+                    .iter()
+                    .map(|layout| {
+                        app_like(
+                            &launcher,
+                            DisplayLauncher::app_data(layout),
+                            counts,
+                            decimals,
+                        )
+                    })
+                    .collect();
+                Some(children)
+            }
+
+            Self::Dnd(dnd) => {
+                // Asked for over D-Bus by the watcher, which also reflects toggles, including
+                // those made outside of Sherlock
+                let (tx, mut rx) = futures::channel::mpsc::unbounded();
+                let stop = launcher.watchers.start();
+                let (watcher, watcher_stop) = (dnd.clone(), stop.clone());
+                std::thread::spawn(move || {
+                    let watched =
+                        watcher.watch(&watcher_stop, |state| tx.unbounded_send(state).is_ok());
+                    if let Err(e) = watched {
+                        eprintln!("{e}");
+                    }
+                });
+                let dnd_launcher = Arc::clone(&launcher);
+                let count = DndLauncher::placeholder().count(&launcher, counts);
+                cx.spawn(move |cx: &mut AsyncApp| {
+                    let cx = cx.clone();
+                    async move {
+                        let mut answered = false;
+                        while let Some(state) = rx.next().await {
+                            if stop.is_stopped() {
+                                return;
+                            }
+                            if !answered {
+                                answered = true;
+                                // Takes the place of the placeholder
+                                let mut inner = DndLauncher::app_data(state);
+                                inner.priority = Some(parse_priority(
+                                    dnd_launcher.priority as f32,
+                                    count,
+                                    decimals,
+                                ));
+                                let tile = RenderableChild::AppLike {
+                                    launcher: Arc::clone(&dnd_launcher),
+                                    inner,
+                                };
+                                let _ = cx.update(|cx| {
+                                    replace_children(
+                                        cx,
+                                        &dnd_launcher,
+                                        &opts,
+                                        &data_handle,
+                                        vec![tile],
+                                    )
+                                });
+                                continue;
+                            }
+                            let _ = cx.update(|cx| {
+                                data_handle.update(cx, |items_arc, cx| {
+                                    let items = Arc::make_mut(items_arc);
+                                    for item in items.iter_mut() {
+                                        if item.is_from(&dnd_launcher)
+                                            && let RenderableChild::AppLike { inner, .. } = item
+                                        {
+                                            DndLauncher::apply_state(inner, state);
+                                        }
+                                    }
+                                    cx.notify();
+                                });
+                            });
+                        }
+                        // No notification daemon answered
+                        if !answered && !stop.is_stopped() {
+                            let _ = cx.update(|cx| {
+                                replace_children(cx, &dnd_launcher, &opts, &data_handle, Vec::new())
+                            });
+                        }
+                    }
+                })
+                .detach();
+
+                let inner = DndLauncher::placeholder();
+                Some(vec![RenderableChild::AppLike { launcher, inner }])
+            }
+
+            Self::Games(games) => {
+                let games = games.games().map_err(|e| eprintln!("{e}")).ok()?;
+                let children = games
+                    .iter()
+                    .map(|game| {
+                        app_like(&launcher, GamesLauncher::app_data(game), counts, decimals)
+                    })
+                    .collect();
+                Some(children)
+            }
+
+            Self::Files(files) => {
+                let paths = files.files();
+                // Thumbnails replace the mime icons in batches, as they are found or generated
+                let pending = paths.clone();
+                let files_launcher = Arc::clone(&launcher);
+                cx.spawn(|cx: &mut AsyncApp| {
+                    let cx = cx.clone();
+                    async move {
+                        for batch in pending.chunks(16) {
+                            let batch = batch.to_vec();
+                            let icons: HashMap<String, Arc<Path>> = cx
+                                .background_executor()
+                                .spawn(async move {
+                                    batch
+                                        .iter()
+                                        .filter_map(|path| {
+                                            Some((FileLauncher::exec(path), thumbnail(path)?))
+                                        })
+                                        .collect()
+                                })
+                                .await;
+                            if !is_loaded(&cx, &data_handle, &files_launcher) {
+                                break;
+                            }
+                            if icons.is_empty() {
+                                continue;
+                            }
+                            let _ = cx.update(|cx| {
+                                data_handle.update(cx, |items_arc, cx| {
+                                    let items = Arc::make_mut(items_arc);
+                                    for item in items.iter_mut() {
+                                        if item.is_from(&files_launcher)
+                                            && let RenderableChild::AppLike { inner, .. } = item
+                                            && let Some(icon) =
+                                                inner.exec.as_ref().and_then(|e| icons.get(e))
+                                        {
+                                            inner.set_icon(Some(Arc::clone(icon)));
+                                        }
+                                    }
+                                    cx.notify();
+                                });
+                            });
+                        }
+                    }
+                })
+                .detach();
+
+                let children = paths
+                    .iter()
+                    .map(|path| app_like(&launcher, FileLauncher::app_data(path), counts, decimals))
+                    .collect();
+                Some(children)
+            }
+
+            Self::Feed(feed) => {
+                let (cached, outdated) = feed.cached_entries();
+                if outdated {
+                    let feed = feed.clone();
+                    let feed_launcher = Arc::clone(&launcher);
+                    cx.spawn(|cx: &mut AsyncApp| {
+                        let cx = cx.clone();
+                        async move {
+                            match feed.fetch_entries().await {
+                                Some(Ok(_)) => {}
+                                Some(Err(e)) => return eprintln!("{e}"),
+                                None => return,
+                            }
+                            if is_loaded(&cx, &data_handle, &feed_launcher) {
+                                let _ = cx.update(|cx| {
+                                    reload_launcher(cx, &feed_launcher, &opts, &data_handle)
+                                });
+                            }
+                        }
+                    })
+                    .detach();
+                }
+
+                let Some(entries) = cached else {
+                    let inner = FeedLauncher::placeholder();
+                    return Some(vec![RenderableChild::AppLike { launcher, inner }]);
+                };
+                let now = unix_now();
+                let children = entries
+                    .iter()
+                    .map(|entry| {
+                        app_like(
+                            &launcher,
+                            FeedLauncher::app_data(entry, now),
+                            counts,
+                            decimals,
+                        )
+                    })
+                    .collect();
+                Some(children)
+            }
+
+            // Filled by the searches of the main window while the mode is active
+            Self::Github(_) => Some(vec![GithubLauncher::placeholder(&launcher)]),
+            Self::Mail(_) => Some(vec![MailLauncher::placeholder(&launcher)]),
+            Self::Nix(_) => Some(vec![NixLauncher::placeholder(&launcher)]),
+
+            Self::Pacman(pacman) => {
+                let (cached, outdated) = pacman.cached_packages();
+                if outdated {
+                    let pacman = pacman.clone();
+                    refresh_in_background(cx, &launcher, opts, data_handle, move || {
+                        pacman.list_packages()
+                    });
+                }
+
+                let Some(packages) = cached else {
+                    let inner = PacmanLauncher::placeholder();
+                    return Some(vec![RenderableChild::AppLike { launcher, inner }]);
+                };
+                let children = packages
+                    .iter()
+                    .map(|package| app_like(&launcher, pacman.app_data(package), counts, decimals))
+                    .collect();
                 Some(children)
             }
 
             Self::Weather(wttr) => {
-                match WeatherData::from_cache(wttr) {
-                    Some(inner) => Some(vec![RenderableChild::WeatherLike { launcher, inner }]),
-                    None => {
-                        // 1. Data isn't cached, start the fetch
-                        let wttr_clone = wttr.clone();
-
-                        cx.spawn(|cx: &mut AsyncApp| {
-                            let cx = cx.clone();
-                            async move {
-                                if let Some((new_weather_data, _)) =
-                                    WeatherData::fetch_async(&wttr_clone).await
-                                {
-                                    let _ = cx.update(|cx| {
-                                        // Update the entity's inner data
-                                        data_handle.update(cx, {
-                                            |items_arc, cx| {
-                                                let items = Arc::make_mut(items_arc);
-
-                                                for item in items.iter_mut() {
-                                                    if let RenderableChild::WeatherLike {
-                                                        inner,
-                                                        ..
-                                                    } = item
-                                                    {
-                                                        *inner = new_weather_data.clone();
-                                                    }
-                                                }
-
-                                                cx.notify();
+                let mut children = Vec::with_capacity(wttr.locations.len());
+                let mut missing = Vec::new();
+                for location in &wttr.locations {
+                    let inner = WeatherData::from_cache(wttr, location).unwrap_or_else(|| {
+                        // Shown as a placeholder until the fetch below finishes
+                        missing.push(location.clone());
+                        WeatherData::uninitialized(location)
+                    });
+                    children.push(RenderableChild::WeatherLike {
+                        launcher: Arc::clone(&launcher),
+                        inner,
+                    });
+                }
+
+                if !missing.is_empty() {
+                    let wttr_clone = wttr.clone();
+                    cx.spawn(|cx: &mut AsyncApp| {
+                        let cx = cx.clone();
+                        async move {
+                            // All locations are fetched at once, each one cached on its own
+                            let fetched =
+                                join_all(missing.iter().map(|location| {
+                                    WeatherData::fetch_async(&wttr_clone, location)
+                                }))
+                                .await;
+                            let fetched: Vec<WeatherData> = fetched
+                                .into_iter()
+                                .flatten()
+                                .map(|(data, _)| data)
+                                .collect();
+                            if fetched.is_empty() {
+                                return;
+                            }
+
+                            let _ = cx.update(|cx| {
+                                data_handle.update(cx, |items_arc, cx| {
+                                    let items = Arc::make_mut(items_arc);
+                                    for item in items.iter_mut() {
+                                        if let RenderableChild::WeatherLike { inner, .. } = item
+                                            && let Some(data) = fetched
+                                                .iter()
+                                                .find(|data| data.location == inner.location)
+                                        {
+                                            *inner = data.clone();
+                                        }
+                                    }
+                                    cx.notify();
+                                });
+                            });
+                        }
+                    })
+                    .detach();
+                }
+
+                Some(children)
+            }
+
+            Self::MusicPlayer(mpris) => {
+                // Keeps the tile in sync with the player instead of showing the state at launch
+                let (tx, mut rx) = futures::channel::mpsc::unbounded();
+                let stop = launcher.watchers.start();
+                std::thread::spawn(move || {
+                    watch_players(&stop, |state| tx.unbounded_send(state).is_ok())
+                });
+                let music_launcher = Arc::clone(&launcher);
+                cx.spawn(|cx: &mut AsyncApp| {
+                    let cx = cx.clone();
+                    async move {
+                        while let Some(mut state) = rx.next().await {
+                            if !is_loaded(&cx, &data_handle, &music_launcher) {
+                                break;
+                            }
+                            state.art = state.cached_art();
+                            let missing = state
+                                .mpris
+                                .metadata
+                                .art
+                                .clone()
+                                .filter(|_| state.art.is_none());
+                            let _ = cx.update(|cx| {
+                                data_handle.update(cx, |items_arc, cx| {
+                                    let items = Arc::make_mut(items_arc);
+                                    for item in items.iter_mut() {
+                                        // The controlled player may have changed
+                                        if item.is_from(&music_launcher)
+                                            && let RenderableChild::MusicLike { inner, .. } = item
+                                        {
+                                            *inner = state.clone();
+                                        }
+                                    }
+                                    cx.notify();
+                                });
+                            });
+
+                            // Decoding and blurring the cover is left to the background
+                            let Some(url) = missing else {
+                                continue;
+                            };
+                            // Downloads need the Tokio runtime of the main thread
+                            let downloaded = AlbumArt::download(&url).await;
+                            let art = cx
+                                .background_executor()
+                                .spawn(async move { AlbumArt::load(&url, downloaded) })
+                                .await;
+                            let Some(art) = art else {
+                                continue;
+                            };
+                            let _ = cx.update(|cx| {
+                                data_handle.update(cx, |items_arc, cx| {
+                                    let items = Arc::make_mut(items_arc);
+                                    for item in items.iter_mut() {
+                                        if let RenderableChild::MusicLike { inner, .. } = item
+                                            && inner.mpris.metadata.art.as_deref()
+                                                == Some(art.url.as_str())
+                                        {
+                                            inner.art = Some(art.clone());
+                                        }
+                                    }
+                                    cx.notify();
+                                });
+                            });
+                        }
+                    }
+                })
+                .detach();
+
+                let mut inner = mpris.clone();
+                inner.art = inner.cached_art();
+                Some(vec![RenderableChild::MusicLike { launcher, inner }])
+            }
+
+            Self::Volume(_) => {
+                // Read by the watcher, which also keeps the tile in sync with changes from the
+                // keys, the mouse and other apps
+                let (tx, mut rx) = futures::channel::mpsc::unbounded();
+                let stop = launcher.watchers.start();
+                let watcher_stop = stop.clone();
+                std::thread::spawn(move || {
+                    watch_volume(&watcher_stop, |state| tx.unbounded_send(state).is_ok())
+                });
+                let volume_launcher = Arc::clone(&launcher);
+                cx.spawn(move |cx: &mut AsyncApp| {
+                    let cx = cx.clone();
+                    async move {
+                        let mut answered = false;
+                        while let Some(state) = rx.next().await {
+                            if stop.is_stopped() {
+                                return;
+                            }
+                            if !answered {
+                                answered = true;
+                                // Takes the place of the placeholder
+                                let tile = RenderableChild::VolumeLike {
+                                    launcher: Arc::clone(&volume_launcher),
+                                    inner: state,
+                                };
+                                let _ = cx.update(|cx| {
+                                    replace_children(
+                                        cx,
+                                        &volume_launcher,
+                                        &opts,
+                                        &data_handle,
+                                        vec![tile],
+                                    )
+                                });
+                                continue;
+                            }
+                            let _ = cx.update(|cx| {
+                                data_handle.update(cx, |items_arc, cx| {
+                                    let items = Arc::make_mut(items_arc);
+                                    for item in items.iter_mut() {
+                                        if item.is_from(&volume_launcher)
+                                            && let RenderableChild::VolumeLike { inner, .. } = item
+                                        {
+                                            *inner = state.clone();
+                                        }
+                                    }
+                                    cx.notify();
+                                });
+                            });
+                        }
+                        // Neither mixer could read the volume
+                        if !answered && !stop.is_stopped() {
+                            let _ = cx.update(|cx| {
+                                replace_children(
+                                    cx,
+                                    &volume_launcher,
+                                    &opts,
+                                    &data_handle,
+                                    Vec::new(),
+                                )
+                            });
+                        }
+                    }
+                })
+                .detach();
+
+                let inner = VolumeLauncher::placeholder();
+                Some(vec![RenderableChild::AppLike { launcher, inner }])
+            }
+
+            Self::Record(_) => {
+                let children = match RecordLauncher::current() {
+                    Some(recording) => {
+                        let inner = RecordLauncher::recording_tile(&recording, unix_now());
+                        vec![RenderableChild::AppLike {
+                            launcher: Arc::clone(&launcher),
+                            inner,
+                        }]
+                    }
+                    None => RecordLauncher::entries()
+                        .into_iter()
+                        .map(|inner| app_like(&launcher, inner, counts, decimals))
+                        .collect(),
+                };
+                watch_recording(cx, &launcher, opts, data_handle);
+                Some(children)
+            }
+
+            Self::Vpn(vpn) => {
+                // Listed by the watcher, as nmcli and ip take a moment. Reflects toggles once
+                // they completed, as well as connections that dropped.
+                let (tx, mut rx) = futures::channel::mpsc::unbounded();
+                let stop = launcher.watchers.start();
+                let (watcher, watcher_stop) = (vpn.clone(), stop.clone());
+                std::thread::spawn(move || {
+                    let watched = watcher.watch(&watcher_stop, |connections| {
+                        tx.unbounded_send(connections).is_ok()
+                    });
+                    if let Err(e) = watched {
+                        eprintln!("{e}");
+                    }
+                });
+                let (updater, vpn_launcher) = (vpn.clone(), Arc::clone(&launcher));
+                let counts = counts.clone();
+                cx.spawn(move |cx: &mut AsyncApp| {
+                    let cx = cx.clone();
+                    async move {
+                        let mut listed = false;
+                        while let Some(connections) = rx.next().await {
+                            if stop.is_stopped() {
+                                return;
+                            }
+                            listed = true;
+                            let _ = cx.update(|cx| {
+                                let same_connections = data_handle
+                                    .read(cx)
+                                    .iter()
+                                    .filter(|item| item.is_from(&vpn_launcher))
+                                    .map(RenderableChild::title)
+                                    .eq(connections.iter().map(|c| Some(c.name.as_str())));
+                                if same_connections {
+                                    data_handle.update(cx, |items_arc, cx| {
+                                        let items = Arc::make_mut(items_arc);
+                                        for item in items.iter_mut() {
+                                            if item.is_from(&vpn_launcher)
+                                                && let RenderableChild::AppLike { inner, .. } = item
+                                                && let Some(connection) =
+                                                    connections.iter().find(|connection| {
+                                                        inner.name.as_ref().is_some_and(|name| {
+                                                            name.as_str() == connection.name
+                                                        })
+                                                    })
+                                            {
+                                                updater.apply_state(inner, connection);
                                             }
-                                        });
+                                        }
+                                        cx.notify();
                                     });
+                                    return;
                                 }
-                            }
-                        })
-                        .detach();
 
-                        // Return None or a "Loading" placeholder for now
-                        Some(vec![RenderableChild::WeatherLike {
-                            launcher: Arc::clone(&launcher),
-                            inner: WeatherData::uninitialized(),
-                        }])
+                                // The first listing, or connections were added or removed
+                                let children = connections
+                                    .iter()
+                                    .map(|connection| {
+                                        app_like(
+                                            &vpn_launcher,
+                                            updater.app_data(connection),
+                                            &counts,
+                                            decimals,
+                                        )
+                                    })
+                                    .collect();
+                                replace_children(cx, &vpn_launcher, &opts, &data_handle, children);
+                            });
+                            // Without tiles, config reloads would not stop the watcher anymore
+                            if connections.is_empty() {
+                                vpn_launcher.watchers.stop();
+                            }
+                        }
+                        // The connections could not be listed
+                        if !listed && !stop.is_stopped() {
+                            let _ = cx.update(|cx| {
+                                replace_children(cx, &vpn_launcher, &opts, &data_handle, Vec::new())
+                            });
+                        }
                     }
-                }
+                })
+                .detach();
+
+                let inner = VpnLauncher::placeholder();
+                Some(vec![RenderableChild::AppLike { launcher, inner }])
+            }
+
+            Self::Web(web) if !web.engines.is_empty() => {
+                // Every engine becomes a tile of its own, backed by a launcher for that engine
+                let children = web
+                    .engines
+                    .iter()
+                    .map(|entry| {
+                        let mut inner = AppData::new();
+                        inner.name = Some(SharedString::from(entry.title()));
+                        let icon = entry
+                            .icon
+                            .as_deref()
+                            .or_else(|| opts.get("icon").and_then(Value::as_str));
+                        if let Some(icon) = icon {
+                            inner.defer_icon(icon);
+                        }
+                        inner.actions = Arc::from([Arc::new(WebLauncher::install_action())]);
+                        let launcher = Arc::new(Launcher {
+                            launcher_type: LauncherType::Web(WebLauncher {
+                                engine: entry.engine.clone(),
+                                engines: Vec::new(),
+                                ..web.clone()
+                            }),
+                            ..(*launcher).clone()
+                        });
+                        app_like(&launcher, inner, counts, decimals)
+                    })
+                    .collect();
+                Some(children)
             }
 
             Self::Web(_) => {
                 let mut inner = AppData::new();
-                inner.icon = opts
-                    .get("icon")
-                    .and_then(Value::as_str)
-                    .and_then(|i| resolve_icon_path(i));
+                if let Some(icon) = opts.get("icon").and_then(Value::as_str) {
+                    inner.defer_icon(icon);
+                }
+                inner.actions = Arc::from([Arc::new(WebLauncher::install_action())]);
 
-                Some(vec![RenderableChild::AppLike { launcher, inner }])
+                Some(vec![app_like(&launcher, inner, counts, decimals)])
             }
 
             _ => None,
@@ -230,6 +956,67 @@ impl LauncherType {
     }
 }
 
+/// Wraps the app data in a tile of the launcher, ranked by the priority of the launcher and the
+/// launch count of the item
+fn app_like(
+    launcher: &Arc<Launcher>,
+    mut inner: AppData,
+    counts: &HashMap<String, f32>,
+    decimals: i32,
+) -> RenderableChild {
+    let count = inner.count(launcher, counts);
+    inner.priority = Some(parse_priority(launcher.priority as f32, count, decimals));
+    RenderableChild::AppLike {
+        launcher: Arc::clone(launcher),
+        inner,
+    }
+}
+
+/// Runs `refresh` on the background executor and reloads the launcher afterwards if it is still
+/// loaded. `refresh` returns `None` if there was nothing to refresh.
+fn refresh_in_background<T: Send + 'static>(
+    cx: &mut App,
+    launcher: &Arc<Launcher>,
+    opts: Arc<Value>,
+    data_handle: Entity<Arc<Vec<RenderableChild>>>,
+    refresh: impl FnOnce() -> Option<Result<T, SherlockError>> + Send + 'static,
+) {
+    let launcher = Arc::clone(launcher);
+    cx.spawn(|cx: &mut AsyncApp| {
+        let cx = cx.clone();
+        async move {
+            let refreshed = cx
+                .background_executor()
+                .spawn(async move { refresh() })
+                .await;
+            match refreshed {
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return eprintln!("{e}"),
+                None => return,
+            }
+            if is_loaded(&cx, &data_handle, &launcher) {
+                let _ = cx.update(|cx| reload_launcher(cx, &launcher, &opts, &data_handle));
+            }
+        }
+    })
+    .detach();
+}
+
+/// Runs a control of a tile, such as a player command, on the background executor. The tile is
+/// updated by the watcher of its launcher once the change is reported.
+fn control_in_background(
+    cx: &App,
+    control: impl FnOnce() -> Result<(), SherlockError> + Send + 'static,
+) {
+    cx.background_executor()
+        .spawn(async move {
+            if let Err(e) = control() {
+                eprintln!("{e}");
+            }
+        })
+        .detach();
+}
+
 // // Async tiles
 // LauncherType::BulkText(bulk_text) => Tile::bulk_text_tile(launcher, &bulk_text).await,
 // LauncherType::MusicPlayer(mpris) => Tile::mpris_tile(launcher, &mpris).await,
@@ -258,11 +1045,14 @@ pub struct Launcher {
     pub display_name: Option<SharedString>,
     pub icon: Option<String>, // nu
     pub alias: Option<String>,
-    pub tag_end: Option<String>,      // nu
+    pub tag_start: Option<String>,
+    pub tag_end: Option<String>,
     pub method: String,               // nu
     pub exit: bool,                   // nu
     pub next_content: Option<String>, // nu
     pub priority: u32,
+    /// Ranking multiplier, higher weights move the launcher's results further up
+    pub weight: f32,
     pub r#async: bool, // nu
     pub home: HomeType,
     pub launcher_type: LauncherType,
@@ -270,6 +1060,14 @@ pub struct Launcher {
     pub spawn_focus: bool,                           // nu
     pub actions: Option<Vec<ApplicationAction>>,     // nu
     pub add_actions: Option<Vec<ApplicationAction>>, // nu
+    /// Keeps the terminal of its commands open after they exited
+    pub hold: bool,
+    /// Launches of its items are not counted
+    pub uncounted: bool,
+    /// Seconds between background reloads of the items, apps reload on changes instead
+    pub refresh_interval: Option<u64>,
+    /// Watchers that keep the tiles up to date, stopped once the launcher is replaced
+    pub watchers: Watchers,
 }
 impl Launcher {
     pub fn from_raw(
@@ -283,11 +1081,13 @@ impl Launcher {
             display_name: raw.display_name.map(|n| SharedString::from(n)),
             icon,
             alias: raw.alias,
+            tag_start: raw.tag_start,
             tag_end: raw.tag_end,
             method,
             exit: raw.exit,
             next_content: raw.next_content,
             priority: raw.priority as u32,
+            weight: if raw.weight > 0.0 { raw.weight } else { 1.0 },
             r#async: raw.r#async,
             home: raw.home,
             launcher_type,
@@ -295,6 +1095,10 @@ impl Launcher {
             spawn_focus: raw.spawn_focus,
             actions: raw.actions,
             add_actions: raw.add_actions,
+            hold: raw.hold,
+            uncounted: raw.uncounted,
+            refresh_interval: raw.refresh_interval,
+            watchers: Watchers::default(),
         }
     }
 
@@ -303,24 +1107,40 @@ impl Launcher {
         what: &'a ExecMode,
         keyword: &str,
         variables: &[(SharedString, SharedString)],
+        cx: &App,
     ) -> Result<bool, SherlockError> {
         match what {
-            ExecMode::App { exec, terminal } => {
-                let cmd = if *terminal {
-                    format!(r#"{{terminal}} {exec}"#)
+            ExecMode::App {
+                exec,
+                terminal,
+                discrete_gpu,
+                codes,
+                hold,
+            } => {
+                let cmd = if *discrete_gpu {
+                    format!("env {} {exec}", discrete_gpu_env())
                 } else {
                     exec.to_string()
                 };
-                spawn_detached(&cmd, keyword, variables)?;
-                increment(exec);
+                let cmd = match (*terminal, *hold) {
+                    (true, true) => hold_in_terminal(&cmd),
+                    (true, false) => format!(r#"{{terminal}} {cmd}"#),
+                    _ => cmd,
+                };
+                spawn_detached(&cmd, keyword, variables, Some(codes))?;
             }
-            ExecMode::Commmand { exec } => {
-                spawn_detached(exec, keyword, variables)?;
-                increment(exec);
+            ExecMode::Commmand { exec, hold, env } => {
+                let exec = if *hold {
+                    hold_in_terminal(exec)
+                } else {
+                    exec.to_string()
+                };
+                spawn_detached_in(&exec, keyword, variables, None, env)?;
             }
             ExecMode::Web {
                 engine,
                 browser,
+                context,
                 exec,
             } => {
                 let engine = engine.as_deref().unwrap_or("plain");
@@ -329,7 +1149,38 @@ impl Launcher {
                 } else {
                     keyword
                 };
-                websearch(engine, query, browser.as_deref(), variables)?;
+                websearch(engine, query, browser.as_deref(), *context, variables)?;
+            }
+            ExecMode::Chain { steps, env } => {
+                run_chain(steps, keyword, variables, env)?;
+            }
+            ExecMode::Type { text } => {
+                type_text(text, keyword, variables)?;
+            }
+            ExecMode::InstallWebApp { browser } => {
+                install_web_app(keyword, *browser)?;
+            }
+            ExecMode::Mpris { player, command } => {
+                let (player, command) = (player.to_string(), *command);
+                control_in_background(cx, move || MusicPlayerLauncher::control(&player, command));
+                return Ok(false);
+            }
+            ExecMode::Volume { command } => {
+                let command = *command;
+                control_in_background(cx, move || VolumeData::control(command));
+                return Ok(false);
+            }
+            ExecMode::AudioDevice { kind, name } => {
+                let (kind, name) = (*kind, name.to_string());
+                control_in_background(cx, move || AudioDeviceLauncher::set_default(kind, &name));
+            }
+            ExecMode::Recording { recorder, command } => {
+                recorder.control(*command)?;
+            }
+            ExecMode::DoNotDisturb(dnd) => {
+                // The watcher updates the tile once the daemon reports the change
+                let dnd = DndLauncher::clone(dnd);
+                control_in_background(cx, move || dnd.toggle());
             }
             _ => {}
         };
@@ -337,54 +1188,169 @@ impl Launcher {
         Ok(true)
     }
 }
-fn increment(key: &str) {
-    if let Ok(count_reader) = CounterReader::new() {
-        let _ = count_reader.increment(key);
-    };
-}
 
 pub enum ExecMode<'a> {
     App {
         exec: &'a str,
         terminal: bool,
+        discrete_gpu: bool,
+        codes: FieldCodes<'a>,
+        /// Keeps the terminal of terminal apps open after they exited
+        hold: bool,
     },
     Commmand {
         exec: &'a str,
+        /// Runs the command in a terminal that stays open after it exited
+        hold: bool,
+        env: ProcessEnv<'a>,
     },
     Web {
         engine: Option<&'a str>,
         browser: Option<&'a str>,
+        context: BrowserContext<'a>,
         exec: Option<&'a str>,
     },
+    /// Runs commands one after another, see [`ChainStep`]
+    Chain {
+        steps: &'a [ChainStep],
+        env: ProcessEnv<'a>,
+    },
+    /// Types the text into the window focused before Sherlock, see [`type_text`]
+    Type {
+        text: &'a str,
+    },
+    /// Installs the query as web app, see [`install_web_app`]
+    InstallWebApp {
+        browser: Option<&'a str>,
+    },
+    Mpris {
+        player: &'a str,
+        command: MprisCommand,
+    },
+    Volume {
+        command: VolumeCommand,
+    },
+    /// Makes the device the default one
+    AudioDevice {
+        kind: DeviceKind,
+        name: &'a str,
+    },
+    Recording {
+        recorder: &'a RecordLauncher,
+        command: RecordCommand,
+    },
+    /// Toggles do-not-disturb of the notification daemon
+    DoNotDisturb(&'a DndLauncher),
     None,
 }
 impl<'a> ExecMode<'a> {
     pub fn from_appdata(app_data: &'a AppData, launcher: &'a Arc<Launcher>) -> Self {
+        if launcher.method == "type" {
+            return Self::Type {
+                text: app_data.exec.as_deref().unwrap_or(""),
+            };
+        }
         match &launcher.launcher_type {
             LauncherType::App(_) => Self::App {
                 exec: app_data.exec.as_deref().unwrap_or(""),
                 terminal: app_data.terminal,
+                discrete_gpu: app_data.discrete_gpu,
+                codes: FieldCodes {
+                    // Apps are launched on their own, not with a file or url
+                    target: None,
+                    icon: app_data.icon.as_deref(),
+                    name: app_data.name.as_ref().map(|name| name.as_str()),
+                    desktop_file: app_data.desktop_file.as_deref(),
+                },
+                hold: app_data.hold || launcher.hold,
             },
+            LauncherType::AudioDevices(_) => app_data
+                .exec
+                .as_deref()
+                .and_then(AudioDevice::parse_exec)
+                .map_or(Self::None, |(kind, name)| Self::AudioDevice { kind, name }),
+            LauncherType::Dnd(dnd) => Self::DoNotDisturb(dnd),
+            LauncherType::Record(recorder) => app_data
+                .exec
+                .as_deref()
+                .and_then(RecordCommand::parse_exec)
+                .map_or(Self::None, |command| Self::Recording { recorder, command }),
             LauncherType::Bookmark(bkm) => Self::Web {
                 engine: None,
                 browser: Some(&bkm.target_browser),
+                context: BrowserContext::default(),
                 exec: app_data.exec.as_deref(),
             },
-            LauncherType::Command(_) => Self::Commmand {
+            LauncherType::Feed(feed) => Self::Web {
+                engine: None,
+                browser: feed.browser.as_deref(),
+                context: BrowserContext::default(),
+                exec: app_data.exec.as_deref(),
+            },
+            LauncherType::Github(github) => Self::Web {
+                engine: None,
+                browser: github.browser.as_deref(),
+                context: BrowserContext::default(),
+                exec: app_data.exec.as_deref(),
+            },
+            LauncherType::Command(_) if launcher.method == "chain" => Self::Chain {
+                steps: &app_data.chain,
+                env: app_data.process_env(),
+            },
+            LauncherType::Agenda(_)
+            | LauncherType::Command(_)
+            | LauncherType::Contacts(_)
+            | LauncherType::Display(_)
+            | LauncherType::Games(_)
+            | LauncherType::Files(_)
+            | LauncherType::Mail(_)
+            | LauncherType::Nix(_)
+            | LauncherType::Pacman(_)
+            | LauncherType::Vpn(_) => Self::Commmand {
                 exec: app_data.exec.as_deref().unwrap_or(""),
+                hold: app_data.hold || launcher.hold,
+                env: app_data.process_env(),
             },
             LauncherType::Web(web) => Self::Web {
                 engine: Some(&web.engine),
                 browser: web.browser.as_deref(),
+                context: BrowserContext {
+                    profile: web.profile.as_deref(),
+                    container: web.container.as_deref(),
+                },
                 exec: app_data.exec.as_deref(),
             },
             _ => Self::None,
         }
     }
-    pub fn from_app_action(action: &'a ApplicationAction, _launcher: &'a Arc<Launcher>) -> Self {
+    pub fn from_app_action(action: &'a ApplicationAction, launcher: &'a Arc<Launcher>) -> Self {
+        if let Some(command) = MprisCommand::from_method(&action.method) {
+            return Self::Mpris {
+                player: action.exec.as_deref().unwrap_or(""),
+                command,
+            };
+        }
+        if let Some(command) = VolumeCommand::from_method(&action.method) {
+            return Self::Volume { command };
+        }
         match action.method.as_str() {
+            "chain" => Self::Chain {
+                steps: &action.chain,
+                env: ProcessEnv::default(),
+            },
+            "type" => Self::Type {
+                text: action.exec.as_deref().unwrap_or(""),
+            },
             "app_launcher" | "command" => Self::Commmand {
                 exec: action.exec.as_deref().unwrap_or(""),
+                hold: false,
+                env: ProcessEnv::default(),
+            },
+            "install_web_app" => Self::InstallWebApp {
+                browser: match &launcher.launcher_type {
+                    LauncherType::Web(web) => web.browser.as_deref(),
+                    _ => None,
+                },
             },
 
             _ => Self::None,
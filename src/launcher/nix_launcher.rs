@@ -0,0 +1,232 @@
+use gpui::SharedString;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::audio_device_launcher::command_output;
+use crate::launcher::Launcher;
+use crate::launcher::children::RenderableChild;
+use crate::loader::resolve_icon_path;
+use crate::loader::utils::{AppData, ApplicationAction, ChainStep, construct_search};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+
+const ICON: &str = "nix-snowflake";
+/// Packages shown per search, common words match thousands of them
+const LIMIT: usize = 50;
+
+/// Searches the packages of a flake with `nix search` while the mode of the launcher is active.
+/// Only the packages matching the query are evaluated, listing the whole package set would
+/// take minutes.
+#[derive(Clone, Debug)]
+pub struct NixLauncher {
+    /// Flake the packages come from, `nixpkgs` by default
+    pub flake: String,
+    /// File that "Add to Configuration" opens, e.g. `~/nixos/configuration.nix`
+    pub configuration: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NixPackage {
+    /// Attribute path below the package set, e.g. `python3Packages.requests`
+    pub attr: String,
+    pub pname: String,
+    pub version: String,
+    pub description: String,
+}
+
+impl NixLauncher {
+    pub const DEFAULT_FLAKE: &str = "nixpkgs";
+
+    /// The packages whose name or description match every word of the query, shortest names
+    /// first
+    pub fn search(&self, query: &str) -> Result<Vec<NixPackage>, SherlockError> {
+        let terms: Vec<String> = query.split_whitespace().map(regex::escape).collect();
+        let mut args = vec![
+            "--extra-experimental-features",
+            "nix-command flakes",
+            "search",
+            &self.flake,
+        ];
+        args.extend(terms.iter().map(String::as_str));
+        args.push("--json");
+        let output = command_output("nix", &args)?;
+        let json: Value = serde_json::from_str(&output).map_err(|e| {
+            sherlock_error!(
+                SherlockErrorType::DeserializationError,
+                format!("nix search {}: {e}", self.flake)
+            )
+        })?;
+        let mut packages = parse_packages(&json);
+        packages.sort_by(|a, b| a.attr.len().cmp(&b.attr.len()).then(a.attr.cmp(&b.attr)));
+        packages.truncate(LIMIT);
+        Ok(packages)
+    }
+
+    /// Tiles of the packages. The query is part of their search string, as `nix search` also
+    /// matches descriptions the titles do not contain.
+    pub fn children(
+        &self,
+        launcher: &Arc<Launcher>,
+        packages: &[NixPackage],
+        query: &str,
+    ) -> Vec<RenderableChild> {
+        if packages.is_empty() {
+            return vec![Self::placeholder(launcher)];
+        }
+        let icon = resolve_icon_path(ICON);
+        packages
+            .iter()
+            .enumerate()
+            .map(|(i, package)| {
+                let mut inner = self.app_data(package, query);
+                inner.icon = icon.clone();
+                inner.priority = Some(launcher.priority as f32 + i as f32 / packages.len() as f32);
+                RenderableChild::AppLike {
+                    launcher: Arc::clone(launcher),
+                    inner,
+                }
+            })
+            .collect()
+    }
+
+    /// The tile of a package, which opens a shell with it
+    fn app_data(&self, package: &NixPackage, query: &str) -> AppData {
+        let installable = format!("{}#{}", self.flake, package.attr);
+        let mut inner = AppData::new();
+        let name = if package.pname.is_empty() {
+            &package.attr
+        } else {
+            &package.pname
+        };
+        inner.name = Some(SharedString::from(name.clone()));
+        inner.generic_name = Some(SharedString::from(
+            [package.version.as_str(), package.description.as_str()]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" · "),
+        ));
+        inner.search_string =
+            construct_search(Some(name), &[query, package.attr.as_str()].join(";"), true);
+        inner.exec = Some(format!("{{terminal}} nix shell {installable}"));
+
+        // Copied, as configurations list their packages in too many different ways to edit
+        let mut add = vec![ChainStep::Exec(format!("wl-copy 'pkgs.{}'", package.attr))];
+        if let Some(configuration) = &self.configuration {
+            add.push(ChainStep::Exec(format!(
+                "{{terminal}} ${{EDITOR:-nano}} '{}'",
+                configuration.display()
+            )));
+        }
+        inner.actions = Arc::from([
+            Arc::new(ApplicationAction {
+                name: Some(SharedString::from("Run")),
+                exec: Some(format!("{{terminal}} nix run {installable}")),
+                ..ApplicationAction::new("command")
+            }),
+            Arc::new(ApplicationAction {
+                name: Some(SharedString::from("Add to Configuration")),
+                chain: add,
+                ..ApplicationAction::new("chain")
+            }),
+        ]);
+        inner
+    }
+
+    /// Stands in for the packages until the first search, which keeps the launcher in its mode
+    pub fn placeholder(launcher: &Arc<Launcher>) -> RenderableChild {
+        Self::tile(
+            launcher,
+            "Search Nix Packages",
+            String::from("Type to search names and descriptions"),
+        )
+    }
+
+    /// Takes the place of the packages if `nix search` failed, e.g. as the flake does not
+    /// evaluate, showing the first line of the reason
+    pub fn failure(launcher: &Arc<Launcher>, error: &SherlockError) -> RenderableChild {
+        let reason = error
+            .traceback
+            .lines()
+            // Below the location and the separator
+            .skip(2)
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or_default();
+        Self::tile(launcher, "Nix Search Failed", reason.trim().to_string())
+    }
+
+    fn tile(launcher: &Arc<Launcher>, name: &str, description: String) -> RenderableChild {
+        let inner = AppData {
+            name: Some(SharedString::from(name.to_string())),
+            generic_name: Some(SharedString::from(description)),
+            search_string: String::from("nix"),
+            icon: resolve_icon_path(ICON),
+            ..AppData::new()
+        };
+        RenderableChild::AppLike {
+            launcher: Arc::clone(launcher),
+            inner,
+        }
+    }
+}
+
+/// Packages in the output of `nix search --json`, whose keys look like
+/// `legacyPackages.x86_64-linux.hello`
+fn parse_packages(json: &Value) -> Vec<NixPackage> {
+    json.as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, package)| {
+            let mut parts = key.splitn(3, '.');
+            let attr = parts.nth(2)?;
+            let text = |field: &str| package[field].as_str().unwrap_or_default().to_string();
+            Some(NixPackage {
+                attr: attr.to_string(),
+                pname: text("pname"),
+                version: text("version"),
+                description: text("description"),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_packages() {
+    let json = serde_json::json!({
+        "legacyPackages.x86_64-linux.hello": {
+            "description": "Program that produces a familiar, friendly greeting",
+            "pname": "hello",
+            "version": "2.12.1"
+        },
+        "legacyPackages.x86_64-linux.python3Packages.requests": {
+            "description": "HTTP library for Python",
+            "pname": "python3.12-requests",
+            "version": "2.32.3"
+        },
+        "malformed": {}
+    });
+    let packages = parse_packages(&json);
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0].attr, "hello");
+    assert_eq!(packages[1].attr, "python3Packages.requests");
+
+    let launcher = NixLauncher {
+        flake: String::from(NixLauncher::DEFAULT_FLAKE),
+        configuration: Some(PathBuf::from("/etc/nixos/configuration.nix")),
+    };
+    let inner = launcher.app_data(&packages[0], "greeting");
+    assert_eq!(
+        inner.exec.as_deref(),
+        Some("{terminal} nix shell nixpkgs#hello")
+    );
+    assert_eq!(
+        inner.generic_name,
+        Some(SharedString::from(
+            "2.12.1 · Program that produces a familiar, friendly greeting"
+        ))
+    );
+    assert!(inner.search_string.contains("greeting"));
+    assert_eq!(inner.actions.len(), 2);
+    assert_eq!(inner.actions[1].chain.len(), 2);
+}
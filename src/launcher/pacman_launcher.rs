@@ -0,0 +1,244 @@
+use gpui::SharedString;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use super::audio_device_launcher::command_output;
+use crate::loader::application_loader::file_has_changed;
+use crate::loader::utils::{AppData, ApplicationAction, construct_search};
+use crate::utils::cache::BinaryCache;
+use crate::utils::command_launch::hold_in_terminal;
+use crate::utils::errors::SherlockError;
+use crate::utils::paths::get_cache_dir;
+
+/// Directory of the installed packages, which changes on every install and removal
+const LOCAL_DB: &str = "/var/lib/pacman/local";
+/// Directory of the sync databases, which changes on every `pacman -Sy`
+const SYNC_DB: &str = "/var/lib/pacman/sync";
+/// The AUR is not part of the sync databases, so its packages are listed anew after this
+const AUR_LIFETIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Set while the packages are listed, so reloads do not start a second listing
+static LISTING: AtomicBool = AtomicBool::new(false);
+
+/// Packages of the sync databases and, through an AUR helper, of the AUR. The listing is cached
+/// until the package databases change.
+#[derive(Clone, Debug)]
+pub struct PacmanLauncher {
+    /// Helper like `paru` or `yay`, which lists and installs AUR packages
+    pub aur_helper: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PacmanPackage {
+    pub repo: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub installed: bool,
+}
+
+impl PacmanLauncher {
+    fn cache_file(&self) -> Result<PathBuf, SherlockError> {
+        let name = match &self.aur_helper {
+            Some(_) => "pacman-aur.bin",
+            None => "pacman.bin",
+        };
+        Ok(get_cache_dir()?.join(name))
+    }
+
+    /// The packages of the last listing, `None` if there was none. The flag tells whether they
+    /// are due for a new listing.
+    pub fn cached_packages(&self) -> (Option<Vec<PacmanPackage>>, bool) {
+        let Ok(cache) = self.cache_file() else {
+            return (None, false);
+        };
+        let aur_outdated = self.aur_helper.is_some()
+            && std::fs::metadata(&cache)
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_none_or(|age| age > AUR_LIFETIME);
+        let outdated = aur_outdated
+            || file_has_changed(Path::new(LOCAL_DB), &cache)
+            || file_has_changed(Path::new(SYNC_DB), &cache);
+        (BinaryCache::read(&cache).ok(), outdated)
+    }
+
+    /// Lists the packages anew and caches them, `None` if another listing is running already
+    pub fn list_packages(&self) -> Option<Result<Vec<PacmanPackage>, SherlockError>> {
+        if LISTING.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        let result = self.list().and_then(|packages| {
+            BinaryCache::write(self.cache_file()?, &packages)?;
+            Ok(packages)
+        });
+        LISTING.store(false, Ordering::Release);
+        Some(result)
+    }
+
+    fn list(&self) -> Result<Vec<PacmanPackage>, SherlockError> {
+        let mut packages = parse_search(&command_output("pacman", &["-Ss"])?);
+        if let Some(helper) = &self.aur_helper {
+            let installed = command_output("pacman", &["-Qq"])?;
+            let installed: HashSet<&str> = installed.lines().collect();
+            let aur = command_output(helper, &["-Sl", "aur"])?;
+            packages.extend(parse_list(&aur, &installed));
+        }
+        Ok(packages)
+    }
+
+    /// The tile of a package. Packages that are not installed are installed on activation,
+    /// installed ones show their info.
+    pub fn app_data(&self, package: &PacmanPackage) -> AppData {
+        let name = &package.name;
+        let mut inner = AppData::new();
+        inner.name = Some(SharedString::from(name.clone()));
+        let state = if package.installed { "Installed" } else { "" };
+        inner.generic_name = Some(SharedString::from(
+            [
+                package.repo.as_str(),
+                package.version.as_str(),
+                state,
+                package.description.as_str(),
+            ]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" · "),
+        ));
+        inner.search_string = construct_search(Some(name), &package.description, true);
+        inner.defer_icon("package-x-generic");
+
+        let install = match (&self.aur_helper, package.repo.as_str()) {
+            (Some(helper), "aur") => format!("{{terminal}} {helper} -S {name}"),
+            _ => format!("{{terminal}} sudo pacman -S {name}"),
+        };
+        let info = match (&self.aur_helper, package.repo.as_str()) {
+            (Some(helper), "aur") => hold_in_terminal(&format!("{helper} -Si {name}")),
+            _ => hold_in_terminal(&format!("pacman -Si {name}")),
+        };
+        let action = |label: &'static str, exec: String| {
+            Arc::new(ApplicationAction {
+                name: Some(SharedString::from(label)),
+                exec: Some(exec),
+                ..ApplicationAction::new("command")
+            })
+        };
+        if package.installed {
+            inner.exec = Some(hold_in_terminal(&format!("pacman -Qi {name}")));
+            inner.actions = Arc::from([
+                action("Reinstall", install),
+                action("Remove", format!("{{terminal}} sudo pacman -Rns {name}")),
+            ]);
+        } else {
+            inner.exec = Some(install);
+            inner.actions = Arc::from([action("Info", info)]);
+        }
+        inner
+    }
+
+    /// Shown until the first listing finished
+    pub fn placeholder() -> AppData {
+        let mut inner = AppData::new();
+        inner.name = Some(SharedString::from("Listing packages…"));
+        inner.search_string = String::from("pacman");
+        inner.defer_icon("package-x-generic");
+        inner
+    }
+}
+
+/// Packages in the output of `pacman -Ss`, where a line like
+/// `extra/ripgrep 14.1.1-1 [installed]` is followed by the indented description
+fn parse_search(output: &str) -> Vec<PacmanPackage> {
+    let mut packages: Vec<PacmanPackage> = Vec::new();
+    for line in output.lines() {
+        if line.starts_with(char::is_whitespace) {
+            if let Some(package) = packages.last_mut() {
+                package.description = line.trim().to_string();
+            }
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let Some((repo, name)) = parts.next().and_then(|id| id.split_once('/')) else {
+            continue;
+        };
+        let version = parts.next().unwrap_or_default();
+        packages.push(PacmanPackage {
+            repo: repo.to_string(),
+            name: name.to_string(),
+            version: version.to_string(),
+            description: String::new(),
+            installed: parts.any(|part| part.starts_with("[installed")),
+        });
+    }
+    packages
+}
+
+/// Packages in the output of `-Sl`, with lines like `aur paru-bin 2.0.4-1`
+fn parse_list(output: &str, installed: &HashSet<&str>) -> Vec<PacmanPackage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let repo = parts.next()?;
+            let name = parts.next()?;
+            let version = parts.next().unwrap_or_default();
+            Some(PacmanPackage {
+                repo: repo.to_string(),
+                name: name.to_string(),
+                version: version.to_string(),
+                description: String::new(),
+                installed: installed.contains(name)
+                    || parts.any(|part| part.starts_with("[installed")),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_packages() {
+    let output = "\
+core/bash 5.2.037-1 (base) [installed]
+    The GNU Bourne Again shell
+extra/ripgrep 14.1.1-1 [installed: 14.1.0-1]
+    A search tool that combines the usability of ag with the raw speed of grep
+extra/fd 10.2.0-1
+    Simple, fast and user-friendly alternative to find
+";
+    let packages = parse_search(output);
+    assert_eq!(packages.len(), 3);
+    assert_eq!(packages[0].repo, "core");
+    assert_eq!(packages[0].description, "The GNU Bourne Again shell");
+    assert!(packages[1].installed);
+    assert!(!packages[2].installed);
+
+    let installed = HashSet::from(["paru-bin"]);
+    let aur = parse_list("aur paru-bin 2.0.4-1\naur yay 12.4.2-1\n", &installed);
+    assert!(aur[0].installed);
+    assert!(!aur[1].installed);
+
+    let launcher = PacmanLauncher {
+        aur_helper: Some(String::from("paru")),
+    };
+    let inner = launcher.app_data(&packages[2]);
+    assert_eq!(inner.exec.as_deref(), Some("{terminal} sudo pacman -S fd"));
+    assert_eq!(
+        inner.generic_name,
+        Some(SharedString::from(
+            "extra · 10.2.0-1 · Simple, fast and user-friendly alternative to find"
+        ))
+    );
+    let inner = launcher.app_data(&aur[1]);
+    assert_eq!(inner.exec.as_deref(), Some("{terminal} paru -S yay"));
+    let inner = launcher.app_data(&packages[0]);
+    assert_eq!(inner.actions.len(), 2);
+    assert_eq!(
+        inner.actions[1].exec.as_deref(),
+        Some("{terminal} sudo pacman -Rns bash")
+    );
+}
@@ -0,0 +1,294 @@
+use chrono::Local;
+use gpui::{App, AsyncApp, Entity, SharedString};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::feed_launcher::unix_now;
+use crate::launcher::Launcher;
+use crate::launcher::children::RenderableChild;
+use crate::loader::utils::{AppData, construct_search};
+use crate::loader::{is_loaded, reload_launcher};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+use crate::utils::paths::get_cache_dir;
+
+/// What an entry of the launcher does, stored in the `exec` of its tile
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordCommand {
+    Screen,
+    /// Records the region selected with `slurp`
+    Region,
+    /// Stops the recording, which saves it
+    Stop,
+}
+impl RecordCommand {
+    pub fn exec(self) -> &'static str {
+        match self {
+            Self::Screen => "record:screen",
+            Self::Region => "record:region",
+            Self::Stop => "record:stop",
+        }
+    }
+    /// Reverses [`RecordCommand::exec`]
+    pub fn parse_exec(exec: &str) -> Option<Self> {
+        match exec {
+            "record:screen" => Some(Self::Screen),
+            "record:region" => Some(Self::Region),
+            "record:stop" => Some(Self::Stop),
+            _ => None,
+        }
+    }
+}
+
+/// Records the screen with `wf-recorder`. While a recording runs, a tile with its elapsed time
+/// takes the place of the entries that start one.
+#[derive(Clone, Debug)]
+pub struct RecordLauncher {
+    /// Where recordings are saved
+    pub directory: PathBuf,
+    /// Records the default audio output along with the screen
+    pub audio: bool,
+}
+
+/// A recording that runs, as kept in the state file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recording {
+    pub pid: i32,
+    /// Unix timestamp of the start
+    pub started: i64,
+    pub file: PathBuf,
+}
+
+impl RecordLauncher {
+    pub const DEFAULT_DIRECTORY: &str = "~/Videos";
+
+    /// Remembers the recording, so it outlives the window that started it
+    fn state_file() -> Result<PathBuf, SherlockError> {
+        Ok(get_cache_dir()?.join("recording"))
+    }
+
+    /// The recording that runs, if any
+    pub fn current() -> Option<Recording> {
+        let state = std::fs::read_to_string(Self::state_file().ok()?).ok()?;
+        let recording = parse_state(&state)?;
+        running(recording.pid).then_some(recording)
+    }
+
+    pub fn control(&self, command: RecordCommand) -> Result<(), SherlockError> {
+        match command {
+            RecordCommand::Stop => {
+                if let Some(recording) = Self::current() {
+                    // wf-recorder finishes the file on interrupts
+                    unsafe { libc::kill(recording.pid, libc::SIGINT) };
+                }
+                Ok(())
+            }
+            RecordCommand::Screen | RecordCommand::Region => self.start(command),
+        }
+    }
+
+    fn start(&self, command: RecordCommand) -> Result<(), SherlockError> {
+        if Self::current().is_some() {
+            return Ok(());
+        }
+        let error = |e: std::io::Error| {
+            sherlock_error!(
+                SherlockErrorType::CommandExecutionError(String::from("wf-recorder")),
+                e.to_string()
+            )
+        };
+        std::fs::create_dir_all(&self.directory).map_err(error)?;
+        let file = self.directory.join(format!(
+            "recording-{}.mp4",
+            Local::now().format("%Y-%m-%d-%H%M%S")
+        ));
+        let audio = if self.audio { " --audio" } else { "" };
+        // The shell runs slurp first, so selecting a region does not block Sherlock
+        let script = match command {
+            RecordCommand::Region => {
+                format!(
+                    r#"region=$(slurp) || exit 1; exec wf-recorder{audio} -g "$region" -f "$1""#
+                )
+            }
+            _ => format!(r#"exec wf-recorder{audio} -f "$1""#),
+        };
+        let mut child = Command::new("sh")
+            .args(["-c", &script, "sh"])
+            .arg(&file)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(error)?;
+        let recording = Recording {
+            pid: child.id() as i32,
+            started: unix_now(),
+            file,
+        };
+        std::fs::write(Self::state_file()?, state(&recording)).map_err(error)?;
+        // Reaps the recorder once it stopped
+        std::thread::spawn(move || child.wait());
+        Ok(())
+    }
+
+    /// The entries that start a recording
+    pub fn entries() -> Vec<AppData> {
+        [
+            (RecordCommand::Screen, "Record Screen"),
+            (RecordCommand::Region, "Record Region"),
+        ]
+        .into_iter()
+        .map(|(command, name)| {
+            let mut inner = AppData {
+                name: Some(SharedString::from(name)),
+                search_string: construct_search(Some(name), "screen;recording;video", true),
+                exec: Some(command.exec().to_string()),
+                ..AppData::new()
+            };
+            inner.defer_icon("media-record");
+            inner
+        })
+        .collect()
+    }
+
+    /// The tile of the running recording, which stops it
+    pub fn recording_tile(recording: &Recording, now: i64) -> AppData {
+        let mut inner = AppData {
+            name: Some(SharedString::from("Recording…")),
+            search_string: construct_search(Some("Recording"), "screen;stop;video", true),
+            exec: Some(RecordCommand::Stop.exec().to_string()),
+            ..AppData::new()
+        };
+        Self::update_elapsed(&mut inner, recording, now);
+        inner.defer_icon("media-playback-stop");
+        inner
+    }
+
+    pub fn update_elapsed(inner: &mut AppData, recording: &Recording, now: i64) {
+        inner.generic_name = Some(SharedString::from(format!(
+            "{} · Stop and save",
+            elapsed(now - recording.started)
+        )));
+    }
+}
+
+/// Updates the elapsed time of the recording every second and reloads the launcher once a
+/// recording started or stopped. Stops when the launcher is unloaded or loaded again, which
+/// starts a ticker of its own.
+pub fn watch_recording(
+    cx: &mut App,
+    launcher: &Arc<Launcher>,
+    opts: Arc<Value>,
+    data_handle: Entity<Arc<Vec<RenderableChild>>>,
+) {
+    let stop = launcher.watchers.start();
+    let launcher = Arc::clone(launcher);
+    cx.spawn(move |cx: &mut AsyncApp| {
+        let cx = cx.clone();
+        async move {
+            let mut shown = RecordLauncher::current();
+            loop {
+                cx.background_executor().timer(Duration::from_secs(1)).await;
+                if stop.is_stopped() || !is_loaded(&cx, &data_handle, &launcher) {
+                    break;
+                }
+                let recording = RecordLauncher::current();
+                if recording.is_some() != shown.is_some() {
+                    shown = recording;
+                    let _ = cx.update(|cx| reload_launcher(cx, &launcher, &opts, &data_handle));
+                    continue;
+                }
+                let Some(recording) = &recording else {
+                    continue;
+                };
+                let now = unix_now();
+                let _ = cx.update(|cx| {
+                    data_handle.update(cx, |items_arc, cx| {
+                        let items = Arc::make_mut(items_arc);
+                        for item in items.iter_mut() {
+                            if item.is_from(&launcher)
+                                && let RenderableChild::AppLike { inner, .. } = item
+                            {
+                                RecordLauncher::update_elapsed(inner, recording, now);
+                            }
+                        }
+                        cx.notify();
+                    });
+                });
+            }
+        }
+    })
+    .detach();
+}
+
+/// Whether the process still records, rather than having exited or being another process
+/// that got the id since
+fn running(pid: i32) -> bool {
+    let proc = Path::new("/proc").join(pid.to_string());
+    let zombie = std::fs::read_to_string(proc.join("stat"))
+        .ok()
+        .and_then(|stat| {
+            // `1234 (wf-recorder) Z …`, the name may contain spaces
+            let state = stat.rsplit_once(')')?.1.trim_start().chars().next()?;
+            Some(state == 'Z')
+        })
+        .unwrap_or(true);
+    let cmdline = std::fs::read(proc.join("cmdline")).unwrap_or_default();
+    !zombie && String::from_utf8_lossy(&cmdline).contains("wf-recorder")
+}
+
+fn state(recording: &Recording) -> String {
+    format!(
+        "{}\n{}\n{}\n",
+        recording.pid,
+        recording.started,
+        recording.file.display()
+    )
+}
+
+/// Reverses [`state`]
+fn parse_state(state: &str) -> Option<Recording> {
+    let mut lines = state.lines();
+    Some(Recording {
+        pid: lines.next()?.parse().ok()?,
+        started: lines.next()?.parse().ok()?,
+        file: PathBuf::from(lines.next()?),
+    })
+}
+
+/// Seconds as `1:02:03`, or `02:03` below an hour
+fn elapsed(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let (hours, minutes, seconds) = (seconds / 3_600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+#[test]
+fn test_recording_state() {
+    let recording = Recording {
+        pid: 4242,
+        started: 1_749_009_600,
+        file: PathBuf::from("/home/user/Videos/recording-2025-06-04-040000.mp4"),
+    };
+    assert_eq!(parse_state(&state(&recording)), Some(recording.clone()));
+    assert_eq!(parse_state("4242\n"), None);
+
+    assert_eq!(elapsed(83), "01:23");
+    assert_eq!(elapsed(3_723), "1:02:03");
+    let inner = RecordLauncher::recording_tile(&recording, recording.started + 83);
+    assert_eq!(
+        inner.generic_name,
+        Some(SharedString::from("01:23 · Stop and save"))
+    );
+    assert_eq!(
+        RecordCommand::parse_exec(RecordCommand::Region.exec()),
+        Some(RecordCommand::Region)
+    );
+}
@@ -0,0 +1,277 @@
+use gpui::SharedString;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::loader::utils::{AppData, ApplicationAction};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+use crate::utils::watch::{STOP_CHECK_INTERVAL, StopToken, wait_readable};
+
+use super::audio_device_launcher::command_output;
+use super::{Launcher, LauncherType};
+
+/// Shows and adjusts the volume of the default output
+#[derive(Clone, Debug)]
+pub struct VolumeLauncher {
+    /// Percent the volume changes by per key press or scroll step
+    pub step: u32,
+}
+impl VolumeLauncher {
+    pub const DEFAULT_STEP: u32 = 5;
+
+    /// Step of the launcher a volume tile belongs to
+    pub fn step_of(launcher: &Launcher) -> i32 {
+        match &launcher.launcher_type {
+            LauncherType::Volume(volume) => volume.step as i32,
+            _ => Self::DEFAULT_STEP as i32,
+        }
+    }
+
+    /// Shown until the watcher read the volume
+    pub fn placeholder() -> AppData {
+        let mut inner = AppData::new();
+        inner.name = Some(SharedString::from("Reading the volume…"));
+        inner.search_string = String::from("volume;sound;audio");
+        inner.defer_icon("audio-volume-medium");
+        inner
+    }
+}
+
+/// The tool the volume is read and changed with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mixer {
+    /// WirePlumber's `wpctl`
+    Wpctl,
+    /// `pactl`, which also works with `pipewire-pulse`
+    Pactl,
+}
+impl Mixer {
+    /// The first mixer that can read the volume, checked once per run
+    pub fn detect() -> Option<Self> {
+        static MIXER: OnceLock<Option<Mixer>> = OnceLock::new();
+        *MIXER.get_or_init(|| {
+            [Self::Wpctl, Self::Pactl]
+                .into_iter()
+                .find(|mixer| mixer.volume().is_ok())
+        })
+    }
+
+    pub fn volume(self) -> Result<VolumeData, SherlockError> {
+        let parsed = match self {
+            Self::Wpctl => parse_wpctl(&command_output(
+                "wpctl",
+                &["get-volume", "@DEFAULT_AUDIO_SINK@"],
+            )?),
+            Self::Pactl => parse_pactl(
+                &command_output("pactl", &["get-sink-volume", "@DEFAULT_SINK@"])?,
+                &command_output("pactl", &["get-sink-mute", "@DEFAULT_SINK@"])?,
+            ),
+        };
+        parsed.ok_or_else(|| {
+            sherlock_error!(
+                SherlockErrorType::DeserializationError,
+                format!("Unexpected volume reported by {self:?}")
+            )
+        })
+    }
+
+    /// Changes the volume by the given percent, see [`target_volume`]
+    pub fn change(self, percent: i32) -> Result<(), SherlockError> {
+        let current = (self.volume()?.level * 100.0).round() as i32;
+        let target = target_volume(current, percent);
+        match self {
+            Self::Wpctl => command_output(
+                "wpctl",
+                &[
+                    "set-volume",
+                    "@DEFAULT_AUDIO_SINK@",
+                    &format!("{:.2}", target as f32 / 100.0),
+                ],
+            ),
+            Self::Pactl => command_output(
+                "pactl",
+                &["set-sink-volume", "@DEFAULT_SINK@", &format!("{target}%")],
+            ),
+        }?;
+        Ok(())
+    }
+
+    pub fn toggle_mute(self) -> Result<(), SherlockError> {
+        match self {
+            Self::Wpctl => command_output("wpctl", &["set-mute", "@DEFAULT_AUDIO_SINK@", "toggle"]),
+            Self::Pactl => command_output("pactl", &["set-sink-mute", "@DEFAULT_SINK@", "toggle"]),
+        }?;
+        Ok(())
+    }
+}
+
+/// The volume after a change, which does not go below 0 %. Raising it stops at 100 %, or at the
+/// current volume if the output is boosted beyond that, so lowering a boosted volume does not
+/// jump down to 100 % either.
+fn target_volume(current: i32, percent: i32) -> i32 {
+    (current + percent).clamp(0, current.max(100))
+}
+
+fn no_mixer() -> SherlockError {
+    sherlock_error!(
+        SherlockErrorType::CommandExecutionError(String::from("volume")),
+        "Neither wpctl nor pactl is available"
+    )
+}
+
+/// Volume of the default output
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VolumeData {
+    /// `1.0` is 100 %, boosted outputs can go beyond that
+    pub level: f32,
+    pub muted: bool,
+}
+impl VolumeData {
+    pub fn control(command: VolumeCommand) -> Result<(), SherlockError> {
+        // Steps of quick scrolling run in the background one after the other, each starting
+        // from the volume the previous one set
+        static CONTROL: Mutex<()> = Mutex::new(());
+        let _guard = CONTROL.lock();
+        let mixer = Mixer::detect().ok_or_else(no_mixer)?;
+        match command {
+            VolumeCommand::Change(percent) => mixer.change(percent),
+            VolumeCommand::ToggleMute => mixer.toggle_mute(),
+        }
+    }
+    /// Context menu entries of the volume tile
+    pub fn actions(&self) -> Arc<[Arc<ApplicationAction>]> {
+        let name = if self.muted { "Unmute" } else { "Mute" };
+        Arc::from([Arc::new(ApplicationAction {
+            name: Some(SharedString::from(name)),
+            exit: false,
+            ..ApplicationAction::new("volume_mute")
+        })])
+    }
+}
+
+/// Commands the volume tile sends to the mixer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VolumeCommand {
+    /// Change in percent
+    Change(i32),
+    ToggleMute,
+}
+impl VolumeCommand {
+    /// Maps the method of a context action to its command
+    pub fn from_method(method: &str) -> Option<Self> {
+        match method {
+            "volume_mute" => Some(Self::ToggleMute),
+            _ => None,
+        }
+    }
+}
+
+/// Parses `Volume: 0.45 [MUTED]`
+fn parse_wpctl(output: &str) -> Option<VolumeData> {
+    let mut words = output.trim().strip_prefix("Volume:")?.split_whitespace();
+    Some(VolumeData {
+        level: words.next()?.parse().ok()?,
+        muted: words.any(|word| word == "[MUTED]"),
+    })
+}
+
+/// Parses `Volume: front-left: 29491 /  45% / -20.81 dB, ...` and `Mute: no`, the level of the
+/// first channel stands for all of them
+fn parse_pactl(volume: &str, mute: &str) -> Option<VolumeData> {
+    let percent = volume
+        .split_whitespace()
+        .find_map(|word| word.strip_suffix('%')?.parse::<f32>().ok())?;
+    Some(VolumeData {
+        level: percent / 100.0,
+        muted: mute.trim().strip_prefix("Mute:")?.trim() == "yes",
+    })
+}
+
+/// Calls `on_change` with the current volume and whenever the volume or the default output
+/// changes, until it returns `false` or the watcher is stopped. Changes are announced by `pactl subscribe`, without it the
+/// volume is polled. Blocks the calling thread.
+pub fn watch_volume(stop: &StopToken, mut on_change: impl FnMut(VolumeData) -> bool) {
+    let Some(mixer) = Mixer::detect() else {
+        return;
+    };
+    let mut last = None;
+    let mut changed = || {
+        let volume = mixer.volume().ok();
+        if volume == last {
+            return true;
+        }
+        last = volume.clone();
+        volume.is_none_or(&mut on_change)
+    };
+    if !changed() {
+        return;
+    }
+
+    let subscription = Command::new("pactl")
+        .arg("subscribe")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Ok(mut child) = subscription
+        && let Some(stdout) = child.stdout.take()
+    {
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+        while !stop.is_stopped() {
+            // Lines read along with the previous one are handled without waiting
+            if reader.buffer().is_empty() && !wait_readable(reader.get_ref(), STOP_CHECK_INTERVAL) {
+                continue;
+            }
+            line.clear();
+            if !matches!(reader.read_line(&mut line), Ok(read) if read > 0) {
+                break;
+            }
+            // `Event 'change' on sink #58`, the server reports a new default output
+            let relevant = line.contains(" on sink ") || line.contains(" on server ");
+            if relevant && !changed() {
+                break;
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+        return;
+    }
+
+    while !stop.is_stopped() && changed() {
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+#[test]
+fn test_parse_volume() {
+    assert_eq!(
+        parse_wpctl("Volume: 0.45\n"),
+        Some(VolumeData {
+            level: 0.45,
+            muted: false
+        })
+    );
+    assert_eq!(
+        parse_wpctl("Volume: 1.20 [MUTED]").map(|v| v.muted),
+        Some(true)
+    );
+    assert_eq!(parse_wpctl("error"), None);
+
+    let volume = "Volume: front-left: 29491 /  45% / -20.81 dB,   front-right: 29491 /  45% / \
+                  -20.81 dB\n        balance 0.00\n";
+    assert_eq!(
+        parse_pactl(volume, "Mute: yes\n"),
+        Some(VolumeData {
+            level: 0.45,
+            muted: true
+        })
+    );
+
+    assert_eq!(target_volume(98, 5), 100);
+    assert_eq!(target_volume(3, -5), 0);
+    // Boosted volumes are kept when raised and lowered from where they are
+    assert_eq!(target_volume(120, 5), 120);
+    assert_eq!(target_volume(120, -5), 115);
+}
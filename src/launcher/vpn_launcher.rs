@@ -0,0 +1,267 @@
+use gpui::SharedString;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use super::audio_device_launcher::command_output;
+use crate::loader::utils::{AppData, construct_search};
+use crate::utils::errors::SherlockError;
+use crate::utils::watch::{STOP_CHECK_INTERVAL, StopToken, wait_readable};
+
+/// How a connection is brought up and down
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VpnKind {
+    /// VPN and WireGuard connections of NetworkManager
+    NetworkManager,
+    /// WireGuard interfaces of `wg-quick`
+    WgQuick,
+}
+
+/// VPN connections of NetworkManager and WireGuard interfaces of `wg-quick`, which are toggled
+/// on activation
+#[derive(Clone, Debug)]
+pub struct VpnLauncher {
+    /// `wg-quick` interfaces to list while they are down, `/etc/wireguard` is usually not
+    /// readable without root
+    pub interfaces: Vec<String>,
+    /// Helper that runs `wg-quick` as root
+    pub elevation: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VpnConnection {
+    pub name: String,
+    pub kind: VpnKind,
+    pub active: bool,
+}
+
+impl VpnLauncher {
+    /// Connections of NetworkManager, then the interfaces of `wg-quick`, each sorted by name
+    pub fn connections(&self) -> Result<Vec<VpnConnection>, SherlockError> {
+        // NetworkManager might not be running, while wg-quick is
+        let (mut connections, managed) = match command_output(
+            "nmcli",
+            &["-t", "-f", "NAME,TYPE,DEVICE", "connection", "show"],
+        ) {
+            Ok(output) => parse_nmcli(&output),
+            Err(_) => (Vec::new(), HashSet::new()),
+        };
+        let links = command_output("ip", &["-o", "link", "show", "type", "wireguard"])?;
+        let up: HashSet<String> = parse_links(&links)
+            .into_iter()
+            .filter(|link| !managed.contains(link))
+            .collect();
+        let configured = std::fs::read_dir("/etc/wireguard")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                name.strip_suffix(".conf").map(str::to_string)
+            });
+        let mut interfaces: Vec<String> = self
+            .interfaces
+            .iter()
+            .cloned()
+            .chain(configured)
+            .chain(up.iter().cloned())
+            .collect();
+        interfaces.sort();
+        interfaces.dedup();
+        connections.extend(interfaces.into_iter().map(|name| VpnConnection {
+            active: up.contains(&name),
+            name,
+            kind: VpnKind::WgQuick,
+        }));
+        Ok(connections)
+    }
+
+    /// The tile of a connection, which brings it down if it is up and up otherwise
+    pub fn app_data(&self, connection: &VpnConnection) -> AppData {
+        let kind = match connection.kind {
+            VpnKind::NetworkManager => "VPN",
+            VpnKind::WgQuick => "WireGuard",
+        };
+        let mut inner = AppData {
+            name: Some(SharedString::from(connection.name.clone())),
+            search_string: construct_search(Some(&connection.name), &format!("vpn;{kind}"), true),
+            ..AppData::new()
+        };
+        self.apply_state(&mut inner, connection);
+        inner
+    }
+
+    /// Sets the parts of the tile that change when the connection goes up or down
+    pub fn apply_state(&self, inner: &mut AppData, connection: &VpnConnection) {
+        let quoted = connection.name.replace('\'', r"'\''");
+        let toggle = if connection.active { "down" } else { "up" };
+        let (kind, exec) = match connection.kind {
+            VpnKind::NetworkManager => ("VPN", format!("nmcli connection {toggle} id '{quoted}'")),
+            VpnKind::WgQuick => (
+                "WireGuard",
+                format!("{} wg-quick {toggle} '{quoted}'", self.elevation),
+            ),
+        };
+        let state = if connection.active {
+            "Connected"
+        } else {
+            "Disconnected"
+        };
+        inner.generic_name = Some(SharedString::from(format!("{kind} · {state}")));
+        inner.exec = Some(exec);
+        if connection.active {
+            inner.defer_icon("network-vpn");
+        } else {
+            inner.defer_icon("network-vpn-disconnected");
+        }
+    }
+
+    /// Calls `on_change` with the connections, then again whenever one goes up or down, until
+    /// it returns `false` or the watcher is stopped. Changes are announced by `ip monitor link`,
+    /// without it the connections are polled. Blocks the calling thread, fails if the
+    /// connections cannot be listed at all.
+    pub fn watch(
+        &self,
+        stop: &StopToken,
+        mut on_change: impl FnMut(Vec<VpnConnection>) -> bool,
+    ) -> Result<(), SherlockError> {
+        let mut last = self.connections()?;
+        if !on_change(last.clone()) {
+            return Ok(());
+        }
+        let mut changed = || match self.connections() {
+            Ok(connections) if connections != last => {
+                last = connections.clone();
+                on_change(connections)
+            }
+            _ => true,
+        };
+
+        let monitor = Command::new("ip")
+            .args(["-o", "monitor", "link"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Ok(mut child) = monitor
+            && let Some(stdout) = child.stdout.take()
+        {
+            let mut reader = BufReader::new(stdout);
+            let mut line = String::new();
+            while !stop.is_stopped() {
+                // Lines read along with the previous one are handled without waiting
+                if reader.buffer().is_empty()
+                    && !wait_readable(reader.get_ref(), STOP_CHECK_INTERVAL)
+                {
+                    continue;
+                }
+                line.clear();
+                if !matches!(reader.read_line(&mut line), Ok(read) if read > 0) || !changed() {
+                    break;
+                }
+                // NetworkManager marks its connection active only after the link came up
+                std::thread::sleep(Duration::from_secs(1));
+                if !changed() {
+                    break;
+                }
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(());
+        }
+
+        while !stop.is_stopped() && changed() {
+            std::thread::sleep(Duration::from_secs(2));
+        }
+        Ok(())
+    }
+
+    /// Shown until the connections were listed
+    pub fn placeholder() -> AppData {
+        let mut inner = AppData::new();
+        inner.name = Some(SharedString::from("Listing VPN connections…"));
+        inner.search_string = String::from("vpn");
+        inner.defer_icon("network-vpn");
+        inner
+    }
+}
+
+/// VPN and WireGuard connections in the output of `nmcli -t -f NAME,TYPE,DEVICE connection
+/// show`, along with the devices of the active ones
+fn parse_nmcli(output: &str) -> (Vec<VpnConnection>, HashSet<String>) {
+    let mut connections = Vec::new();
+    let mut devices = HashSet::new();
+    for line in output.lines() {
+        // Colons within the fields are escaped as `\:`
+        let fields: Vec<String> = line
+            .replace(r"\:", "\u{0}")
+            .split(':')
+            .map(|field| field.replace('\u{0}', ":"))
+            .collect();
+        let [name, kind, device] = fields.as_slice() else {
+            continue;
+        };
+        if kind != "vpn" && kind != "wireguard" {
+            continue;
+        }
+        if !device.is_empty() {
+            devices.insert(device.clone());
+        }
+        connections.push(VpnConnection {
+            name: name.clone(),
+            kind: VpnKind::NetworkManager,
+            active: !device.is_empty(),
+        });
+    }
+    connections.sort_by(|a, b| a.name.cmp(&b.name));
+    (connections, devices)
+}
+
+/// Names of the links in the output of `ip -o link show`, like `7: wg0: <POINTOPOINT,…> …`
+fn parse_links(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let name = line.split(": ").nth(1)?;
+            // Links within other namespaces are named like `wg0@if3`
+            Some(name.split('@').next()?.to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_connections() {
+    let output = "Home WiFi:802-11-wireless:wlan0\n\
+Work\\: Berlin:vpn:\n\
+mullvad:wireguard:wg-mullvad\n";
+    let (connections, devices) = parse_nmcli(output);
+    assert_eq!(connections.len(), 2);
+    assert_eq!(connections[0].name, "Work: Berlin");
+    assert!(!connections[0].active);
+    assert!(connections[1].active);
+    assert_eq!(devices, HashSet::from([String::from("wg-mullvad")]));
+
+    let links = "7: wg0: <POINTOPOINT,NOARP,UP,LOWER_UP> mtu 1420 qdisc noqueue state UNKNOWN\n";
+    assert_eq!(parse_links(links), vec!["wg0"]);
+
+    let launcher = VpnLauncher {
+        interfaces: Vec::new(),
+        elevation: String::from("pkexec"),
+    };
+    let inner = launcher.app_data(&connections[0]);
+    assert_eq!(
+        inner.exec.as_deref(),
+        Some("nmcli connection up id 'Work: Berlin'")
+    );
+    let wg0 = VpnConnection {
+        name: String::from("wg0"),
+        kind: VpnKind::WgQuick,
+        active: true,
+    };
+    let inner = launcher.app_data(&wg0);
+    assert_eq!(inner.exec.as_deref(), Some("pkexec wg-quick down 'wg0'"));
+    assert_eq!(
+        inner.generic_name,
+        Some(SharedString::from("WireGuard · Connected"))
+    );
+}
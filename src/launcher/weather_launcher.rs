@@ -1,6 +1,5 @@
 use gpui::{LinearColorStop, hsla, linear_color_stop, rgb};
 use serde::{Deserialize, Serialize};
-use simd_json::base::{ValueAsArray, ValueAsScalar};
 use std::collections::HashSet;
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
@@ -9,8 +8,9 @@ use std::time::{Duration, SystemTime};
 use strum::Display;
 
 use super::utils::to_title_case;
+use super::weather_provider::WeatherProviderKind;
 use crate::loader::resolve_icon_path;
-use crate::utils::config::ConfigGuard;
+use crate::utils::config::ConfigUnits;
 use crate::utils::files::home_dir;
 
 #[derive(Clone, Debug, Deserialize)]
@@ -18,44 +18,121 @@ pub enum WeatherIconTheme {
     Sherlock,
     None,
 }
+impl WeatherIconTheme {
+    pub fn icon(&self, css: &WeatherClass) -> Option<Arc<Path>> {
+        match self {
+            Self::Sherlock => resolve_icon_path(&format!("sherlock-weather-{}", css)),
+            Self::None => resolve_icon_path(&format!("weather-{}", css)),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct WeatherLauncher {
-    pub location: String,
+    /// Every location is shown as a tile of its own
+    pub locations: Vec<String>,
     pub update_interval: u64,
     pub icon_theme: WeatherIconTheme,
     pub show_datetime: bool,
+    pub provider: WeatherProviderKind,
+    /// Read from the config once the launcher is loaded
+    pub units: WeatherUnits,
 }
 impl WeatherLauncher {}
 
+/// Weather as reported for a location. Values are stored in metric units and converted to the
+/// configured ones when they are shown, see [`WeatherUnits`].
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WeatherData {
-    pub temperature: String,
+    /// Temperature in °C
+    pub temperature: f32,
+    /// Wind speed in km/h
+    pub wind_speed: f32,
+    /// Direction the wind is coming from, in degrees
+    pub wind_direction: f32,
     pub icon: Option<Arc<Path>>,
-    pub format_str: String,
     pub location: String,
     pub css: WeatherClass,
     pub sunset: chrono::NaiveTime,
     pub init: bool,
+    /// Upcoming days, shown once the tile is expanded
+    #[serde(default)]
+    pub forecast: Vec<ForecastDay>,
+    #[serde(skip)]
+    pub expanded: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ForecastDay {
+    pub date: chrono::NaiveDate,
+    /// Temperatures in °C
+    pub min: f32,
+    pub max: f32,
+    pub css: WeatherClass,
+    #[serde(skip)]
+    pub icon: Option<Arc<Path>>,
+    /// Three-hourly temperatures of the day
+    pub hourly: Vec<ForecastHour>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ForecastHour {
+    pub time: chrono::NaiveDateTime,
+    /// Temperature in °C
+    pub temperature: f32,
+    pub css: WeatherClass,
+    #[serde(skip)]
+    pub icon: Option<Arc<Path>>,
 }
 impl WeatherData {
-    pub fn uninitialized() -> Self {
+    /// Placeholder for a location whose weather is still being fetched
+    pub fn uninitialized(location: &str) -> Self {
         Self {
-            temperature: String::new(),
+            temperature: 0.0,
+            wind_speed: 0.0,
+            wind_direction: 0.0,
             icon: None,
-            format_str: String::new(),
-            location: String::new(),
+            location: location.to_string(),
             css: WeatherClass::None,
             sunset: chrono::NaiveTime::default(),
             init: false,
+            forecast: Vec::new(),
+            expanded: false,
         }
     }
-    pub fn from_cache(launcher: &WeatherLauncher) -> Option<Self> {
+    /// Location and wind shown above the temperature, e.g. `Berlin  ↗ 12km/h`
+    pub fn summary(&self, units: &WeatherUnits) -> String {
+        let sector_size: f32 = 45.0;
+        let index = ((self.wind_direction + sector_size / 2.0) / sector_size).floor() as usize % 8;
+        let wind_dirs = ["↑", "↗", "→", "↘", "↓", "↙", "←", "↖"];
+        format!(
+            "{}  {} {}",
+            to_title_case(&self.location),
+            wind_dirs[index],
+            units.wind_speed(self.wind_speed)
+        )
+    }
+    /// The next three-hourly slots, starting with the current one
+    pub fn upcoming_hours(&self, count: usize) -> impl Iterator<Item = &ForecastHour> {
+        let now = chrono::Local::now().naive_local();
+        self.forecast
+            .iter()
+            .flat_map(|day| &day.hourly)
+            .filter(move |hour| hour.time + chrono::Duration::hours(3) > now)
+            .take(count)
+    }
+    fn resolve_icons(&mut self, theme: &WeatherIconTheme) {
+        self.icon = theme.icon(&self.css);
+        for day in &mut self.forecast {
+            day.icon = theme.icon(&day.css);
+            for hour in &mut day.hourly {
+                hour.icon = theme.icon(&hour.css);
+            }
+        }
+    }
+    pub fn from_cache(launcher: &WeatherLauncher, location: &str) -> Option<Self> {
         let mut path = home_dir().ok()?;
-        path.push(format!(
-            ".cache/sherlock/weather/{}.json",
-            launcher.location
-        ));
+        path.push(format!(".cache/sherlock/weather/{}.json", location));
         fn modtime(path: &PathBuf) -> Option<SystemTime> {
             fs::metadata(path).ok().and_then(|m| m.modified().ok())
         }
@@ -66,11 +143,7 @@ impl WeatherData {
                 .ok()
                 .and_then(|f| simd_json::from_reader(f).ok())?;
 
-            cached_data.icon = if matches!(launcher.icon_theme, WeatherIconTheme::Sherlock) {
-                resolve_icon_path(&format!("sherlock-weather-{}", cached_data.css))
-            } else {
-                resolve_icon_path(&format!("weather-{}", cached_data.css))
-            };
+            cached_data.resolve_icons(&launcher.icon_theme);
 
             return Some(cached_data);
         } else {
@@ -93,101 +166,117 @@ impl WeatherData {
         }
         None
     }
-    pub async fn fetch_async(launcher: &WeatherLauncher) -> Option<(WeatherData, bool)> {
-        let config = ConfigGuard::read().ok()?;
+    pub async fn fetch_async(
+        launcher: &WeatherLauncher,
+        location: &str,
+    ) -> Option<(WeatherData, bool)> {
         // try read cache
-        if let Some(data) = WeatherData::from_cache(launcher) {
+        if let Some(data) = WeatherData::from_cache(launcher, location) {
             return Some((data, false));
         };
 
-        let url = format!("https://de.wttr.in/{}?format=j2", launcher.location);
+        let report = launcher.provider.fetch(location).await?;
+        let mut data = WeatherData {
+            temperature: report.temperature,
+            wind_speed: report.wind_speed,
+            wind_direction: report.wind_direction,
+            icon: None,
+            location: location.to_string(),
+            css: report.css,
+            sunset: report.sunset,
+            init: true,
+            forecast: report.forecast,
+            expanded: false,
+        };
+        data.cache();
+        data.resolve_icons(&launcher.icon_theme);
 
-        let response = reqwest::get(url).await.ok()?.text().await.ok()?;
-        let mut response_bytes = response.into_bytes();
-        let json: simd_json::OwnedValue = simd_json::to_owned_value(&mut response_bytes).ok()?;
-        let current_condition = json["current_condition"].as_array()?.get(0)?;
+        Some((data, true))
+    }
+}
 
-        // Get sunset time
-        let astronomy = json["weather"].as_array()?.get(0)?["astronomy"]
-            .as_array()?
-            .get(0)?;
-        let sunset_raw = astronomy["sunset"].as_str()?;
-        let sunset = chrono::NaiveTime::parse_from_str(sunset_raw, "%I:%M %p").ok()?;
+/// Units of `config.units` the weather is shown in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeatherUnits {
+    pub temperature: TemperatureUnit,
+    pub wind_speed: WindSpeedUnit,
+}
+impl WeatherUnits {
+    /// The units of the config. Without a `wind_speeds` unit, the wind speed follows `lengths`,
+    /// so mph is used alongside imperial lengths.
+    pub fn from_config(units: &ConfigUnits) -> Self {
+        Self {
+            temperature: TemperatureUnit::parse(&units.temperatures),
+            wind_speed: units
+                .wind_speeds
+                .as_deref()
+                .and_then(WindSpeedUnit::parse)
+                .unwrap_or_else(|| WindSpeedUnit::from_lengths(&units.lengths)),
+        }
+    }
+    /// Formats a temperature given in °C
+    pub fn temperature(&self, celsius: f32) -> String {
+        match self.temperature {
+            TemperatureUnit::Celsius => format!("{:.0}°C", celsius),
+            TemperatureUnit::Fahrenheit => format!("{:.0}°F", celsius * 9.0 / 5.0 + 32.0),
+            TemperatureUnit::Kelvin => format!("{:.0}K", celsius + 273.15),
+        }
+    }
+    /// Formats a wind speed given in km/h
+    pub fn wind_speed(&self, kmh: f32) -> String {
+        match self.wind_speed {
+            WindSpeedUnit::KilometersPerHour => format!("{:.0}km/h", kmh),
+            WindSpeedUnit::MilesPerHour => format!("{:.0}mph", kmh / 1.609_344),
+            WindSpeedUnit::MetersPerSecond => format!("{:.1}m/s", kmh / 3.6),
+            WindSpeedUnit::Knots => format!("{:.0}kn", kmh / 1.852),
+        }
+    }
+}
 
-        // Parse Temperature
-        let temperature = match config.units.temperatures.as_str() {
-            "f" | "F" => format!("{}°F", current_condition["temp_F"].as_str()?),
-            _ => format!("{}°C", current_condition["temp_C"].as_str()?),
-        };
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+impl TemperatureUnit {
+    /// Unknown units fall back to Celsius
+    fn parse(unit: &str) -> Self {
+        match unit.trim_start_matches('°').to_lowercase().as_str() {
+            "f" | "fahrenheit" => Self::Fahrenheit,
+            "k" | "kelvin" => Self::Kelvin,
+            _ => Self::Celsius,
+        }
+    }
+}
 
-        // Parse Icon
-        let code = current_condition["weatherCode"].as_str()?;
-        let icon = if matches!(launcher.icon_theme, WeatherIconTheme::Sherlock) {
-            resolve_icon_path(&format!(
-                "sherlock-weather-{}",
-                Self::match_weather_code(code)
-            ))
-        } else {
-            resolve_icon_path(&format!("weather-{}", Self::match_weather_code(code)))
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindSpeedUnit {
+    KilometersPerHour,
+    MilesPerHour,
+    MetersPerSecond,
+    Knots,
+}
+impl WindSpeedUnit {
+    fn parse(unit: &str) -> Option<Self> {
+        let unit = match unit.to_lowercase().as_str() {
+            "km/h" | "kmh" | "kph" => Self::KilometersPerHour,
+            "mph" => Self::MilesPerHour,
+            "m/s" | "ms" => Self::MetersPerSecond,
+            "kn" | "kt" | "knots" => Self::Knots,
+            _ => return None,
         };
-
-        // Parse wind dir
-        let wind_deg = current_condition["winddirDegree"]
-            .as_str()?
-            .parse::<f32>()
-            .ok()?;
-        let sector_size: f32 = 45.0;
-        let index = ((wind_deg + sector_size / 2.0) / sector_size).floor() as usize % 8;
-        let win_dirs = ["↑", "↗", "→", "↘", "↓", "↙", "←", "↖"];
-        let wind_dir = win_dirs.get(index)?;
-
-        // Parse wind speed
+        Some(unit)
+    }
+    fn from_lengths(lengths: &str) -> Self {
         let imperials: HashSet<&str> = HashSet::from([
             "inches", "inch", "in", "feet", "foot", "ft", "yards", "yard", "yd", "miles", "mile",
             "mi",
         ]);
-        let wind = if imperials.contains(config.units.lengths.to_lowercase().as_str()) {
-            let speed = current_condition["windspeedMiles"].as_str()?;
-            format!("{} {}mph", wind_dir, speed)
+        if imperials.contains(lengths.to_lowercase().as_str()) {
+            Self::MilesPerHour
         } else {
-            let speed = current_condition["windspeedKmph"].as_str()?;
-            format!("{} {}km/h", wind_dir, speed)
-        };
-
-        let loc = to_title_case(&launcher.location);
-        let format_str = format!("{}  {}", loc, wind);
-        let data = WeatherData {
-            temperature,
-            icon,
-            format_str,
-            location: launcher.location.clone(),
-            css: Self::match_weather_code(code),
-            sunset,
-            init: true,
-        };
-        data.cache();
-
-        Some((data, true))
-    }
-    fn match_weather_code(code: &str) -> WeatherClass {
-        match code {
-            "113" => WeatherClass::Clear,
-            "116" => WeatherClass::FewClouds,
-            "119" | "122" => WeatherClass::ManyClouds,
-            "143" | "248" | "260" => WeatherClass::Mist,
-            "176" | "263" | "299" | "305" | "353" | "356" => WeatherClass::Showers,
-            "179" | "362" | "365" | "374" => WeatherClass::FreezingScatteredRainStorm,
-            "182" | "185" | "281" | "284" | "311" | "314" | "317" | "350" | "377" => {
-                WeatherClass::FreezingScatteredRain
-            }
-            "200" | "302" | "308" | "359" | "386" | "389" => WeatherClass::Storm,
-            "227" | "320" => WeatherClass::SnowScatteredDay,
-            "230" | "329" | "332" | "338" => WeatherClass::SnowStorm,
-            "323" | "326" | "335" | "368" | "371" | "392" | "395" => {
-                WeatherClass::SnowScatteredStorm
-            }
-            "266" | "293" | "296" => WeatherClass::ShowersScattered,
-            _ => WeatherClass::None,
+            Self::KilometersPerHour
         }
     }
 }
@@ -266,3 +355,21 @@ impl WeatherClass {
         }
     }
 }
+
+#[test]
+fn test_weather_units() {
+    let units = WeatherUnits {
+        temperature: TemperatureUnit::parse("°F"),
+        wind_speed: WindSpeedUnit::from_lengths("Miles"),
+    };
+    assert_eq!(units.temperature(20.0), "68°F");
+    assert_eq!(units.wind_speed(16.1), "10mph");
+
+    let units = WeatherUnits {
+        temperature: TemperatureUnit::parse("C"),
+        wind_speed: WindSpeedUnit::parse("m/s").unwrap(),
+    };
+    assert_eq!(units.temperature(-3.4), "-3°C");
+    assert_eq!(units.wind_speed(18.0), "5.0m/s");
+    assert_eq!(WindSpeedUnit::parse("furlongs"), None);
+}
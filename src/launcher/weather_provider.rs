@@ -0,0 +1,302 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::weather_launcher::{ForecastDay, ForecastHour, WeatherClass};
+
+/// Current weather and forecast of a location, in metric units
+#[derive(Debug, Clone)]
+pub struct WeatherReport {
+    /// Temperature in °C
+    pub temperature: f32,
+    /// Wind speed in km/h
+    pub wind_speed: f32,
+    /// Direction the wind is coming from, in degrees
+    pub wind_direction: f32,
+    pub css: WeatherClass,
+    pub sunset: NaiveTime,
+    pub forecast: Vec<ForecastDay>,
+}
+
+/// A weather service sherlock can request reports from
+pub trait WeatherProvider {
+    /// Requests the report of a location
+    async fn fetch(&self, location: &str) -> Option<WeatherReport>;
+    /// Maps the provider's weather codes to the icons of [`WeatherClass`]
+    fn weather_class(code: u32) -> WeatherClass;
+}
+
+/// Selected through the `provider` argument of the weather launcher
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WeatherProviderKind {
+    #[default]
+    #[serde(alias = "wttr.in")]
+    Wttr,
+    #[serde(alias = "open_meteo", alias = "openmeteo")]
+    OpenMeteo,
+}
+impl WeatherProviderKind {
+    pub async fn fetch(self, location: &str) -> Option<WeatherReport> {
+        match self {
+            Self::Wttr => Wttr.fetch(location).await,
+            Self::OpenMeteo => OpenMeteo.fetch(location).await,
+        }
+    }
+}
+
+/// The weather at noon stands for the day
+fn noon_class(hourly: &[ForecastHour]) -> WeatherClass {
+    hourly
+        .iter()
+        .find(|hour| hour.time.hour() == 12)
+        .map(|hour| hour.css.clone())
+        .unwrap_or_default()
+}
+
+/// [wttr.in](https://wttr.in), which takes the location as it is
+pub struct Wttr;
+impl Wttr {
+    fn parse(json: &Value) -> Option<WeatherReport> {
+        // wttr.in reports every number as a string
+        let number = |value: &Value| value.as_str()?.parse::<f32>().ok();
+        let current = json["current_condition"].as_array()?.first()?;
+
+        let sunset = json["weather"].as_array()?.first()?["astronomy"]
+            .as_array()?
+            .first()?["sunset"]
+            .as_str()?;
+        let sunset = NaiveTime::parse_from_str(sunset, "%I:%M %p").ok()?;
+
+        // Days without usable data are left out
+        let forecast = json["weather"]
+            .as_array()?
+            .iter()
+            .filter_map(|day| {
+                let date = NaiveDate::parse_from_str(day["date"].as_str()?, "%Y-%m-%d").ok()?;
+                let hourly: Vec<ForecastHour> = day["hourly"]
+                    .as_array()?
+                    .iter()
+                    .filter_map(|hour| {
+                        // Times are given as `0`, `300`, ..., `2100`
+                        let time = number(&hour["time"])? as u32;
+                        Some(ForecastHour {
+                            time: date.and_hms_opt(time / 100, time % 100, 0)?,
+                            temperature: number(&hour["tempC"])?,
+                            css: Self::weather_class(number(&hour["weatherCode"])? as u32),
+                            icon: None,
+                        })
+                    })
+                    .collect();
+                Some(ForecastDay {
+                    date,
+                    min: number(&day["mintempC"])?,
+                    max: number(&day["maxtempC"])?,
+                    css: noon_class(&hourly),
+                    icon: None,
+                    hourly,
+                })
+            })
+            .collect();
+
+        Some(WeatherReport {
+            temperature: number(&current["temp_C"])?,
+            wind_speed: number(&current["windspeedKmph"])?,
+            wind_direction: number(&current["winddirDegree"])?,
+            css: Self::weather_class(number(&current["weatherCode"])? as u32),
+            sunset,
+            forecast,
+        })
+    }
+}
+impl WeatherProvider for Wttr {
+    async fn fetch(&self, location: &str) -> Option<WeatherReport> {
+        // Unlike j2, j1 includes the hourly forecast
+        let url = format!("https://de.wttr.in/{}?format=j1", location);
+        let response = reqwest::get(url).await.ok()?.text().await.ok()?;
+        Self::parse(&serde_json::from_str(&response).ok()?)
+    }
+    fn weather_class(code: u32) -> WeatherClass {
+        match code {
+            113 => WeatherClass::Clear,
+            116 => WeatherClass::FewClouds,
+            119 | 122 => WeatherClass::ManyClouds,
+            143 | 248 | 260 => WeatherClass::Mist,
+            176 | 263 | 299 | 305 | 353 | 356 => WeatherClass::Showers,
+            179 | 362 | 365 | 374 => WeatherClass::FreezingScatteredRainStorm,
+            182 | 185 | 281 | 284 | 311 | 314 | 317 | 350 | 377 => {
+                WeatherClass::FreezingScatteredRain
+            }
+            200 | 302 | 308 | 359 | 386 | 389 => WeatherClass::Storm,
+            227 | 320 => WeatherClass::SnowScatteredDay,
+            230 | 329 | 332 | 338 => WeatherClass::SnowStorm,
+            323 | 326 | 335 | 368 | 371 | 392 | 395 => WeatherClass::SnowScatteredStorm,
+            266 | 293 | 296 => WeatherClass::ShowersScattered,
+            _ => WeatherClass::None,
+        }
+    }
+}
+
+/// [Open-Meteo](https://open-meteo.com), which needs no API key. Locations are either
+/// `latitude,longitude` or a place name that is looked up through its geocoding API.
+pub struct OpenMeteo;
+impl OpenMeteo {
+    async fn coordinates(location: &str) -> Option<(f64, f64)> {
+        if let Some((lat, lon)) = location.split_once(',')
+            && let (Ok(lat), Ok(lon)) = (lat.trim().parse(), lon.trim().parse())
+        {
+            return Some((lat, lon));
+        }
+        let url = format!(
+            "https://geocoding-api.open-meteo.com/v1/search?name={}&count=1",
+            percent_encoding::utf8_percent_encode(location, percent_encoding::NON_ALPHANUMERIC)
+        );
+        let response = reqwest::get(url).await.ok()?.text().await.ok()?;
+        let json: Value = serde_json::from_str(&response).ok()?;
+        let place = json["results"].as_array()?.first()?;
+        Some((place["latitude"].as_f64()?, place["longitude"].as_f64()?))
+    }
+
+    fn parse(json: &Value) -> Option<WeatherReport> {
+        let number = |value: &Value| value.as_f64().map(|n| n as f32);
+        let current = &json["current"];
+        let hourly = &json["hourly"];
+        let daily = &json["daily"];
+
+        let hours: Vec<ForecastHour> = hourly["time"]
+            .as_array()?
+            .iter()
+            .enumerate()
+            .filter_map(|(i, time)| {
+                Some(ForecastHour {
+                    time: NaiveDateTime::parse_from_str(time.as_str()?, "%Y-%m-%dT%H:%M").ok()?,
+                    temperature: number(&hourly["temperature_2m"][i])?,
+                    css: Self::weather_class(hourly["weather_code"][i].as_u64()? as u32),
+                    icon: None,
+                })
+            })
+            // Thinned out to the three-hourly slots wttr.in reports
+            .filter(|hour| hour.time.hour() % 3 == 0)
+            .collect();
+
+        let forecast = daily["time"]
+            .as_array()?
+            .iter()
+            .enumerate()
+            .filter_map(|(i, date)| {
+                let date = NaiveDate::parse_from_str(date.as_str()?, "%Y-%m-%d").ok()?;
+                let hourly: Vec<ForecastHour> = hours
+                    .iter()
+                    .filter(|hour| hour.time.date() == date)
+                    .cloned()
+                    .collect();
+                Some(ForecastDay {
+                    date,
+                    min: number(&daily["temperature_2m_min"][i])?,
+                    max: number(&daily["temperature_2m_max"][i])?,
+                    css: Self::weather_class(daily["weather_code"][i].as_u64()? as u32),
+                    icon: None,
+                    hourly,
+                })
+            })
+            .collect();
+
+        let sunset = daily["sunset"][0].as_str()?;
+        let sunset = NaiveDateTime::parse_from_str(sunset, "%Y-%m-%dT%H:%M")
+            .ok()?
+            .time();
+
+        Some(WeatherReport {
+            temperature: number(&current["temperature_2m"])?,
+            wind_speed: number(&current["wind_speed_10m"])?,
+            wind_direction: number(&current["wind_direction_10m"])?,
+            css: Self::weather_class(current["weather_code"].as_u64()? as u32),
+            sunset,
+            forecast,
+        })
+    }
+}
+impl WeatherProvider for OpenMeteo {
+    async fn fetch(&self, location: &str) -> Option<WeatherReport> {
+        let (lat, lon) = Self::coordinates(location).await?;
+        let url = format!(
+            "https://api.open-meteo.com/v1/forecast?latitude={lat}&longitude={lon}\
+            &current=temperature_2m,weather_code,wind_speed_10m,wind_direction_10m\
+            &hourly=temperature_2m,weather_code\
+            &daily=weather_code,temperature_2m_max,temperature_2m_min,sunset\
+            &timezone=auto&forecast_days=3"
+        );
+        let response = reqwest::get(url).await.ok()?.text().await.ok()?;
+        Self::parse(&serde_json::from_str(&response).ok()?)
+    }
+    /// WMO weather interpretation codes
+    fn weather_class(code: u32) -> WeatherClass {
+        match code {
+            0 => WeatherClass::Clear,
+            1 | 2 => WeatherClass::FewClouds,
+            3 => WeatherClass::ManyClouds,
+            45 | 48 => WeatherClass::Mist,
+            51 | 53 | 55 | 80 | 81 => WeatherClass::ShowersScattered,
+            61 | 63 | 65 | 82 => WeatherClass::Showers,
+            56 | 57 | 66 | 67 => WeatherClass::FreezingScatteredRain,
+            71 | 73 | 77 => WeatherClass::SnowScatteredDay,
+            75 => WeatherClass::SnowStorm,
+            85 | 86 => WeatherClass::SnowScatteredStorm,
+            95 => WeatherClass::Storm,
+            96 | 99 => WeatherClass::FreezingScatteredRainStorm,
+            _ => WeatherClass::None,
+        }
+    }
+}
+
+#[test]
+fn test_parse_reports() {
+    let wttr = serde_json::json!({
+        "current_condition": [{
+            "temp_C": "14", "windspeedKmph": "11", "winddirDegree": "250", "weatherCode": "116"
+        }],
+        "weather": [{
+            "date": "2026-10-16",
+            "mintempC": "8",
+            "maxtempC": "15",
+            "astronomy": [{ "sunset": "06:21 PM" }],
+            "hourly": [
+                { "time": "900", "tempC": "10", "weatherCode": "113" },
+                { "time": "1200", "tempC": "14", "weatherCode": "296" }
+            ]
+        }]
+    });
+    let report = Wttr::parse(&wttr).unwrap();
+    assert_eq!(report.temperature, 14.0);
+    assert_eq!(report.css.to_string(), "few-clouds");
+    assert_eq!(report.sunset, NaiveTime::from_hms_opt(18, 21, 0).unwrap());
+    let day = &report.forecast[0];
+    assert_eq!((day.min, day.max), (8.0, 15.0));
+    assert_eq!(day.css.to_string(), "showers-scattered");
+    assert_eq!(day.hourly[0].time.hour(), 9);
+
+    let open_meteo = serde_json::json!({
+        "current": {
+            "temperature_2m": 12.5, "wind_speed_10m": 7.2,
+            "wind_direction_10m": 90, "weather_code": 3
+        },
+        "hourly": {
+            "time": ["2026-10-16T11:00", "2026-10-16T12:00"],
+            "temperature_2m": [12.1, 12.9],
+            "weather_code": [3, 61]
+        },
+        "daily": {
+            "time": ["2026-10-16"],
+            "weather_code": [61],
+            "temperature_2m_max": [13.4],
+            "temperature_2m_min": [7.9],
+            "sunset": ["2026-10-16T18:19"]
+        }
+    });
+    let report = OpenMeteo::parse(&open_meteo).unwrap();
+    assert_eq!(report.wind_direction, 90.0);
+    assert_eq!(report.css.to_string(), "many-clouds");
+    let day = &report.forecast[0];
+    assert_eq!(day.hourly.len(), 1);
+    assert_eq!(day.css.to_string(), "showers");
+}
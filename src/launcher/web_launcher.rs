@@ -1,5 +1,74 @@
+use gpui::SharedString;
+use serde::Deserialize;
+
+use crate::loader::utils::ApplicationAction;
+use crate::utils::favicon::{FaviconSource, url_host};
+
 #[derive(Clone, Debug)]
 pub struct WebLauncher {
     pub engine: String,
     pub browser: Option<String>,
+    pub favicons: FaviconSource,
+    /// Browser profile searches are opened in
+    pub profile: Option<String>,
+    /// Firefox container tab searches are opened in
+    pub container: Option<String>,
+    /// Further engines that are shown as tiles of their own
+    pub engines: Vec<WebEngine>,
+}
+
+impl WebLauncher {
+    /// Context menu entry that installs the typed url as web app
+    pub fn install_action() -> ApplicationAction {
+        ApplicationAction {
+            name: Some(SharedString::from("Install as Web App")),
+            ..ApplicationAction::new("install_web_app")
+        }
+    }
+}
+
+/// An entry of the `engines` argument of a web launcher
+#[derive(Clone, Debug, Deserialize)]
+pub struct WebEngine {
+    /// Name of a built-in engine or a url template
+    pub engine: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+impl WebEngine {
+    /// Title of the tile, `Search Google` unless a name has been configured
+    pub fn title(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let engine = url_host(&self.engine).unwrap_or_else(|| {
+            let mut chars = self.engine.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        });
+        format!("Search {engine}")
+    }
+}
+
+#[test]
+fn test_engine_title() {
+    let engine = |engine: &str, name: Option<&str>| WebEngine {
+        engine: engine.to_string(),
+        name: name.map(str::to_string),
+        icon: None,
+    };
+    assert_eq!(engine("google", None).title(), "Search Google");
+    assert_eq!(
+        engine(
+            "https://www.youtube.com/results?search_query={keyword}",
+            None
+        )
+        .title(),
+        "Search www.youtube.com"
+    );
+    assert_eq!(engine("ecosia", Some("Plant trees")).title(), "Plant trees");
 }
@@ -0,0 +1,153 @@
+use futures::{StreamExt, channel::mpsc};
+use gpui::{App, AsyncApp, Entity};
+use inotify::{Inotify, WatchMask};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::Loader;
+use super::application_loader::get_applications_dir;
+use crate::launcher::Launcher;
+use crate::launcher::children::RenderableChild;
+use crate::loader::utils::ActionTable;
+use crate::ui::main_window::SherlockMainWindow;
+use crate::utils::config::ConfigGuard;
+use crate::utils::watch::{STOP_CHECK_INTERVAL, StopToken, wait_readable};
+
+/// Package managers install many files at once, so changes are collected for a moment before the
+/// apps are reloaded
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+
+/// Watches the application directories and reloads the apps of the launcher whenever desktop
+/// files are installed, changed or removed, until `stop` is stopped.
+pub fn watch_applications(
+    cx: &mut App,
+    launcher: Arc<Launcher>,
+    use_keywords: bool,
+    data_handle: Entity<Arc<Vec<RenderableChild>>>,
+    stop: StopToken,
+) {
+    let (tx, mut rx) = mpsc::unbounded::<()>();
+
+    std::thread::spawn(move || {
+        let Ok(mut inotify) = Inotify::init() else {
+            return;
+        };
+        let mask = WatchMask::CREATE
+            | WatchMask::DELETE
+            | WatchMask::CLOSE_WRITE
+            | WatchMask::MOVED_TO
+            | WatchMask::MOVED_FROM;
+        for dir in get_applications_dir().iter().filter(|dir| dir.is_dir()) {
+            let _ = inotify.watches().add(dir, mask);
+        }
+
+        let mut buffer = [0; 4096];
+        while !stop.is_stopped() {
+            if !wait_readable(&inotify, STOP_CHECK_INTERVAL) {
+                continue;
+            }
+            let Ok(mut events) = inotify.read_events(&mut buffer) else {
+                return;
+            };
+            let is_desktop_file = |name: Option<&std::ffi::OsStr>| {
+                name.is_some_and(|name| Path::new(name).extension() == Some("desktop".as_ref()))
+            };
+            if !events.any(|event| is_desktop_file(event.name)) {
+                continue;
+            }
+
+            std::thread::sleep(SETTLE_TIME);
+            while inotify
+                .read_events(&mut buffer)
+                .is_ok_and(|mut events| events.next().is_some())
+            {}
+            if tx.unbounded_send(()).is_err() {
+                return;
+            }
+        }
+    });
+
+    cx.spawn(move |cx: &mut AsyncApp| {
+        let cx = cx.clone();
+        async move {
+            while rx.next().await.is_some() {
+                // The launcher was replaced when the config was reloaded
+                if !is_loaded(&cx, &data_handle, &launcher) {
+                    break;
+                }
+                let reload_launcher = Arc::clone(&launcher);
+                let apps = cx
+                    .background_executor()
+                    .spawn(
+                        async move { Loader::reload_applications(reload_launcher, use_keywords) },
+                    )
+                    .await;
+                let apps = match apps {
+                    Ok(apps) => apps,
+                    Err(e) => {
+                        eprintln!("{e}");
+                        continue;
+                    }
+                };
+
+                let actions = ConfigGuard::read()
+                    .and_then(|config| ActionTable::from_config(&config))
+                    .unwrap_or_else(|e| {
+                        eprintln!("{e}");
+                        ActionTable::default()
+                    });
+
+                let _ = cx.update(|cx| {
+                    data_handle.update(cx, |items_arc, cx| {
+                        let items = Arc::make_mut(items_arc);
+                        // The apps replace the old ones in place, so other launchers keep their
+                        // position
+                        let start = items
+                            .iter()
+                            .position(|item| item.is_from(&launcher))
+                            .unwrap_or(items.len());
+                        items.retain(|item| !item.is_from(&launcher));
+                        let children = apps.into_iter().map(|inner| {
+                            let mut child = RenderableChild::AppLike {
+                                launcher: Arc::clone(&launcher),
+                                inner,
+                            };
+                            child.add_declared_actions(&actions);
+                            child
+                        });
+                        items.splice(start..start, children);
+                        cx.notify();
+                    });
+
+                    // Open windows still refer to the old indices
+                    for window in cx.windows() {
+                        if let Some(view) = window.downcast::<SherlockMainWindow>() {
+                            let _ = view.update(cx, |view, _, cx| {
+                                view.last_query = None;
+                                view.filter_and_sort(cx);
+                            });
+                        }
+                    }
+                });
+            }
+        }
+    })
+    .detach();
+}
+
+/// Whether the items still contain tiles of the launcher. Watchers of launchers that were
+/// replaced by a config reload use this to stop.
+pub fn is_loaded(
+    cx: &AsyncApp,
+    data_handle: &Entity<Arc<Vec<RenderableChild>>>,
+    launcher: &Arc<Launcher>,
+) -> bool {
+    cx.update(|cx| {
+        data_handle
+            .read(cx)
+            .iter()
+            .any(|item| item.is_from(launcher))
+    })
+    .unwrap_or(false)
+}
@@ -1,5 +1,5 @@
-use glob::Pattern;
 use gpui::SharedString;
+use once_cell::sync::Lazy;
 use rayon::prelude::*;
 use simd_json;
 use simd_json::prelude::ArrayTrait;
@@ -7,12 +7,12 @@ use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use super::Loader;
 use super::utils::ApplicationAction;
-use super::utils::{AppData, SherlockAlias};
+use super::utils::{AliasTable, AppData, AppOrigin, CounterReader, IgnoreList};
 use crate::launcher::Launcher;
 use crate::loader::resolve_icon_path;
 use crate::prelude::PathHelpers;
@@ -20,7 +20,7 @@ use crate::utils::cache::BinaryCache;
 use crate::utils::{
     config::ConfigGuard,
     errors::{SherlockError, SherlockErrorType},
-    files::read_lines,
+    files::{find_executable, read_lines},
 };
 use crate::{sher_log, sherlock_error};
 
@@ -28,7 +28,7 @@ impl Loader {
     pub fn load_applications_from_disk(
         launcher: Arc<Launcher>,
         applications: Option<Vec<PathBuf>>,
-        counts: &HashMap<String, u32>,
+        counts: &HashMap<String, f32>,
         decimals: i32,
         use_keywords: bool,
     ) -> Result<Vec<AppData>, SherlockError> {
@@ -38,20 +38,10 @@ impl Loader {
         let system_apps = get_applications_dir();
 
         // Parse user-specified 'sherlockignore' file
-        let ignore_apps: Vec<Pattern> = match read_lines(&config.files.ignore) {
-            Ok(lines) => lines
-                .map_while(Result::ok)
-                .filter_map(|line| Pattern::new(&line.to_lowercase()).ok())
-                .collect(),
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Default::default(),
-            Err(e) => Err(sherlock_error!(
-                SherlockErrorType::FileReadError(config.files.ignore.clone()),
-                e.to_string()
-            ))?,
-        };
+        let ignore = IgnoreList::read(&config.files.ignore)?;
 
         // Parse user-specified 'sherlock_alias.json' file
-        let aliases: HashMap<String, SherlockAlias> = match File::open(&config.files.alias) {
+        let aliases: AliasTable = match File::open(&config.files.alias) {
             Ok(f) => simd_json::from_reader(f).map_err(|e| {
                 sherlock_error!(
                     SherlockErrorType::FileReadError(config.files.alias.clone()),
@@ -64,7 +54,6 @@ impl Loader {
                 e.to_string()
             ))?,
         };
-        let aliases = Arc::new(RwLock::new(aliases));
 
         // Gather '.desktop' files
         let desktop_files: Vec<PathBuf> = match applications {
@@ -79,10 +68,15 @@ impl Loader {
                 let r_path = entry.to_str()?;
                 match read_lines(r_path) {
                     Ok(content) => {
-                        let mut buffer = Vec::new();
                         let mut data = AppData::new();
                         let mut current_section = None;
-                        let mut current_action = ApplicationAction::new("app_launcher");
+                        let mut actions: HashMap<String, ApplicationAction> = HashMap::new();
+                        let mut declared_actions: Vec<String> = Vec::new();
+                        let mut keywords = None;
+                        let mut comment = None;
+                        let mut only_show_in = None;
+                        let mut not_show_in = None;
+                        let mut dbus_activatable = false;
                         data.desktop_file = Some(entry);
                         for line in content.flatten() {
                             let line = line.trim();
@@ -91,33 +85,20 @@ impl Loader {
                                 continue;
                             }
                             if line.starts_with('[') && line.ends_with(']') {
-                                current_section = Some(line[1..line.len() - 1].to_string());
-                                if current_action.is_valid() {
-                                    buffer.push(Arc::new(current_action))
-                                }
-                                current_action = ApplicationAction::new("app_launcher");
-                                continue;
-                            }
-                            if current_section.is_none() {
+                                current_section =
+                                    Some(DesktopSection::parse(&line[1..line.len() - 1]));
                                 continue;
                             }
                             if let Some((key, value)) = line.split_once('=') {
                                 let key = key.trim().to_ascii_lowercase();
                                 let value = value.trim();
-                                if current_section.as_deref().unwrap() == "Desktop Entry" {
+                                if let Some(DesktopSection::Entry) = current_section {
                                     match key.as_ref() {
                                         "name" => {
-                                            data.name = {
-                                                if should_ignore(&ignore_apps, value) {
-                                                    return None;
-                                                }
-                                                Some(SharedString::from(value.to_string()))
-                                            }
+                                            data.name = Some(SharedString::from(value.to_string()))
                                         }
-                                        "icon" => {
-                                            data.icon = resolve_icon_path(value);
-                                        }
-                                        "exec" => data.exec = Some(value.to_string()),
+                                        "icon" => data.defer_icon(value),
+                                        "exec" => data.exec = Some(unescape_string(value)),
                                         "nodisplay" if value.eq_ignore_ascii_case("true") => {
                                             return None;
                                         }
@@ -127,43 +108,121 @@ impl Loader {
                                         "terminal" => {
                                             data.terminal = value.eq_ignore_ascii_case("true");
                                         }
-                                        "keywords" => data.search_string = value.to_lowercase(),
+                                        "genericname" => {
+                                            data.generic_name =
+                                                Some(SharedString::from(value.to_string()))
+                                        }
+                                        "comment" => comment = Some(value.to_string()),
+                                        "keywords" => keywords = Some(value.to_string()),
+                                        "tryexec" if find_executable(value).is_none() => {
+                                            return None;
+                                        }
+                                        "onlyshowin" => only_show_in = Some(value.to_string()),
+                                        "notshowin" => not_show_in = Some(value.to_string()),
+                                        "dbusactivatable" => {
+                                            dbus_activatable = value.eq_ignore_ascii_case("true");
+                                        }
+                                        "prefersnondefaultgpu" => {
+                                            data.discrete_gpu = value.eq_ignore_ascii_case("true");
+                                        }
+                                        "startupwmclass" => data.wm_class = Some(value.to_string()),
+                                        "x-flatpak" => {
+                                            data.origin =
+                                                Some(AppOrigin::Flatpak(value.to_string()))
+                                        }
+                                        "x-snapinstancename" => {
+                                            data.origin = Some(AppOrigin::Snap(value.to_string()))
+                                        }
+                                        "actions" => {
+                                            declared_actions = value
+                                                .split(';')
+                                                .map(str::trim)
+                                                .filter(|id| !id.is_empty())
+                                                .map(str::to_string)
+                                                .collect();
+                                        }
                                         _ => {}
                                     }
-                                } else {
-                                    // Application Actions
+                                } else if let Some(DesktopSection::Action(id)) = &current_section {
+                                    let action = actions
+                                        .entry(id.clone())
+                                        .or_insert_with(|| ApplicationAction::new("app_launcher"));
                                     match key.as_ref() {
                                         "name" => {
-                                            current_action.name =
+                                            action.name =
                                                 Some(SharedString::from(value.to_string()))
                                         }
-                                        "exec" => current_action.exec = Some(value.to_string()),
-                                        "icon" => current_action.icon = resolve_icon_path(value),
+                                        "exec" => action.exec = Some(unescape_string(value)),
+                                        "icon" => action.icon = resolve_icon_path(value),
                                         _ => {}
                                     }
-                                    if current_action.icon.is_none() {
-                                        current_action.icon = data.icon.clone();
-                                    }
-                                    if current_action.is_full() {
-                                        buffer.push(Arc::new(current_action));
-                                        current_action = ApplicationAction::new("app_launcher");
-                                        current_section = None;
-                                    }
                                 }
                             }
                         }
-                        let alias = {
-                            let mut aliases = aliases.write().unwrap();
-                            aliases.remove(data.name.as_ref().unwrap().as_str())
-                        };
+                        if ignore.is_ignored(
+                            "app",
+                            data.name.as_ref().map(|name| name.as_str()),
+                            data.exec.as_deref(),
+                        ) {
+                            return None;
+                        }
+                        // Actions follow the order of the `Actions` key, undeclared groups are
+                        // ignored. Actions without an icon inherit the app's icon once it is set.
+                        let mut buffer: Vec<Arc<ApplicationAction>> = declared_actions
+                            .iter()
+                            .filter_map(|id| actions.remove(id))
+                            .filter(ApplicationAction::is_valid)
+                            .map(Arc::new)
+                            .collect();
+                        if data.origin.is_none() {
+                            data.origin = data
+                                .desktop_file
+                                .as_deref()
+                                .and_then(AppOrigin::from_desktop_file);
+                        }
+                        if let Some(origin) = data.origin.as_ref() {
+                            buffer.extend(origin.actions().into_iter().map(Arc::new));
+                            if let Some(icon) = origin.manifest_icon() {
+                                data.set_icon(Some(Arc::from(icon.into_boxed_path())));
+                            }
+                        }
+                        if !shown_in_desktop(
+                            &CURRENT_DESKTOPS,
+                            only_show_in.as_deref(),
+                            not_show_in.as_deref(),
+                        ) {
+                            return None;
+                        }
+                        // The spec asks to prefer D-Bus activation over the Exec key
+                        if let Some(cmd) = data
+                            .desktop_file
+                            .as_deref()
+                            .filter(|_| dbus_activatable)
+                            .and_then(dbus_activation_command)
+                        {
+                            data.exec = Some(cmd);
+                        }
+                        // Generic name and comment are searchable just like the keywords
+                        data.search_string = [
+                            keywords.as_deref(),
+                            data.generic_name.as_ref().map(|name| name.as_str()),
+                            comment.as_deref(),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .join(";")
+                        .to_lowercase();
+                        let alias = aliases
+                            .find(data.name.as_ref()?.as_str(), data.desktop_file.as_deref())
+                            .cloned();
                         data.apply_alias(&launcher, alias, use_keywords, buffer);
+                        if data.discrete_gpu {
+                            data.add_integrated_gpu_action();
+                        }
                         // apply counts
-                        let count = data
-                            .exec
-                            .as_ref()
-                            .and_then(|exec| counts.get(exec))
-                            .unwrap_or(&0);
-                        let priority = parse_priority(launcher.priority as f32, *count, decimals);
+                        let count = data.count(&launcher, counts);
+                        let priority = parse_priority(launcher.priority as f32, count, decimals);
                         data.priority = Some(priority);
                         Some(data)
                     }
@@ -177,7 +236,7 @@ impl Loader {
     fn get_new_applications(
         launcher: Arc<Launcher>,
         mut apps: Vec<AppData>,
-        counts: &HashMap<String, u32>,
+        counts: &HashMap<String, f32>,
         decimals: i32,
         last_changed: Option<SystemTime>,
         use_keywords: bool,
@@ -228,7 +287,7 @@ impl Loader {
 
     pub fn load_applications(
         launcher: Arc<Launcher>,
-        counts: &HashMap<String, u32>,
+        counts: &HashMap<String, f32>,
         decimals: i32,
         use_keywords: bool,
     ) -> Result<Vec<AppData>, SherlockError> {
@@ -238,19 +297,23 @@ impl Loader {
             || file_has_changed(&config.files.ignore, &config.caching.cache)
             || file_has_changed(&config.files.config, &config.caching.cache);
 
-        if !changed {
+        let config_hash = apps_cache_hash(use_keywords);
+
+        // An empty cache is either missing or outdated, so it has to be rebuilt from disk as well
+        let cached_apps: Vec<AppData> = if changed {
+            Vec::new()
+        } else {
+            BinaryCache::read_keyed(&config.caching.cache, config_hash).unwrap_or_default()
+        };
+
+        if !cached_apps.is_empty() {
             let _ = sher_log!("Loading cached apps");
-            let cached_apps: Vec<AppData> = BinaryCache::read(&config.caching.cache)?;
 
             let cleaned_apps: Vec<AppData> = cached_apps
                 .into_iter()
                 .map(|mut v| {
-                    let count = v
-                        .exec
-                        .as_ref()
-                        .and_then(|exec| counts.get(exec))
-                        .unwrap_or(&0);
-                    let new_priority = parse_priority(launcher.priority as f32, *count, decimals);
+                    let count = v.count(&launcher, counts);
+                    let new_priority = parse_priority(launcher.priority as f32, count, decimals);
                     v.priority = Some(new_priority);
                     v
                 })
@@ -263,7 +326,7 @@ impl Loader {
             rayon::spawn_fifo({
                 let counts_clone = counts.clone();
                 move || {
-                    if let Ok(new_apps) = Loader::get_new_applications(
+                    if let Ok(mut new_apps) = Loader::get_new_applications(
                         launcher,
                         old_apps,
                         &counts_clone,
@@ -271,7 +334,8 @@ impl Loader {
                         last_changed,
                         use_keywords,
                     ) {
-                        if let Err(e) = BinaryCache::write(cache, &new_apps) {
+                        new_apps.par_iter_mut().for_each(AppData::resolve_icon);
+                        if let Err(e) = BinaryCache::write_keyed(cache, &new_apps, config_hash) {
                             eprintln!("{e}");
                         }
                     }
@@ -283,27 +347,143 @@ impl Loader {
         let _ = sher_log!("Updating cached apps");
         let apps =
             Loader::load_applications_from_disk(launcher, None, counts, decimals, use_keywords)?;
-        // Write the cache in the background
-        let app_clone = apps.clone();
+        // Write the cache in the background. Icons are resolved first so that they are cached too.
+        let mut app_clone = apps.clone();
         let cache = config.caching.cache.clone();
         rayon::spawn_fifo(move || {
-            if let Err(e) = BinaryCache::write(cache, &app_clone) {
+            app_clone.par_iter_mut().for_each(AppData::resolve_icon);
+            if let Err(e) = BinaryCache::write_keyed(cache, &app_clone, config_hash) {
                 eprintln!("{e}");
             }
         });
         Ok(apps)
     }
+
+    /// Parses all desktop files again and replaces the cache. Used when applications are
+    /// installed or removed while Sherlock is running.
+    pub fn reload_applications(
+        launcher: Arc<Launcher>,
+        use_keywords: bool,
+    ) -> Result<Vec<AppData>, SherlockError> {
+        let counts = CounterReader::new()?.read();
+        let decimals = count_decimals(&counts);
+        let mut apps =
+            Loader::load_applications_from_disk(launcher, None, &counts, decimals, use_keywords)?;
+        apps.par_iter_mut().for_each(AppData::resolve_icon);
+
+        let cache = ConfigGuard::read()?.caching.cache.clone();
+        BinaryCache::write_keyed(cache, &apps, apps_cache_hash(use_keywords))?;
+        Ok(apps)
+    }
 }
 
-fn should_ignore(ignore_apps: &Vec<Pattern>, app: &str) -> bool {
-    let app_name = app.to_lowercase();
-    ignore_apps.iter().any(|pattern| pattern.matches(&app_name))
+/// Search strings depend on the launcher's keyword setting and the visible apps on the current
+/// desktop, so the cache is only valid for the same settings
+fn apps_cache_hash(use_keywords: bool) -> u64 {
+    crc32fast::hash(format!("{};{}", use_keywords, CURRENT_DESKTOPS.join(":")).as_bytes()) as u64
 }
-pub fn parse_priority(priority: f32, count: u32, decimals: i32) -> f32 {
-    if count == 0 {
+
+/// Desktops listed in `$XDG_CURRENT_DESKTOP`
+static CURRENT_DESKTOPS: Lazy<Vec<String>> = Lazy::new(|| {
+    env::var("XDG_CURRENT_DESKTOP")
+        .map(|desktops| {
+            desktops
+                .split(':')
+                .filter(|d| !d.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+});
+
+/// Evaluates the `OnlyShowIn` and `NotShowIn` keys against the current desktops
+fn shown_in_desktop(
+    desktops: &[String],
+    only_show_in: Option<&str>,
+    not_show_in: Option<&str>,
+) -> bool {
+    let listed = |list: &str| {
+        list.split(';')
+            .filter(|d| !d.is_empty())
+            .any(|d| desktops.iter().any(|current| current == d))
+    };
+    if not_show_in.is_some_and(listed) {
+        return false;
+    }
+    only_show_in.is_none_or(listed)
+}
+
+/// Builds a command activating the application through `org.freedesktop.Application`. Requires
+/// `gdbus`, otherwise the Exec key is kept.
+fn dbus_activation_command(desktop_file: &Path) -> Option<String> {
+    find_executable("gdbus")?;
+    let app_id = desktop_file.file_stem()?.to_str()?;
+    let object_path = format!("/{}", app_id.replace('.', "/").replace('-', "_"));
+    Some(format!(
+        "gdbus call --session --dest {app_id} --object-path {object_path} \
+        --method org.freedesktop.Application.Activate {{}}"
+    ))
+}
+
+/// Group of a desktop file the parser is currently in
+enum DesktopSection {
+    Entry,
+    Action(String),
+    Other,
+}
+impl DesktopSection {
+    fn parse(header: &str) -> Self {
+        match header {
+            "Desktop Entry" => Self::Entry,
+            _ => header
+                .strip_prefix("Desktop Action ")
+                .map_or(Self::Other, |id| Self::Action(id.to_string())),
+        }
+    }
+}
+
+/// Undoes the escapes of string values in desktop files. Quoting inside `Exec` keys is left to
+/// the command parser.
+pub fn unescape_string(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => {
+                // Unknown escapes are kept as they are
+                if other != '\\' {
+                    unescaped.push('\\');
+                }
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Number of decimals needed to order apps by their launch counts
+pub fn count_decimals(counts: &HashMap<String, f32>) -> i32 {
+    let max_count = counts.values().copied().fold(0.0, f32::max);
+    if max_count < 1.0 {
+        0
+    } else {
+        max_count.log10().floor() as i32 + 1
+    }
+}
+pub fn parse_priority(priority: f32, count: f32, decimals: i32) -> f32 {
+    if count <= 0.0 {
         priority + 0.99
     } else {
-        priority + 0.99 - count as f32 * 10f32.powi(-decimals)
+        priority + 0.99 - count * 10f32.powi(-decimals)
     }
 }
 
@@ -420,6 +600,18 @@ fn test_get_applications_dir() {
     assert_eq!(res, expected_app_dirs);
 }
 
+#[test]
+fn test_shown_in_desktop() {
+    let desktops = vec![String::from("ubuntu"), String::from("GNOME")];
+
+    assert!(shown_in_desktop(&desktops, None, None));
+    assert!(shown_in_desktop(&desktops, Some("KDE;GNOME;"), None));
+    assert!(!shown_in_desktop(&desktops, Some("KDE;"), None));
+    assert!(!shown_in_desktop(&desktops, None, Some("GNOME;")));
+    assert!(!shown_in_desktop(&[], Some("Hyprland;"), None));
+    assert!(shown_in_desktop(&[], None, Some("Hyprland;")));
+}
+
 impl PathHelpers for Path {
     fn modtime(&self) -> Option<SystemTime> {
         self.metadata().ok().and_then(|m| m.modified().ok())
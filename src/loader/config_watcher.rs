@@ -0,0 +1,146 @@
+use futures::{StreamExt, channel::mpsc};
+use gpui::{App, AsyncApp, Entity};
+use inotify::{Inotify, WatchMask};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::Loader;
+use crate::launcher::children::RenderableChild;
+use crate::ui::main_window::{LauncherMode, SherlockMainWindow};
+use crate::utils::config::{ConfigGuard, SherlockFlags};
+use crate::utils::errors::SherlockError;
+
+/// Editors save through temporary files and renames, so changes are collected for a moment
+/// before the config is rebuilt
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Watches config.toml and fallback.json and rebuilds the config and the launchers whenever one
+/// of them changes, so edits apply on the next open without restarting the daemon. The files are
+/// located once, when the watcher starts.
+pub fn watch_config(
+    cx: &mut App,
+    flags: Entity<SherlockFlags>,
+    data_handle: Entity<Arc<Vec<RenderableChild>>>,
+    modes: Entity<Arc<[LauncherMode]>>,
+    errors: Entity<Arc<[SherlockError]>>,
+) {
+    let Some(files) = ConfigGuard::read()
+        .ok()
+        .map(|config| watched_files(&config.files.config, &config.files.fallback))
+    else {
+        return;
+    };
+    let (tx, mut rx) = mpsc::unbounded::<()>();
+
+    std::thread::spawn(move || {
+        let Ok(mut inotify) = Inotify::init() else {
+            return;
+        };
+        // The directories are watched, files that are replaced on save would lose their watch
+        let mask =
+            WatchMask::CREATE | WatchMask::DELETE | WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO;
+        for (dir, _) in &files {
+            let _ = inotify.watches().add(dir, mask);
+        }
+
+        let mut buffer = [0; 4096];
+        loop {
+            let Ok(mut events) = inotify.read_events_blocking(&mut buffer) else {
+                return;
+            };
+            let is_watched = |name: Option<&std::ffi::OsStr>| {
+                name.is_some_and(|name| files.iter().any(|(_, file)| file == name))
+            };
+            if !events.any(|event| is_watched(event.name)) {
+                continue;
+            }
+
+            std::thread::sleep(SETTLE_TIME);
+            while inotify
+                .read_events(&mut buffer)
+                .is_ok_and(|mut events| events.next().is_some())
+            {}
+            if tx.unbounded_send(()).is_err() {
+                return;
+            }
+        }
+    });
+
+    cx.spawn(move |cx: &mut AsyncApp| {
+        let cx = cx.clone();
+        async move {
+            while rx.next().await.is_some() {
+                let _ = cx.update(|cx| reload_config(cx, &flags, &data_handle, &modes, &errors));
+            }
+        }
+    })
+    .detach();
+}
+
+/// Rebuilds the config, then the launchers, which read it. Also used to switch profiles.
+pub fn reload_config(
+    cx: &mut App,
+    flags: &Entity<SherlockFlags>,
+    data_handle: &Entity<Arc<Vec<RenderableChild>>>,
+    modes: &Entity<Arc<[LauncherMode]>>,
+    errors: &Entity<Arc<[SherlockError]>>,
+) {
+    let config = flags.read(cx).load_config();
+    if let Err(e) = ConfigGuard::write_key(|current| *current = config) {
+        eprintln!("{e}");
+        return;
+    }
+    let (new_modes, new_errors) = match Loader::load_launchers(cx, data_handle.clone()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    modes.update(cx, |modes, cx| {
+        *modes = Arc::clone(&new_modes);
+        cx.notify();
+    });
+    let new_errors: Arc<[SherlockError]> = Arc::from(new_errors);
+    errors.update(cx, |errors, cx| {
+        *errors = Arc::clone(&new_errors);
+        cx.notify();
+    });
+
+    // Open windows still refer to the old launchers
+    for window in cx.windows() {
+        if let Some(view) = window.downcast::<SherlockMainWindow>() {
+            let _ = view.update(cx, |view, _, cx| {
+                if matches!(view.mode, LauncherMode::Alias { .. })
+                    && !new_modes.contains(&view.mode)
+                {
+                    view.mode = LauncherMode::Home;
+                }
+                view.modes = Arc::clone(&new_modes);
+                view.show_errors &= !new_errors.is_empty();
+                view.errors = Arc::clone(&new_errors);
+                view.last_query = None;
+                view.selected_index = 0;
+                view.filter_and_sort(cx);
+            });
+        }
+    }
+}
+
+/// Directories to watch along with the file names that trigger a reload. Config files may be
+/// written as either toml or json.
+fn watched_files(config: &Path, fallback: &Path) -> Vec<(PathBuf, OsString)> {
+    let mut files = Vec::new();
+    let mut push = |path: &Path| {
+        if let (Some(dir), Some(name)) = (path.parent(), path.file_name()) {
+            files.push((dir.to_path_buf(), name.to_os_string()));
+        }
+    };
+    push(&config.with_extension("toml"));
+    push(&config.with_extension("json"));
+    push(&fallback.with_extension("json"));
+    push(&fallback.with_extension("toml"));
+    files
+}
@@ -0,0 +1,365 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::utils::RawLauncher;
+use super::{LAUNCHER_TYPES, WATCHED_TYPES};
+use crate::launcher::{
+    audio_device_launcher::DeviceKind, games_launcher::GameSource, github_launcher::GithubKind,
+    weather_launcher::WeatherIconTheme, weather_provider::WeatherProviderKind,
+    web_launcher::WebEngine,
+};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+use crate::utils::favicon::FaviconSource;
+
+/// An argument a launcher type understands, along with the shape its value has to have
+struct ArgSchema {
+    key: &'static str,
+    expected: &'static str,
+    valid: fn(&Value) -> bool,
+}
+
+const fn arg(key: &'static str, expected: &'static str, valid: fn(&Value) -> bool) -> ArgSchema {
+    ArgSchema {
+        key,
+        expected,
+        valid,
+    }
+}
+
+fn parses<T: DeserializeOwned>(value: &Value) -> bool {
+    serde_json::from_value::<T>(value.clone()).is_ok()
+}
+fn is_text_list(value: &Value) -> bool {
+    value
+        .as_array()
+        .is_some_and(|items| items.iter().all(Value::is_string))
+}
+fn is_text_table(value: &Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|items| items.values().all(Value::is_string))
+}
+fn is_text_or_list(value: &Value) -> bool {
+    value.is_string() || is_text_list(value)
+}
+
+/// Understood by every launcher type
+const COMMON_ARGS: &[ArgSchema] = &[
+    arg(
+        "boosts",
+        "a table of keyword boosts by item name",
+        parses::<HashMap<String, HashMap<String, f32>>>,
+    ),
+    arg("icon", "an icon name", Value::is_string),
+    arg("icon_paths", "a list of directories", is_text_list),
+    arg("icons", "a table of icons by item name", is_text_table),
+];
+
+/// The `args` of each launcher type, types without an entry are not checked
+const LAUNCHER_ARGS: &[(&str, &[ArgSchema])] = &[
+    (
+        "agenda",
+        &[
+            arg(
+                "calendars",
+                "an ICS file or directory or a list of them",
+                is_text_or_list,
+            ),
+            arg("khal_database", "a file path", Value::is_string),
+            arg("days", "a number of days", Value::is_u64),
+        ],
+    ),
+    (
+        "app_launcher",
+        &[arg("use_keywords", "true or false", Value::is_boolean)],
+    ),
+    (
+        "audio_devices",
+        &[arg(
+            "devices",
+            r#"a list of "sink" and "source""#,
+            parses::<Vec<DeviceKind>>,
+        )],
+    ),
+    ("audio_sink", &[]),
+    (
+        "bookmarks",
+        &[
+            arg("browser", "a browser name", Value::is_string),
+            arg(
+                "favicons",
+                r#""browser", "network" or "disabled""#,
+                parses::<FaviconSource>,
+            ),
+            arg("profiles", "a list of profile names", is_text_list),
+        ],
+    ),
+    (
+        "calculation",
+        &[
+            arg(
+                "currency_update_interval",
+                "a number of seconds",
+                Value::is_u64,
+            ),
+            arg("capabilities", "a list of capabilities", is_text_list),
+        ],
+    ),
+    (
+        "command",
+        &[arg(
+            "commands",
+            "a table of named commands",
+            Value::is_object,
+        )],
+    ),
+    (
+        "contacts",
+        &[
+            arg(
+                "directories",
+                "a directory path or a list of them",
+                is_text_or_list,
+            ),
+            arg("call", "a command with {phone}", Value::is_string),
+        ],
+    ),
+    (
+        "debug",
+        &[arg(
+            "commands",
+            "a table of named commands",
+            Value::is_object,
+        )],
+    ),
+    (
+        "display",
+        &[
+            arg("kanshi_config", "a file path", Value::is_string),
+            arg(
+                "layouts",
+                "a table of names and wlr-randr arguments",
+                Value::is_object,
+            ),
+        ],
+    ),
+    ("dnd", &[arg("mako_mode", "a mode name", Value::is_string)]),
+    (
+        "feed",
+        &[
+            arg("feeds", "a feed url or a list of them", is_text_or_list),
+            arg("limit", "a number of headlines", Value::is_u64),
+            arg("update_interval", "a number of minutes", Value::is_u64),
+            arg("browser", "a browser command", Value::is_string),
+        ],
+    ),
+    (
+        "files",
+        &[
+            arg(
+                "directories",
+                "a directory path or a list of them",
+                is_text_or_list,
+            ),
+            arg("recent", "true or false", Value::is_boolean),
+            arg("limit", "a number of files", Value::is_u64),
+        ],
+    ),
+    (
+        "games",
+        &[arg(
+            "sources",
+            r#"a list of "steam", "lutris" and "heroic""#,
+            parses::<Vec<GameSource>>,
+        )],
+    ),
+    (
+        "github",
+        &[
+            arg("token", "a GitHub access token", Value::is_string),
+            arg(
+                "search",
+                r#"a list of "repositories", "issues" and "pull_requests""#,
+                parses::<Vec<GithubKind>>,
+            ),
+            arg("limit", "a number of results", Value::is_u64),
+            arg("browser", "a browser command", Value::is_string),
+        ],
+    ),
+    (
+        "mail",
+        &[
+            arg("maildir", "a directory path", Value::is_string),
+            arg("client", "a command with {file} or {id}", Value::is_string),
+            arg("limit", "a number of messages", Value::is_u64),
+        ],
+    ),
+    (
+        "nix",
+        &[
+            arg("flake", "a flake reference", Value::is_string),
+            arg("configuration", "a file path", Value::is_string),
+        ],
+    ),
+    (
+        "pacman",
+        &[arg(
+            "aur_helper",
+            "a program like paru or yay",
+            Value::is_string,
+        )],
+    ),
+    (
+        "record",
+        &[
+            arg("directory", "a directory path", Value::is_string),
+            arg("audio", "true or false", Value::is_boolean),
+        ],
+    ),
+    ("volume", &[arg("step", "a percentage", Value::is_u64)]),
+    (
+        "vpn",
+        &[arg(
+            "interfaces",
+            "a list of wg-quick interfaces",
+            Value::is_array,
+        )],
+    ),
+    (
+        "weather",
+        &[
+            arg("location", "a location or a list of them", is_text_or_list),
+            arg("update_interval", "a number of minutes", Value::is_u64),
+            arg(
+                "icon_theme",
+                r#""Sherlock" or "None""#,
+                parses::<WeatherIconTheme>,
+            ),
+            arg("show_datetime", "true or false", Value::is_boolean),
+            arg(
+                "provider",
+                r#""wttr" or "open-meteo""#,
+                parses::<WeatherProviderKind>,
+            ),
+        ],
+    ),
+    (
+        "web_launcher",
+        &[
+            arg("search_engine", "an engine name or url", Value::is_string),
+            arg("browser", "a browser name", Value::is_string),
+            arg(
+                "favicons",
+                r#""browser", "network" or "disabled""#,
+                parses::<FaviconSource>,
+            ),
+            arg(
+                "engines",
+                "a list of tables with an `engine`",
+                parses::<Vec<WebEngine>>,
+            ),
+            arg("profile", "a profile name", Value::is_string),
+            arg("container", "a container name", Value::is_string),
+        ],
+    ),
+];
+
+/// Problems with the `type` and the `args` of a launcher, each naming what was expected
+/// instead. Launchers with problems still load, misconfigured arguments fall back to their
+/// defaults.
+pub fn launcher_problems(raw: &RawLauncher) -> Vec<String> {
+    let kind = raw.r#type.to_lowercase();
+    if !LAUNCHER_TYPES.contains(&kind.as_str()) {
+        return vec![format!(
+            r#"Unknown type "{}", expected one of: {}"#,
+            raw.r#type,
+            LAUNCHER_TYPES.join(", ")
+        )];
+    }
+    let mut problems = Vec::new();
+    if raw.refresh_interval.is_some() && WATCHED_TYPES.contains(&kind.as_str()) {
+        problems.push(format!(
+            r#""refresh_interval" is ignored, "{kind}" launchers update as soon as something changes"#
+        ));
+    }
+    let args = match raw.args.as_ref() {
+        Value::Null => return problems,
+        Value::Object(args) => args,
+        _ => {
+            problems.push(String::from("`args` has to be a table of options"));
+            return problems;
+        }
+    };
+    let Some(&(_, schema)) = LAUNCHER_ARGS.iter().find(|(name, _)| *name == kind) else {
+        return problems;
+    };
+
+    for (key, value) in args {
+        match COMMON_ARGS.iter().chain(schema).find(|arg| arg.key == key) {
+            Some(arg) if !(arg.valid)(value) => problems.push(format!(
+                r#"Argument "{key}" is {value}, expected {}"#,
+                arg.expected
+            )),
+            Some(_) => {}
+            None => {
+                let known: Vec<_> = COMMON_ARGS.iter().chain(schema).map(|a| a.key).collect();
+                problems.push(format!(
+                    r#"Unknown argument "{key}", "{}" launchers take: {}"#,
+                    kind,
+                    known.join(", ")
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// The problems of a launcher as non-breaking errors, shown in the error view
+pub fn diagnose_launcher(raw: &RawLauncher, index: usize) -> Vec<SherlockError> {
+    let name = raw
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("#{}", index + 1));
+    launcher_problems(raw)
+        .into_iter()
+        .map(|problem| {
+            sherlock_error!(
+                SherlockErrorType::LauncherConfigError(name.clone()),
+                problem
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_launcher_problems() {
+    let launcher = |json: &str| serde_json::from_str::<RawLauncher>(json).unwrap();
+
+    let valid = launcher(
+        r#"{"type": "weather", "priority": 1, "args": {"location": ["Berlin", "Paris"], "provider": "open-meteo", "icon": "weather"}}"#,
+    );
+    assert!(launcher_problems(&valid).is_empty());
+
+    let unknown_type = launcher(r#"{"type": "dockr", "priority": 1}"#);
+    assert_eq!(launcher_problems(&unknown_type).len(), 1);
+
+    let not_a_table = launcher(r#"{"type": "volume", "priority": 1, "args": [5]}"#);
+    assert_eq!(launcher_problems(&not_a_table).len(), 1);
+
+    let malformed = launcher(
+        r#"{"name": "Volume", "type": "volume", "priority": 1, "args": {"step": "5", "steps": 5}}"#,
+    );
+    let problems = launcher_problems(&malformed);
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].contains("expected a percentage"));
+    assert!(problems[1].contains("boosts, icon, icon_paths, icons, step"));
+    assert_eq!(diagnose_launcher(&malformed, 0).len(), 2);
+
+    // Watched launchers would start new watchers with every refresh
+    let refreshed = launcher(r#"{"type": "dnd", "priority": 1, "refresh_interval": 5}"#);
+    assert_eq!(launcher_problems(&refreshed).len(), 1);
+    let refreshed = launcher(r#"{"type": "feed", "priority": 1, "refresh_interval": 5}"#);
+    assert!(launcher_problems(&refreshed).is_empty());
+}
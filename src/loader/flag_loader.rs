@@ -2,7 +2,7 @@ use std::{env, path::PathBuf, str::FromStr};
 
 use super::Loader;
 use crate::utils::{
-    config::{SherlockConfig, SherlockFlags},
+    config::{SherlockConfig, SherlockFlags, check_config},
     errors::SherlockError,
 };
 
@@ -25,6 +25,10 @@ impl Loader {
             let _ = print_version();
             std::process::exit(0);
         }
+        if args.contains(&"--check-config".to_string()) {
+            let flags = SherlockFlags::new(args)?;
+            std::process::exit(check_config(&flags));
+        }
 
         SherlockFlags::new(args)
     }
@@ -84,6 +88,7 @@ impl SherlockFlags {
             photo_mode: check_flag_existence("--photo"),
             input: Self::extract_flag_value::<bool>(&args, "--input", None),
             placeholder: Self::extract_flag_value::<String>(&args, "--placeholder", Some("-p")),
+            profile: Self::extract_flag_value::<String>(&args, "--profile", None),
         })
     }
 }
@@ -101,6 +106,10 @@ pub fn flag_documentation() -> Result<(), SherlockError> {
         ("-v, --version", "Print the version of the application."),
         ("-h, --help", "Show this help message with allowed flags."),
         ("init", "Writes default configs into your config directory."),
+        (
+            "--check-config",
+            "Checks the config files for errors and unknown keys without opening a window.",
+        ),
         ("\nFILES:", ""),
         ("--config", "Specify the configuration file to load."),
         ("--fallback", "Specify the fallback file to load."),
@@ -112,6 +121,10 @@ pub fn flag_documentation() -> Result<(), SherlockError> {
             "--config-dir",
             "Specify the directly Sherlock will look for its configuration in.",
         ),
+        (
+            "--profile",
+            "Lay the [profile.<name>] section of the config over the rest of it. Also switches the profile of a running instance.",
+        ),
         ("\nBEHAVIOR:", ""),
         (
             "-p, --placeholder",
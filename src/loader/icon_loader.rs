@@ -1,11 +1,18 @@
+use gpui::{App, AsyncApp, Entity};
 use linicon::lookup_icon;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use serde_json::Value;
 
+use crate::launcher::children::RenderableChild;
 use crate::loader::assets::Assets;
+use crate::loader::icon_theme::find_icon;
 use crate::utils::errors::{SherlockError, SherlockErrorType};
-use crate::utils::files::home_dir;
+use crate::utils::files::{home_dir, read_lines};
 use crate::utils::paths::get_cache_dir;
 use crate::{ICONS, sherlock_error};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
@@ -19,20 +26,8 @@ impl CustomIconTheme {
         }
     }
     pub fn add_path<T: AsRef<Path>>(&mut self, path: T) {
-        let path_ref = path.as_ref();
-
-        let path = if let Some(str_path) = path_ref.to_str() {
-            if let Some(stripped) = str_path.strip_prefix("~/") {
-                if let Ok(home) = home_dir() {
-                    home.join(stripped)
-                } else {
-                    return;
-                }
-            } else {
-                path_ref.to_path_buf()
-            }
-        } else {
-            path_ref.to_path_buf()
+        let Some(path) = expand_home(path.as_ref()) else {
+            return;
         };
         Self::scan_path(&path, &mut self.buf);
     }
@@ -67,6 +62,55 @@ impl CustomIconTheme {
     }
 }
 
+/// Expands a leading `~/`, `None` if the home directory is unknown
+fn expand_home(path: &Path) -> Option<PathBuf> {
+    match path.to_str().and_then(|p| p.strip_prefix("~/")) {
+        Some(stripped) => home_dir().ok().map(|home| home.join(stripped)),
+        None => Some(path.to_path_buf()),
+    }
+}
+
+/// Icons a launcher brings along through its `icon_paths` and `icons` args, e.g.
+/// `"icons": { "Shutdown": "~/.config/sherlock/icons/power.svg" }`. They apply to the items of
+/// that launcher only, the global icon theme stays untouched.
+pub struct LauncherIcons {
+    theme: CustomIconTheme,
+    /// Icon names or files by item name
+    overrides: HashMap<String, String>,
+}
+impl LauncherIcons {
+    /// `None` for launchers that bring no icons of their own
+    pub fn from_args(args: &Value) -> Option<Self> {
+        let paths = args.get("icon_paths").and_then(Value::as_array);
+        let overrides: HashMap<String, String> = args
+            .get("icons")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        if paths.is_none() && overrides.is_empty() {
+            return None;
+        }
+
+        let mut theme = CustomIconTheme::new();
+        for path in paths.into_iter().flatten().filter_map(Value::as_str) {
+            theme.add_path(path);
+        }
+        Some(Self { theme, overrides })
+    }
+    /// The icon configured for an item
+    pub fn override_for(&self, item: &str) -> Option<&str> {
+        self.overrides.get(item).map(String::as_str)
+    }
+    /// Looks an icon up in the launcher's own paths. Names containing a `/` are files.
+    pub fn lookup(&self, name: &str) -> Option<Arc<Path>> {
+        if name.contains('/') {
+            let path = expand_home(Path::new(name))?;
+            let stem = path.file_stem()?.to_str()?.to_string();
+            return render_svg_to_cache(&stem, path);
+        }
+        self.theme.lookup_icon(name).flatten()
+    }
+}
+
 pub struct IconThemeGuard;
 impl<'g> IconThemeGuard {
     fn get_theme() -> Result<&'g RwLock<CustomIconTheme>, SherlockError> {
@@ -121,6 +165,41 @@ impl<'g> IconThemeGuard {
     }
 }
 
+/// Icon theme configured for GTK, read once on first use
+static SYSTEM_ICON_THEME: Lazy<Option<String>> = Lazy::new(system_icon_theme);
+
+/// Reads the icon theme from GSettings, falling back to the `gtk-icon-theme-name` key of the GTK
+/// `settings.ini` files.
+fn system_icon_theme() -> Option<String> {
+    let from_gsettings = std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "icon-theme"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map(|theme| theme.trim().trim_matches('\'').to_string())
+        .filter(|theme| !theme.is_empty());
+    if from_gsettings.is_some() {
+        return from_gsettings;
+    }
+
+    let xdg_dirs = xdg::BaseDirectories::new();
+    ["gtk-4.0/settings.ini", "gtk-3.0/settings.ini"]
+        .iter()
+        .filter_map(|file| xdg_dirs.find_config_file(file))
+        .find_map(|path| {
+            read_lines(path)
+                .ok()?
+                .map_while(Result::ok)
+                .find_map(|line| {
+                    let (key, value) = line.split_once('=')?;
+                    (key.trim() == "gtk-icon-theme-name")
+                        .then(|| value.trim().trim_matches('"').to_string())
+                        .filter(|theme| !theme.is_empty())
+                })
+        })
+}
+
 pub fn resolve_icon_path(name: &str) -> Option<Arc<Path>> {
     // 1. Check in-memory HashMap cache
     if let Ok(Some(icon)) = IconThemeGuard::lookup_icon(name) {
@@ -134,11 +213,20 @@ pub fn resolve_icon_path(name: &str) -> Option<Arc<Path>> {
         result = render_to_png_cache(name, &asset.data);
     }
 
+    // Themed lookup following the inheritance chain of the system theme
+    if result.is_none() {
+        result = find_icon(name, SYSTEM_ICON_THEME.as_deref(), 128, 1)
+            .and_then(|path| render_svg_to_cache(name, path));
+    }
+
     // Fallback to local linicon lookup (~/.local/share/icons)
     if result.is_none() {
         result = (|| {
-            let icon_path = lookup_icon(name)
-                .with_size(128)
+            let mut lookup = lookup_icon(name).with_size(128);
+            if let Some(theme) = SYSTEM_ICON_THEME.as_deref() {
+                lookup = lookup.from_theme(theme);
+            }
+            let icon_path = lookup
                 .with_search_paths(&["~/.local/share/icons/"])
                 .ok()?
                 .next()?
@@ -150,10 +238,11 @@ pub fn resolve_icon_path(name: &str) -> Option<Arc<Path>> {
 
     // Fallback to global Freedesktop lookup
     if result.is_none() {
-        result = freedesktop_icons::lookup(name)
-            .with_size(128)
-            .find()
-            .and_then(|i| render_svg_to_cache(name, i));
+        let mut lookup = freedesktop_icons::lookup(name).with_size(128);
+        if let Some(theme) = SYSTEM_ICON_THEME.as_deref() {
+            lookup = lookup.with_theme(theme);
+        }
+        result = lookup.find().and_then(|i| render_svg_to_cache(name, i));
     }
 
     // Finalize: Write found result back to the Guard buffer
@@ -164,6 +253,49 @@ pub fn resolve_icon_path(name: &str) -> Option<Arc<Path>> {
     result
 }
 
+/// Looks up all icons that have been deferred during loading on the background executor and
+/// patches them into the already displayed children.
+pub fn resolve_deferred_icons(cx: &mut App, data_handle: Entity<Arc<Vec<RenderableChild>>>) {
+    let pending: HashSet<String> = data_handle
+        .read(cx)
+        .iter()
+        .filter_map(|child| child.pending_icon())
+        .map(str::to_string)
+        .collect();
+    if pending.is_empty() {
+        return;
+    }
+
+    cx.spawn(|cx: &mut AsyncApp| {
+        let cx = cx.clone();
+        async move {
+            let resolved: HashMap<String, Option<Arc<Path>>> = cx
+                .background_executor()
+                .spawn(async move {
+                    pending
+                        .into_par_iter()
+                        .map(|name| {
+                            let icon = resolve_icon_path(&name);
+                            (name, icon)
+                        })
+                        .collect()
+                })
+                .await;
+
+            let _ = cx.update(|cx| {
+                data_handle.update(cx, |items_arc, cx| {
+                    let items = Arc::make_mut(items_arc);
+                    for item in items.iter_mut() {
+                        item.apply_icons(&resolved);
+                    }
+                    cx.notify();
+                });
+            });
+        }
+    })
+    .detach();
+}
+
 /// Renders an svg icon into a high-resolution png version.
 fn render_svg_to_cache(key: &str, path: PathBuf) -> Option<Arc<Path>> {
     // Early return if file does not exist
@@ -185,7 +317,10 @@ fn render_svg_to_cache(key: &str, path: PathBuf) -> Option<Arc<Path>> {
         }
     };
 
-    render_to_png_cache(key, &svg_data)
+    // Icons of different themes share their names, so the source decides about the cache entry
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    render_to_png_cache(&format!("{key}-{:x}", hasher.finish()), &svg_data)
 }
 
 fn render_to_png_cache(key: &str, svg_data: &[u8]) -> Option<Arc<Path>> {
@@ -202,12 +337,18 @@ fn render_to_png_cache(key: &str, svg_data: &[u8]) -> Option<Arc<Path>> {
         return Some(Arc::from(out.into_boxed_path()));
     }
 
+    render_svg_to_png(svg_data, &out)?;
+    Some(Arc::from(out.into_boxed_path()))
+}
+
+/// Rasterizes svg data into a png file at the given destination.
+pub fn render_svg_to_png(svg_data: &[u8], out: &Path) -> Option<()> {
     // Parse svg
     let opt = usvg::Options::default();
-    let tree = match usvg::Tree::from_data(&svg_data, &opt) {
+    let tree = match usvg::Tree::from_data(svg_data, &opt) {
         Ok(t) => t,
         Err(e) => {
-            eprintln!("Failed to parse SVG {key}: {e}");
+            eprintln!("Failed to parse SVG {}: {e}", out.display());
             return None;
         }
     };
@@ -219,7 +360,7 @@ fn render_to_png_cache(key: &str, svg_data: &[u8]) -> Option<Arc<Path>> {
     let width = (tree.size().width() * zoom).round() as u32;
     let height = (tree.size().height() * zoom).round() as u32;
 
-    let mut pixmap = tiny_skia::Pixmap::new(width, height).unwrap();
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)?;
 
     let sx = width as f32 / tree.size().width();
     let sy = height as f32 / tree.size().height();
@@ -232,10 +373,32 @@ fn render_to_png_cache(key: &str, svg_data: &[u8]) -> Option<Arc<Path>> {
     );
 
     // Save svg to destination
-    if let Err(e) = pixmap.save_png(&out) {
+    if let Err(e) = pixmap.save_png(out) {
         eprintln!("Warning: Failed to cache file: {e}");
         return None;
     }
 
-    Some(Arc::from(out.into_boxed_path()))
+    Some(())
+}
+
+#[test]
+fn test_launcher_icons() {
+    let dir = std::env::temp_dir().join(format!("sherlock-{}-icons", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("power-off.png"), b"").unwrap();
+
+    let args = serde_json::json!({
+        "icon_paths": [dir],
+        "icons": { "Shutdown": "power-off" }
+    });
+    let icons = LauncherIcons::from_args(&args).unwrap();
+    assert_eq!(icons.override_for("Shutdown"), Some("power-off"));
+    assert_eq!(
+        icons.lookup("power-off").as_deref(),
+        Some(dir.join("power-off.png").as_path())
+    );
+    assert!(icons.lookup("reboot").is_none());
+    assert!(LauncherIcons::from_args(&serde_json::json!({ "icon": "google" })).is_none());
+
+    let _ = std::fs::remove_dir_all(dir);
 }
@@ -0,0 +1,296 @@
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::utils::files::{home_dir, read_lines};
+
+const EXTENSIONS: [&str; 2] = ["png", "svg"];
+
+/// Parsed themes by name. `None` marks themes that are not installed.
+static THEMES: Lazy<RwLock<HashMap<String, Option<Arc<IconTheme>>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Directories icon themes are installed to, in order of precedence
+static BASE_DIRS: Lazy<Vec<PathBuf>> = Lazy::new(|| {
+    let xdg_dirs = xdg::BaseDirectories::new();
+    let mut dirs = Vec::new();
+    if let Ok(home) = home_dir() {
+        dirs.push(home.join(".icons"));
+    }
+    dirs.extend(xdg_dirs.get_data_home().map(|d| d.join("icons")));
+    dirs.extend(
+        xdg_dirs
+            .get_data_dirs()
+            .into_iter()
+            .map(|d| d.join("icons")),
+    );
+    dirs.push(PathBuf::from("/usr/share/pixmaps"));
+    dirs
+});
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DirType {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+/// A size directory as described in the `index.theme`
+#[derive(Debug)]
+struct ThemeDir {
+    path: String,
+    size: u32,
+    scale: u32,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+    kind: DirType,
+}
+impl ThemeDir {
+    fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+        match self.kind {
+            DirType::Fixed => self.size == size,
+            DirType::Scalable => (self.min_size..=self.max_size).contains(&size),
+            DirType::Threshold => self.size.abs_diff(size) <= self.threshold,
+        }
+    }
+    fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let (min, max) = match self.kind {
+            DirType::Fixed => (self.size, self.size),
+            DirType::Scalable => (self.min_size, self.max_size),
+            DirType::Threshold => (
+                self.size.saturating_sub(self.threshold),
+                self.size + self.threshold,
+            ),
+        };
+        let target = size * scale;
+        if target < min * self.scale {
+            min * self.scale - target
+        } else {
+            target.saturating_sub(max * self.scale)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct IconTheme {
+    roots: Vec<PathBuf>,
+    dirs: Vec<ThemeDir>,
+    inherits: Vec<String>,
+}
+impl IconTheme {
+    fn load(name: &str) -> Option<Self> {
+        let roots: Vec<PathBuf> = BASE_DIRS
+            .iter()
+            .map(|base| base.join(name))
+            .filter(|root| root.is_dir())
+            .collect();
+        let index = roots
+            .iter()
+            .map(|root| root.join("index.theme"))
+            .find(|index| index.is_file())?;
+        let lines = read_lines(index).ok()?.map_while(Result::ok);
+
+        let mut theme = Self::parse(lines);
+        theme.roots = roots;
+        Some(theme)
+    }
+
+    fn parse<I: Iterator<Item = String>>(lines: I) -> Self {
+        let mut listed: Vec<String> = Vec::new();
+        let mut inherits = Vec::new();
+        let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let mut current: Option<String> = None;
+
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                current = Some(line[1..line.len() - 1].to_string());
+                continue;
+            }
+            let (Some(section), Some((key, value))) = (current.as_ref(), line.split_once('='))
+            else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if section == "Icon Theme" {
+                let list = value.split(',').map(str::trim).filter(|v| !v.is_empty());
+                match key {
+                    "Inherits" => inherits.extend(list.map(str::to_string)),
+                    "Directories" | "ScaledDirectories" => listed.extend(list.map(str::to_string)),
+                    _ => {}
+                }
+            } else {
+                sections
+                    .entry(section.clone())
+                    .or_default()
+                    .insert(key.to_string(), value.to_string());
+            }
+        }
+
+        let dirs = listed
+            .into_iter()
+            .filter_map(|path| {
+                let section = sections.get(&path)?;
+                let get = |key: &str| section.get(key).and_then(|v| v.parse::<u32>().ok());
+                let size = get("Size")?;
+                let kind = match section.get("Type").map(String::as_str) {
+                    Some("Fixed") => DirType::Fixed,
+                    Some("Scalable") => DirType::Scalable,
+                    _ => DirType::Threshold,
+                };
+                Some(ThemeDir {
+                    size,
+                    scale: get("Scale").unwrap_or(1),
+                    min_size: get("MinSize").unwrap_or(size),
+                    max_size: get("MaxSize").unwrap_or(size),
+                    threshold: get("Threshold").unwrap_or(2),
+                    kind,
+                    path,
+                })
+            })
+            .collect();
+
+        Self {
+            roots: Vec::new(),
+            dirs,
+            inherits,
+        }
+    }
+
+    fn find_file(&self, dir: &ThemeDir, icon: &str) -> Option<PathBuf> {
+        self.roots.iter().find_map(|root| {
+            EXTENSIONS
+                .iter()
+                .map(|ext| root.join(&dir.path).join(format!("{icon}.{ext}")))
+                .find(|file| file.is_file())
+        })
+    }
+
+    /// Prefers directories matching the size exactly, otherwise the closest one wins
+    fn lookup(&self, icon: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        let exact = self
+            .dirs
+            .iter()
+            .filter(|dir| dir.matches_size(size, scale))
+            .find_map(|dir| self.find_file(dir, icon));
+        if exact.is_some() {
+            return exact;
+        }
+
+        let mut closest = None;
+        let mut min_distance = u32::MAX;
+        for dir in &self.dirs {
+            let distance = dir.size_distance(size, scale);
+            if distance >= min_distance {
+                continue;
+            }
+            if let Some(file) = self.find_file(dir, icon) {
+                closest = Some(file);
+                min_distance = distance;
+            }
+        }
+        closest
+    }
+}
+
+fn get_theme(name: &str) -> Option<Arc<IconTheme>> {
+    if let Some(theme) = THEMES.read().ok()?.get(name) {
+        return theme.clone();
+    }
+    let theme = IconTheme::load(name).map(Arc::new);
+    if let Ok(mut themes) = THEMES.write() {
+        themes.insert(name.to_string(), theme.clone());
+    }
+    theme
+}
+
+fn find_in_theme(
+    icon: &str,
+    theme_name: &str,
+    size: u32,
+    scale: u32,
+    visited: &mut HashSet<String>,
+) -> Option<PathBuf> {
+    // Guards against inheritance cycles
+    if !visited.insert(theme_name.to_string()) {
+        return None;
+    }
+    let theme = get_theme(theme_name)?;
+    theme.lookup(icon, size, scale).or_else(|| {
+        theme
+            .inherits
+            .iter()
+            .find_map(|parent| find_in_theme(icon, parent, size, scale, visited))
+    })
+}
+
+/// Looks up an icon in the given theme and the themes it inherits from, falling back to
+/// `hicolor` and finally to icons placed directly in one of the base directories.
+pub fn find_icon(icon: &str, theme: Option<&str>, size: u32, scale: u32) -> Option<PathBuf> {
+    let path = Path::new(icon);
+    if path.is_absolute() {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+
+    let mut visited = HashSet::new();
+    theme
+        .and_then(|theme| find_in_theme(icon, theme, size, scale, &mut visited))
+        .or_else(|| find_in_theme(icon, "hicolor", size, scale, &mut visited))
+        .or_else(|| {
+            BASE_DIRS.iter().find_map(|base| {
+                EXTENSIONS
+                    .iter()
+                    .map(|ext| base.join(format!("{icon}.{ext}")))
+                    .find(|file| file.is_file())
+            })
+        })
+}
+
+#[test]
+fn test_parse_index_theme() {
+    let index = "[Icon Theme]\n\
+        Name=Papirus\n\
+        Inherits=breeze, hicolor\n\
+        Directories=48x48/apps,symbolic/apps,missing\n\
+        ScaledDirectories=48x48@2x/apps\n\
+        \n\
+        [48x48/apps]\n\
+        Size=48\n\
+        Type=Fixed\n\
+        \n\
+        [48x48@2x/apps]\n\
+        Size=48\n\
+        Scale=2\n\
+        Type=Fixed\n\
+        \n\
+        [symbolic/apps]\n\
+        Size=16\n\
+        MinSize=16\n\
+        MaxSize=512\n\
+        Type=Scalable\n";
+    let theme = IconTheme::parse(index.lines().map(str::to_string));
+
+    assert_eq!(theme.inherits, vec!["breeze", "hicolor"]);
+    assert_eq!(theme.dirs.len(), 3);
+
+    let fixed = &theme.dirs[0];
+    assert!(fixed.matches_size(48, 1));
+    assert!(!fixed.matches_size(48, 2));
+    assert_eq!(fixed.size_distance(128, 1), 80);
+
+    let scalable = &theme.dirs[1];
+    assert!(scalable.matches_size(128, 1));
+    assert_eq!(scalable.size_distance(128, 1), 0);
+
+    let scaled = &theme.dirs[2];
+    assert_eq!(scaled.scale, 2);
+    assert!(scaled.matches_size(48, 2));
+}
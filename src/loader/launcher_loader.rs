@@ -1,57 +1,128 @@
 use gpui::{App, Entity};
+use serde::Deserialize;
 use serde_json::Value;
 use simd_json::prelude::ArrayTrait;
-use std::{collections::HashMap, fs::File, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use crate::{
     launcher::{
         Launcher, LauncherType,
+        agenda_launcher::{AgendaLauncher, AgendaSource},
         app_launcher::AppLauncher,
+        audio_device_launcher::{AudioDeviceLauncher, DeviceKind},
         audio_launcher::AudioLauncherFunctions,
         bookmark_launcher::BookmarkLauncher,
         calc_launcher::{CURRENCIES, CalculatorLauncher, Currency},
         category_launcher::CategoryLauncher,
         children::RenderableChild,
+        contacts_launcher::ContactsLauncher,
+        display_launcher::DisplayLauncher,
+        dnd_launcher::DndLauncher,
+        feed_launcher::FeedLauncher,
+        file_launcher::FileLauncher,
+        games_launcher::{GameSource, GamesLauncher},
+        github_launcher::{GithubKind, GithubLauncher},
+        mail_launcher::MailLauncher,
+        nix_launcher::NixLauncher,
+        pacman_launcher::PacmanLauncher,
+        record_launcher::RecordLauncher,
         system_cmd_launcher::CommandLauncher,
-        weather_launcher::{WeatherIconTheme, WeatherLauncher},
+        volume_launcher::VolumeLauncher,
+        vpn_launcher::VpnLauncher,
+        weather_launcher::{WeatherIconTheme, WeatherLauncher, WeatherUnits},
+        weather_provider::WeatherProviderKind,
         web_launcher::WebLauncher,
     },
     loader::utils::RawLauncher,
     sherlock_error,
     ui::main_window::LauncherMode,
     utils::{
-        cache::BinaryCache,
-        config::{ConfigGuard, ConstantDefaults},
+        config::{ConfigGuard, ConfigUnits, ConstantDefaults},
         errors::{SherlockError, SherlockErrorType},
+        favicon::{FaviconSource, resolve_favicons},
+        files::{expand_path, home_dir},
     },
 };
 
 use super::Loader;
-use super::utils::CounterReader;
+use super::application_loader::count_decimals;
+use super::diagnostics::diagnose_launcher;
+use super::refresher::refresh_periodically;
+use super::utils::{ActionTable, CounterReader, IgnoreList};
+use super::{LauncherIcons, resolve_deferred_icons};
+
+/// Launchers written by `sherlock init` and used while there is no fallback.json
+pub const DEFAULT_FALLBACK: &str = include_str!("fallback.json");
+
+/// The launcher types understood in `fallback.json`
+pub const LAUNCHER_TYPES: &[&str] = &[
+    "agenda",
+    "app_launcher",
+    "audio_devices",
+    "audio_sink",
+    "bookmarks",
+    "calculation",
+    "categories",
+    "command",
+    "contacts",
+    "debug",
+    "display",
+    "dnd",
+    "feed",
+    "files",
+    "games",
+    "github",
+    "mail",
+    "nix",
+    "pacman",
+    "record",
+    "volume",
+    "vpn",
+    "weather",
+    "web_launcher",
+];
+
+/// Launcher types whose tiles are kept up to date by watchers, see
+/// [`LauncherType::watches_changes`]. Reloading them periodically would start more and more
+/// watchers, so they take no `refresh_interval`.
+pub const WATCHED_TYPES: &[&str] = &["app_launcher", "audio_sink", "dnd", "volume", "vpn"];
 
 impl Loader {
+    /// Loads the launchers into `data_handle`. Returns their modes along with the non-breaking
+    /// errors of their configuration, which are shown in the error view.
     pub fn load_launchers(
         cx: &mut App,
         data_handle: Entity<Arc<Vec<RenderableChild>>>,
-    ) -> Result<Arc<[LauncherMode]>, SherlockError> {
+    ) -> Result<(Arc<[LauncherMode]>, Vec<SherlockError>), SherlockError> {
         // read config
         let config = ConfigGuard::read()?;
 
         // Read fallback data here:
-        let (raw_launchers, _n) = parse_launcher_configs(&config.files.fallback)?;
+        let (raw_launchers, mut non_breaking) =
+            parse_launcher_configs(&config.files.fallback, &config.launcher)?;
+
+        // Hides children of all launchers
+        let ignore = IgnoreList::read(&config.files.ignore).unwrap_or_else(|e| {
+            non_breaking.push(e);
+            IgnoreList::default()
+        });
+
+        // Context actions of sherlock_actions.json
+        let actions = ActionTable::from_config(&config).unwrap_or_else(|e| {
+            non_breaking.push(e);
+            ActionTable::default()
+        });
 
-        // Read cached counter file
-        let counter_reader = CounterReader::new()?;
-        let counts: HashMap<String, u32> =
-            BinaryCache::read(&counter_reader.path).unwrap_or_default();
+        // Read the launch counts
+        let counts = CounterReader::new()?.read();
 
         // Construct max decimal count
-        let max_count = counts.values().max().cloned().unwrap_or(0);
-        let max_decimals = if max_count == 0 {
-            0
-        } else {
-            (max_count as f32).log10().floor() as i32 + 1
-        };
+        let max_decimals = count_decimals(&counts);
 
         let submenu = config
             .runtime
@@ -61,16 +132,24 @@ impl Loader {
         // Parse the launchers
         let mut launchers: Vec<(Arc<Launcher>, Arc<serde_json::Value>)> = raw_launchers
             .into_iter()
-            .filter_map(|raw| {
+            .enumerate()
+            .filter_map(|(i, mut raw)| {
                 // Logic to restrict in submenu mode
                 if submenu != "all" && raw.alias.as_ref() != Some(&submenu) {
                     return None;
                 }
+                // Entries for tools that are not installed are skipped silently
+                if raw.only_if.as_ref().is_some_and(|c| !c.is_met()) {
+                    return None;
+                }
+                non_breaking.extend(diagnose_launcher(&raw, i));
 
                 let method = raw.on_return.clone().unwrap_or_else(|| raw.r#type.clone());
 
                 let launcher_type: LauncherType = match raw.r#type.to_lowercase().as_str() {
+                    "agenda" => parse_agenda_launcher(&raw, &config.default_apps.calendar_client),
                     "app_launcher" => parse_app_launcher(&raw),
+                    "audio_devices" => parse_audio_device_launcher(&raw),
                     "audio_sink" => parse_audio_sink_launcher(),
                     "bookmarks" => {
                         parse_bookmarks_launcher(&raw, config.default_apps.browser.as_ref())
@@ -78,19 +157,36 @@ impl Loader {
                     "calculation" => parse_calculator(&raw),
                     "categories" => parse_category_launcher(&raw),
                     "command" => parse_command_launcher(&raw),
+                    "contacts" => parse_contacts_launcher(&raw),
                     "debug" => parse_debug_launcher(&raw),
-                    "weather" => parse_weather_launcher(&raw),
+                    "display" => parse_display_launcher(&raw),
+                    "dnd" => parse_dnd_launcher(&raw),
+                    "feed" => parse_feed_launcher(&raw),
+                    "files" => parse_file_launcher(&raw),
+                    "games" => parse_games_launcher(&raw),
+                    "github" => parse_github_launcher(&raw),
+                    "mail" => parse_mail_launcher(&raw),
+                    "nix" => parse_nix_launcher(&raw),
+                    "pacman" => parse_pacman_launcher(&raw),
+                    "record" => parse_record_launcher(&raw),
+                    "weather" => parse_weather_launcher(&raw, &config.units),
+                    "volume" => parse_volume_launcher(&raw),
+                    "vpn" => parse_vpn_launcher(&raw, &config.default_apps.elevation),
                     "web_launcher" => parse_web_launcher(&raw),
                     // "bulk_text" => parse_bulk_text_launcher(&raw),
                     // "clipboard-execution" => parse_clipboard_launcher(&raw).ok()?,
                     // "emoji_picker" => parse_emoji_launcher(&raw),
-                    // "files" => parse_file_launcher(&raw),
                     // "teams_event" => parse_event_launcher(&raw),
                     // "theme_picker" => parse_theme_launcher(&raw),
                     // "process" => parse_process_launcher(&raw),
                     // "pomodoro" => parse_pomodoro(&raw),
                     _ => LauncherType::Empty,
                 };
+                // Games get a mode of their own unless the config names one
+                if matches!(launcher_type, LauncherType::Games(_)) {
+                    raw.alias.get_or_insert_with(|| String::from("games"));
+                    raw.name.get_or_insert_with(|| String::from("Games"));
+                }
 
                 let icon = raw
                     .args
@@ -118,77 +214,183 @@ impl Loader {
                     });
                 }
 
-                launcher.launcher_type.get_render_obj(
+                // Watched launchers are updated by their watchers instead, the interval is
+                // reported by the diagnostics
+                if let Some(interval) = launcher.refresh_interval
+                    && !launcher.launcher_type.watches_changes()
+                {
+                    refresh_periodically(
+                        cx,
+                        Arc::clone(&launcher),
+                        Arc::clone(&opts),
+                        interval,
+                        data_handle.clone(),
+                    );
+                }
+
+                let Some(mut children) = launcher.launcher_type.get_render_obj(
                     Arc::clone(&launcher),
-                    opts, //
+                    Arc::clone(&opts),
                     &counts,
                     max_decimals,
                     cx,
                     data_handle.clone(),
-                )
+                ) else {
+                    launcher.watchers.stop();
+                    return None;
+                };
+                prepare_children(&mut children, &launcher, &opts, &ignore, &actions);
+                Some(children)
             })
             .flatten()
             .collect();
 
-        // Get errors and launchers
-        let mut non_breaking = Vec::new();
-        if counts.is_empty() {
-            let counts: HashMap<String, u32> = renders
-                .iter()
-                .filter_map(|render| render.get_exec())
-                .map(|exec| (exec, 0))
-                .collect();
-            if let Err(e) = BinaryCache::write(&counter_reader.path, &counts) {
-                non_breaking.push(e)
-            };
-        }
-
         data_handle.update(cx, |items, cx| {
+            // The watchers of the replaced launchers would keep running otherwise
+            for item in items.iter() {
+                item.stop_watchers();
+            }
             *items = Arc::new(renders);
             cx.notify();
         });
 
-        Ok(Arc::from(modes))
+        // Icons are looked up after the first render
+        resolve_deferred_icons(cx, data_handle.clone());
+        resolve_favicons(cx, data_handle);
+
+        Ok((Arc::from(modes), non_breaking))
+    }
+}
+
+/// Applies the icon overrides and keyword boosts of the launcher, the sherlockignore file and the
+/// declared actions to freshly loaded children
+pub(super) fn prepare_children(
+    children: &mut Vec<RenderableChild>,
+    launcher: &Launcher,
+    opts: &serde_json::Value,
+    ignore: &IgnoreList,
+    actions: &ActionTable,
+) {
+    if let Some(icons) = LauncherIcons::from_args(opts) {
+        children
+            .iter_mut()
+            .for_each(|child| child.apply_launcher_icons(&icons));
     }
+    if let Some(boosts) = opts
+        .get("boosts")
+        .and_then(|boosts| HashMap::deserialize(boosts).ok())
+    {
+        children
+            .iter_mut()
+            .for_each(|child| child.apply_launcher_boosts(&boosts));
+    }
+    let kind = launcher.launcher_type.kind();
+    children.retain(|child| !ignore.is_ignored(kind, child.title(), child.exec()));
+    children
+        .iter_mut()
+        .for_each(|child| child.add_declared_actions(actions));
 }
 
 fn parse_launcher_configs(
-    fallback_path: &PathBuf,
+    fallback_path: &Path,
+    config_launchers: &[RawLauncher],
 ) -> Result<(Vec<RawLauncher>, Vec<SherlockError>), SherlockError> {
-    // Reads all the configurations of launchers. Either from fallback.json or from default
-    // file.
+    // Reads all the configurations of launchers. Either from fallback.json, fallback.toml or
+    // from the default file, followed by the `[[launcher]]` tables of the config.
 
     let mut non_breaking: Vec<SherlockError> = Vec::new();
 
-    fn load_user_fallback(fallback_path: &PathBuf) -> Result<Vec<RawLauncher>, SherlockError> {
-        // Tries to load the user-specified launchers. If it failes, it returns a non breaking
-        // error.
-        match File::open(&fallback_path) {
-            Ok(f) => simd_json::from_reader(f).map_err(|e| {
+    let mut launchers = match fallback_file(fallback_path) {
+        Some(path) => load_user_fallback(&path)
+            .map_err(|e| non_breaking.push(e))
+            .unwrap_or_default(),
+        // Without any launchers, the ones `sherlock init` would write are used
+        None if config_launchers.is_empty() => {
+            serde_json::from_str(DEFAULT_FALLBACK).map_err(|e| {
                 sherlock_error!(
-                    SherlockErrorType::FileParseError(fallback_path.clone()),
+                    SherlockErrorType::FileParseError(fallback_path.to_path_buf()),
                     e.to_string()
                 )
-            }),
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
-            Err(e) => Err(sherlock_error!(
-                SherlockErrorType::FileReadError(fallback_path.clone()),
-                e.to_string()
-            )),
+            })?
         }
-    }
-
-    let config = match load_user_fallback(fallback_path)
-        .map_err(|e| non_breaking.push(e))
-        .ok()
-    {
-        Some(v) => v,
         None => Vec::new(),
     };
+    launchers.extend(config_launchers.iter().cloned());
+
+    Ok((launchers, non_breaking))
+}
 
-    return Ok((config, non_breaking));
+/// The fallback file, falling back to a `fallback.toml` next to the configured `fallback.json`
+pub fn fallback_file(fallback_path: &Path) -> Option<PathBuf> {
+    if fallback_path.exists() {
+        return Some(fallback_path.to_path_buf());
+    }
+    let toml = fallback_path.with_extension("toml");
+    toml.exists().then_some(toml)
+}
+
+/// `[[launcher]]` tables of a fallback.toml
+#[derive(Deserialize)]
+pub struct LauncherTables {
+    #[serde(default)]
+    pub launcher: Vec<RawLauncher>,
 }
 
+fn load_user_fallback(path: &Path) -> Result<Vec<RawLauncher>, SherlockError> {
+    let parse_error =
+        |e: String| sherlock_error!(SherlockErrorType::FileParseError(path.to_path_buf()), e);
+    if path.extension().is_some_and(|ext| ext == "toml") {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            sherlock_error!(
+                SherlockErrorType::FileReadError(path.to_path_buf()),
+                e.to_string()
+            )
+        })?;
+        return toml::from_str::<LauncherTables>(&content)
+            .map(|tables| tables.launcher)
+            .map_err(|e| parse_error(e.to_string()));
+    }
+    let file = File::open(path).map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::FileReadError(path.to_path_buf()),
+            e.to_string()
+        )
+    })?;
+    simd_json::from_reader(file).map_err(|e| parse_error(e.to_string()))
+}
+
+fn parse_agenda_launcher(raw: &RawLauncher, calendar_client: &str) -> LauncherType {
+    let expand = |path: &str| match home_dir() {
+        Ok(home) => expand_path(path, &home),
+        Err(_) => PathBuf::from(path),
+    };
+    // ICS files or directories if given, khal otherwise
+    let calendars: Vec<PathBuf> = match raw.args.get("calendars") {
+        Some(Value::String(path)) => vec![expand(path)],
+        Some(Value::Array(paths)) => paths.iter().filter_map(Value::as_str).map(expand).collect(),
+        _ => Vec::new(),
+    };
+    let source = if calendars.is_empty() {
+        let database = raw
+            .args
+            .get("khal_database")
+            .and_then(Value::as_str)
+            .unwrap_or(AgendaLauncher::DEFAULT_KHAL_DATABASE);
+        AgendaSource::Khal(expand(database))
+    } else {
+        AgendaSource::Ics(calendars)
+    };
+    let days = raw
+        .args
+        .get("days")
+        .and_then(Value::as_u64)
+        .unwrap_or(AgendaLauncher::DEFAULT_DAYS);
+    LauncherType::Agenda(AgendaLauncher {
+        source,
+        days,
+        client: calendar_client.to_string(),
+    })
+}
 fn parse_app_launcher(raw: &RawLauncher) -> LauncherType {
     let use_keywords = raw
         .args
@@ -197,6 +399,15 @@ fn parse_app_launcher(raw: &RawLauncher) -> LauncherType {
         .unwrap_or(true);
     LauncherType::App(AppLauncher { use_keywords })
 }
+fn parse_audio_device_launcher(raw: &RawLauncher) -> LauncherType {
+    // Outputs and inputs unless restricted through `devices`
+    let kinds = raw
+        .args
+        .get("devices")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_else(|| vec![DeviceKind::Sink, DeviceKind::Source]);
+    LauncherType::AudioDevices(AudioDeviceLauncher { kinds })
+}
 fn parse_audio_sink_launcher() -> LauncherType {
     AudioLauncherFunctions::new()
         .and_then(|launcher| {
@@ -219,10 +430,24 @@ fn parse_bookmarks_launcher(
         .or_else(|| default_browser.cloned())
         .or_else(|| ConstantDefaults::browser().ok());
 
+    let favicons = launcher
+        .args
+        .get("favicons")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+
+    let profiles = launcher
+        .args
+        .get("profiles")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+
     // TODO parse bookmarks later
     if let Some(browser) = browser_target {
         return LauncherType::Bookmark(BookmarkLauncher {
             target_browser: browser,
+            favicons,
+            profiles,
         });
     }
     LauncherType::Empty
@@ -254,14 +479,265 @@ fn parse_command_launcher(_raw: &RawLauncher) -> LauncherType {
     LauncherType::Command(CommandLauncher {})
 }
 
+fn parse_contacts_launcher(raw: &RawLauncher) -> LauncherType {
+    // A single directory or a list of them
+    let directories: Vec<&str> = match raw.args.get("directories") {
+        Some(Value::String(directory)) => vec![directory.as_str()],
+        Some(Value::Array(directories)) => directories.iter().filter_map(Value::as_str).collect(),
+        _ => vec![ContactsLauncher::DEFAULT_DIRECTORY],
+    };
+    let directories = directories
+        .into_iter()
+        .map(|path| match home_dir() {
+            Ok(home) => expand_path(path, &home),
+            Err(_) => PathBuf::from(path),
+        })
+        .collect();
+    let call = raw
+        .args
+        .get("call")
+        .and_then(Value::as_str)
+        .unwrap_or(ContactsLauncher::DEFAULT_CALL)
+        .to_string();
+    LauncherType::Contacts(ContactsLauncher { directories, call })
+}
+
 fn parse_debug_launcher(_: &RawLauncher) -> LauncherType {
     // let prio = raw.priority;
     // let value = &raw.args["commands"];
     // let commands = parse_appdata(value, prio, counts, max_decimals);
     LauncherType::Command(CommandLauncher {})
 }
-fn parse_weather_launcher(raw: &RawLauncher) -> LauncherType {
-    if let Some(location) = raw.args.get("location").and_then(Value::as_str) {
+fn parse_display_launcher(raw: &RawLauncher) -> LauncherType {
+    let kanshi_config = raw
+        .args
+        .get("kanshi_config")
+        .and_then(Value::as_str)
+        .unwrap_or(DisplayLauncher::DEFAULT_KANSHI_CONFIG);
+    let kanshi_config = match home_dir() {
+        Ok(home) => expand_path(kanshi_config, &home),
+        Err(_) => PathBuf::from(kanshi_config),
+    };
+    // A table of names and wlr-randr arguments
+    let layouts = raw
+        .args
+        .get("layouts")
+        .and_then(Value::as_object)
+        .map(|layouts| {
+            layouts
+                .iter()
+                .filter_map(|(name, args)| Some((name.clone(), args.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    LauncherType::Display(DisplayLauncher {
+        kanshi_config,
+        layouts,
+    })
+}
+fn parse_dnd_launcher(raw: &RawLauncher) -> LauncherType {
+    let mako_mode = raw
+        .args
+        .get("mako_mode")
+        .and_then(Value::as_str)
+        .unwrap_or(DndLauncher::DEFAULT_MAKO_MODE)
+        .to_string();
+    LauncherType::Dnd(DndLauncher { mako_mode })
+}
+fn parse_feed_launcher(raw: &RawLauncher) -> LauncherType {
+    // A single feed or a list of them
+    let feeds: Vec<String> = match raw.args.get("feeds") {
+        Some(Value::String(feed)) => vec![feed.clone()],
+        Some(Value::Array(feeds)) => feeds
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let number = |key: &str| raw.args.get(key).and_then(Value::as_u64);
+    LauncherType::Feed(FeedLauncher {
+        feeds,
+        limit: number("limit").map_or(FeedLauncher::DEFAULT_LIMIT, |limit| limit as usize),
+        update_interval: number("update_interval").unwrap_or(FeedLauncher::DEFAULT_UPDATE_INTERVAL),
+        browser: raw
+            .args
+            .get("browser")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+fn parse_file_launcher(raw: &RawLauncher) -> LauncherType {
+    // A single directory or a list of them
+    let directories: Vec<&str> = match raw.args.get("directories") {
+        Some(Value::String(directory)) => vec![directory.as_str()],
+        Some(Value::Array(directories)) => directories.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let directories = directories
+        .into_iter()
+        .map(|path| match home_dir() {
+            Ok(home) => expand_path(path, &home),
+            Err(_) => PathBuf::from(path),
+        })
+        .collect();
+    LauncherType::Files(FileLauncher {
+        directories,
+        recent: raw
+            .args
+            .get("recent")
+            .and_then(Value::as_bool)
+            .unwrap_or(true),
+        limit: raw
+            .args
+            .get("limit")
+            .and_then(Value::as_u64)
+            .map_or(FileLauncher::DEFAULT_LIMIT, |limit| limit as usize),
+    })
+}
+fn parse_games_launcher(raw: &RawLauncher) -> LauncherType {
+    let sources = raw
+        .args
+        .get("sources")
+        .and_then(|sources| Vec::<GameSource>::deserialize(sources).ok())
+        .unwrap_or_else(|| GamesLauncher::DEFAULT_SOURCES.to_vec());
+    LauncherType::Games(GamesLauncher { sources })
+}
+fn parse_github_launcher(raw: &RawLauncher) -> LauncherType {
+    let text = |key: &str| {
+        raw.args
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let kinds = raw
+        .args
+        .get("search")
+        .and_then(|kinds| Vec::<GithubKind>::deserialize(kinds).ok())
+        .unwrap_or_else(|| GithubLauncher::DEFAULT_KINDS.to_vec());
+    let limit = raw
+        .args
+        .get("limit")
+        .and_then(Value::as_u64)
+        .map_or(GithubLauncher::DEFAULT_LIMIT, |limit| limit as u32);
+    LauncherType::Github(GithubLauncher {
+        token: text("token"),
+        kinds,
+        limit,
+        browser: text("browser"),
+    })
+}
+fn parse_mail_launcher(raw: &RawLauncher) -> LauncherType {
+    let maildir = raw
+        .args
+        .get("maildir")
+        .and_then(Value::as_str)
+        .map(|path| match home_dir() {
+            Ok(home) => expand_path(path, &home),
+            Err(_) => PathBuf::from(path),
+        });
+    let client = raw
+        .args
+        .get("client")
+        .and_then(Value::as_str)
+        .unwrap_or(MailLauncher::DEFAULT_CLIENT)
+        .to_string();
+    let limit = raw
+        .args
+        .get("limit")
+        .and_then(Value::as_u64)
+        .map_or(MailLauncher::DEFAULT_LIMIT, |limit| limit as usize);
+    LauncherType::Mail(MailLauncher {
+        maildir,
+        client,
+        limit,
+    })
+}
+fn parse_nix_launcher(raw: &RawLauncher) -> LauncherType {
+    let flake = raw
+        .args
+        .get("flake")
+        .and_then(Value::as_str)
+        .unwrap_or(NixLauncher::DEFAULT_FLAKE)
+        .to_string();
+    let configuration = raw
+        .args
+        .get("configuration")
+        .and_then(Value::as_str)
+        .map(|path| match home_dir() {
+            Ok(home) => expand_path(path, &home),
+            Err(_) => PathBuf::from(path),
+        });
+    LauncherType::Nix(NixLauncher {
+        flake,
+        configuration,
+    })
+}
+fn parse_pacman_launcher(raw: &RawLauncher) -> LauncherType {
+    let aur_helper = raw
+        .args
+        .get("aur_helper")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    LauncherType::Pacman(PacmanLauncher { aur_helper })
+}
+fn parse_record_launcher(raw: &RawLauncher) -> LauncherType {
+    let directory = raw
+        .args
+        .get("directory")
+        .and_then(Value::as_str)
+        .unwrap_or(RecordLauncher::DEFAULT_DIRECTORY);
+    let directory = match home_dir() {
+        Ok(home) => expand_path(directory, &home),
+        Err(_) => PathBuf::from(directory),
+    };
+    let audio = raw
+        .args
+        .get("audio")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    LauncherType::Record(RecordLauncher { directory, audio })
+}
+fn parse_volume_launcher(raw: &RawLauncher) -> LauncherType {
+    let step = raw
+        .args
+        .get("step")
+        .and_then(Value::as_u64)
+        .map_or(VolumeLauncher::DEFAULT_STEP, |step| {
+            step.clamp(1, 100) as u32
+        });
+    LauncherType::Volume(VolumeLauncher { step })
+}
+fn parse_vpn_launcher(raw: &RawLauncher, elevation: &str) -> LauncherType {
+    let interfaces = raw
+        .args
+        .get("interfaces")
+        .and_then(Value::as_array)
+        .map(|interfaces| {
+            interfaces
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    LauncherType::Vpn(VpnLauncher {
+        interfaces,
+        elevation: elevation.to_string(),
+    })
+}
+fn parse_weather_launcher(raw: &RawLauncher, units: &ConfigUnits) -> LauncherType {
+    // A single location or a list of them
+    let locations: Vec<String> = match raw.args.get("location") {
+        Some(Value::String(location)) => vec![location.clone()],
+        Some(Value::Array(locations)) => locations
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    if !locations.is_empty() {
         let update_interval = raw
             .args
             .get("update_interval")
@@ -281,11 +757,19 @@ fn parse_weather_launcher(raw: &RawLauncher) -> LauncherType {
             .and_then(Value::as_bool)
             .unwrap_or(true);
 
+        let provider: WeatherProviderKind = raw
+            .args
+            .get("provider")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
         LauncherType::Weather(WeatherLauncher {
-            location: location.to_string(),
+            locations,
             update_interval,
             icon_theme,
             show_datetime,
+            provider,
+            units: WeatherUnits::from_config(units),
         })
     } else {
         LauncherType::Empty
@@ -299,6 +783,19 @@ fn parse_web_launcher(raw: &RawLauncher) -> LauncherType {
         .and_then(|s| s.as_str())
         .map(|s| s.to_string());
 
+    // Favicons replace the engine icon only when asked for
+    let favicons = raw
+        .args
+        .get("favicons")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or(FaviconSource::Disabled);
+
+    let engines = raw
+        .args
+        .get("engines")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+
     // Adds functionality for variables
     LauncherType::Web(WebLauncher {
         engine: raw
@@ -308,5 +805,49 @@ fn parse_web_launcher(raw: &RawLauncher) -> LauncherType {
             .unwrap_or_default()
             .to_string(),
         browser,
+        favicons,
+        profile: raw
+            .args
+            .get("profile")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        container: raw
+            .args
+            .get("container")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        engines,
     })
 }
+
+#[test]
+fn test_default_fallback() {
+    let launchers: Vec<RawLauncher> = serde_json::from_str(DEFAULT_FALLBACK).unwrap();
+    assert!(!launchers.is_empty());
+    for launcher in &launchers {
+        assert!(LAUNCHER_TYPES.contains(&launcher.r#type.as_str()));
+    }
+}
+
+#[test]
+fn test_toml_launchers() {
+    let content = r#"
+[[launcher]]
+name = "App Launcher"
+type = "app_launcher"
+priority = 2
+home = "Home"
+
+[[launcher]]
+name = "Web Search"
+alias = "gg"
+type = "web_launcher"
+priority = 100
+args = { search_engine = "google" }
+"#;
+    let launchers = toml::from_str::<LauncherTables>(content).unwrap().launcher;
+    assert_eq!(launchers.len(), 2);
+    assert_eq!(launchers[0].priority, 2.0);
+    assert_eq!(launchers[1].alias.as_deref(), Some("gg"));
+    assert_eq!(launchers[1].args["search_engine"], "google");
+}
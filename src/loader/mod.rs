@@ -1,9 +1,24 @@
+mod app_watcher;
 pub mod application_loader;
 pub mod assets;
+mod config_watcher;
+mod diagnostics;
 mod flag_loader;
 mod icon_loader;
+mod icon_theme;
 mod launcher_loader;
+mod refresher;
 pub mod utils;
 
 pub struct Loader;
-pub use icon_loader::{CustomIconTheme, IconThemeGuard, resolve_icon_path};
+pub use app_watcher::{is_loaded, watch_applications};
+pub use config_watcher::{reload_config, watch_config};
+pub use diagnostics::launcher_problems;
+pub use icon_loader::{
+    CustomIconTheme, IconThemeGuard, LauncherIcons, render_svg_to_png, resolve_deferred_icons,
+    resolve_icon_path,
+};
+pub use launcher_loader::{
+    DEFAULT_FALLBACK, LAUNCHER_TYPES, LauncherTables, WATCHED_TYPES, fallback_file,
+};
+pub use refresher::{reload_launcher, replace_children};
@@ -0,0 +1,112 @@
+use gpui::{App, AsyncApp, Entity};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::application_loader::count_decimals;
+use super::launcher_loader::prepare_children;
+use super::utils::{ActionTable, CounterReader, IgnoreList};
+use super::{is_loaded, resolve_deferred_icons};
+use crate::launcher::Launcher;
+use crate::launcher::children::RenderableChild;
+use crate::ui::main_window::SherlockMainWindow;
+use crate::utils::config::ConfigGuard;
+use crate::utils::favicon::resolve_favicons;
+
+/// Reloads the items of the launcher every `interval` seconds, for providers whose content
+/// changes while Sherlock keeps running, such as bookmarks. Stops once a config reload replaced
+/// the launcher.
+pub fn refresh_periodically(
+    cx: &mut App,
+    launcher: Arc<Launcher>,
+    opts: Arc<Value>,
+    interval: u64,
+    data_handle: Entity<Arc<Vec<RenderableChild>>>,
+) {
+    let interval = Duration::from_secs(interval.max(1));
+    cx.spawn(move |cx: &mut AsyncApp| {
+        let cx = cx.clone();
+        async move {
+            loop {
+                cx.background_executor().timer(interval).await;
+                if !is_loaded(&cx, &data_handle, &launcher) {
+                    break;
+                }
+                let _ = cx.update(|cx| reload_launcher(cx, &launcher, &opts, &data_handle));
+            }
+        }
+    })
+    .detach();
+}
+
+/// Loads the items of the launcher again and puts them in place of its current ones, for
+/// providers that refresh in the background
+pub fn reload_launcher(
+    cx: &mut App,
+    launcher: &Arc<Launcher>,
+    opts: &Arc<Value>,
+    data_handle: &Entity<Arc<Vec<RenderableChild>>>,
+) {
+    let counts = CounterReader::new()
+        .map(|reader| reader.read())
+        .unwrap_or_default();
+    let max_decimals = count_decimals(&counts);
+
+    let Some(children) = launcher.launcher_type.get_render_obj(
+        Arc::clone(launcher),
+        Arc::clone(opts),
+        &counts,
+        max_decimals,
+        cx,
+        data_handle.clone(),
+    ) else {
+        return;
+    };
+    replace_children(cx, launcher, opts, data_handle, children);
+}
+
+/// Puts the children in place of the current tiles of the launcher, for launchers whose tiles
+/// are built once their data was loaded in the background
+pub fn replace_children(
+    cx: &mut App,
+    launcher: &Arc<Launcher>,
+    opts: &Arc<Value>,
+    data_handle: &Entity<Arc<Vec<RenderableChild>>>,
+    mut children: Vec<RenderableChild>,
+) {
+    let (ignore, actions) = match ConfigGuard::read() {
+        Ok(config) => (
+            IgnoreList::read(&config.files.ignore).unwrap_or_default(),
+            ActionTable::from_config(&config).unwrap_or_default(),
+        ),
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    prepare_children(&mut children, launcher, opts, &ignore, &actions);
+
+    data_handle.update(cx, |items_arc, cx| {
+        let items = Arc::make_mut(items_arc);
+        // Replaced in place, so other launchers keep their position
+        let start = items
+            .iter()
+            .position(|item| item.is_from(launcher))
+            .unwrap_or(items.len());
+        items.retain(|item| !item.is_from(launcher));
+        items.splice(start..start, children);
+        cx.notify();
+    });
+    resolve_deferred_icons(cx, data_handle.clone());
+    resolve_favicons(cx, data_handle.clone());
+
+    // Open windows still refer to the old indices
+    for window in cx.windows() {
+        if let Some(view) = window.downcast::<SherlockMainWindow>() {
+            let _ = view.update(cx, |view, _, cx| {
+                view.last_query = None;
+                view.filter_and_sort(cx);
+            });
+        }
+    }
+}
@@ -4,22 +4,28 @@ use serde::{
     de::{MapAccess, Visitor},
 };
 use std::{
-    collections::{BTreeSet, HashMap, HashSet},
+    collections::{HashMap, HashSet},
     fmt::Debug,
     hash::{Hash, Hasher},
     path::{Path, PathBuf},
-    sync::Arc,
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use crate::{
     launcher::{Launcher, LauncherType},
-    loader::resolve_icon_path,
+    loader::{IconThemeGuard, resolve_icon_path},
     sherlock_error,
     utils::{
         cache::BinaryCache,
-        config::HomeType,
+        command_launch::{ProcessEnv, capture_output, discrete_gpu_env, parse_variables},
+        config::{ConfigGuard, HomeType, OtherDefaults, SherlockConfig},
         errors::{SherlockError, SherlockErrorType},
+        files::{env_matches, expand_path, find_executable, home_dir, read_lines},
         paths,
+        user_state::{StateFile, UserState},
+        watch::StopToken,
     },
 };
 
@@ -31,6 +37,12 @@ pub struct ApplicationAction {
     pub method: String,
     #[serde(default = "default_true")]
     pub exit: bool,
+    /// Steps run one after another by the `chain` method
+    #[serde(default)]
+    pub chain: Vec<ChainStep>,
+    /// Question asked before the action runs, such as "Shut down now?"
+    #[serde(default)]
+    pub confirm: Option<String>,
 }
 impl ApplicationAction {
     pub fn new(method: &str) -> Self {
@@ -40,13 +52,156 @@ impl ApplicationAction {
             icon: None,
             method: method.to_string(),
             exit: true,
+            chain: Vec::new(),
+            confirm: None,
         }
     }
     pub fn is_valid(&self) -> bool {
-        self.name.is_some() && self.exec.is_some()
+        self.name.is_some() && (self.exec.is_some() || !self.chain.is_empty())
+    }
+}
+
+/// A step of the `chain` method, a command or a pause in milliseconds, e.g.
+/// `["wl-copy {keyword}", { "sleep": 200 }, "notify-send Copied"]`. Each command waits for the
+/// one before it, a failing command ends the chain.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ChainStep {
+    Exec(String),
+    Sleep { sleep: u64 },
+}
+
+/// Sandboxed package or browser an application has been installed from
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum AppOrigin {
+    /// Flatpak with its application id
+    Flatpak(String),
+    /// Snap with its instance name
+    Snap(String),
+    /// Web app installed by a browser, boxed as most apps are not
+    WebApp(Box<WebApp>),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct WebApp {
+    /// Browser prefix of the desktop file, e.g. `chrome` or `brave`
+    pub browser: String,
+    pub id: String,
+    /// Browser profile, `None` for Firefox PWAs
+    pub profile: Option<String>,
+}
+impl AppOrigin {
+    /// Derives the origin from the location of the desktop file, for entries that lack the
+    /// `X-Flatpak` or `X-SnapInstanceName` keys
+    pub fn from_desktop_file(path: &Path) -> Option<Self> {
+        let stem = path.file_stem()?.to_str()?;
+        let dir = path.parent()?.to_string_lossy();
+        if let Some(web_app) = Self::web_app(stem) {
+            Some(web_app)
+        } else if dir.ends_with("flatpak/exports/share/applications") {
+            Some(Self::Flatpak(stem.to_string()))
+        } else if dir.ends_with("snapd/desktop/applications") {
+            let name = stem.split_once('_').map_or(stem, |(name, _)| name);
+            Some(Self::Snap(name.to_string()))
+        } else {
+            None
+        }
+    }
+    /// Chromium based browsers name the desktop files of web apps
+    /// `<browser>-<app id>-<profile>`, PWAsForFirefox uses `FFPWA-<id>`
+    fn web_app(stem: &str) -> Option<Self> {
+        if let Some(id) = stem.strip_prefix("FFPWA-") {
+            return Some(Self::WebApp(Box::new(WebApp {
+                browser: String::from("firefoxpwa"),
+                id: id.to_string(),
+                profile: None,
+            })));
+        }
+        let (browser, rest) = stem.split_once('-')?;
+        if !matches!(
+            browser,
+            "chrome" | "chromium" | "brave" | "msedge" | "vivaldi"
+        ) {
+            return None;
+        }
+        // App ids consist of 32 letters from a to p
+        let (id, profile) = rest.split_once('-')?;
+        if id.len() != 32 || !id.bytes().all(|b| (b'a'..=b'p').contains(&b)) {
+            return None;
+        }
+        Some(Self::WebApp(Box::new(WebApp {
+            browser: browser.to_string(),
+            id: id.to_string(),
+            profile: Some(profile.replace('_', " ")),
+        })))
+    }
+    /// Largest icon of the web app's manifest, as stored by Chromium based browsers
+    pub fn manifest_icon(&self) -> Option<PathBuf> {
+        let Self::WebApp(web_app) = self else {
+            return None;
+        };
+        let WebApp {
+            browser,
+            id,
+            profile: Some(profile),
+        } = web_app.as_ref()
+        else {
+            return None;
+        };
+        let config = match browser.as_str() {
+            "chrome" => "google-chrome",
+            "chromium" => "chromium",
+            "brave" => "BraveSoftware/Brave-Browser",
+            "msedge" => "microsoft-edge",
+            "vivaldi" => "vivaldi",
+            _ => return None,
+        };
+        let icons = home_dir()
+            .ok()?
+            .join(".config")
+            .join(config)
+            .join(profile)
+            .join("Web Applications/Manifest Resources")
+            .join(id)
+            .join("Icons");
+        std::fs::read_dir(icons)
+            .ok()?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let size: u32 = path.file_stem()?.to_str()?.parse().ok()?;
+                Some((size, path))
+            })
+            .max_by_key(|(size, _)| *size)
+            .map(|(_, path)| path)
     }
-    pub fn is_full(&self) -> bool {
-        self.name.is_some() && self.exec.is_some() && self.icon.is_some()
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Flatpak(_) => "Flatpak",
+            Self::Snap(_) => "Snap",
+            Self::WebApp(_) => "Web App",
+        }
+    }
+    /// Context menu entries offered for the package
+    pub fn actions(&self) -> Vec<ApplicationAction> {
+        let action = |name: &str, exec: String| ApplicationAction {
+            name: Some(SharedString::from(name.to_string())),
+            exec: Some(exec),
+            ..ApplicationAction::new("app_launcher")
+        };
+        match self {
+            Self::Flatpak(id) => vec![
+                action("Run with --devel", format!("flatpak run --devel {id}")),
+                action(
+                    "Show App Info",
+                    format!("{{terminal}} sh -c 'flatpak info {id}; read _'"),
+                ),
+            ],
+            Self::Snap(name) => vec![action(
+                "Show App Info",
+                format!("{{terminal}} sh -c 'snap info {name}; read _'"),
+            )],
+            Self::WebApp(_) => Vec::new(),
+        }
     }
 }
 
@@ -54,11 +209,16 @@ impl ApplicationAction {
 pub struct AppData {
     #[serde(default)]
     pub name: Option<SharedString>,
+    #[serde(default)]
+    pub generic_name: Option<SharedString>,
     pub exec: Option<String>,
     pub search_string: String,
     #[serde(default)]
     pub priority: Option<f32>,
     pub icon: Option<Arc<Path>>,
+    /// Icon that still has to be looked up, see [`AppData::defer_icon`]
+    #[serde(default)]
+    pub icon_name: Option<String>,
     pub desktop_file: Option<PathBuf>,
     #[serde(default)]
     pub actions: Arc<[Arc<ApplicationAction>]>,
@@ -67,6 +227,39 @@ pub struct AppData {
     pub vars: Vec<ExecVariable>,
     #[serde(default)]
     pub terminal: bool,
+    /// Keeps the terminal open after the command exited, so its output can be read
+    #[serde(default)]
+    pub hold: bool,
+    /// Working directory of commands, `~` stands for the home directory
+    #[serde(default, deserialize_with = "deserialize_cwd")]
+    pub cwd: Option<PathBuf>,
+    /// Environment variables set for commands
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Steps of commands whose launcher runs them with the `chain` method
+    #[serde(default)]
+    pub chain: Vec<ChainStep>,
+    /// Question asked before the command runs, such as "Shut down now?"
+    #[serde(default)]
+    pub confirm: Option<String>,
+    /// What the command printed, shown inside the tile of `show_output` launchers
+    #[serde(skip)]
+    pub output: Option<SharedString>,
+    #[serde(default)]
+    pub origin: Option<AppOrigin>,
+    /// Short labels next to the name, such as the stars of a repository
+    #[serde(default)]
+    pub badges: Arc<[SharedString]>,
+    /// `PrefersNonDefaultGPU` of the desktop entry
+    #[serde(default)]
+    pub discrete_gpu: bool,
+    /// `StartupWMClass` of the desktop entry
+    #[serde(default)]
+    pub wm_class: Option<String>,
+    /// Lowercase keywords that move the item up while the query contains them, see
+    /// [`AppData::add_boosts`]
+    #[serde(skip)]
+    pub boosts: Arc<[(String, f32)]>,
 }
 impl Eq for AppData {}
 impl Hash for AppData {
@@ -80,16 +273,105 @@ impl AppData {
     pub fn new() -> Self {
         Self {
             name: None,
+            generic_name: None,
             exec: None,
             search_string: String::new(),
             priority: None,
             icon: None,
+            icon_name: None,
             desktop_file: None,
             actions: Arc::new([]),
             vars: vec![],
             terminal: false,
+            hold: false,
+            cwd: None,
+            env: HashMap::new(),
+            chain: Vec::new(),
+            confirm: None,
+            output: None,
+            origin: None,
+            badges: Arc::new([]),
+            discrete_gpu: false,
+            wm_class: None,
+            boosts: Arc::new([]),
+        }
+    }
+    /// Adds keyword boosts such as `{ "mail": 2 }`, which lower the priority of the item by two
+    /// while the query contains "mail"
+    pub fn add_boosts<'a>(&mut self, boosts: impl IntoIterator<Item = (&'a String, &'a f32)>) {
+        self.boosts = self
+            .boosts
+            .iter()
+            .cloned()
+            .chain(
+                boosts
+                    .into_iter()
+                    .map(|(keyword, boost)| (keyword.to_lowercase(), *boost)),
+            )
+            .collect();
+    }
+    /// Key of the launch counts, the kind of launcher along with the desktop file of apps or the
+    /// command, url or name of other items, e.g. `app:firefox` or `bookmark:https://github.com`
+    pub fn count_key(&self, launcher: &Launcher) -> String {
+        let id = self
+            .desktop_file
+            .as_deref()
+            .and_then(|file| file.file_stem()?.to_str())
+            .or(self.exec.as_deref())
+            .or(self.name.as_ref().map(|name| name.as_str()))
+            .or(launcher.name.as_deref())
+            .unwrap_or_default();
+        format!("{}:{id}", launcher.launcher_type.kind())
+    }
+    /// Whether launching the item counts towards its ranking, see [`ConfigBehavior::uncounted`]
+    ///
+    /// [`ConfigBehavior::uncounted`]: crate::utils::config::ConfigBehavior::uncounted
+    pub fn is_counted(&self, launcher: &Launcher, uncounted: &IgnoreList) -> bool {
+        !launcher.uncounted
+            && !uncounted.is_ignored(
+                launcher.launcher_type.kind(),
+                self.name.as_ref().map(|name| name.as_str()),
+                self.exec.as_deref(),
+            )
+    }
+    /// How often the item was launched. Counts that were recorded by command alone, before they
+    /// were kept apart by launcher, still apply until they decay.
+    pub fn count(&self, launcher: &Launcher, counts: &HashMap<String, f32>) -> f32 {
+        counts
+            .get(&self.count_key(launcher))
+            .or_else(|| counts.get(self.exec.as_deref()?))
+            .copied()
+            .unwrap_or(0.0)
+    }
+    /// Working directory and environment of the command
+    pub fn process_env(&self) -> ProcessEnv<'_> {
+        ProcessEnv {
+            cwd: self.cwd.as_deref(),
+            vars: Some(&self.env).filter(|vars| !vars.is_empty()),
         }
     }
+    /// Runs the command and waits for what it prints, see [`capture_output`]
+    pub fn capture_output(
+        &self,
+        keyword: &str,
+        variables: &[(SharedString, SharedString)],
+        stop: &StopToken,
+    ) -> Result<String, SherlockError> {
+        let exec = self.exec.as_deref().unwrap_or_default();
+        let cmd = {
+            let config = ConfigGuard::read()?;
+            parse_variables(exec, keyword, variables, &config)
+        };
+        capture_output(&cmd, &self.process_env(), stop)
+    }
+    /// Sum of the boosts whose keyword is part of the lowercase query
+    pub fn boost(&self, query: &str) -> f32 {
+        self.boosts
+            .iter()
+            .filter(|(keyword, _)| query.contains(keyword.as_str()))
+            .map(|(_, boost)| boost)
+            .sum()
+    }
     pub fn apply_alias(
         &mut self,
         launcher: &Arc<Launcher>,
@@ -103,7 +385,7 @@ impl AppData {
             }
 
             if let Some(alias_icon) = alias.icon.as_ref().map(|i| resolve_icon_path(i)) {
-                self.icon = alias_icon;
+                self.set_icon(alias_icon);
             }
 
             let name: Option<&str> = self
@@ -147,6 +429,14 @@ impl AppData {
             if let Some(variables) = alias.variables {
                 self.vars.extend(variables);
             }
+
+            if let Some(hold) = alias.hold {
+                self.hold = hold;
+            }
+
+            if let Some(boosts) = alias.boosts {
+                self.add_boosts(&boosts);
+            }
         } else {
             let name: Option<&str> = self
                 .name
@@ -156,18 +446,81 @@ impl AppData {
             self.search_string = construct_search(name, &self.search_string, use_keywords);
         }
     }
-    pub fn get_exec(&self, launcher: &Arc<Launcher>) -> Option<String> {
-        match &launcher.launcher_type {
-            LauncherType::Web(web) => Some(format!("websearch-{}", web.engine)),
-
-            LauncherType::App(_) | LauncherType::Command(_) | LauncherType::Category(_) => {
-                self.exec.clone()
+    /// Uses the icon right away if it has been resolved before. Otherwise the lookup is deferred
+    /// to [`resolve_icon`](Self::resolve_icon) so that loading never blocks on disk lookups or svg
+    /// rendering.
+    pub fn defer_icon(&mut self, name: &str) {
+        match IconThemeGuard::lookup_icon(name) {
+            Ok(Some(icon)) => self.set_icon(icon),
+            _ => {
+                self.icon = None;
+                self.icon_name = Some(name.to_string());
             }
-
-            // None-Home Launchers
-            LauncherType::Calc(_) => None,
-            LauncherType::Event(_) => None,
-            _ => None,
+        }
+    }
+    /// Resolves a deferred icon
+    pub fn resolve_icon(&mut self) {
+        if let Some(name) = self.icon_name.take() {
+            self.set_icon(resolve_icon_path(&name));
+        }
+    }
+    /// Offers to launch an app that prefers the discrete GPU on the default one instead
+    pub fn add_integrated_gpu_action(&mut self) {
+        let Some(exec) = self.exec.as_deref() else {
+            return;
+        };
+        let terminal = if self.terminal { "{terminal} " } else { "" };
+        let action = ApplicationAction {
+            name: Some(SharedString::from("Launch on Integrated GPU")),
+            exec: Some(format!("{terminal}env DRI_PRIME=0 {exec}")),
+            icon: self.icon.clone(),
+            ..ApplicationAction::new("app_launcher")
+        };
+        self.actions = self
+            .actions
+            .iter()
+            .cloned()
+            .chain(std::iter::once(Arc::new(action)))
+            .collect();
+    }
+    /// Identifies the app's windows, the `StartupWMClass` or else the desktop file id
+    pub fn window_class(&self) -> Option<&str> {
+        self.wm_class
+            .as_deref()
+            .or_else(|| self.desktop_file.as_deref()?.file_stem()?.to_str())
+    }
+    /// Starts another instance of an app instead of switching to its open window
+    pub fn new_instance_action(&self) -> Option<ApplicationAction> {
+        let exec = self.exec.as_deref()?;
+        let terminal = if self.terminal { "{terminal} " } else { "" };
+        let env = if self.discrete_gpu {
+            format!("env {} ", discrete_gpu_env())
+        } else {
+            String::new()
+        };
+        Some(ApplicationAction {
+            name: Some(SharedString::from("Launch New Instance")),
+            exec: Some(format!("{terminal}{env}{exec}")),
+            icon: self.icon.clone(),
+            ..ApplicationAction::new("app_launcher")
+        })
+    }
+    /// Sets the icon and hands it down to all actions without an icon of their own
+    pub fn set_icon(&mut self, icon: Option<Arc<Path>>) {
+        self.icon_name = None;
+        self.icon = icon;
+        if self.icon.is_some() && self.actions.iter().any(|a| a.icon.is_none()) {
+            self.actions = self
+                .actions
+                .iter()
+                .map(|action| match action.icon {
+                    Some(_) => Arc::clone(action),
+                    None => Arc::new(ApplicationAction {
+                        icon: self.icon.clone(),
+                        ..(**action).clone()
+                    }),
+                })
+                .collect();
         }
     }
 }
@@ -181,6 +534,102 @@ pub struct SherlockAlias {
     pub actions: Option<Vec<ApplicationAction>>,
     pub add_actions: Option<Vec<ApplicationAction>>,
     pub variables: Option<Vec<ExecVariable>>,
+    /// Keywords of the query that move the app up, by how much
+    pub boosts: Option<HashMap<String, f32>>,
+    /// Keeps the terminal of terminal apps open after they exited
+    pub hold: Option<bool>,
+}
+
+/// How a key of sherlock_alias.json picks its apps. Keys containing `*`, `?` or `[` are globs,
+/// keys wrapped in slashes like `/^code(-oss)?$/` are regular expressions. Keys are matched
+/// against the `Name=` of an app as well as the name of its desktop file.
+#[derive(Clone, Debug)]
+pub enum AliasKey {
+    Exact(String),
+    Glob(glob::Pattern),
+    Regex(regex::Regex),
+}
+impl AliasKey {
+    pub fn parse(key: &str) -> Result<Self, String> {
+        if let Some(pattern) = key
+            .strip_prefix('/')
+            .and_then(|key| key.strip_suffix('/'))
+            .filter(|pattern| !pattern.is_empty())
+        {
+            return regex::Regex::new(pattern)
+                .map(Self::Regex)
+                .map_err(|e| format!(r#"invalid regex "{key}": {e}"#));
+        }
+        if key.contains(['*', '?', '[']) {
+            return glob::Pattern::new(key)
+                .map(Self::Glob)
+                .map_err(|e| format!(r#"invalid glob "{key}": {e}"#));
+        }
+        Ok(Self::Exact(key.to_string()))
+    }
+    fn matches(&self, candidate: &str) -> bool {
+        const CASE_INSENSITIVE: glob::MatchOptions = glob::MatchOptions {
+            case_sensitive: false,
+            require_literal_separator: false,
+            require_literal_leading_dot: false,
+        };
+        match self {
+            Self::Exact(key) => key == candidate,
+            Self::Glob(pattern) => pattern.matches_with(candidate, CASE_INSENSITIVE),
+            Self::Regex(regex) => regex.is_match(candidate),
+        }
+    }
+}
+
+/// The entries of sherlock_alias.json, in the order of the file
+#[derive(Clone, Debug, Default)]
+pub struct AliasTable {
+    entries: Vec<(AliasKey, SherlockAlias)>,
+}
+impl AliasTable {
+    /// The alias of an app. Exact keys take precedence over patterns, of which the first one that
+    /// matches applies.
+    pub fn find(&self, name: &str, desktop_file: Option<&Path>) -> Option<&SherlockAlias> {
+        let file_name = desktop_file.and_then(|file| file.file_name()?.to_str());
+        let file_stem = desktop_file.and_then(|file| file.file_stem()?.to_str());
+        let candidates: Vec<&str> = [Some(name), file_stem, file_name]
+            .into_iter()
+            .flatten()
+            .collect();
+        let mut matching = self
+            .entries
+            .iter()
+            .filter(|(key, _)| candidates.iter().any(|c| key.matches(c)));
+        matching
+            .clone()
+            .find(|(key, _)| matches!(key, AliasKey::Exact(_)))
+            .or_else(|| matching.next())
+            .map(|(_, alias)| alias)
+    }
+}
+impl<'de> Deserialize<'de> for AliasTable {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AliasTableVisitor;
+        impl<'de> Visitor<'de> for AliasTableVisitor {
+            type Value = AliasTable;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of aliases keyed by app name or pattern")
+            }
+            fn visit_map<M>(self, mut map: M) -> Result<AliasTable, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                let mut entries = Vec::new();
+                while let Some((key, alias)) = map.next_entry::<String, SherlockAlias>()? {
+                    let key = AliasKey::parse(&key).map_err(serde::de::Error::custom)?;
+                    entries.push((key, alias));
+                }
+                Ok(AliasTable { entries })
+            }
+        }
+        deserializer.deserialize_map(AliasTableVisitor)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -188,21 +637,76 @@ pub struct SherlockAlias {
 pub enum ExecVariable {
     StringInput(SharedString),
     PasswordInput(SharedString),
+    #[serde(rename = "suggested_input")]
+    Suggested(SuggestedInput),
 }
 impl ExecVariable {
     pub fn placeholder(&self) -> SharedString {
         match self {
             Self::StringInput(s) => s.clone(),
             Self::PasswordInput(s) => s.clone(),
+            Self::Suggested(input) => input.placeholder.clone(),
+        }
+    }
+    /// Value the input is filled with at first
+    pub fn default_value(&self) -> Option<&str> {
+        match self {
+            Self::Suggested(input) => input.default.as_deref(),
+            _ => None,
         }
     }
 }
 
+/// A text input that comes pre-filled and offers values for Tab to cycle through
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SuggestedInput {
+    pub placeholder: SharedString,
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub suggestions: Vec<String>,
+    /// Command whose output lines are offered after the `suggestions`
+    #[serde(default)]
+    pub command: Option<String>,
+}
+impl SuggestedInput {
+    /// The `suggestions` followed by the output of the `command`, without duplicates
+    pub fn load_suggestions(&self) -> Vec<SharedString> {
+        let output = self
+            .command
+            .as_deref()
+            .map(|cmd| capture_output(cmd, &ProcessEnv::default(), &StopToken::default()))
+            .transpose()
+            .unwrap_or_else(|e| {
+                eprintln!("{e}");
+                None
+            })
+            .unwrap_or_default();
+        let mut suggestions: Vec<SharedString> = Vec::new();
+        for suggestion in self
+            .suggestions
+            .iter()
+            .map(String::as_str)
+            .chain(output.lines())
+        {
+            let suggestion = suggestion.trim();
+            if !suggestion.is_empty() && !suggestions.iter().any(|s| s == suggestion) {
+                suggestions.push(suggestion.to_string().into());
+            }
+        }
+        suggestions
+    }
+}
+
 fn default_true() -> bool {
     true
 }
 
-#[derive(Deserialize, Debug, Serialize)]
+fn default_weight() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct RawLauncher {
     pub name: Option<String>,
     pub alias: Option<String>,
@@ -213,6 +717,8 @@ pub struct RawLauncher {
     pub next_content: Option<String>,
     pub r#type: String,
     pub priority: f32,
+    #[serde(default = "default_weight")]
+    pub weight: f32,
 
     #[serde(default = "default_true")]
     pub exit: bool,
@@ -222,6 +728,12 @@ pub struct RawLauncher {
     pub spawn_focus: bool,
     #[serde(default)]
     pub r#async: bool,
+    /// Keeps the terminal of the launcher's commands open after they exited
+    #[serde(default)]
+    pub hold: bool,
+    /// Launches of the launcher's items are not counted, so they keep their place in the ranking
+    #[serde(default)]
+    pub uncounted: bool,
     #[serde(default)]
     pub home: HomeType,
     #[serde(default)]
@@ -232,48 +744,506 @@ pub struct RawLauncher {
     pub add_actions: Option<Vec<ApplicationAction>>,
     #[serde(default)]
     pub variables: Option<Vec<ExecVariable>>,
+    /// Seconds between background reloads of the launcher's items
+    #[serde(default)]
+    pub refresh_interval: Option<u64>,
+    /// Skips the launcher unless the condition holds on this machine
+    #[serde(default)]
+    pub only_if: Option<LauncherCondition>,
+}
+
+/// Condition a launcher is loaded under. Every given check has to pass, e.g.
+/// `{ "binary": "docker" }` or `{ "env": "XDG_CURRENT_DESKTOP=Hyprland" }`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct LauncherCondition {
+    /// Program that has to be in `$PATH`
+    #[serde(default)]
+    pub binary: Option<String>,
+    /// `NAME` has to be set and not empty, `NAME=value` has to match
+    #[serde(default)]
+    pub env: Option<String>,
+    /// Shell predicate that has to exit successfully
+    #[serde(default)]
+    pub command: Option<String>,
+}
+impl LauncherCondition {
+    pub fn is_met(&self) -> bool {
+        let binary = || {
+            self.binary
+                .as_deref()
+                .is_none_or(|b| find_executable(b).is_some())
+        };
+        let env = || self.env.as_deref().is_none_or(env_matches);
+        let command = || {
+            self.command.as_deref().is_none_or(|command| {
+                Command::new("sh")
+                    .args(["-c", command])
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .is_ok_and(|status| status.success())
+            })
+        };
+        // The shell predicate is the most expensive check, so it runs last
+        binary() && env() && command()
+    }
 }
 
+/// Decay is applied at most once a day, so the file is not rewritten on every start
+const DECAY_INTERVAL: u64 = 24 * 60 * 60;
+/// Counts below this are dropped, the apps have not been used in a long time
+const MIN_COUNT: f32 = 0.01;
+
+/// Launch counts by [`AppData::count_key`], along with the time they were last decayed at
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct UsageCounts {
+    /// Seconds since the epoch
+    decayed_at: u64,
+    counts: HashMap<String, f32>,
+}
+impl UsageCounts {
+    /// Decays the counts by the time passed since the last decay. Returns whether they changed.
+    fn decay(&mut self, now: u64, half_life_days: f32) -> bool {
+        if self.decayed_at == 0 {
+            self.decayed_at = now;
+            return true;
+        }
+        let elapsed = now.saturating_sub(self.decayed_at);
+        if half_life_days <= 0.0 || elapsed < DECAY_INTERVAL {
+            return false;
+        }
+        let factor = 0.5f32.powf(elapsed as f32 / (half_life_days * 86_400.0));
+        for count in self.counts.values_mut() {
+            *count *= factor;
+        }
+        self.counts.retain(|_, count| *count >= MIN_COUNT);
+        self.decayed_at = now;
+        true
+    }
+}
+
+impl UserState for UsageCounts {
+    const VERSION: u32 = 1;
+    fn migrate(version: u32, _data: serde_json::Value) -> Result<Self, String> {
+        Err(format!("unknown version {version}"))
+    }
+}
+
+/// Held while the counts are read and written, so launches in quick succession do not overwrite
+/// each other's count
+static USAGE_LOCK: Mutex<()> = Mutex::new(());
+
 pub struct CounterReader {
     pub path: PathBuf,
 }
 impl CounterReader {
     pub fn new() -> Result<Self, SherlockError> {
-        let data_dir = paths::get_data_dir()?;
-        let path = data_dir.join("counts.bin");
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| {
+        let path = paths::get_state_file("usage.json")?;
+        Ok(CounterReader { path })
+    }
+    /// Reads the launch counts, decaying them first if a day has passed since the last time.
+    /// Counts that cannot be read are logged and left alone, the apps are ordered without them.
+    pub fn read(&self) -> HashMap<String, f32> {
+        let _guard = USAGE_LOCK.lock();
+        match self.load() {
+            Ok((usage, decayed)) => {
+                if decayed && let Err(e) = StateFile::write(&self.path, &usage) {
+                    eprintln!("{e}");
+                }
+                usage.counts
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                HashMap::new()
+            }
+        }
+    }
+    /// Counts a launch, writing the decayed counts along with it
+    pub fn increment(&self, key: &str) -> Result<(), SherlockError> {
+        let _guard = USAGE_LOCK.lock();
+        let (mut usage, _) = self.load()?;
+        *usage.counts.entry(key.to_string()).or_insert(0.0) += 1.0;
+        StateFile::write(&self.path, &usage)
+    }
+    /// Reads the counts and decays them. Returns whether the decay changed them.
+    fn load(&self) -> Result<(UsageCounts, bool), SherlockError> {
+        let mut usage = match StateFile::read(&self.path)? {
+            Some(usage) => usage,
+            None => self.migrate()?,
+        };
+        let half_life = ConfigGuard::read()
+            .map(|config| config.behavior.usage_half_life)
+            .unwrap_or_else(|_| OtherDefaults::usage_half_life());
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let decayed = usage.decay(now, half_life);
+        Ok((usage, decayed))
+    }
+    /// Older releases kept the counts in caches: `usage.bin`, and whole counts in `counts.bin`
+    /// before that. They are taken over once, whatever schema version they were written with,
+    /// and `counts.bin` even from before caches had a header.
+    fn migrate(&self) -> Result<UsageCounts, SherlockError> {
+        let usage = paths::get_state_file("usage.bin")?;
+        let counts = paths::get_state_file("counts.bin")?;
+        let migrated = BinaryCache::read_unchecked(&usage).unwrap_or_else(|| {
+            let counts: HashMap<String, u32> = BinaryCache::read_unchecked(&counts)
+                .or_else(|| BinaryCache::read_legacy(&counts))
+                .unwrap_or_default();
+            UsageCounts {
+                decayed_at: 0,
+                counts: counts
+                    .into_iter()
+                    .map(|(exec, count)| (exec, count as f32))
+                    .collect(),
+            }
+        });
+        // The caches are only removed once the counts are safe
+        StateFile::write(&self.path, &migrated)?;
+        let _ = std::fs::remove_file(&usage);
+        let _ = std::fs::remove_file(&counts);
+        Ok(migrated)
+    }
+}
+
+/// An entry of sherlock_actions.json, a context action along with the items it is offered for:
+/// `{ "name": "Private Window", "exec": "{exec} --private-window", "for_name": "firefox*" }`.
+/// All given conditions have to hold, an entry without any applies to every item. `{exec}` and
+/// `{name}` in the command stand for the command and the name of the item.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DeclaredAction {
+    pub name: String,
+    pub exec: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default = "default_true")]
+    pub exit: bool,
+    #[serde(default)]
+    pub confirm: Option<String>,
+    /// Name of the items, a glob or regex like the keys of sherlock_alias.json
+    #[serde(default)]
+    pub for_name: Option<String>,
+    /// Command of the items, a glob or regex like the keys of sherlock_alias.json
+    #[serde(default)]
+    pub for_exec: Option<String>,
+    /// Kind of launcher the items come from, see [`LauncherType::KINDS`]
+    #[serde(default)]
+    pub for_launcher: Option<String>,
+}
+
+/// The entries of sherlock_actions.json, ready to be matched against items
+#[derive(Clone, Debug, Default)]
+pub struct ActionTable {
+    entries: Vec<(DeclaredAction, Option<AliasKey>, Option<AliasKey>)>,
+    elevation: Option<Elevation>,
+}
+/// Offers to run the commands of the items in `behavior.run_as_root` through a helper like
+/// `pkexec`
+#[derive(Clone, Debug)]
+struct Elevation {
+    helper: String,
+    allow: Vec<AliasKey>,
+}
+impl ActionTable {
+    /// Reads the actions file and adds "Run as root" for the items the config allows it for
+    pub fn from_config(config: &SherlockConfig) -> Result<Self, SherlockError> {
+        Self::read(&config.files.actions)?
+            .with_elevation(&config.default_apps.elevation, &config.behavior.run_as_root)
+            .map_err(|e| {
                 sherlock_error!(
-                    SherlockErrorType::DirCreateError(parent.to_string_lossy().to_string()),
-                    e.to_string()
+                    SherlockErrorType::ConfigError(Some(String::from("behavior.run_as_root"))),
+                    e
                 )
-            })?;
+            })
+    }
+    /// Offers "Run as root" for the items whose name or command matches one of the patterns,
+    /// globs or regexes like the keys of sherlock_alias.json
+    pub fn with_elevation(mut self, helper: &str, allow: &[String]) -> Result<Self, String> {
+        if helper.trim().is_empty() || allow.is_empty() {
+            return Ok(self);
         }
-        Ok(CounterReader { path })
+        let allow = allow
+            .iter()
+            .map(|pattern| AliasKey::parse(pattern))
+            .collect::<Result<_, _>>()?;
+        self.elevation = Some(Elevation {
+            helper: helper.trim().to_string(),
+            allow,
+        });
+        Ok(self)
     }
-    pub fn increment(&self, key: &str) -> Result<(), SherlockError> {
-        let mut content: HashMap<String, u32> = BinaryCache::read(&self.path)?;
-        let unique_values: HashMap<u32, u32> = content
-            .values()
-            .copied()
-            .collect::<BTreeSet<_>>()
+    /// "Run as root" for apps and commands of the allowlist
+    pub fn root_action(
+        &self,
+        kind: &str,
+        name: Option<&str>,
+        exec: Option<&str>,
+        terminal: bool,
+    ) -> Option<ApplicationAction> {
+        let elevation = self.elevation.as_ref()?;
+        let exec = exec.filter(|_| matches!(kind, "app" | "command"))?;
+        let allowed = elevation.allow.iter().any(|key| {
+            [name, Some(exec)]
+                .into_iter()
+                .flatten()
+                .any(|v| key.matches(v))
+        });
+        if !allowed {
+            return None;
+        }
+        let terminal = if terminal { "{terminal} " } else { "" };
+        Some(ApplicationAction {
+            name: Some(SharedString::from("Run as root")),
+            exec: Some(format!("{terminal}{} {exec}", elevation.helper)),
+            ..ApplicationAction::new("command")
+        })
+    }
+    /// Reads the actions file, a missing file declares no actions
+    pub fn read(path: &Path) -> Result<Self, SherlockError> {
+        let parse_error =
+            |e: String| sherlock_error!(SherlockErrorType::FileParseError(path.to_path_buf()), e);
+        let declared: Vec<DeclaredAction> = match std::fs::File::open(path) {
+            Ok(f) => simd_json::from_reader(f).map_err(|e| parse_error(e.to_string()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(sherlock_error!(
+                    SherlockErrorType::FileReadError(path.to_path_buf()),
+                    e.to_string()
+                ));
+            }
+        };
+        Self::new(declared).map_err(parse_error)
+    }
+    pub fn new(declared: Vec<DeclaredAction>) -> Result<Self, String> {
+        let key = |pattern: &Option<String>| pattern.as_deref().map(AliasKey::parse).transpose();
+        let entries = declared
+            .into_iter()
+            .map(|action| {
+                let name = key(&action.for_name)?;
+                let exec = key(&action.for_exec)?;
+                Ok((action, name, exec))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            entries,
+            elevation: None,
+        })
+    }
+    /// The actions declared for an item
+    pub fn actions_for(
+        &self,
+        kind: &str,
+        name: Option<&str>,
+        exec: Option<&str>,
+    ) -> Vec<ApplicationAction> {
+        let matches = |key: &Option<AliasKey>, value: Option<&str>| {
+            key.as_ref()
+                .is_none_or(|key| value.is_some_and(|value| key.matches(value)))
+        };
+        self.entries
+            .iter()
+            .filter(|(action, name_key, exec_key)| {
+                action.for_launcher.as_deref().is_none_or(|k| k == kind)
+                    && matches(name_key, name)
+                    && matches(exec_key, exec)
+            })
+            .map(|(action, _, _)| ApplicationAction {
+                name: Some(SharedString::from(action.name.clone())),
+                exec: Some(
+                    action
+                        .exec
+                        .replace("{exec}", exec.unwrap_or_default())
+                        .replace("{name}", name.unwrap_or_default()),
+                ),
+                // Resolved icons are buffered, so each one is looked up once
+                icon: action.icon.as_deref().and_then(resolve_icon_path),
+                exit: action.exit,
+                confirm: action.confirm.clone(),
+                ..ApplicationAction::new("command")
+            })
+            .collect()
+    }
+}
+
+/// Patterns of the sherlockignore file. A pattern hides every child whose name or command
+/// matches it, the command of a bookmark being its url. Patterns that start with the kind of a
+/// launcher, like `bookmark:*.internal.corp/*`, only apply to that kind of launcher.
+#[derive(Clone, Debug, Default)]
+pub struct IgnoreList {
+    patterns: Vec<(Option<&'static str>, glob::Pattern)>,
+}
+impl IgnoreList {
+    /// Reads the ignore file, a missing file ignores nothing
+    pub fn read(path: &Path) -> Result<Self, SherlockError> {
+        let lines = match read_lines(path) {
+            Ok(lines) => lines,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(sherlock_error!(
+                    SherlockErrorType::FileReadError(path.to_path_buf()),
+                    e.to_string()
+                ));
+            }
+        };
+        Ok(Self::from_lines(lines.map_while(Result::ok)))
+    }
+    /// Builds the list from lines in the format of the ignore file, invalid patterns are skipped
+    pub fn from_lines<S: AsRef<str>>(lines: impl IntoIterator<Item = S>) -> Self {
+        let patterns = lines
+            .into_iter()
+            .filter_map(|line| Self::parse_line(line.as_ref())?.ok())
+            .collect();
+        Self { patterns }
+    }
+    /// Parses a line into the kind it is restricted to and its pattern, `None` for blank lines
+    /// and comments
+    pub fn parse_line(
+        line: &str,
+    ) -> Option<Result<(Option<&'static str>, glob::Pattern), glob::PatternError>> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        // Urls contain colons as well, so only known kinds count as a prefix
+        let (kind, pattern) = line
+            .split_once(':')
+            .and_then(|(kind, pattern)| {
+                let kind = LauncherType::KINDS.iter().find(|k| **k == kind)?;
+                Some((Some(*kind), pattern))
+            })
+            .unwrap_or((None, line));
+        Some(glob::Pattern::new(&pattern.to_lowercase()).map(|pattern| (kind, pattern)))
+    }
+    pub fn is_ignored(&self, kind: &str, name: Option<&str>, exec: Option<&str>) -> bool {
+        if self.patterns.is_empty() {
+            return false;
+        }
+        let candidates: Vec<String> = [name, exec]
             .into_iter()
-            .enumerate()
-            .map(|(i, v)| (v, (i + 1) as u32))
+            .flatten()
+            .map(str::to_lowercase)
             .collect();
+        self.patterns
+            .iter()
+            .filter(|(only, _)| only.is_none_or(|only| only == kind))
+            .any(|(_, pattern)| candidates.iter().any(|c| pattern.matches(c)))
+    }
+}
+
+/// Keys of the pinned items
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(transparent)]
+struct Pins(Vec<String>);
+impl UserState for Pins {
+    const VERSION: u32 = 1;
+    fn migrate(version: u32, _data: serde_json::Value) -> Result<Self, String> {
+        Err(format!("unknown version {version}"))
+    }
+}
 
-        content.iter_mut().for_each(|(_, v)| {
-            if let Some(new) = unique_values.get(v) {
-                *v = new.clone();
+/// Items pinned to the top of the home view through their context menu, in the order they were
+/// pinned
+pub struct PinReader {
+    pub path: PathBuf,
+}
+impl PinReader {
+    pub fn new() -> Result<Self, SherlockError> {
+        let path = paths::get_state_file("pins.json")?;
+        Ok(PinReader { path })
+    }
+    /// Pins that cannot be read are logged and left alone, the home view is shown without them
+    pub fn read(&self) -> Vec<String> {
+        self.load().map(|pins| pins.0).unwrap_or_default()
+    }
+    /// Pins the item, or unpins it if it is pinned already. Returns the new pins.
+    pub fn toggle(&self, key: &str) -> Result<Vec<String>, SherlockError> {
+        let mut pins = self.load()?;
+        match pins.0.iter().position(|pin| pin == key) {
+            Some(i) => {
+                pins.0.remove(i);
             }
-        });
+            None => pins.0.push(key.to_string()),
+        }
+        StateFile::write(&self.path, &pins)?;
+        Ok(pins.0)
+    }
+    fn load(&self) -> Result<Pins, SherlockError> {
+        if let Some(pins) = StateFile::read(&self.path)? {
+            return Ok(pins);
+        }
+        // Older releases kept the pins in the cache `pins.bin`, which is taken over once
+        let legacy = self.path.with_extension("bin");
+        let Some(pins) = BinaryCache::read_unchecked(&legacy).map(Pins) else {
+            return Ok(Pins::default());
+        };
+        StateFile::write(&self.path, &pins)?;
+        let _ = std::fs::remove_file(&legacy);
+        Ok(pins)
+    }
+}
+
+/// Queries of the history, the most recent last
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(transparent)]
+struct History(Vec<String>);
+impl UserState for History {
+    const VERSION: u32 = 1;
+    fn migrate(version: u32, _data: serde_json::Value) -> Result<Self, String> {
+        Err(format!("unknown version {version}"))
+    }
+}
 
-        *content.entry(key.to_string()).or_insert(0) += 1;
-        BinaryCache::write(&self.path, &content)?;
-        Ok(())
+/// Queries that ran an item, the most recent last
+pub struct HistoryReader {
+    pub path: PathBuf,
+}
+impl HistoryReader {
+    pub fn new() -> Result<Self, SherlockError> {
+        let path = paths::get_state_file("history.json")?;
+        Ok(HistoryReader { path })
+    }
+    /// A history that cannot be read is logged and left alone, recalling queries starts empty
+    pub fn read(&self) -> Vec<String> {
+        self.load().map(|history| history.0).unwrap_or_default()
+    }
+    /// Moves the query to the end of the history, keeping the last `limit` queries. Returns the
+    /// new history.
+    pub fn push(&self, query: &str, limit: usize) -> Result<Vec<String>, SherlockError> {
+        let mut history = self.load()?;
+        history.0.retain(|entry| entry != query);
+        history.0.push(query.to_string());
+        let excess = history.0.len().saturating_sub(limit);
+        history.0.drain(..excess);
+        StateFile::write(&self.path, &history)?;
+        Ok(history.0)
+    }
+    fn load(&self) -> Result<History, SherlockError> {
+        if let Some(history) = StateFile::read(&self.path)? {
+            return Ok(history);
+        }
+        // Older releases kept the history in the cache `history.bin`, which is taken over once
+        let legacy = self.path.with_extension("bin");
+        let Some(history) = BinaryCache::read_unchecked(&legacy).map(History) else {
+            return Ok(History::default());
+        };
+        StateFile::write(&self.path, &history)?;
+        let _ = std::fs::remove_file(&legacy);
+        Ok(history)
     }
 }
 
+fn deserialize_cwd<'de, D>(deserializer: D) -> Result<Option<PathBuf>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let cwd = Option::<PathBuf>::deserialize(deserializer)?;
+    Ok(match (cwd, home_dir()) {
+        (Some(cwd), Ok(home)) => Some(expand_path(cwd, &home)),
+        (cwd, _) => cwd,
+    })
+}
+
 pub fn deserialize_named_appdata<'de, D>(deserializer: D) -> Result<HashSet<AppData>, D::Error>
 where
     D: Deserializer<'de>,
@@ -315,3 +1285,316 @@ pub fn construct_search(name: Option<&str>, search_str: &str, use_keywords: bool
     s.make_ascii_lowercase();
     s
 }
+
+#[test]
+fn test_launcher_condition() {
+    let condition = |json: serde_json::Value| {
+        serde_json::from_value::<LauncherCondition>(json)
+            .unwrap()
+            .is_met()
+    };
+    assert!(condition(serde_json::json!({})));
+    assert!(condition(serde_json::json!({ "binary": "sh" })));
+    assert!(!condition(
+        serde_json::json!({ "binary": "sherlock-missing-binary" })
+    ));
+    assert!(condition(serde_json::json!({ "env": "PATH" })));
+    assert!(!condition(
+        serde_json::json!({ "env": "PATH=sherlock-missing-value" })
+    ));
+    assert!(condition(serde_json::json!({ "command": "true" })));
+    assert!(!condition(
+        serde_json::json!({ "binary": "sh", "command": "false" })
+    ));
+}
+
+#[test]
+fn test_usage_decay() {
+    const DAY: u64 = 86_400;
+    let mut usage = UsageCounts {
+        decayed_at: 0,
+        counts: HashMap::from([("firefox".to_string(), 8.0), ("gimp".to_string(), 0.015)]),
+    };
+    // The clock starts with the first read
+    assert!(usage.decay(100 * DAY, 30.0));
+    assert_eq!(usage.counts["firefox"], 8.0);
+    assert!(!usage.decay(100 * DAY + 3600, 30.0));
+
+    assert!(usage.decay(130 * DAY, 30.0));
+    assert!((usage.counts["firefox"] - 4.0).abs() < 1e-4);
+    assert!(!usage.counts.contains_key("gimp"));
+
+    assert!(!usage.decay(200 * DAY, 0.0));
+    assert!((usage.counts["firefox"] - 4.0).abs() < 1e-4);
+}
+
+#[test]
+fn test_pin_toggle() {
+    let path = std::env::temp_dir().join(format!("sherlock-{}-pins.json", std::process::id()));
+    let reader = PinReader { path };
+    assert_eq!(reader.toggle("kitty").unwrap(), vec!["kitty"]);
+    assert_eq!(reader.toggle("firefox").unwrap(), vec!["kitty", "firefox"]);
+    assert_eq!(reader.toggle("kitty").unwrap(), vec!["firefox"]);
+    assert_eq!(reader.read(), vec!["firefox"]);
+    let _ = std::fs::remove_file(&reader.path);
+}
+
+#[test]
+fn test_history_push() {
+    let path = std::env::temp_dir().join(format!("sherlock-{}-history.json", std::process::id()));
+    let reader = HistoryReader { path };
+    assert_eq!(
+        reader.push("5 usd to eur", 2).unwrap(),
+        vec!["5 usd to eur"]
+    );
+    assert_eq!(
+        reader.push("firefox", 2).unwrap(),
+        vec!["5 usd to eur", "firefox"]
+    );
+    assert_eq!(
+        reader.push("5 usd to eur", 2).unwrap(),
+        vec!["firefox", "5 usd to eur"]
+    );
+    assert_eq!(
+        reader.push("kitty", 2).unwrap(),
+        vec!["5 usd to eur", "kitty"]
+    );
+    assert_eq!(reader.read(), vec!["5 usd to eur", "kitty"]);
+    let _ = std::fs::remove_file(&reader.path);
+}
+
+#[test]
+fn test_suggested_input() {
+    let variables: Vec<ExecVariable> = serde_json::from_str(
+        r#"[
+            {"string_input": "Query"},
+            {"suggested_input": {"placeholder": "Branch", "default": "main", "suggestions": ["main", "dev"], "command": "printf 'dev\\nrelease\\n'"}}
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!(variables[0].default_value(), None);
+    assert_eq!(variables[1].placeholder(), "Branch");
+    assert_eq!(variables[1].default_value(), Some("main"));
+
+    let ExecVariable::Suggested(input) = &variables[1] else {
+        panic!("expected a suggested input");
+    };
+    assert_eq!(input.load_suggestions(), vec!["main", "dev", "release"]);
+}
+
+#[test]
+fn test_alias_patterns() {
+    let table: AliasTable = serde_json::from_str(
+        r#"{
+            "code*": { "name": "Code" },
+            "/^org\\.gnome\\./": { "keywords": "gnome" },
+            "Visual Studio Code": { "name": "VS Code" }
+        }"#,
+    )
+    .unwrap();
+    let name = |alias: Option<&SherlockAlias>| alias.and_then(|a| a.name.clone());
+
+    // Exact keys win over patterns, globs also match the desktop file
+    assert_eq!(
+        name(table.find(
+            "Visual Studio Code",
+            Some(Path::new("/usr/share/applications/code.desktop"))
+        )),
+        Some(String::from("VS Code"))
+    );
+    assert_eq!(
+        name(table.find(
+            "Code - OSS",
+            Some(Path::new("/usr/share/applications/code-oss.desktop"))
+        )),
+        Some(String::from("Code"))
+    );
+    assert!(
+        table
+            .find("Files", Some(Path::new("org.gnome.Nautilus.desktop")))
+            .is_some_and(|alias| alias.keywords.as_deref() == Some("gnome"))
+    );
+    assert!(
+        table
+            .find("Firefox", Some(Path::new("firefox.desktop")))
+            .is_none()
+    );
+
+    assert!(serde_json::from_str::<AliasTable>(r#"{ "/(/": {} }"#).is_err());
+}
+
+#[test]
+fn test_ignore_list() {
+    let patterns = [
+        "# comment",
+        "",
+        "steam*",
+        "bookmark:*.internal.corp/*",
+        "https://*",
+    ]
+    .iter()
+    .filter_map(|line| IgnoreList::parse_line(line)?.ok())
+    .collect();
+    let ignore = IgnoreList { patterns };
+
+    assert!(ignore.is_ignored("app", Some("Steam"), Some("steam %U")));
+    assert!(ignore.is_ignored(
+        "bookmark",
+        Some("Wiki"),
+        Some("https://wiki.internal.corp/start")
+    ));
+    assert!(!ignore.is_ignored(
+        "command",
+        Some("Wiki"),
+        Some("xdg-open wiki.internal.corp/start")
+    ));
+    // Urls are patterns without a kind
+    assert!(ignore.is_ignored("web", None, Some("https://example.org")));
+    assert!(!ignore.is_ignored("app", Some("Firefox"), Some("firefox %u")));
+    assert!(IgnoreList::parse_line("app:[").unwrap().is_err());
+}
+
+#[test]
+fn test_declared_actions() {
+    let declared: Vec<DeclaredAction> = serde_json::from_str(
+        r#"[
+            { "name": "Private Window", "exec": "{exec} --private-window", "for_name": "firefox*" },
+            { "name": "Copy Url", "exec": "wl-copy {exec}", "for_launcher": "bookmark" },
+            { "name": "Kill", "exec": "pkill -9 {exec}", "for_name": "Gimp", "confirm": "Kill Gimp?" }
+        ]"#,
+    )
+    .unwrap();
+    let table = ActionTable::new(declared).unwrap();
+
+    let actions = table.actions_for("app", Some("Firefox"), Some("firefox"));
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].exec.as_deref(), Some("firefox --private-window"));
+    assert_eq!(actions[0].method, "command");
+    assert_eq!(actions[0].confirm, None);
+
+    let actions = table.actions_for("bookmark", Some("Docs"), Some("https://docs.rs"));
+    assert_eq!(actions[0].exec.as_deref(), Some("wl-copy https://docs.rs"));
+    let actions = table.actions_for("app", Some("Gimp"), Some("gimp"));
+    assert_eq!(actions[0].confirm.as_deref(), Some("Kill Gimp?"));
+    assert!(
+        table
+            .actions_for("app", Some("Inkscape"), Some("inkscape"))
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_count_keys() {
+    use crate::launcher::{
+        app_launcher::AppLauncher, bookmark_launcher::BookmarkLauncher,
+        system_cmd_launcher::CommandLauncher,
+    };
+
+    let apps = Launcher {
+        launcher_type: LauncherType::App(AppLauncher { use_keywords: true }),
+        ..Default::default()
+    };
+    let commands = Launcher {
+        launcher_type: LauncherType::Command(CommandLauncher {}),
+        ..Default::default()
+    };
+    let bookmarks = Launcher {
+        launcher_type: LauncherType::Bookmark(BookmarkLauncher {
+            target_browser: String::from("firefox"),
+            favicons: Default::default(),
+            profiles: Vec::new(),
+        }),
+        ..Default::default()
+    };
+
+    let mut firefox = AppData::new();
+    firefox.exec = Some(String::from("firefox %u"));
+    firefox.desktop_file = Some(PathBuf::from("/usr/share/applications/firefox.desktop"));
+    let mut command = AppData::new();
+    command.exec = Some(String::from("firefox %u"));
+    let mut bookmark = AppData::new();
+    bookmark.exec = Some(String::from("https://github.com"));
+
+    assert_eq!(firefox.count_key(&apps), "app:firefox");
+    assert_eq!(command.count_key(&commands), "command:firefox %u");
+    assert_eq!(
+        bookmark.count_key(&bookmarks),
+        "bookmark:https://github.com"
+    );
+
+    let counts = HashMap::from([
+        (String::from("app:firefox"), 3.0),
+        (String::from("https://github.com"), 2.0),
+    ]);
+    assert_eq!(firefox.count(&apps, &counts), 3.0);
+    assert_eq!(command.count(&commands, &counts), 0.0);
+    assert_eq!(bookmark.count(&bookmarks, &counts), 2.0);
+
+    let uncounted = IgnoreList::from_lines(["command:firefox*"]);
+    assert!(firefox.is_counted(&apps, &uncounted));
+    assert!(!command.is_counted(&commands, &uncounted));
+    let power = Launcher {
+        uncounted: true,
+        ..Default::default()
+    };
+    assert!(!bookmark.is_counted(&power, &IgnoreList::default()));
+}
+
+#[test]
+fn test_root_action() {
+    let allow = [String::from("GParted"), String::from("/^systemctl /")];
+    let table = ActionTable::default()
+        .with_elevation("pkexec", &allow)
+        .unwrap();
+
+    let action = table
+        .root_action("app", Some("GParted"), Some("gparted %f"), false)
+        .unwrap();
+    assert_eq!(action.exec.as_deref(), Some("pkexec gparted %f"));
+    let action = table
+        .root_action("command", None, Some("systemctl restart sshd"), true)
+        .unwrap();
+    assert_eq!(
+        action.exec.as_deref(),
+        Some("{terminal} pkexec systemctl restart sshd")
+    );
+    assert!(
+        table
+            .root_action("app", Some("Gimp"), Some("gimp"), false)
+            .is_none()
+    );
+    assert!(
+        table
+            .root_action(
+                "bookmark",
+                Some("gparted"),
+                Some("https://gparted.org"),
+                false
+            )
+            .is_none()
+    );
+    assert!(
+        ActionTable::default()
+            .with_elevation("pkexec", &[String::from("/(/")])
+            .is_err()
+    );
+}
+
+#[test]
+fn test_command_environment() {
+    let commands = serde_json::json!({
+        "Build": {
+            "exec": "make",
+            "search_string": "build",
+            "cwd": "~/src/sherlock",
+            "env": { "CC": "clang" }
+        }
+    });
+    let commands = deserialize_named_appdata(commands).unwrap();
+    let build = commands.iter().next().unwrap();
+    assert_eq!(
+        build.cwd.as_deref(),
+        Some(home_dir().unwrap().join("src/sherlock").as_path())
+    );
+    assert_eq!(build.env["CC"], "clang");
+}
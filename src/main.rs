@@ -1,25 +1,33 @@
 use once_cell::sync::OnceCell;
 use std::{
+    collections::HashMap,
     io::Write,
-    sync::{Arc, RwLock},
+    sync::{Arc, RwLock, atomic::AtomicUsize},
 };
-use tokio::net::UnixListener;
+use tokio::{io::AsyncReadExt, net::UnixListener};
 
 use gpui::{
-    layer_shell::{Layer, LayerShellOptions},
+    layer_shell::{Anchor, Layer, LayerShellOptions},
     *,
 };
 
 use crate::{
     launcher::children::RenderableChild,
-    loader::{CustomIconTheme, IconThemeGuard, Loader, assets::Assets},
+    loader::{
+        CustomIconTheme, IconThemeGuard, Loader,
+        assets::Assets,
+        reload_config,
+        utils::{HistoryReader, PinReader},
+        watch_config,
+    },
     ui::{
-        main_window::{LauncherMode, NextVar, OpenContext, PrevVar},
+        icon_cache::IconCache, main_window::LauncherMode, placement::Placement,
         search_bar::EmptyBackspace,
     },
     utils::{
-        config::{ConfigGuard, SherlockConfig},
+        config::{ConfigGuard, SherlockConfig, SherlockFlags, WindowPosition},
         errors::SherlockErrorType,
+        windows::cursor_position,
     },
 };
 
@@ -29,32 +37,18 @@ mod prelude;
 mod ui;
 mod utils;
 
-use ui::main_window::{Execute, FocusNext, FocusPrev, Quit, SherlockMainWindow};
-use ui::search_bar::{
-    Backspace, Copy, Cut, Delete, DeleteAll, End, Home, Left, Paste, Right, SelectAll, TextInput,
-};
+use ui::keybinds;
+use ui::main_window::SherlockMainWindow;
+use ui::search_bar::TextInput;
 
 use utils::errors::SherlockError;
 
 static ICONS: OnceCell<RwLock<CustomIconTheme>> = OnceCell::new();
 static CONFIG: OnceCell<RwLock<SherlockConfig>> = OnceCell::new();
 
-fn setup() -> Result<(), SherlockError> {
-    let mut flags = Loader::load_flags()?;
-
-    let config = flags.to_config().map_or_else(
-        |e| {
-            eprintln!("{e}");
-            let defaults = SherlockConfig::default();
-            SherlockConfig::apply_flags(&mut flags, defaults)
-        },
-        |(cfg, non_crit)| {
-            if !non_crit.is_empty() {
-                eprintln!("{:?}", non_crit);
-            }
-            cfg
-        },
-    );
+fn setup() -> Result<SherlockFlags, SherlockError> {
+    let flags = Loader::load_flags()?;
+    let config = flags.load_config();
 
     // Load custom icons
     let _ = ICONS.set(RwLock::new(CustomIconTheme::new()));
@@ -69,7 +63,7 @@ fn setup() -> Result<(), SherlockError> {
         .set(RwLock::new(config.clone()))
         .map_err(|_| sherlock_error!(SherlockErrorType::ConfigError(None), ""))?;
 
-    Ok(())
+    Ok(flags)
 }
 
 #[tokio::main]
@@ -77,49 +71,61 @@ async fn main() {
     // connect to existing socket
     let socket_path = "/tmp/sherlock.sock";
     if let Ok(mut stream) = std::os::unix::net::UnixStream::connect(socket_path) {
-        let _ = stream.write_all(b"open");
+        // `open <profile>` switches the running instance to another profile
+        let args: Vec<String> = std::env::args().collect();
+        let message = match args.iter().position(|arg| arg == "--profile") {
+            Some(i) => format!("open {}", args.get(i + 1).map_or("", String::as_str)),
+            None => String::from("open"),
+        };
+        let _ = stream.write_all(message.as_bytes());
         return;
     }
 
-    if let Err(e) = setup() {
-        eprintln!("{e}");
-    }
+    let flags = setup().map_err(|e| eprintln!("{e}")).ok();
 
     // start primary instance
     let app = Application::new().with_assets(Assets);
     app.with_quit_mode(QuitMode::Explicit).run(|cx: &mut App| {
-        cx.bind_keys([
-            KeyBinding::new("backspace", Backspace, None),
-            KeyBinding::new("delete", Delete, None),
-            KeyBinding::new("ctrl-backspace", DeleteAll, None),
-            KeyBinding::new("ctrl-a", SelectAll, None),
-            KeyBinding::new("ctrl-v", Paste, None),
-            KeyBinding::new("ctrl-c", Copy, None),
-            KeyBinding::new("ctrl-x", Cut, None),
-            KeyBinding::new("home", Home, None),
-            KeyBinding::new("end", End, None),
-            KeyBinding::new("left", Left, None),
-            KeyBinding::new("right", Right, None),
-            KeyBinding::new("escape", Quit, None),
-            KeyBinding::new("down", FocusNext, None),
-            KeyBinding::new("up", FocusPrev, None),
-            KeyBinding::new("enter", Execute, None),
-            KeyBinding::new("tab", NextVar, None),
-            KeyBinding::new("shift-tab", PrevVar, None),
-            KeyBinding::new("ctrl-l", OpenContext, None),
-        ]);
+        keybinds::bind_keys(cx);
 
         let socket_path = "/tmp/sherlock.sock";
         let data: Entity<Arc<Vec<RenderableChild>>> = cx.new(|_| Arc::new(Vec::new()));
-        let modes = match Loader::load_launchers(cx, data.clone()) {
-            Ok(modes) => modes,
+        let (modes, errors) = match Loader::load_launchers(cx, data.clone()) {
+            Ok(loaded) => loaded,
             Err(e) => {
                 eprintln!("{e}");
                 return;
             }
         };
+        let modes: Entity<Arc<[LauncherMode]>> = cx.new(|_| modes);
+        let errors: Entity<Arc<[SherlockError]>> = cx.new(|_| Arc::from(errors));
 
-        spawn_launcher(cx, data.clone(), Arc::clone(&modes));
+        // Edits to the config apply on the next open
+        let flags = flags.map(|flags| cx.new(|_| flags));
+        if let Some(flags) = &flags {
+            watch_config(
+                cx,
+                flags.clone(),
+                data.clone(),
+                modes.clone(),
+                errors.clone(),
+            );
+        }
+        let launch_profile = flags.as_ref().and_then(|f| f.read(cx).profile.clone());
+
+        // Decoded icons are shared across all windows
+        let icon_memory = ConfigGuard::read()
+            .map(|c| c.caching.icon_memory)
+            .unwrap_or(64);
+        let icon_cache = IconCache::new(icon_memory * 1024 * 1024, cx);
+
+        spawn_launcher(
+            cx,
+            data.clone(),
+            icon_cache.clone(),
+            modes.read(cx).clone(),
+            errors.read(cx).clone(),
+        );
 
         // listen for open requests
         let _ = std::fs::remove_file(socket_path);
@@ -130,8 +136,25 @@ async fn main() {
             async move {
                 let mut win: Option<AnyWindowHandle> = None;
                 loop {
-                    if let Ok((_stream, _)) = listener.accept().await {
+                    if let Ok((mut stream, _)) = listener.accept().await {
+                        let mut message = String::new();
+                        let _ = stream.read_to_string(&mut message).await;
+                        // Plain opens return to the profile Sherlock was started with
+                        let profile = message
+                            .strip_prefix("open")
+                            .map(str::trim)
+                            .filter(|profile| !profile.is_empty())
+                            .map(String::from)
+                            .or_else(|| launch_profile.clone());
+
                         cx.update(|cx| {
+                            if let Some(flags) = &flags
+                                && flags.read(cx).profile != profile
+                            {
+                                flags.update(cx, |flags, _| flags.profile = profile);
+                                reload_config(cx, flags, &data, &modes, &errors);
+                            }
+
                             // Close old window
                             if let Some(old_win) = win.take() {
                                 let _ = old_win.update(cx, |_, win, _| {
@@ -140,7 +163,13 @@ async fn main() {
                             }
 
                             // Create new window
-                            win = Some(spawn_launcher(cx, data.clone(), Arc::clone(&modes)));
+                            win = Some(spawn_launcher(
+                                cx,
+                                data.clone(),
+                                icon_cache.clone(),
+                                modes.read(cx).clone(),
+                                errors.read(cx).clone(),
+                            ));
                         })
                         .ok();
                     } else {
@@ -156,7 +185,9 @@ async fn main() {
 fn spawn_launcher(
     cx: &mut App,
     data: Entity<Arc<Vec<RenderableChild>>>,
+    icon_cache: Entity<IconCache>,
     modes: Arc<[LauncherMode]>,
+    errors: Arc<[SherlockError]>,
 ) -> AnyWindowHandle {
     // For now load application here
     let window = cx
@@ -164,7 +195,9 @@ fn spawn_launcher(
             let text_input = cx.new(|cx| TextInput {
                 focus_handle: cx.focus_handle(),
                 content: "".into(),
-                placeholder: "Search:".into(),
+                placeholder: ConfigGuard::read()
+                    .map_or_else(|_| "Search:".into(), |c| c.appearance.placeholder.clone())
+                    .into(),
                 variable: None,
                 selected_range: 0..0,
                 selection_reversed: false,
@@ -172,26 +205,26 @@ fn spawn_launcher(
                 last_layout: None,
                 last_bounds: None,
                 is_selecting: false,
+                word_selection: None,
+                completion: None,
+                suggestions: Arc::new([]),
             });
             cx.new(|cx| {
                 let data_len = data.read(cx).len();
                 let sub = cx.observe(
                     &text_input,
                     move |this: &mut SherlockMainWindow, _ev, cx| {
+                        // A dead key or compose sequence is not done yet
+                        if this.text_input.read(cx).marked_range.is_some() {
+                            return;
+                        }
                         this.selected_index = 0;
                         this.filter_and_sort(cx);
                     },
                 );
                 let backspace_sub =
                     cx.subscribe(&text_input, |this, _, _ev: &EmptyBackspace, cx| {
-                        if this.mode != LauncherMode::Home {
-                            this.mode = LauncherMode::Home;
-
-                            // Propagate changes to ui
-                            this.last_query = None;
-                            this.selected_index = 0;
-                            this.filter_and_sort(cx);
-                        }
+                        this.exit_mode(cx);
                     });
 
                 let list_state = ListState::new(data_len, ListAlignment::Top, px(48.));
@@ -208,16 +241,42 @@ fn spawn_launcher(
                     // context menu
                     context_idx: None,
                     context_actions: Arc::new([]),
+                    open_windows: Arc::new([]),
+                    pins: PinReader::new()
+                        .map(|reader| reader.read())
+                        .unwrap_or_default()
+                        .into(),
+                    history: HistoryReader::new()
+                        .map(|reader| reader.read())
+                        .unwrap_or_default()
+                        .into(),
+                    history_pos: None,
+                    // error view
+                    errors,
+                    show_errors: false,
+                    confirmation: None,
+                    shortcuts: keybinds::Shortcuts::from_config(),
+                    typography: ui::typography::Typography::from_config(),
+                    background_opacity: ConfigGuard::read()
+                        .map_or(1.0, |c| c.appearance.opacity.clamp(0.0, 1.0) as f32),
                     // variable inputs
                     variable_input: Vec::new(),
                     active_bar: 0,
                     // Data model
                     data,
+                    icon_cache,
                     deferred_render_task: None,
+                    running_outputs: HashMap::new(),
+                    filter_generation: Arc::new(AtomicUsize::new(0)),
                     last_query: None,
+                    filter_time: None,
                     filtered_indices: (0..data_len).collect(),
+                    searched_query: None,
+                    search_task: None,
                 };
                 view.filter_and_sort(cx);
+                view.load_open_windows(cx);
+                view.cycle_placeholder_hints(cx);
 
                 view
             })
@@ -235,21 +294,63 @@ fn spawn_launcher(
 }
 
 fn get_window_options() -> WindowOptions {
-    let (width, height) = ConfigGuard::read()
-        .map(|c| (c.appearance.width, c.appearance.height))
-        .unwrap_or((900i32, 600i32));
+    let (width, height, blur, opacity, position, margins) = ConfigGuard::read()
+        .map(|c| {
+            (
+                c.appearance.width,
+                c.appearance.height,
+                c.appearance.blur,
+                c.appearance.opacity,
+                c.appearance.position,
+                c.appearance.margins,
+            )
+        })
+        .unwrap_or((
+            900i32,
+            600i32,
+            true,
+            1.0,
+            WindowPosition::Center,
+            (0, 0, 0, 0),
+        ));
+    let cursor = (position == WindowPosition::Cursor)
+        .then(cursor_position)
+        .flatten();
+    let placement = Placement::new(position, margins, (width as f32, height as f32), cursor);
 
     WindowOptions {
         kind: WindowKind::LayerShell(LayerShellOptions {
             namespace: "sherlock".to_string(),
             layer: Layer::Overlay,
+            anchor: match (placement.top, placement.left) {
+                (true, true) => Anchor::TOP | Anchor::LEFT,
+                (true, false) => Anchor::TOP,
+                _ => Anchor::empty(),
+            },
+            margin: Some((
+                px(placement.margins.0),
+                px(placement.margins.1),
+                px(placement.margins.2),
+                px(placement.margins.3),
+            )),
             ..Default::default()
         }),
         window_bounds: Some(WindowBounds::Windowed(Bounds {
             origin: point(px(0.), px(0.)),
             size: Size::new(px(width as f32), px(height as f32)),
         })),
-        window_background: WindowBackgroundAppearance::Blurred,
+        window_background: window_background(blur, opacity),
         ..Default::default()
     }
 }
+
+/// Blurred if enabled, otherwise transparent as long as the background lets the desktop through
+fn window_background(blur: bool, opacity: f64) -> WindowBackgroundAppearance {
+    if blur {
+        WindowBackgroundAppearance::Blurred
+    } else if opacity < 1.0 {
+        WindowBackgroundAppearance::Transparent
+    } else {
+        WindowBackgroundAppearance::Opaque
+    }
+}
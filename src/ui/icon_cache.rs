@@ -0,0 +1,119 @@
+use std::{collections::HashMap, sync::Arc};
+
+use futures::FutureExt;
+use gpui::{
+    App, AppContext, Asset, AssetLogger, Entity, ImageAssetLoader, ImageCache, ImageCacheError,
+    ImageCacheItem, RenderImage, Resource, Window, hash,
+};
+
+/// Image cache shared by all windows. Decoded icons stay in memory until the configured budget
+/// is exceeded, after which the least recently used ones are dropped again.
+pub struct IconCache {
+    items: HashMap<u64, CacheEntry>,
+    /// Budget in bytes
+    budget: usize,
+    /// Bytes currently held by decoded images
+    used: usize,
+    tick: u64,
+}
+
+struct CacheEntry {
+    item: ImageCacheItem,
+    size: usize,
+    last_used: u64,
+}
+
+impl IconCache {
+    pub fn new(budget: usize, cx: &mut App) -> Entity<Self> {
+        let cache = cx.new(|_| Self {
+            items: HashMap::new(),
+            budget,
+            used: 0,
+            tick: 0,
+        });
+        cx.observe_release(&cache, |cache, cx| {
+            for (_, mut entry) in cache.items.drain() {
+                if let Some(Ok(image)) = entry.item.get() {
+                    cx.drop_image(image, None);
+                }
+            }
+        })
+        .detach();
+        cache
+    }
+
+    /// Drops the least recently used images until the budget is met again. The image with the
+    /// given key is kept as it is about to be painted.
+    fn evict(&mut self, keep: u64, window: &mut Window, cx: &mut App) {
+        while self.used > self.budget {
+            let Some(key) = self
+                .items
+                .iter()
+                .filter(|(key, entry)| **key != keep && entry.size > 0)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key)
+            else {
+                break;
+            };
+
+            if let Some(mut entry) = self.items.remove(&key) {
+                self.used -= entry.size;
+                if let Some(Ok(image)) = entry.item.get() {
+                    cx.drop_image(image, Some(window));
+                }
+            }
+        }
+    }
+}
+
+impl ImageCache for IconCache {
+    fn load(
+        &mut self,
+        resource: &Resource,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Result<Arc<RenderImage>, ImageCacheError>> {
+        self.tick += 1;
+        let key = hash(resource);
+
+        if let Some(entry) = self.items.get_mut(&key) {
+            entry.last_used = self.tick;
+            let result = entry.item.get()?;
+
+            // Account for the image as soon as it finished loading
+            if let (Ok(image), 0) = (&result, entry.size) {
+                entry.size = (0..image.frame_count())
+                    .filter_map(|frame| image.as_bytes(frame))
+                    .map(<[u8]>::len)
+                    .sum();
+                self.used += entry.size;
+                self.evict(key, window, cx);
+            }
+            return Some(result);
+        }
+
+        let fut = AssetLogger::<ImageAssetLoader>::load(resource.clone(), cx);
+        let task = cx.background_executor().spawn(fut).shared();
+        self.items.insert(
+            key,
+            CacheEntry {
+                item: ImageCacheItem::Loading(task.clone()),
+                size: 0,
+                last_used: self.tick,
+            },
+        );
+
+        // Redraw once the image is ready
+        let entity = window.current_view();
+        window
+            .spawn(cx, async move |cx| {
+                _ = task.await;
+                cx.on_next_frame(move |_, cx| {
+                    cx.notify(entity);
+                });
+            })
+            .detach();
+
+        None
+    }
+}
@@ -0,0 +1,381 @@
+use gpui::{Action, App, KeyBinding, Keystroke, Modifiers, SharedString};
+
+use crate::{
+    ui::{
+        UIFunction,
+        main_window::{
+            CloseContext, Execute, ExecuteInplace, FocusNext, FocusPrev, HistoryNext, HistoryPrev,
+            NextVar, OpenContext, PrevVar, Quit, ToggleErrors,
+        },
+        search_bar::{
+            Backspace, Copy, Cut, Delete, DeleteAll, DeleteWordLeft, DeleteWordRight, End, Home,
+            KillToEnd, KillToStart, Left, Paste, PastePrimary, Right, SelectAll, WordLeft,
+            WordRight,
+        },
+    },
+    utils::config::ConfigGuard,
+};
+
+/// A binding along with its parsed keys, which tell whether two bindings conflict
+struct Bind {
+    keys: Vec<Keystroke>,
+    binding: KeyBinding,
+}
+impl Bind {
+    fn new<A: Action>(keys: &str, action: A) -> Result<Self, String> {
+        // `KeyBinding::new` panics on invalid keys, so they are checked first
+        let parsed = keys
+            .split_whitespace()
+            .map(Keystroke::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            keys: parsed,
+            binding: KeyBinding::new(keys, action, None),
+        })
+    }
+}
+
+/// The bindings Sherlock ships with. With `lr_nav`, the left and right arrows move the
+/// selection instead of the text cursor, with `readline`, ctrl-a moves to the start instead of
+/// selecting everything.
+fn default_bindings(lr_nav: bool, readline: bool) -> Vec<Bind> {
+    let (left, right) = if lr_nav {
+        (Bind::new("left", FocusPrev), Bind::new("right", FocusNext))
+    } else {
+        (Bind::new("left", Left), Bind::new("right", Right))
+    };
+    let bindings = [
+        left,
+        right,
+        Bind::new("backspace", Backspace),
+        Bind::new("delete", Delete),
+        Bind::new("ctrl-backspace", DeleteAll),
+        Bind::new("ctrl-left", WordLeft),
+        Bind::new("ctrl-right", WordRight),
+        Bind::new("ctrl-w", DeleteWordLeft),
+        Bind::new("ctrl-delete", DeleteWordRight),
+        Bind::new("ctrl-v", Paste),
+        Bind::new("shift-insert", PastePrimary),
+        Bind::new("ctrl-c", Copy),
+        Bind::new("ctrl-x", Cut),
+        Bind::new("home", Home),
+        Bind::new("end", End),
+        Bind::new("escape", Quit),
+        Bind::new("down", FocusNext),
+        Bind::new("up", FocusPrev),
+        Bind::new("enter", Execute),
+        Bind::new("tab", NextVar),
+        Bind::new("shift-tab", PrevVar),
+        Bind::new("ctrl-l", OpenContext),
+        Bind::new("ctrl-p", HistoryPrev),
+        Bind::new("ctrl-n", HistoryNext),
+        Bind::new("ctrl-shift-e", ToggleErrors),
+    ];
+    let editing = if readline {
+        vec![
+            Bind::new("ctrl-a", Home),
+            Bind::new("ctrl-e", End),
+            Bind::new("ctrl-u", KillToStart),
+            Bind::new("ctrl-k", KillToEnd),
+        ]
+    } else {
+        vec![Bind::new("ctrl-a", SelectAll)]
+    };
+    bindings.into_iter().chain(editing).flatten().collect()
+}
+
+impl UIFunction {
+    /// Binds the function to the keys, `None` for functions that have no action yet
+    fn bind(&self, keys: &str) -> Option<Result<Bind, String>> {
+        let bind = match self {
+            Self::Exit => Bind::new(keys, Quit),
+            Self::ItemDown => Bind::new(keys, FocusNext),
+            Self::ItemUp => Bind::new(keys, FocusPrev),
+            Self::ItemLeft => Bind::new(keys, Left),
+            Self::ItemRight => Bind::new(keys, Right),
+            Self::ArgNext => Bind::new(keys, NextVar),
+            Self::ArgPrev => Bind::new(keys, PrevVar),
+            Self::Exec => Bind::new(keys, Execute),
+            Self::ExecInplace => Bind::new(keys, ExecuteInplace),
+            Self::ToggleContext => Bind::new(keys, OpenContext),
+            Self::CloseContext => Bind::new(keys, CloseContext),
+            Self::ClearBar => Bind::new(keys, DeleteAll),
+            Self::Backspace => Bind::new(keys, Backspace),
+            Self::WordLeft => Bind::new(keys, WordLeft),
+            Self::WordRight => Bind::new(keys, WordRight),
+            Self::DeleteWordLeft => Bind::new(keys, DeleteWordLeft),
+            Self::DeleteWordRight => Bind::new(keys, DeleteWordRight),
+            Self::ErrorPage => Bind::new(keys, ToggleErrors),
+            Self::HistoryPrev => Bind::new(keys, HistoryPrev),
+            Self::HistoryNext => Bind::new(keys, HistoryNext),
+            Self::MultiSelect | Self::Shortcut => return None,
+        };
+        Some(bind)
+    }
+}
+
+/// Modifiers in the order of `appearance.mod_key_ascii`, which holds their symbols
+const MODIFIER_NAMES: [&str; 7] = ["shift", "lock", "control", "meta", "alt", "super", "hyper"];
+
+/// The modifier along with a digit that runs one of the first results, e.g. `⌘1` for the
+/// first one
+#[derive(Clone, Debug)]
+pub struct Shortcuts {
+    pub modifiers: Modifiers,
+    /// How the modifier reads in hints
+    pub symbol: SharedString,
+    /// Number of results that get a shortcut, at most ten with `0` for the tenth
+    pub count: usize,
+}
+impl Shortcuts {
+    /// Resolves `shortcut_mod`, a symbol of `mod_key_ascii` such as `⌘` or the name of a
+    /// modifier such as `alt`
+    pub fn new(shortcut_mod: &str, mod_key_ascii: &[String], count: u8) -> Self {
+        let name = match shortcut_mod.to_lowercase() {
+            name if name == "ctrl" => String::from("control"),
+            name => name,
+        };
+        let (index, symbol) = match MODIFIER_NAMES.iter().position(|m| *m == name) {
+            Some(i) => (Some(i), mod_key_ascii.get(i).map(String::as_str)),
+            None => (
+                mod_key_ascii
+                    .iter()
+                    .take(MODIFIER_NAMES.len())
+                    .position(|symbol| symbol == shortcut_mod),
+                None,
+            ),
+        };
+        let symbol = symbol.unwrap_or(shortcut_mod).to_string();
+        let modifiers = match index.map(|i| MODIFIER_NAMES[i]) {
+            Some("shift") => Modifiers::shift(),
+            Some("meta" | "alt") => Modifiers::alt(),
+            Some("super" | "hyper") => Modifiers {
+                platform: true,
+                ..Default::default()
+            },
+            // Lock is no modifier of its own to gpui
+            _ => Modifiers::control(),
+        };
+        Self {
+            modifiers,
+            symbol: SharedString::from(symbol),
+            count: usize::from(count.min(10)),
+        }
+    }
+    pub fn from_config() -> Self {
+        match ConfigGuard::read() {
+            Ok(config) => Self::new(
+                &config.appearance.shortcut_mod,
+                &config.appearance.mod_key_ascii,
+                config.appearance.num_shortcuts,
+            ),
+            Err(_) => Self::new("control", &[], 5),
+        }
+    }
+    /// The digit that runs the result at `index`
+    pub fn digit(index: usize) -> SharedString {
+        SharedString::from(((index + 1) % 10).to_string())
+    }
+    /// The result the keystroke runs, counting from zero. `layout` is the name of the active
+    /// keyboard layout. AZERTY layouts put the digits on the shift level of the number row, so
+    /// both levels of the row count there, while other layouts need the digit itself.
+    pub fn index_of(&self, keystroke: &Keystroke, layout: &str) -> Option<usize> {
+        let azerty = has_azerty_number_row(layout);
+        let with_shift = Modifiers {
+            shift: true,
+            ..self.modifiers
+        };
+        if keystroke.modifiers != self.modifiers && !(azerty && keystroke.modifiers == with_shift) {
+            return None;
+        }
+        let digit = match keystroke.key.parse::<usize>() {
+            Ok(digit) if digit < 10 => digit,
+            _ if azerty => AZERTY_NUMBER_ROW
+                .iter()
+                .position(|key| *key == keystroke.key)
+                .map(|i| (i + 1) % 10)?,
+            _ => return None,
+        };
+        let index = (digit + 9) % 10;
+        (index < self.count).then_some(index)
+    }
+}
+
+/// Unshifted keys of the AZERTY number row, from `1` to `0`
+const AZERTY_NUMBER_ROW: [&str; 10] = ["&", "é", "\"", "'", "(", "-", "è", "_", "ç", "à"];
+
+/// Whether the layout, by its name such as `French (AZERTY)`, is one of the French or Belgian
+/// layouts. The Canadian and Swiss French layouts keep the digits on the unshifted level.
+fn has_azerty_number_row(layout: &str) -> bool {
+    let layout = layout.to_lowercase();
+    ["french", "belgian", "azerty"]
+        .iter()
+        .any(|name| layout.contains(name))
+        && !["canad", "switzerland", "swiss"]
+            .iter()
+            .any(|name| layout.contains(name))
+}
+
+/// Letters of non-Latin layouts, each followed by the keys at the same positions of a US layout
+const US_EQUIVALENTS: &[(&str, &str)] = &[
+    // Russian ЙЦУКЕН
+    ("йцукенгшщзхъ", "qwertyuiop[]"),
+    ("фывапролджэ", "asdfghjkl;'"),
+    ("ячсмитьбю", "zxcvbnm,."),
+    // Ukrainian
+    ("їіє", "]s'"),
+    // Greek
+    ("ςερτυθιοπ", "wertyuiop"),
+    ("ασδφγηξκλ", "asdfghjkl"),
+    ("ζχψωβνμ", "zxcvbnm"),
+];
+
+/// The keystroke as it would have been pressed on a US layout, for keystrokes with a modifier
+/// on layouts without Latin letters. Bindings are registered against US keys, so `ctrl-j`
+/// would never trigger on a Cyrillic layout otherwise. Plain letters are left alone, they type
+/// text.
+pub fn layout_fallback(keystroke: &Keystroke) -> Option<Keystroke> {
+    let modifiers = &keystroke.modifiers;
+    if !(modifiers.control || modifiers.alt || modifiers.platform) {
+        return None;
+    }
+    let mut chars = keystroke.key.chars();
+    let key = chars.next()?.to_lowercase().next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    let us_key = US_EQUIVALENTS.iter().find_map(|(local, us)| {
+        let position = local.chars().position(|c| c == key)?;
+        us.chars().nth(position)
+    })?;
+    Some(Keystroke {
+        modifiers: *modifiers,
+        key: us_key.to_string(),
+        key_char: None,
+    })
+}
+
+/// Binds the default keys along with the `keybinds` of the config, e.g.
+/// `"ctrl-n" = "item_down"`. A keybind for keys that are bound by default replaces the default
+/// binding, so `"enter" = "exec_inplace"` keeps Sherlock open on every launch.
+pub fn bind_keys(cx: &mut App) {
+    let (keybinds, lr_nav, readline) = ConfigGuard::read()
+        .map(|config| {
+            (
+                config.keybinds.clone(),
+                config.behavior.use_lr_nav,
+                config.behavior.use_readline,
+            )
+        })
+        .unwrap_or_default();
+    let mut bindings = default_bindings(lr_nav, readline);
+
+    // Sorted, so conflicting keybinds in the config resolve the same way on every start
+    let mut keybinds: Vec<_> = keybinds.into_iter().collect();
+    keybinds.sort_by(|a, b| a.0.cmp(&b.0));
+    for (keys, function) in keybinds {
+        match function.bind(&keys) {
+            Some(Ok(bind)) => {
+                bindings.retain(|existing| existing.keys != bind.keys);
+                bindings.push(bind);
+            }
+            Some(Err(e)) => eprintln!("Keybind \"{keys}\": {e}"),
+            None => eprintln!("Keybind \"{keys}\": {function:?} cannot be bound to keys yet"),
+        }
+    }
+
+    cx.bind_keys(bindings.into_iter().map(|bind| bind.binding));
+}
+
+#[test]
+fn test_keybind_replaces_default() {
+    let bind = UIFunction::ItemDown.bind("ctrl-n").unwrap().unwrap();
+    assert_eq!(bind.keys, vec![Keystroke::parse("ctrl-n").unwrap()]);
+    assert!(UIFunction::Exec.bind("ctrl-x-y").unwrap().is_err());
+    assert!(UIFunction::Shortcut.bind("ctrl-e").is_none());
+
+    let enter = UIFunction::ExecInplace.bind("enter").unwrap().unwrap();
+    assert!(
+        default_bindings(false, false)
+            .iter()
+            .any(|bind| bind.keys == enter.keys)
+    );
+    // Readline bindings take over ctrl-a
+    let ctrl_a = vec![Keystroke::parse("ctrl-a").unwrap()];
+    let ctrl_a_action = |readline: bool| {
+        default_bindings(false, readline)
+            .into_iter()
+            .find(|bind| bind.keys == ctrl_a)
+            .map(|bind| bind.binding.action().name())
+    };
+    assert_eq!(ctrl_a_action(false), Some(SelectAll.name()));
+    assert_eq!(ctrl_a_action(true), Some(Home.name()));
+}
+
+#[test]
+fn test_shortcuts() {
+    let symbols = ["⇧", "⇧", "⌘", "⌘", "⎇", "✦", "✦", "⌘"].map(String::from);
+    let control = Shortcuts::new("⌘", &symbols, 5);
+    const US: &str = "English (US)";
+    assert_eq!(control.modifiers, Modifiers::control());
+    assert_eq!(control.symbol, "⌘");
+    assert_eq!(
+        control.index_of(&Keystroke::parse("ctrl-1").unwrap(), US),
+        Some(0)
+    );
+    assert_eq!(
+        control.index_of(&Keystroke::parse("ctrl-5").unwrap(), US),
+        Some(4)
+    );
+    assert_eq!(
+        control.index_of(&Keystroke::parse("ctrl-6").unwrap(), US),
+        None
+    );
+    assert_eq!(
+        control.index_of(&Keystroke::parse("alt-1").unwrap(), US),
+        None
+    );
+
+    let alt = Shortcuts::new("alt", &symbols, 12);
+    assert_eq!(alt.modifiers, Modifiers::alt());
+    assert_eq!(alt.symbol, "⎇");
+    assert_eq!(
+        alt.index_of(&Keystroke::parse("alt-0").unwrap(), US),
+        Some(9)
+    );
+    assert_eq!(Shortcuts::digit(9), "0");
+
+    // AZERTY number row, with and without shift, but only on AZERTY layouts
+    let ampersand = Keystroke {
+        modifiers: Modifiers::control(),
+        key: String::from("&"),
+        key_char: None,
+    };
+    let shift_2 = Keystroke::parse("ctrl-shift-2").unwrap();
+    assert_eq!(control.index_of(&ampersand, "French (AZERTY)"), Some(0));
+    assert_eq!(control.index_of(&shift_2, "Belgian"), Some(1));
+    assert_eq!(control.index_of(&ampersand, US), None);
+    assert_eq!(control.index_of(&shift_2, US), None);
+    assert_eq!(control.index_of(&shift_2, "French (Canada)"), None);
+}
+
+#[test]
+fn test_layout_fallback() {
+    let cyrillic = |keys: &str, key: &str| {
+        let mut keystroke = Keystroke::parse(keys).unwrap();
+        keystroke.key = key.to_string();
+        keystroke
+    };
+    assert_eq!(
+        layout_fallback(&cyrillic("ctrl-x", "о")),
+        Some(Keystroke::parse("ctrl-j").unwrap())
+    );
+    assert_eq!(
+        layout_fallback(&cyrillic("ctrl-shift-x", "Л")),
+        Some(Keystroke::parse("ctrl-shift-k").unwrap())
+    );
+    // Typing stays untouched, as do layouts with Latin letters
+    assert_eq!(layout_fallback(&cyrillic("x", "о")), None);
+    assert_eq!(layout_fallback(&Keystroke::parse("ctrl-j").unwrap()), None);
+}
@@ -1,12 +1,22 @@
-use std::sync::Arc;
+use std::sync::{Arc, atomic::Ordering};
 
-use gpui::{AppContext, Context, SharedString, Window, actions};
+use gpui::{
+    AppContext, AsyncApp, ClipboardItem, Context, KeyDownEvent, SharedString, WeakEntity, Window,
+    actions,
+};
 use smallvec::SmallVec;
 
 use crate::{
-    launcher::children::{RenderableChild, RenderableChildDelegate},
-    loader::utils::ExecVariable,
-    ui::{main_window::SherlockMainWindow, search_bar::TextInput},
+    launcher::children::{
+        COPY_OUTPUT_METHOD, PIN_METHOD, RenderableChild, RenderableChildDelegate,
+    },
+    loader::utils::{ExecVariable, HistoryReader, PinReader},
+    ui::{
+        keybinds::layout_fallback,
+        main_window::{Confirmation, LauncherMode, SherlockMainWindow},
+        search_bar::{Left, Right, TextInput},
+    },
+    utils::{config::ConfigGuard, watch::StopToken},
 };
 
 actions!(
@@ -18,8 +28,13 @@ actions!(
         NextVar,
         PrevVar,
         Execute,
+        ExecuteInplace,
         OpenContext,
+        CloseContext,
+        ToggleErrors,
         Backspace,
+        HistoryPrev,
+        HistoryNext,
     ]
 );
 
@@ -27,24 +42,41 @@ impl SherlockMainWindow {
     pub fn focus_nth(&mut self, n: usize, cx: &mut Context<Self>) {
         self.selected_index = n;
         self.list_state.scroll_to_reveal_item(n);
+        // Moving on answers the question with no
+        self.confirmation = None;
 
         // Handle variable inputs
         self.update_vars(cx);
         self.active_bar = 0;
 
-        // Handle context menu entries
-        self.context_actions = self
+        self.update_context_actions(cx);
+        cx.notify()
+    }
+    /// Collects the context menu entries of the selected child
+    fn update_context_actions(&mut self, cx: &mut Context<Self>) {
+        let selected = self
             .filtered_indices
-            .get(n)
-            .and_then(|i| self.data.read(cx).get(*i))
+            .get(self.selected_index)
+            .and_then(|i| self.data.read(cx).get(*i));
+        let actions = selected
             .and_then(RenderableChild::actions)
             .unwrap_or_default();
-
-        cx.notify()
+        // Enter switches to an open window, so launching anew moves into the context menu
+        let new_instance = selected.and_then(|child| child.new_instance_action(&self.open_windows));
+        let pin = selected.and_then(|child| child.pin_action(&self.pins));
+        let copy_output = selected.and_then(RenderableChild::copy_output_action);
+        self.context_actions = new_instance
+            .into_iter()
+            .chain(copy_output)
+            .map(Arc::new)
+            .chain(actions.iter().cloned())
+            .chain(pin.map(Arc::new))
+            .collect();
     }
     pub(super) fn focus_next(&mut self, _: &FocusNext, _: &mut Window, cx: &mut Context<Self>) {
         let count = self.filtered_indices.len();
         if count == 0 {
+            self.step_history(false, cx);
             return;
         }
 
@@ -62,8 +94,8 @@ impl SherlockMainWindow {
         }
     }
     pub(super) fn focus_prev(&mut self, _: &FocusPrev, _: &mut Window, cx: &mut Context<Self>) {
-        let count = self.data.read(cx).len();
-        if count == 0 {
+        if self.filtered_indices.is_empty() {
+            self.step_history(true, cx);
             return;
         }
 
@@ -80,7 +112,69 @@ impl SherlockMainWindow {
             }
         }
     }
+    pub(super) fn history_prev(&mut self, _: &HistoryPrev, _: &mut Window, cx: &mut Context<Self>) {
+        self.step_history(true, cx);
+    }
+    pub(super) fn history_next(&mut self, _: &HistoryNext, _: &mut Window, cx: &mut Context<Self>) {
+        self.step_history(false, cx);
+    }
+    /// Replaces the query with an older or newer one from the history
+    fn step_history(&mut self, back: bool, cx: &mut Context<Self>) {
+        // Editing the shown query starts over at the most recent one
+        let content = self.text_input.read(cx).content.clone();
+        let pos = self
+            .history_pos
+            .filter(|&pos| self.history.get(pos).is_some_and(|query| *query == content));
+        let Some(pos) = history_step(self.history.len(), pos, back) else {
+            return;
+        };
+        self.history_pos = pos;
+        let query = pos.map_or_else(String::new, |pos| self.history[pos].clone());
+        self.text_input.update(cx, |input, cx| {
+            input.selected_range = query.len()..query.len();
+            input.content = query.into();
+            cx.notify();
+        });
+    }
+    /// Completes the mode alias the query starts, which switches into the mode. Returns whether
+    /// there was an alias to complete.
+    fn accept_completion(&mut self, cx: &mut Context<Self>) -> bool {
+        let Some(completion) = self.text_input.read(cx).completion.clone() else {
+            return false;
+        };
+        self.text_input.update(cx, |input, cx| {
+            let content = format!("{}{completion} ", input.content);
+            input.selected_range = content.len()..content.len();
+            input.content = content.into();
+            cx.notify();
+        });
+        true
+    }
+    /// Adds the query to the history, as it ran an item
+    fn record_query(&mut self, cx: &mut Context<Self>) {
+        let query = self.text_input.read(cx).content.trim().to_string();
+        let limit = ConfigGuard::read().map_or(0, |c| c.behavior.history_size);
+        if query.is_empty() || limit == 0 {
+            return;
+        }
+        match HistoryReader::new().and_then(|reader| reader.push(&query, limit)) {
+            Ok(history) => self.history = Arc::from(history),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
     pub(super) fn next_var(&mut self, _: &NextVar, win: &mut Window, cx: &mut Context<Self>) {
+        if self.active_bar == 0 && self.accept_completion(cx) {
+            return;
+        }
+        // Suggestions come first, the last one moves on to the next input
+        if let Some(input) = self
+            .active_bar
+            .checked_sub(1)
+            .map(|i| &self.variable_input[i])
+            && input.update(cx, |input, cx| input.next_suggestion(cx))
+        {
+            return;
+        }
         let total_inputs = 1 + self.variable_input.len();
 
         if self.active_bar < total_inputs - 1 {
@@ -114,21 +208,102 @@ impl SherlockMainWindow {
         }
     }
     pub(super) fn execute(&mut self, _: &Execute, win: &mut Window, cx: &mut Context<Self>) {
+        self.run_selected(false, win, cx);
+    }
+    /// Runs the selected entry but keeps Sherlock open
+    pub(super) fn execute_inplace(
+        &mut self,
+        _: &ExecuteInplace,
+        win: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.run_selected(true, win, cx);
+    }
+    fn run_selected(&mut self, inplace: bool, win: &mut Window, cx: &mut Context<Self>) {
+        // A pending confirmation only counts for the entry it was asked for
+        let Some(&data_idx) = self.filtered_indices.get(self.selected_index) else {
+            return;
+        };
+        let confirmed = self.confirmation.take().is_some_and(|confirmation| {
+            confirmation.data_idx == data_idx && confirmation.context_idx == self.context_idx
+        });
+
         if let Some(idx) = self.context_idx {
+            if !confirmed
+                && let Some(message) = self
+                    .context_actions
+                    .get(idx)
+                    .and_then(|action| action.confirm.clone())
+            {
+                self.ask_confirmation(message, data_idx, cx);
+                return;
+            }
+            let internal = self.context_actions.get(idx).is_some_and(|action| {
+                action.method == PIN_METHOD || action.method == COPY_OUTPUT_METHOD
+            });
+            if !internal {
+                self.record_query(cx);
+            }
+            let keyword = self.text_input.read(cx).content.as_str();
             if let Some(action) = self.context_actions.get(idx) {
+                if action.method == PIN_METHOD {
+                    self.toggle_pin(cx);
+                    return;
+                }
+                if action.method == COPY_OUTPUT_METHOD {
+                    self.copy_output(cx);
+                    return;
+                }
                 if let Some(selected) = self
                     .data
                     .read(cx)
                     .get(self.filtered_indices[self.selected_index])
                 {
-                    match selected.execute_action(action) {
-                        Ok(exit) if exit => self.close_window(win, cx),
+                    match selected.execute_action(action, keyword, cx) {
+                        Ok(exit) if exit && !inplace => self.close_window(win, cx),
                         Err(e) => eprintln!("{e}"),
                         _ => {}
                     }
                 }
             }
         } else {
+            let message = self
+                .data
+                .read(cx)
+                .get(data_idx)
+                .and_then(|child| child.confirm().map(str::to_string));
+            if !confirmed && let Some(message) = message {
+                self.ask_confirmation(message, data_idx, cx);
+                return;
+            }
+            if self
+                .data
+                .read(cx)
+                .get(data_idx)
+                .is_some_and(RenderableChild::shows_output)
+            {
+                self.toggle_output(data_idx, cx);
+                return;
+            }
+            if self
+                .data
+                .read(cx)
+                .get(data_idx)
+                .is_some_and(RenderableChild::is_expandable)
+            {
+                self.data.update(cx, |items_arc, cx| {
+                    Arc::make_mut(items_arc)[data_idx].toggle_expanded();
+                    cx.notify();
+                });
+                // The list has to measure the tile again
+                let idx = self.selected_index;
+                self.list_state.splice(idx..idx + 1, 1);
+                self.list_state.scroll_to_reveal_item(idx);
+                cx.notify();
+                return;
+            }
+
+            self.record_query(cx);
             let keyword = self.text_input.read(cx).content.as_str();
             // collect variables
             let mut variables: SmallVec<[(SharedString, SharedString); 4]> = SmallVec::new();
@@ -142,14 +317,209 @@ impl SherlockMainWindow {
                 .read(cx)
                 .get(self.filtered_indices[self.selected_index])
             {
-                match selected.execute(keyword, &variables) {
-                    Ok(exit) if exit => self.close_window(win, cx),
+                // Open windows of apps are switched to instead of launching them again
+                let result = match selected.focus_open_window(&self.open_windows) {
+                    true => Ok(true),
+                    false => selected.execute(keyword, &variables, cx),
+                };
+                match result {
+                    Ok(exit) if exit && !inplace => self.close_window(win, cx),
                     Err(e) => eprintln!("{e}"),
                     _ => {}
                 }
             }
         }
     }
+    /// Leaves the active mode and shows every launcher again
+    pub fn exit_mode(&mut self, cx: &mut Context<Self>) {
+        if self.mode != LauncherMode::Home {
+            self.mode = LauncherMode::Home;
+
+            // Propagate changes to ui
+            self.last_query = None;
+            self.selected_index = 0;
+            self.filter_and_sort(cx);
+        }
+    }
+    /// Selects the clicked result and runs it once it was clicked `behavior.n_clicks` times
+    pub fn click_item(
+        &mut self,
+        idx: usize,
+        clicks: usize,
+        win: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let n_clicks = ConfigGuard::read()
+            .map_or(Some(2), |c| c.behavior.n_clicks)
+            .unwrap_or(0);
+        self.context_idx = None;
+        self.focus_nth(idx, cx);
+        if n_clicks > 0 && clicks >= usize::from(n_clicks) {
+            self.run_selected(false, win, cx);
+        }
+    }
+    /// Runs the result whose shortcut was pressed, such as `ctrl-1` for the first one
+    pub(super) fn run_shortcut(
+        &mut self,
+        event: &KeyDownEvent,
+        win: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.context_idx.is_some() || self.show_errors {
+            return;
+        }
+        let Some(idx) = self
+            .shortcuts
+            .index_of(&event.keystroke, cx.keyboard_layout().name())
+        else {
+            return;
+        };
+        let has_shortcut = self
+            .filtered_indices
+            .get(idx)
+            .and_then(|i| self.data.read(cx).get(*i))
+            .is_some_and(RenderableChild::has_shortcut);
+        if has_shortcut {
+            cx.stop_propagation();
+            self.focus_nth(idx, cx);
+            self.run_selected(false, win, cx);
+        }
+    }
+    /// Dispatches keystrokes of non-Latin layouts again as their US equivalent, see
+    /// [`layout_fallback`]. Only reached by keystrokes no binding matched.
+    pub(super) fn translate_layout(
+        &mut self,
+        event: &KeyDownEvent,
+        win: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(keystroke) = layout_fallback(&event.keystroke) {
+            cx.stop_propagation();
+            win.defer(cx, move |win, cx| {
+                win.dispatch_keystroke(keystroke, cx);
+            });
+        }
+    }
+    /// Holds the entry back until it is run again, Escape cancels it
+    fn ask_confirmation(&mut self, message: String, data_idx: usize, cx: &mut Context<Self>) {
+        self.confirmation = Some(Confirmation {
+            message: SharedString::from(message),
+            data_idx,
+            context_idx: self.context_idx,
+        });
+        cx.notify();
+    }
+    /// Runs the command of a `show_output` child and shows what it printed inside its tile, or
+    /// collapses the output again
+    fn toggle_output(&mut self, data_idx: usize, cx: &mut Context<Self>) {
+        let Some(child) = self.data.read(cx).get(data_idx).cloned() else {
+            return;
+        };
+        if child.output().is_some() {
+            // Collapsing the tile kills the command if it still runs
+            if let Some(stop) = self.running_outputs.remove(&data_idx) {
+                stop.stop();
+            }
+            self.set_output(data_idx, None, cx);
+            return;
+        }
+
+        let keyword = self.text_input.read(cx).content.to_string();
+        let variables: Vec<(SharedString, SharedString)> = self
+            .variable_input
+            .iter()
+            .map(|input| {
+                let input = input.read(cx);
+                (input.placeholder.clone(), input.content.clone())
+            })
+            .collect();
+        self.set_output(data_idx, Some(SharedString::from("Running…")), cx);
+        let stop = StopToken::default();
+        self.running_outputs.insert(data_idx, stop.clone());
+
+        cx.spawn(
+            move |this: WeakEntity<SherlockMainWindow>, cx: &mut AsyncApp| {
+                let mut cx = cx.clone();
+                async move {
+                    let output = cx
+                        .background_executor()
+                        .spawn({
+                            let stop = stop.clone();
+                            async move { child.capture_output(&keyword, &variables, &stop) }
+                        })
+                        .await;
+                    // Collapsed while the command was still running
+                    if stop.is_stopped() {
+                        return;
+                    }
+                    let text = match output {
+                        Some(Ok(text)) if text.is_empty() => String::from("No output"),
+                        Some(Ok(text)) => text,
+                        Some(Err(e)) => e.to_string(),
+                        None => return,
+                    };
+                    let _ = this.update(&mut cx, |this, cx| {
+                        this.running_outputs.remove(&data_idx);
+                        this.set_output(data_idx, Some(SharedString::from(text)), cx);
+                    });
+                }
+            },
+        )
+        .detach();
+    }
+    fn set_output(
+        &mut self,
+        data_idx: usize,
+        output: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) {
+        self.data.update(cx, |items_arc, cx| {
+            if let Some(child) = Arc::make_mut(items_arc).get_mut(data_idx) {
+                child.set_output(output);
+            }
+            cx.notify();
+        });
+        // The list has to measure the tile again
+        if let Some(idx) = self.filtered_indices.iter().position(|i| *i == data_idx) {
+            self.list_state.splice(idx..idx + 1, 1);
+            self.list_state.scroll_to_reveal_item(idx);
+        }
+        self.update_context_actions(cx);
+        cx.notify();
+    }
+    /// Copies the output shown in the selected tile
+    fn copy_output(&mut self, cx: &mut Context<Self>) {
+        let output = self
+            .filtered_indices
+            .get(self.selected_index)
+            .and_then(|i| self.data.read(cx).get(*i))
+            .and_then(|child| child.output().cloned());
+        if let Some(output) = output {
+            cx.write_to_clipboard(ClipboardItem::new_string(output.to_string()));
+        }
+        self.context_idx = None;
+        cx.notify();
+    }
+    /// Pins the selected item to the home view or unpins it
+    fn toggle_pin(&mut self, cx: &mut Context<Self>) {
+        let key = self
+            .filtered_indices
+            .get(self.selected_index)
+            .and_then(|i| self.data.read(cx).get(*i))
+            .and_then(|child| child.pin_key().map(str::to_string));
+        let Some(key) = key else {
+            return;
+        };
+        match PinReader::new().and_then(|reader| reader.toggle(&key)) {
+            Ok(pins) => self.pins = Arc::from(pins),
+            Err(e) => eprintln!("{e}"),
+        }
+
+        self.context_idx = None;
+        self.focus_nth(self.selected_index, cx);
+        self.last_query = None;
+        self.filter_and_sort(cx);
+    }
     pub(super) fn open_context(
         &mut self,
         _: &OpenContext,
@@ -167,18 +537,52 @@ impl SherlockMainWindow {
 
         cx.notify();
     }
-    pub(super) fn close_context(&mut self, cx: &mut Context<Self>) {
+    pub(super) fn close_context(
+        &mut self,
+        _: &CloseContext,
+        _win: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
         if let Some(_) = self.context_idx.take() {
             cx.notify();
         }
     }
+    /// Switches between the results and the errors of the launcher configuration
+    pub(super) fn toggle_errors(
+        &mut self,
+        _: &ToggleErrors,
+        _win: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.show_errors = !self.show_errors && !self.errors.is_empty();
+        cx.notify();
+    }
     pub(super) fn quit(&mut self, _: &Quit, win: &mut Window, cx: &mut Context<Self>) {
-        if self.context_idx.is_some() {
-            self.close_context(cx);
+        if self.confirmation.take().is_some() {
+            cx.notify();
+        } else if self.context_idx.is_some() {
+            self.close_context(&CloseContext, win, cx);
+        } else if self.show_errors {
+            self.toggle_errors(&ToggleErrors, win, cx);
         } else {
             self.close_window(win, cx);
         }
     }
+    /// Left and right arrows of an empty search bar, used by tiles such as the music player
+    pub(super) fn left(&mut self, _: &Left, _: &mut Window, cx: &mut Context<Self>) {
+        self.arrow_key(false, cx);
+    }
+    pub(super) fn right(&mut self, _: &Right, _: &mut Window, cx: &mut Context<Self>) {
+        self.arrow_key(true, cx);
+    }
+    fn arrow_key(&mut self, forward: bool, cx: &mut Context<Self>) {
+        let Some(&idx) = self.filtered_indices.get(self.selected_index) else {
+            return;
+        };
+        if let Some(child) = self.data.read(cx).get(idx) {
+            child.arrow_key(forward, cx);
+        }
+    }
     pub(super) fn backspace(&mut self, _: &Backspace, win: &mut Window, cx: &mut Context<Self>) {
         println!("testing");
         cx.stop_propagation();
@@ -190,6 +594,10 @@ impl SherlockMainWindow {
         if let Some(task) = self.deferred_render_task.take() {
             drop(task)
         }
+        self.filter_generation.fetch_add(1, Ordering::Relaxed);
+        for (_, stop) in self.running_outputs.drain() {
+            stop.stop();
+        }
 
         // Close window
         win.remove_window();
@@ -212,24 +620,32 @@ impl SherlockMainWindow {
         if let Some(vars_to_create) = needed_vars {
             self.variable_input = vars_to_create
                 .into_iter()
-                .map(|var| {
-                    cx.new(|cx| TextInput {
-                        focus_handle: cx.focus_handle(),
-                        content: "".into(),
-                        placeholder: var.placeholder(),
-                        variable: Some(var),
-                        // Initialize your other fields here...
-                        selected_range: 0..0,
-                        selection_reversed: false,
-                        marked_range: None,
-                        last_layout: None,
-                        last_bounds: None,
-                        is_selecting: false,
-                    })
-                })
+                .map(|var| cx.new(|cx| TextInput::for_variable(var, cx)))
                 .collect();
         } else {
             self.variable_input.clear();
         }
     }
 }
+
+/// Position in a history of `len` queries after stepping back or forth from `pos`, where `None`
+/// is the empty query after the most recent one. `None` if there is nowhere to go.
+fn history_step(len: usize, pos: Option<usize>, back: bool) -> Option<Option<usize>> {
+    match (pos, back) {
+        (None, true) => len.checked_sub(1).map(Some),
+        (Some(pos), true) => pos.checked_sub(1).map(Some),
+        (None, false) => None,
+        (Some(pos), false) => Some((pos + 1 < len).then_some(pos + 1)),
+    }
+}
+
+#[test]
+fn test_history_step() {
+    assert_eq!(history_step(3, None, true), Some(Some(2)));
+    assert_eq!(history_step(3, Some(2), true), Some(Some(1)));
+    assert_eq!(history_step(3, Some(0), true), None);
+    assert_eq!(history_step(3, Some(1), false), Some(Some(2)));
+    assert_eq!(history_step(3, Some(2), false), Some(None));
+    assert_eq!(history_step(3, None, false), None);
+    assert_eq!(history_step(0, None, true), None);
+}
@@ -1,21 +1,40 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::time::{Duration, Instant};
 
 use crate::launcher::children::{LauncherValues, RenderableChild};
-use crate::launcher::children::{RenderableChildDelegate, SherlockSearch};
+use crate::launcher::children::{
+    MatchQuality, RenderableChildDelegate, SherlockSearch, allowed_typos,
+};
+use crate::launcher::github_launcher::GithubLauncher;
+use crate::launcher::nix_launcher::NixLauncher;
+use crate::launcher::{Launcher, LauncherType};
 use crate::loader::utils::{ApplicationAction, ExecVariable};
-use crate::utils::config::HomeType;
+use crate::ui::keybinds::Shortcuts;
+use crate::ui::typography::Typography;
+use crate::utils::config::{ConfigGuard, HomeType};
+use crate::utils::errors::SherlockError;
+use crate::utils::watch::StopToken;
+use crate::utils::windows::{OpenWindow, open_windows};
 use gpui::{App, Context, Entity, FocusHandle, Focusable, ListState, SharedString, Subscription};
 use gpui::{AppContext, WeakEntity};
 use gpui::{AsyncApp, Task};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use simd_json::prelude::Indexed;
 
+use crate::ui::icon_cache::IconCache;
 use crate::ui::search_bar::TextInput;
 
 pub mod actions;
 pub mod render;
 
-pub use actions::{Execute, FocusNext, FocusPrev, NextVar, OpenContext, PrevVar, Quit};
+pub use actions::{
+    CloseContext, Execute, ExecuteInplace, FocusNext, FocusPrev, HistoryNext, HistoryPrev, NextVar,
+    OpenContext, PrevVar, Quit, ToggleErrors,
+};
 
 pub struct SherlockMainWindow {
     pub text_input: Entity<TextInput>,
@@ -31,16 +50,49 @@ pub struct SherlockMainWindow {
     // context menu
     pub context_idx: Option<usize>,
     pub context_actions: Arc<[Arc<ApplicationAction>]>,
+    /// Windows open when the launcher was shown
+    pub open_windows: Arc<[OpenWindow]>,
+    /// Items pinned to the top of the home view
+    pub pins: Arc<[String]>,
+    /// Queries that ran an item, the most recent last
+    pub history: Arc<[String]>,
+    /// Entry of the history shown in the search bar
+    pub history_pos: Option<usize>,
+
+    // error view
+    /// Non-breaking errors of the launcher configuration
+    pub errors: Arc<[SherlockError]>,
+    pub show_errors: bool,
+
+    /// Entry waiting for a yes or no before it runs
+    pub confirmation: Option<Confirmation>,
+    /// Modifier and digit keys that run the first results
+    pub shortcuts: Shortcuts,
+    /// Font family and text scale of the `appearance` config
+    pub typography: Typography,
+    /// Alpha of the window background, see `appearance.opacity`
+    pub background_opacity: f32,
 
     // variable input fields
     pub variable_input: Vec<Entity<TextInput>>,
     pub active_bar: usize,
 
     // Model
+    pub icon_cache: Entity<IconCache>,
     pub deferred_render_task: Option<Task<Option<()>>>,
+    /// Commands whose output is being captured, by the index of their tile. Collapsing the tile
+    /// stops them.
+    pub running_outputs: HashMap<usize, StopToken>,
+    pub filter_generation: Arc<AtomicUsize>,
     pub data: Entity<Arc<Vec<RenderableChild>>>,
     pub filtered_indices: Arc<[usize]>,
     pub last_query: Option<String>,
+    /// How long the last filter pass took
+    pub filter_time: Option<Duration>,
+    /// Query of the last search of the launchers that search it, see
+    /// [`search_launchers`](Self::search_launchers)
+    pub searched_query: Option<String>,
+    pub search_task: Option<Task<Option<()>>>,
 }
 
 impl Focusable for SherlockMainWindow {
@@ -50,7 +102,77 @@ impl Focusable for SherlockMainWindow {
 }
 
 impl SherlockMainWindow {
-    pub fn apply_results(&mut self, results: Arc<[usize]>, query: String, cx: &mut Context<Self>) {
+    /// Asks the compositor for the open windows in the background, so that running apps offer to
+    /// launch a new instance from their context menu
+    pub fn load_open_windows(&mut self, cx: &mut Context<Self>) {
+        if !ConfigGuard::read().map_or(true, |c| c.behavior.switch_to_window) {
+            return;
+        }
+        cx.spawn(
+            move |this: WeakEntity<SherlockMainWindow>, cx: &mut AsyncApp| {
+                let mut cx = cx.clone();
+                async move {
+                    let windows = cx
+                        .background_executor()
+                        .spawn(async move { open_windows() })
+                        .await;
+                    let _ = this.update(&mut cx, |this, cx| {
+                        this.open_windows = windows.into();
+                        this.focus_nth(this.selected_index, cx);
+                    });
+                }
+            },
+        )
+        .detach();
+    }
+    /// Alternates the placeholder of the search bar with [`placeholder_hints`], if
+    /// `appearance.placeholder_hints` is set
+    pub fn cycle_placeholder_hints(&mut self, cx: &mut Context<Self>) {
+        let Some(placeholder) = ConfigGuard::read()
+            .ok()
+            .filter(|c| c.appearance.placeholder_hints)
+            .map(|c| SharedString::from(c.appearance.placeholder.clone()))
+        else {
+            return;
+        };
+        cx.spawn(
+            move |this: WeakEntity<SherlockMainWindow>, cx: &mut AsyncApp| {
+                let mut cx = cx.clone();
+                async move {
+                    for tick in 1usize.. {
+                        cx.background_executor().timer(HINT_INTERVAL).await;
+                        let updated = this.update(&mut cx, |this, cx| {
+                            // Launchers may have loaded or reloaded in the meantime
+                            let data = this.data.read(cx);
+                            let hints = placeholder_hints(
+                                &this.modes,
+                                data.iter().map(|child| child.launcher_type()),
+                            );
+                            let hint = match tick % (hints.len() + 1) {
+                                0 => placeholder.clone(),
+                                i => hints[i - 1].clone(),
+                            };
+                            // Notifying the input would filter again
+                            this.text_input
+                                .update(cx, |input, _| input.placeholder = hint);
+                            cx.notify();
+                        });
+                        if updated.is_err() {
+                            break;
+                        }
+                    }
+                }
+            },
+        )
+        .detach();
+    }
+    pub fn apply_results(
+        &mut self,
+        results: Arc<[usize]>,
+        query: String,
+        elapsed: Duration,
+        cx: &mut Context<Self>,
+    ) {
         let old_count = self.list_state.item_count();
         let new_count = results.len();
 
@@ -67,20 +189,7 @@ impl SherlockMainWindow {
                 if current_top_idx != Some(first_idx) {
                     self.variable_input = vars_to_create
                         .into_iter()
-                        .map(|var| {
-                            cx.new(|cx| TextInput {
-                                focus_handle: cx.focus_handle(),
-                                content: "".into(),
-                                placeholder: var.placeholder(),
-                                variable: Some(var),
-                                selected_range: 0..0,
-                                selection_reversed: false,
-                                marked_range: None,
-                                last_layout: None,
-                                last_bounds: None,
-                                is_selecting: false,
-                            })
-                        })
+                        .map(|var| cx.new(|cx| TextInput::for_variable(var, cx)))
                         .collect();
                 }
             } else {
@@ -92,6 +201,7 @@ impl SherlockMainWindow {
         self.active_bar = 0;
         self.filtered_indices = results;
         self.last_query = Some(query);
+        self.filter_time = Some(elapsed);
 
         self.list_state.splice(0..old_count, new_count);
         self.list_state.scroll_to_reveal_item(self.selected_index);
@@ -116,80 +226,210 @@ impl SherlockMainWindow {
             });
             query = "".into();
         }
+        // Not notified, as that would filter again
+        let completion = LauncherMode::complete_alias(&query, &self.modes);
+        self.text_input
+            .update(cx, |this, _cx| this.completion = completion);
+        self.search_launchers(&query, cx);
+
+        let (debounce, typo_tolerance, home_order) = ConfigGuard::read()
+            .map(|c| {
+                (
+                    c.behavior.search_debounce,
+                    c.behavior.typo_tolerance,
+                    c.behavior.home_order.clone(),
+                )
+            })
+            .unwrap_or_default();
+        let pins = Arc::clone(&self.pins);
+
+        // If the query only grew, every match must have been part of the last results already.
+        // Children with a based show (calc for example) are the exception and always rechecked.
+        // A growing query can also unlock further typos, which again widens the results.
+        let candidates: Option<Vec<usize>> =
+            self.last_query
+                .as_deref()
+                .filter(|prev| {
+                    !prev.is_empty()
+                        && query.starts_with(prev)
+                        && allowed_typos(prev, typo_tolerance)
+                            == allowed_typos(&query, typo_tolerance)
+                })
+                .map(|_| {
+                    let data = self.data.read(cx);
+                    let mut candidates = self.filtered_indices.to_vec();
+                    candidates.extend((0..data.len()).filter(|i| {
+                        data[*i].has_based_show() && !self.filtered_indices.contains(i)
+                    }));
+                    candidates
+                });
+
+        // Invalidate every filter pass that is still in flight
+        let generation = self.filter_generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let current_generation = Arc::clone(&self.filter_generation);
 
         let data_arc = self.data.read(cx).clone();
         let mode = self.mode.clone();
         self.deferred_render_task = Some(cx.spawn(
-            |this: WeakEntity<SherlockMainWindow>, cx: &mut AsyncApp| {
+            move |this: WeakEntity<SherlockMainWindow>, cx: &mut AsyncApp| {
                 let mut cx = cx.clone();
                 async move {
-                    let mode = mode.as_str();
-                    let is_home = query.is_empty() && mode == "all";
-
-                    // collects Vec<(index, priority)>
-                    let mut results: Vec<(usize, f32)> = (0..data_arc.len())
-                        .into_par_iter()
-                        .map(|i| (i, &data_arc[i]))
-                        .filter(|(_, data)| {
-                            let home = data.home();
-
-                            // [Rule 1]
-                            // Case 1: Early return if mode applies but item is not assigned to that mode
-                            // Case 2: Early return if current mode is not required mode for item
-                            if Some(mode) != data.alias() {
-                                if mode != "all" || data.priority() < 1.0 {
-                                    return false;
-                                }
-                            }
-
-                            // [Rule 2]
-                            // Early return if item should always show (websearch for example)
-                            if home == HomeType::Persist {
-                                return true;
-                            }
-
-                            // [Rule 3]
-                            // Early return if based show (calc for example) applies
-                            if let Some(based) = data.based_show(&query) {
-                                return based;
-                            }
-
-                            // [Rule 4]
-                            // Early return if not home but item is assigned to only show on home
-                            if !is_home && home == HomeType::OnlyHome {
-                                return false;
-                            }
+                    if debounce > 0 {
+                        cx.background_executor()
+                            .timer(Duration::from_millis(debounce))
+                            .await;
+                    }
+                    if current_generation.load(Ordering::Relaxed) != generation {
+                        return None;
+                    }
 
-                            // [Rule 5]
-                            // Early return if item should only show on search but mode is home
-                            if is_home && home == HomeType::Search {
-                                return false;
+                    let started = Instant::now();
+                    let query_clone = query.clone();
+                    let results_arc = cx
+                        .background_executor()
+                        .spawn(async move {
+                            let query = query_clone;
+                            let is_stale =
+                                || current_generation.load(Ordering::Relaxed) != generation;
+                            let mode = mode.as_str();
+                            let is_home = query.is_empty() && mode == "all";
+                            let max_typos = allowed_typos(&query, typo_tolerance);
+
+                            let candidates =
+                                candidates.unwrap_or_else(|| (0..data_arc.len()).collect());
+
+                            // collects Vec<(index, priority, match quality)>
+                            let mut results: Vec<(usize, f32, MatchQuality)> = candidates
+                                .into_par_iter()
+                                .map(|i| (i, &data_arc[i]))
+                                .filter_map(|(i, data)| {
+                                    // Bail out cheaply once a newer query has been typed
+                                    if is_stale() {
+                                        return None;
+                                    }
+
+                                    let home = data.home();
+
+                                    // Tiles that skip the matcher keep their configured position
+                                    let unmatched = Some((i, data.priority(), MatchQuality::Exact));
+
+                                    // [Rule 1]
+                                    // Case 1: Early return if mode applies but item is not assigned to that mode
+                                    // Case 2: Early return if current mode is not required mode for item
+                                    if Some(mode) != data.alias() {
+                                        if mode != "all" || data.priority() < 1.0 {
+                                            return None;
+                                        }
+                                    }
+
+                                    // [Rule 2]
+                                    // Early return if item should always show (websearch for example)
+                                    if home == HomeType::Persist {
+                                        return unmatched;
+                                    }
+                                    // Pinned items lead the home view regardless of their home type
+                                    if is_home && data.home_rank(&pins, &home_order).is_some() {
+                                        return unmatched;
+                                    }
+
+                                    // [Rule 3]
+                                    // Early return if based show (calc for example) applies
+                                    if let Some(based) = data.based_show(&query) {
+                                        return if based { unmatched } else { None };
+                                    }
+
+                                    // [Rule 4]
+                                    // Early return if not home but item is assigned to only show on home
+                                    if !is_home && home == HomeType::OnlyHome {
+                                        return None;
+                                    }
+
+                                    // [Rule 5]
+                                    // Early return if item should only show on search but mode is home
+                                    if is_home && home == HomeType::Search {
+                                        return None;
+                                    }
+
+                                    // [Rule 6]
+                                    // Check if query matches
+                                    let search = data.search();
+                                    let quality = search.match_quality(&query).or_else(|| {
+                                        search
+                                            .typo_match(&query, max_typos)
+                                            .then_some(MatchQuality::Typo)
+                                    })?;
+                                    // Keyword boosts lower the priority, moving the item up
+                                    Some((i, data.priority() - data.boost(&query), quality))
+                                })
+                                .collect();
+
+                            // results of an outdated pass are incomplete
+                            if is_stale() {
+                                return None;
                             }
 
-                            // [Rule 6]
-                            // Check if query matches
-                            data.search().fuzzy_match(&query)
+                            // Pins and the `home_order` go first on the home view
+                            let ranks: HashMap<usize, usize> = if is_home {
+                                results
+                                    .iter()
+                                    .filter_map(|(i, _, _)| {
+                                        Some((*i, data_arc[*i].home_rank(&pins, &home_order)?))
+                                    })
+                                    .collect()
+                            } else {
+                                HashMap::new()
+                            };
+                            let rank = |(i, _, _): &(usize, f32, MatchQuality)| {
+                                ranks.get(i).copied().unwrap_or(usize::MAX)
+                            };
+
+                            // sort by weighted launcher priority first, then by how well the
+                            // query matched and by the count-based priority. Ties are broken
+                            // alphabetically so the list does not jump between keystrokes.
+                            let band = |(i, priority, _): &(usize, f32, MatchQuality)| {
+                                priority.floor() / data_arc[*i].weight()
+                            };
+                            results.sort_unstable_by(|a, b| {
+                                rank(a)
+                                    .cmp(&rank(b))
+                                    .then_with(|| {
+                                        band(a)
+                                            .partial_cmp(&band(b))
+                                            .unwrap_or(std::cmp::Ordering::Equal)
+                                    })
+                                    .then_with(|| b.2.cmp(&a.2))
+                                    .then_with(|| {
+                                        a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)
+                                    })
+                                    .then_with(|| {
+                                        let lower = |i: usize| {
+                                            data_arc[i]
+                                                .title()
+                                                .unwrap_or_default()
+                                                .chars()
+                                                .flat_map(char::to_lowercase)
+                                        };
+                                        lower(a.0).cmp(lower(b.0))
+                                    })
+                                    .then_with(|| a.0.cmp(&b.0))
+                            });
+
+                            // drop here to release lock faster
+                            drop(data_arc);
+
+                            // strip the priority from results
+                            let results_arc: Arc<[usize]> = results
+                                .into_iter()
+                                .map(|(i, _, _)| i)
+                                .collect::<Vec<_>>()
+                                .into();
+
+                            Some(results_arc)
                         })
-                        .map(|(i, data)| (i, data.priority()))
-                        .collect();
-
-                    // drop here to release lock faster
-                    drop(data_arc);
-
-                    // sort based on priority
-                    results.sort_unstable_by(|a, b| {
-                        a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)
-                    });
-
-                    // strip the priority from results
-                    let results_arc: Arc<[usize]> = results
-                        .into_iter()
-                        .map(|(i, _)| i)
-                        .collect::<Vec<_>>()
-                        .into();
+                        .await?;
 
                     this.update(&mut cx, |this, cx| {
-                        this.apply_results(results_arc, query, cx);
+                        this.apply_results(results_arc, query, started.elapsed(), cx);
                     })
                     .ok();
 
@@ -200,6 +440,136 @@ impl SherlockMainWindow {
     }
 }
 
+impl SherlockMainWindow {
+    /// Searches the query with the launchers of the active mode whose items depend on it, such
+    /// as the GitHub, mail or Nix launcher, once it rested for [`SEARCH_DELAY`]. Their results take
+    /// the place of the previous ones.
+    pub fn search_launchers(&mut self, query: &str, cx: &mut Context<Self>) {
+        if self.searched_query.as_deref() == Some(query) {
+            return;
+        }
+        self.searched_query = Some(query.to_string());
+        // Dropping the task cancels the search of the previous query
+        self.search_task = None;
+        if !self.mode.is_active() || query.chars().count() < SEARCH_MIN_CHARS {
+            return;
+        }
+
+        let mut launchers: Vec<Arc<Launcher>> = Vec::new();
+        for child in self.data.read(cx).iter() {
+            if let RenderableChild::AppLike { launcher, .. } = child
+                && launcher.launcher_type.searches_query()
+                && launcher.alias.as_deref() == Some(self.mode.as_str())
+                && !launchers.iter().any(|known| Arc::ptr_eq(known, launcher))
+            {
+                launchers.push(Arc::clone(launcher));
+            }
+        }
+        if launchers.is_empty() {
+            return;
+        }
+
+        let query = query.to_string();
+        self.search_task = Some(cx.spawn(
+            move |this: WeakEntity<SherlockMainWindow>, cx: &mut AsyncApp| {
+                let mut cx = cx.clone();
+                async move {
+                    cx.background_executor().timer(SEARCH_DELAY).await;
+                    for launcher in launchers {
+                        let children = match &launcher.launcher_type {
+                            LauncherType::Github(github) => github
+                                .search(&query)
+                                .await
+                                .map(|items| GithubLauncher::children(&launcher, &items, &query)),
+                            LauncherType::Mail(mail) => {
+                                let (searcher, mail_query) = (mail.clone(), query.clone());
+                                cx.background_executor()
+                                    .spawn(async move { searcher.search(&mail_query) })
+                                    .await
+                                    .map(|messages| mail.children(&launcher, &messages, &query))
+                            }
+                            LauncherType::Nix(nix) => {
+                                let (searcher, nix_query) = (nix.clone(), query.clone());
+                                let packages = cx
+                                    .background_executor()
+                                    .spawn(async move { searcher.search(&nix_query) })
+                                    .await;
+                                // Failures are shown in place of the packages, since a flake
+                                // that does not evaluate would leave the mode empty otherwise
+                                Ok(match packages {
+                                    Ok(packages) => nix.children(&launcher, &packages, &query),
+                                    Err(e) => vec![NixLauncher::failure(&launcher, &e)],
+                                })
+                            }
+                            _ => continue,
+                        };
+                        let children = match children {
+                            Ok(children) => children,
+                            Err(e) => {
+                                eprintln!("{e}");
+                                continue;
+                            }
+                        };
+                        this.update(&mut cx, |this, cx| {
+                            this.data.update(cx, |items_arc, cx| {
+                                let items = Arc::make_mut(items_arc);
+                                let start = items
+                                    .iter()
+                                    .position(|item| item.is_from(&launcher))
+                                    .unwrap_or(items.len());
+                                items.retain(|item| !item.is_from(&launcher));
+                                items.splice(start..start, children);
+                                cx.notify();
+                            });
+                            // The indices of the results changed
+                            this.last_query = None;
+                            this.filter_and_sort(cx);
+                        })
+                        .ok()?;
+                    }
+                    Some(())
+                }
+            },
+        ));
+    }
+}
+
+/// How long the query has to rest before the launchers search it
+const SEARCH_DELAY: Duration = Duration::from_millis(500);
+/// Shorter queries are not searched
+const SEARCH_MIN_CHARS: usize = 3;
+
+/// The question of an entry with a `confirm` message, answered by running the entry again
+pub struct Confirmation {
+    pub message: SharedString,
+    pub data_idx: usize,
+    /// Action of the context menu the question is about
+    pub context_idx: Option<usize>,
+}
+
+/// How long each placeholder hint is shown
+const HINT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Hints on what to type, for the loaded modes and the launchers of the given types
+pub fn placeholder_hints<'a>(
+    modes: &[LauncherMode],
+    types: impl IntoIterator<Item = &'a LauncherType>,
+) -> Vec<SharedString> {
+    let mut hints: Vec<SharedString> = Vec::new();
+    if types
+        .into_iter()
+        .any(|kind| matches!(kind, LauncherType::Calc(_)))
+    {
+        hints.push("Try '50 eur to usd'".into());
+        hints.push("Try '5 miles in km'".into());
+    }
+    hints.extend(modes.iter().filter_map(|mode| match mode {
+        LauncherMode::Alias { short, name } => Some(format!("Type '{short} ' for {name}").into()),
+        _ => None,
+    }));
+    hints
+}
+
 #[derive(PartialEq, Eq, Clone)]
 pub enum LauncherMode {
     Home,
@@ -228,6 +598,21 @@ impl LauncherMode {
             Self::Alias { name, .. } => name.clone(),
         }
     }
+    /// Rest of the first mode alias the query is the start of, e.g. `m` for `p` and `pm`
+    pub fn complete_alias(query: &str, modes: &[Self]) -> Option<SharedString> {
+        if query.is_empty() || query.contains(char::is_whitespace) {
+            return None;
+        }
+        modes.iter().find_map(|mode| {
+            let Self::Alias { short, .. } = mode else {
+                return None;
+            };
+            let rest = short.get(query.len()..).filter(|rest| !rest.is_empty())?;
+            short[..query.len()]
+                .eq_ignore_ascii_case(query)
+                .then(|| SharedString::from(rest.to_string()))
+        })
+    }
     pub fn transition_for_query(&mut self, query: &str, modes: &[Self]) -> bool {
         match (self, query.is_empty()) {
             (m @ Self::Search, true) => *m = Self::Home,
@@ -256,3 +641,42 @@ impl LauncherMode {
         false
     }
 }
+
+#[test]
+fn test_complete_alias() {
+    let alias = |short: &str| LauncherMode::Alias {
+        short: short.to_string().into(),
+        name: "Mode".into(),
+    };
+    let modes = [LauncherMode::Home, alias("pm"), alias("wé")];
+    let complete = |query: &str| LauncherMode::complete_alias(query, &modes);
+    assert_eq!(complete("P"), Some(SharedString::from("m")));
+    assert_eq!(complete("w"), Some(SharedString::from("é")));
+    assert_eq!(complete("pm"), None);
+    assert_eq!(complete("p m"), None);
+    assert_eq!(complete(""), None);
+}
+
+#[test]
+fn test_placeholder_hints() {
+    use crate::launcher::calc_launcher::CalculatorLauncher;
+
+    let modes = [
+        LauncherMode::Home,
+        LauncherMode::Alias {
+            short: "pm".into(),
+            name: "Power Menu".into(),
+        },
+    ];
+    let hints = placeholder_hints(&modes, &[LauncherType::Empty]);
+    assert_eq!(hints, vec![SharedString::from("Type 'pm ' for Power Menu")]);
+
+    let calc = [
+        LauncherType::Calc(CalculatorLauncher {}),
+        LauncherType::Empty,
+    ];
+    let hints = placeholder_hints(&modes, &calc);
+    assert_eq!(hints.len(), 3);
+    assert_eq!(hints[0], SharedString::from("Try '50 eur to usd'"));
+    assert!(placeholder_hints(&[], &[]).is_empty());
+}
@@ -1,26 +1,33 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use gpui::{
-    AnyElement, Context, Element, Focusable, FontWeight, Image, ImageSource, InteractiveElement,
-    IntoElement, ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Window,
-    div, hsla, img, list, px, relative, rgb,
+    AnyElement, Context, Div, Element, Focusable, FontWeight, Image, ImageSource,
+    InteractiveElement, IntoElement, ParentElement, Render, SharedString,
+    StatefulInteractiveElement, Styled, WeakEntity, Window, div, hsla, image_cache, img, list, px,
+    relative, rgb,
 };
 
 use crate::{
     launcher::children::{RenderableChild, RenderableChildDelegate},
-    ui::main_window::SherlockMainWindow,
+    ui::{
+        keybinds::Shortcuts,
+        main_window::{SherlockMainWindow, ToggleErrors},
+        typography::text_px,
+    },
 };
 
 impl Render for SherlockMainWindow {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        // Every text size is relative to the root font
+        window.set_rem_size(self.typography.rem_size());
         let weak_self = cx.entity().downgrade();
-        div()
+        let root = div()
             .id("sherlock")
             .track_focus(&self.focus_handle(cx))
             .flex()
             .flex_col()
             .size_full()
-            .bg(rgb(0x0F0F0F))
+            .bg(hsla(0., 0., 0.0588, self.background_opacity))
             .border_2()
             .border_color(hsla(0., 0., 0.1882, 1.0))
             .rounded(px(5.))
@@ -28,12 +35,21 @@ impl Render for SherlockMainWindow {
             .overflow_hidden()
             .on_action(cx.listener(Self::focus_next))
             .on_action(cx.listener(Self::focus_prev))
+            .on_action(cx.listener(Self::history_prev))
+            .on_action(cx.listener(Self::history_next))
             .on_action(cx.listener(Self::next_var))
             .on_action(cx.listener(Self::prev_var))
             .on_action(cx.listener(Self::execute))
+            .on_action(cx.listener(Self::execute_inplace))
             .on_action(cx.listener(Self::quit))
             .on_action(cx.listener(Self::open_context))
+            .on_action(cx.listener(Self::close_context))
+            .on_action(cx.listener(Self::toggle_errors))
             .on_action(cx.listener(Self::backspace))
+            .on_action(cx.listener(Self::left))
+            .on_action(cx.listener(Self::right))
+            .on_key_down(cx.listener(Self::run_shortcut))
+            .on_key_down(cx.listener(Self::translate_layout))
             .child(
                 // search bar
                 div()
@@ -45,6 +61,27 @@ impl Render for SherlockMainWindow {
                     .py(px(4.))
                     .gap_3()
                     .child(div().text_color(rgb(0x888888)).child(""))
+                    .children(self.mode.is_active().then(|| {
+                        // Same as a backspace into the empty bar
+                        div()
+                            .id("mode-chip")
+                            .flex()
+                            .flex_none()
+                            .items_center()
+                            .gap(px(6.))
+                            .px(px(8.))
+                            .py(px(3.))
+                            .rounded_full()
+                            .bg(rgb(0x262626))
+                            .text_size(text_px(12.))
+                            .line_height(relative(1.0))
+                            .text_color(hsla(0.0, 0.0, 0.8, 1.0))
+                            .cursor_pointer()
+                            .hover(|s| s.bg(hsla(0., 0., 0.2, 1.0)))
+                            .on_click(cx.listener(|this, _, _, cx| this.exit_mode(cx)))
+                            .child(self.mode.display_str())
+                            .child(div().text_color(rgb(0x888888)).child("×"))
+                    }))
                     .child(div().w_auto().child(self.text_input.clone()))
                     .children(self.variable_input.iter().cloned())
                     .border_b_2()
@@ -54,12 +91,14 @@ impl Render for SherlockMainWindow {
                 div()
                     .px(px(14.))
                     .py(px(4.))
-                    .text_size(px(14.))
+                    .text_size(text_px(14.))
                     .font_weight(FontWeight::BOLD)
                     .text_color(rgb(0x2e2e2e))
                     .child(self.mode.display_str()),
             )
-            .child(
+            .child(if self.show_errors {
+                self.render_errors()
+            } else {
                 div()
                     .id("results-container")
                     .flex_1()
@@ -86,7 +125,8 @@ impl Render for SherlockMainWindow {
                                 None => return div().into_any_element(),
                             };
 
-                            state.render_list_item(&child, idx)
+                            let keyword = state.text_input.read(cx).content.as_str();
+                            state.render_list_item(&child, idx, keyword, weak_self.clone())
                         })
                         .size_full(),
                     )
@@ -121,7 +161,7 @@ impl Render for SherlockMainWindow {
                                             } else {
                                                 hsla(0.6, 0.0217, 0.3608, 1.0)
                                             })
-                                            .text_size(px(13.))
+                                            .text_size(text_px(13.))
                                             .line_height(relative(1.0))
                                             .items_center()
                                             .bg(if is_selected {
@@ -151,8 +191,14 @@ impl Render for SherlockMainWindow {
                         )
                     } else {
                         div()
-                    }),
-            )
+                    })
+                    .children(
+                        self.confirmation
+                            .as_ref()
+                            .map(|confirmation| render_confirmation(confirmation.message.clone())),
+                    )
+                    .into_any_element()
+            })
             .child(
                 // statusbar
                 div()
@@ -160,15 +206,58 @@ impl Render for SherlockMainWindow {
                     .line_height(px(30.))
                     .w_full()
                     .flex()
-                    .bg(hsla(0., 0., 0.098, 1.0))
+                    .bg(hsla(0., 0., 0.098, self.background_opacity))
                     .border_t_1()
                     .border_color(hsla(0., 0., 0.1882, 1.0))
                     .px_5()
-                    .text_size(px(13.))
+                    .text_size(text_px(13.))
                     .items_center()
                     .text_color(hsla(0.6, 0.0217, 0.3608, 1.0))
                     .child(String::from("Sherlock"))
+                    .child(if self.errors.is_empty() {
+                        div().into_any_element()
+                    } else {
+                        let count = self.errors.len();
+                        div()
+                            .id("error-indicator")
+                            .ml_4()
+                            .cursor_pointer()
+                            .text_color(hsla(0.0, 0.6, 0.6, 1.0))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(Box::new(ToggleErrors), cx)
+                            })
+                            .child(format!(
+                                "⚠ {count} {}",
+                                if count == 1 { "Issue" } else { "Issues" }
+                            ))
+                            .into_any_element()
+                    })
+                    .children(self.filter_time.map(|elapsed| {
+                        div()
+                            .ml_4()
+                            .child(result_summary(self.filtered_indices.len(), elapsed))
+                    }))
                     .child(div().flex_1())
+                    .child({
+                        let count = self.shortcuts.count.min(self.filtered_indices.len());
+                        if count > 0 && !self.show_errors {
+                            let digits = if count == 1 {
+                                Shortcuts::digit(0)
+                            } else {
+                                format!("1…{}", Shortcuts::digit(count - 1)).into()
+                            };
+                            div()
+                                .flex()
+                                .items_center()
+                                .gap(px(5.))
+                                .mr_4()
+                                .child(div().mr_1().child(SharedString::from("Quick Launch")))
+                                .child(keybind_box(self.shortcuts.symbol.clone()))
+                                .child(keybind_box(digits))
+                        } else {
+                            div()
+                        }
+                    })
                     .child({
                         let guard = self.data.read(cx);
                         if let Some(true) = self
@@ -189,52 +278,206 @@ impl Render for SherlockMainWindow {
                             div()
                         }
                     }),
-            )
+            );
+
+        let root = match self.typography.family.clone() {
+            Some(family) => root.font_family(family),
+            None => root,
+        };
+        image_cache(self.icon_cache.clone()).size_full().child(root)
     }
 }
 
-fn keybind_box(text: &'static str) -> impl Element {
+/// Size and duration of the last filter pass, e.g. `42 results · 3 ms`
+fn result_summary(count: usize, elapsed: Duration) -> String {
+    let noun = if count == 1 { "result" } else { "results" };
+    match elapsed.as_millis() {
+        0 => format!("{count} {noun} · <1 ms"),
+        ms => format!("{count} {noun} · {ms} ms"),
+    }
+}
+
+/// Label of `tag_start` and `tag_end` at the edges of a row
+fn tag_label(text: SharedString) -> Div {
+    div()
+        .flex_none()
+        .max_w(px(160.))
+        .px(px(6.))
+        .py(px(3.))
+        .rounded_sm()
+        .bg(rgb(0x262626))
+        .text_size(text_px(11.))
+        .line_height(relative(1.0))
+        .text_color(hsla(0.6, 0.0217, 0.5, 1.0))
+        .overflow_hidden()
+        .text_ellipsis()
+        .whitespace_nowrap()
+        .child(text)
+}
+
+fn keybind_box(text: impl Into<SharedString>) -> impl Element {
     div()
         .flex_none()
         .p(px(5.))
         .bg(rgb(0x262626))
         .rounded_sm()
-        .text_size(px(11.))
+        .text_size(text_px(11.))
         .line_height(relative(1.0))
-        .child(text)
+        .child(text.into())
+}
+
+/// Asks for a yes or no on top of the results, see [`Confirmation`](super::Confirmation)
+fn render_confirmation(message: SharedString) -> AnyElement {
+    div()
+        .inset_0()
+        .absolute()
+        .flex()
+        .items_center()
+        .justify_center()
+        .bg(hsla(0., 0., 0., 0.6))
+        .child(
+            div()
+                .p(px(14.))
+                .max_w(px(400.))
+                .bg(rgb(0x0F0F0F))
+                .border_color(hsla(0., 0., 0.1882, 1.0))
+                .border(px(1.))
+                .rounded_md()
+                .flex()
+                .flex_col()
+                .gap(px(12.))
+                .child(
+                    div()
+                        .text_size(text_px(14.))
+                        .text_color(hsla(0.0, 0.0, 0.8, 1.0))
+                        .child(message),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap(px(5.))
+                        .text_size(text_px(13.))
+                        .text_color(hsla(0.6, 0.0217, 0.3608, 1.0))
+                        .child(div().mr_1().child(SharedString::from("Confirm")))
+                        .child(keybind_box("↵"))
+                        .child(div().ml_3().mr_1().child(SharedString::from("Cancel")))
+                        .child(keybind_box("Esc")),
+                ),
+        )
+        .into_any_element()
 }
 
 impl SherlockMainWindow {
-    fn render_list_item(&self, ad: &RenderableChild, idx: usize) -> AnyElement {
-        let is_selected = self.selected_index == idx;
+    /// Lists the errors of the launcher configuration in place of the results
+    fn render_errors(&self) -> AnyElement {
         div()
-            .id(("keystroke", idx))
+            .id("error-view")
+            .flex_1()
+            .min_h_0()
+            .overflow_y_scroll()
+            .px(px(14.))
+            .py(px(4.))
+            .flex()
+            .flex_col()
+            .gap(px(10.))
+            .children(self.errors.iter().map(|error| {
+                let (_, message) = error.error.get_message();
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap(px(4.))
+                    .p(px(10.))
+                    .rounded_md()
+                    .bg(hsla(0., 0., 0.098, 1.0))
+                    .child(
+                        div()
+                            .text_size(text_px(14.))
+                            .text_color(hsla(0.0, 0.6, 0.6, 1.0))
+                            .child(message),
+                    )
+                    .child(
+                        div()
+                            .text_size(text_px(12.))
+                            .text_color(hsla(0.6, 0.0217, 0.3608, 1.0))
+                            .child(error.traceback.clone()),
+                    )
+            }))
+            .into_any_element()
+    }
+    fn render_list_item(
+        &self,
+        ad: &RenderableChild,
+        idx: usize,
+        keyword: &str,
+        view: WeakEntity<Self>,
+    ) -> AnyElement {
+        let is_selected = self.selected_index == idx;
+        let (tag_start, tag_end) = ad.tags(keyword);
+        // List items are laid out outside of the window's image cache scope
+        image_cache(self.icon_cache.clone())
             .w_full()
-            .on_click(move |_, _, _| {
-                println!("Clicked item {}", idx);
-            })
             .child(
                 div()
-                    .group("")
-                    .rounded_md()
-                    .relative()
-                    .mb(px(5.0))
+                    .id(("keystroke", idx))
                     .w_full()
-                    .cursor_pointer()
-                    .bg(if is_selected {
-                        hsla(0., 0., 0.149, 1.0)
-                    } else {
-                        hsla(0., 0., 0., 0.)
-                    })
-                    .hover(|s| {
-                        if is_selected || self.context_idx.is_some() {
-                            s
-                        } else {
-                            s.bg(hsla(0., 0., 0.12, 1.0))
-                        }
+                    .on_click(move |event, window, cx| {
+                        let _ = view.update(cx, |this, cx| {
+                            this.click_item(idx, event.click_count(), window, cx)
+                        });
                     })
-                    .child(ad.render(is_selected)),
+                    .child(
+                        div()
+                            .group("")
+                            .rounded_md()
+                            .relative()
+                            .mb(px(5.0))
+                            .w_full()
+                            .cursor_pointer()
+                            .bg(if is_selected {
+                                hsla(0., 0., 0.149, 1.0)
+                            } else {
+                                hsla(0., 0., 0., 0.)
+                            })
+                            .hover(|s| {
+                                if is_selected || self.context_idx.is_some() {
+                                    s
+                                } else {
+                                    s.bg(hsla(0., 0., 0.12, 1.0))
+                                }
+                            })
+                            .flex()
+                            .items_center()
+                            .children(tag_start.map(|tag| tag_label(tag).ml(px(10.))))
+                            .child(div().flex_1().min_w_0().child(ad.render(is_selected)))
+                            .children(tag_end.map(|tag| tag_label(tag).mr(px(10.))))
+                            .children((idx < self.shortcuts.count && ad.has_shortcut()).then(
+                                || {
+                                    div()
+                                        .flex()
+                                        .flex_none()
+                                        .items_center()
+                                        .gap(px(3.))
+                                        .mr(px(10.))
+                                        .text_color(hsla(0.6, 0.0217, 0.3608, 1.0))
+                                        .child(keybind_box(self.shortcuts.symbol.clone()))
+                                        .child(keybind_box(Shortcuts::digit(idx)))
+                                },
+                            )),
+                    ),
             )
             .into_any_element()
     }
 }
+
+#[test]
+fn test_result_summary() {
+    assert_eq!(
+        result_summary(42, Duration::from_micros(3200)),
+        "42 results · 3 ms"
+    );
+    assert_eq!(
+        result_summary(1, Duration::from_micros(300)),
+        "1 result · <1 ms"
+    );
+}
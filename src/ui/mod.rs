@@ -1,5 +1,9 @@
+pub mod icon_cache;
+pub mod keybinds;
 pub mod main_window;
+pub mod placement;
 pub mod search_bar;
+pub mod typography;
 
 use serde::{Deserialize, Serialize};
 
@@ -26,8 +30,15 @@ pub enum UIFunction {
 
     ClearBar,
     Backspace,
+    WordLeft,
+    WordRight,
+    DeleteWordLeft,
+    DeleteWordRight,
 
     ErrorPage,
 
+    HistoryPrev,
+    HistoryNext,
+
     Shortcut,
 }
@@ -0,0 +1,73 @@
+use crate::utils::config::WindowPosition;
+
+/// Edges of the screen the layer-shell window is anchored to, along with its margins as top,
+/// right, bottom and left. A window without anchors is centered by the compositor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Placement {
+    pub top: bool,
+    pub left: bool,
+    pub margins: (f32, f32, f32, f32),
+}
+impl Placement {
+    /// Places a window of `size` according to the `position` of the config. `cursor` is the
+    /// pointer on the output the window opens on.
+    pub fn new(
+        position: WindowPosition,
+        margins: (i32, i32, i32, i32),
+        size: (f32, f32),
+        cursor: Option<(f32, f32)>,
+    ) -> Self {
+        let margins = (
+            margins.0 as f32,
+            margins.1 as f32,
+            margins.2 as f32,
+            margins.3 as f32,
+        );
+        match (position, cursor) {
+            (WindowPosition::TopCenter, _) => Self {
+                top: true,
+                left: false,
+                margins,
+            },
+            (WindowPosition::Cursor, Some((x, y))) => Self {
+                top: true,
+                left: true,
+                margins: (
+                    (y - size.1 / 2. + margins.0).max(0.),
+                    margins.1,
+                    margins.2,
+                    (x - size.0 / 2. + margins.3).max(0.),
+                ),
+            },
+            _ => Self {
+                top: false,
+                left: false,
+                margins,
+            },
+        }
+    }
+}
+
+#[test]
+fn test_placement() {
+    let size = (900., 600.);
+    let center = Placement::new(WindowPosition::Center, (0, 0, 0, 0), size, None);
+    assert!(!center.top && !center.left);
+
+    let top = Placement::new(WindowPosition::TopCenter, (40, 0, 0, 0), size, None);
+    assert!(top.top && !top.left);
+    assert_eq!(top.margins.0, 40.);
+
+    let cursor = Placement::new(
+        WindowPosition::Cursor,
+        (0, 0, 0, 0),
+        size,
+        Some((1000., 200.)),
+    );
+    assert!(cursor.top && cursor.left);
+    assert_eq!(cursor.margins, (0., 0., 0., 550.));
+
+    // Without a pointer position the window stays centered
+    let fallback = Placement::new(WindowPosition::Cursor, (0, 0, 0, 0), size, None);
+    assert_eq!(fallback, center);
+}
@@ -1,16 +1,18 @@
 use std::ops::Range;
+use std::sync::Arc;
 
 use gpui::{
-    AbsoluteLength, App, Bounds, ClipboardItem, Context, CursorStyle, Element, ElementId,
+    AbsoluteLength, App, AsyncApp, Bounds, ClipboardItem, Context, CursorStyle, Element, ElementId,
     ElementInputHandler, Entity, EntityInputHandler, EventEmitter, FocusHandle, Focusable,
     GlobalElementId, InteractiveElement, IntoElement, LayoutId, MouseButton, MouseDownEvent,
     MouseMoveEvent, MouseUpEvent, PaintQuad, ParentElement, Pixels, Point, Render, ShapedLine,
-    SharedString, Style, Styled, TextRun, UTF16Selection, UnderlineStyle, Window, actions, div,
-    fill, hsla, point, px, rgb, rgba,
+    SharedString, Style, Styled, TextRun, UTF16Selection, UnderlineStyle, WeakEntity, Window,
+    actions, div, fill, hsla, point, px, rgb, rgba,
 };
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::loader::utils::ExecVariable;
+use crate::ui::typography::text_px;
 
 actions!(
     text_input,
@@ -18,15 +20,22 @@ actions!(
         Backspace,
         Delete,
         DeleteAll,
+        DeleteWordLeft,
+        DeleteWordRight,
         Left,
         Right,
+        WordLeft,
+        WordRight,
         SelectLeft,
         SelectRight,
         SelectAll,
         Home,
         End,
+        KillToStart,
+        KillToEnd,
         ShowCharacterPalette,
         Paste,
+        PastePrimary,
         Cut,
         Copy,
     ]
@@ -46,11 +55,73 @@ pub struct TextInput {
     pub last_layout: Option<ShapedLine>,
     pub last_bounds: Option<Bounds<Pixels>>,
     pub is_selecting: bool,
+    /// Word a double-click selected, dragging from it selects whole words
+    pub word_selection: Option<Range<usize>>,
+    /// Rest of a mode alias the content starts, shown dimmed after it
+    pub completion: Option<SharedString>,
+    /// Values Tab cycles through, see [`SuggestedInput`](crate::loader::utils::SuggestedInput)
+    pub suggestions: Arc<[SharedString]>,
     pub variable: Option<ExecVariable>,
 }
 
 impl TextInput {
+    /// An input for a variable of the selected entry, filled with its default value. The
+    /// suggestions of a command are loaded in the background.
+    pub fn for_variable(var: ExecVariable, cx: &mut Context<Self>) -> Self {
+        let content: SharedString = var.default_value().unwrap_or_default().to_string().into();
+        if let ExecVariable::Suggested(input) = &var {
+            let input = input.clone();
+            cx.spawn(move |this: WeakEntity<TextInput>, cx: &mut AsyncApp| {
+                let mut cx = cx.clone();
+                async move {
+                    let suggestions = cx
+                        .background_executor()
+                        .spawn(async move { input.load_suggestions() })
+                        .await;
+                    let _ = this.update(&mut cx, |this, _| this.suggestions = suggestions.into());
+                }
+            })
+            .detach();
+        }
+        Self {
+            focus_handle: cx.focus_handle(),
+            selected_range: content.len()..content.len(),
+            content,
+            placeholder: var.placeholder(),
+            variable: Some(var),
+            selection_reversed: false,
+            marked_range: None,
+            last_layout: None,
+            last_bounds: None,
+            is_selecting: false,
+            word_selection: None,
+            completion: None,
+            suggestions: Arc::new([]),
+        }
+    }
+
+    /// Replaces the content with the suggestion after it. Returns false past the last one.
+    pub fn next_suggestion(&mut self, cx: &mut Context<Self>) -> bool {
+        let next = self
+            .suggestions
+            .iter()
+            .position(|suggestion| *suggestion == self.content)
+            .map_or(0, |i| i + 1);
+        let Some(suggestion) = self.suggestions.get(next).cloned() else {
+            return false;
+        };
+        self.selected_range = suggestion.len()..suggestion.len();
+        self.content = suggestion;
+        cx.notify();
+        true
+    }
+
     fn left(&mut self, _: &Left, _: &mut Window, cx: &mut Context<Self>) {
+        // With nothing to move through, the key goes to the selected tile
+        if self.content.is_empty() {
+            cx.propagate();
+            return;
+        }
         if self.selected_range.is_empty() {
             self.move_to(self.previous_boundary(self.cursor_offset()), cx);
         } else {
@@ -59,6 +130,10 @@ impl TextInput {
     }
 
     fn right(&mut self, _: &Right, _: &mut Window, cx: &mut Context<Self>) {
+        if self.content.is_empty() {
+            cx.propagate();
+            return;
+        }
         if self.selected_range.is_empty() {
             self.move_to(self.next_boundary(self.selected_range.end), cx);
         } else {
@@ -66,6 +141,17 @@ impl TextInput {
         }
     }
 
+    fn word_left(&mut self, _: &WordLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(
+            previous_word_boundary(&self.content, self.cursor_offset()),
+            cx,
+        );
+    }
+
+    fn word_right(&mut self, _: &WordRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(next_word_boundary(&self.content, self.cursor_offset()), cx);
+    }
+
     fn select_left(&mut self, _: &SelectLeft, _: &mut Window, cx: &mut Context<Self>) {
         self.select_to(self.previous_boundary(self.cursor_offset()), cx);
     }
@@ -105,6 +191,43 @@ impl TextInput {
         }
         self.replace_text_in_range(None, "", window, cx)
     }
+    fn delete_word_left(
+        &mut self,
+        _: &DeleteWordLeft,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.selected_range.is_empty() {
+            self.select_to(
+                previous_word_boundary(&self.content, self.cursor_offset()),
+                cx,
+            )
+        }
+        self.replace_text_in_range(None, "", window, cx)
+    }
+
+    fn delete_word_right(
+        &mut self,
+        _: &DeleteWordRight,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.selected_range.is_empty() {
+            self.select_to(next_word_boundary(&self.content, self.cursor_offset()), cx)
+        }
+        self.replace_text_in_range(None, "", window, cx)
+    }
+    fn kill_to_start(&mut self, _: &KillToStart, window: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(self.cursor_offset(), cx);
+        self.select_to(0, cx);
+        self.replace_text_in_range(None, "", window, cx)
+    }
+
+    fn kill_to_end(&mut self, _: &KillToEnd, window: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(self.cursor_offset(), cx);
+        self.select_to(self.content.len(), cx);
+        self.replace_text_in_range(None, "", window, cx)
+    }
     fn delete_all(&mut self, _: &DeleteAll, _window: &mut Window, cx: &mut Context<Self>) {
         self.reset();
         cx.notify();
@@ -117,22 +240,49 @@ impl TextInput {
         cx: &mut Context<Self>,
     ) {
         self.is_selecting = true;
-
-        if event.modifiers.shift {
-            self.select_to(self.index_for_mouse_position(event.position), cx);
-        } else {
-            self.move_to(self.index_for_mouse_position(event.position), cx)
+        self.word_selection = None;
+        let offset = self.index_for_mouse_position(event.position);
+
+        match event.click_count {
+            2 => {
+                let word = word_range_at(&self.content, offset);
+                self.move_to(word.start, cx);
+                self.select_to(word.end, cx);
+                self.word_selection = Some(word);
+            }
+            3.. => {
+                self.move_to(0, cx);
+                self.select_to(self.content.len(), cx);
+            }
+            _ if event.modifiers.shift => self.select_to(offset, cx),
+            _ => self.move_to(offset, cx),
         }
     }
 
     fn on_mouse_up(&mut self, _: &MouseUpEvent, _window: &mut Window, _: &mut Context<Self>) {
         self.is_selecting = false;
+        self.word_selection = None;
     }
 
     fn on_mouse_move(&mut self, event: &MouseMoveEvent, _: &mut Window, cx: &mut Context<Self>) {
-        if self.is_selecting {
-            self.select_to(self.index_for_mouse_position(event.position), cx);
+        if !self.is_selecting {
+            return;
+        }
+        let offset = self.index_for_mouse_position(event.position);
+        let Some(anchor) = self.word_selection.clone() else {
+            self.select_to(offset, cx);
+            return;
+        };
+        // The double-clicked word stays selected, whichever way the drag goes
+        let word = word_range_at(&self.content, offset);
+        if word.start < anchor.start {
+            self.selected_range = word.start..anchor.end;
+            self.selection_reversed = true;
+        } else {
+            self.selected_range = anchor.start..word.end.max(anchor.end);
+            self.selection_reversed = false;
         }
+        cx.notify()
     }
 
     fn show_character_palette(
@@ -150,6 +300,23 @@ impl TextInput {
         }
     }
 
+    /// Pastes the primary selection, the text last selected in any window
+    fn paste_primary(&mut self, _: &PastePrimary, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(text) = cx.read_from_primary().and_then(|item| item.text()) {
+            self.replace_text_in_range(None, &text.replace("\n", " "), window, cx);
+        }
+    }
+
+    fn on_middle_click(
+        &mut self,
+        event: &MouseDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_to(self.index_for_mouse_position(event.position), cx);
+        self.paste_primary(&PastePrimary, window, cx);
+    }
+
     fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
         if !self.selected_range.is_empty() {
             cx.write_to_clipboard(ClipboardItem::new_string(
@@ -168,6 +335,7 @@ impl TextInput {
 
     fn move_to(&mut self, offset: usize, cx: &mut Context<Self>) {
         self.selected_range = offset..offset;
+        self.selection_reversed = false;
         cx.notify()
     }
 
@@ -179,6 +347,14 @@ impl TextInput {
         }
     }
 
+    /// Offset into the shown text, where each character of a password is a bullet
+    fn display_offset(&self, offset: usize) -> usize {
+        match &self.variable {
+            Some(ExecVariable::PasswordInput(_)) => masked_offset(&self.content, offset),
+            _ => offset,
+        }
+    }
+
     fn index_for_mouse_position(&self, position: Point<Pixels>) -> usize {
         if self.content.is_empty() {
             return 0;
@@ -195,6 +371,7 @@ impl TextInput {
             return self.content.len();
         }
         line.closest_index_for_x(position.x - bounds.left())
+            .min(self.content.len())
     }
 
     fn select_to(&mut self, offset: usize, cx: &mut Context<Self>) {
@@ -271,9 +448,48 @@ impl TextInput {
         self.last_layout = None;
         self.last_bounds = None;
         self.is_selecting = false;
+        self.word_selection = None;
+        self.completion = None;
     }
 }
 
+fn masked_offset(content: &str, offset: usize) -> usize {
+    content[..offset].chars().count() * "•".len()
+}
+
+fn is_word(segment: &str) -> bool {
+    segment.chars().any(char::is_alphanumeric)
+}
+
+/// The word, run of spaces or punctuation mark at `offset`
+fn word_range_at(text: &str, offset: usize) -> Range<usize> {
+    text.split_word_bound_indices()
+        .map(|(idx, segment)| idx..idx + segment.len())
+        .find(|range| range.contains(&offset))
+        .or_else(|| {
+            // Past the last character, the last word counts
+            let (idx, segment) = text.split_word_bound_indices().next_back()?;
+            Some(idx..idx + segment.len())
+        })
+        .unwrap_or(offset..offset)
+}
+
+/// Start of the word before `offset`, skipping the spaces and punctuation in between
+fn previous_word_boundary(text: &str, offset: usize) -> usize {
+    text.split_word_bound_indices()
+        .rev()
+        .find(|(idx, segment)| *idx < offset && is_word(segment))
+        .map_or(0, |(idx, _)| idx)
+}
+
+/// End of the word after `offset`, skipping the spaces and punctuation in between
+fn next_word_boundary(text: &str, offset: usize) -> usize {
+    text.split_word_bound_indices()
+        .map(|(idx, segment)| (idx + segment.len(), segment))
+        .find(|(end, segment)| *end > offset && is_word(segment))
+        .map_or(text.len(), |(end, _)| end)
+}
+
 impl EntityInputHandler for TextInput {
     fn text_for_range(
         &mut self,
@@ -359,7 +575,7 @@ impl EntityInputHandler for TextInput {
         self.selected_range = new_selected_range_utf16
             .as_ref()
             .map(|range_utf16| self.range_from_utf16(range_utf16))
-            .map(|new_range| new_range.start + range.start..new_range.end + range.end)
+            .map(|new_range| new_range.start + range.start..new_range.end + range.start)
             .unwrap_or_else(|| range.start + new_text.len()..range.start + new_text.len());
 
         cx.notify();
@@ -395,7 +611,10 @@ impl EntityInputHandler for TextInput {
         let line_point = self.last_bounds?.localize(&point)?;
         let last_layout = self.last_layout.as_ref()?;
 
-        assert_eq!(last_layout.text, self.content);
+        // The layout may end in a completion after the content
+        if !last_layout.text.starts_with(self.content.as_str()) {
+            return None;
+        }
         let utf8_index = last_layout.index_for_x(point.x - line_point.x)?;
         Some(self.offset_to_utf16(utf8_index))
     }
@@ -456,6 +675,12 @@ impl Element for TextElement {
         } else {
             (content, style.color)
         };
+        // Only offered while typing at the end
+        let completion = input
+            .completion
+            .clone()
+            .filter(|_| !display_text.is_empty() && input.marked_range.is_none())
+            .filter(|_| input.selected_range == (input.content.len()..input.content.len()));
 
         let run = TextRun {
             len: display_text.len(),
@@ -465,7 +690,12 @@ impl Element for TextElement {
             underline: None,
             strikethrough: None,
         };
-        let runs = if let Some(marked_range) = input.marked_range.as_ref() {
+        // Composed text, e.g. the accent of a dead key waiting for its letter
+        let marked_range = input
+            .marked_range
+            .as_ref()
+            .map(|range| input.display_offset(range.start)..input.display_offset(range.end));
+        let runs = if let Some(marked_range) = marked_range {
             vec![
                 TextRun {
                     len: marked_range.start,
@@ -488,9 +718,22 @@ impl Element for TextElement {
             .into_iter()
             .filter(|run| run.len > 0)
             .collect()
+        } else if let Some(completion) = completion.as_ref() {
+            vec![
+                run.clone(),
+                TextRun {
+                    len: completion.len(),
+                    color: hsla(1., 1., 1., 0.2),
+                    ..run
+                },
+            ]
         } else {
             vec![run]
         };
+        let display_text = match completion {
+            Some(completion) => format!("{display_text}{completion}").into(),
+            None => display_text,
+        };
 
         let font_size = style.font_size.to_pixels(window.rem_size());
         let line = window
@@ -520,17 +763,10 @@ impl Element for TextElement {
         cx: &mut App,
     ) -> Self::PrepaintState {
         let input = self.input.read(cx);
-        let mut selected_range = input.selected_range.clone();
-        let mut cursor = input.cursor_offset();
-
         // handle password fields
-        if let Some(ExecVariable::PasswordInput(_)) = &input.variable {
-            cursor = input.content[..cursor].chars().count() * "•".len();
-
-            let start = input.content[..selected_range.start].chars().count() * "•".len();
-            let end = input.content[..selected_range.end].chars().count() * "•".len();
-            selected_range = start..end;
-        }
+        let cursor = input.display_offset(input.cursor_offset());
+        let selected_range = input.display_offset(input.selected_range.start)
+            ..input.display_offset(input.selected_range.end);
 
         // Cached from request layout
         let line = &request_layout.l;
@@ -621,18 +857,26 @@ impl Render for TextInput {
             .on_action(cx.listener(Self::backspace))
             .on_action(cx.listener(Self::delete))
             .on_action(cx.listener(Self::delete_all))
+            .on_action(cx.listener(Self::delete_word_left))
+            .on_action(cx.listener(Self::delete_word_right))
             .on_action(cx.listener(Self::left))
             .on_action(cx.listener(Self::right))
+            .on_action(cx.listener(Self::word_left))
+            .on_action(cx.listener(Self::word_right))
             .on_action(cx.listener(Self::select_left))
             .on_action(cx.listener(Self::select_right))
             .on_action(cx.listener(Self::select_all))
             .on_action(cx.listener(Self::home))
             .on_action(cx.listener(Self::end))
+            .on_action(cx.listener(Self::kill_to_start))
+            .on_action(cx.listener(Self::kill_to_end))
             .on_action(cx.listener(Self::show_character_palette))
             .on_action(cx.listener(Self::paste))
+            .on_action(cx.listener(Self::paste_primary))
             .on_action(cx.listener(Self::cut))
             .on_action(cx.listener(Self::copy))
             .on_mouse_down(MouseButton::Left, cx.listener(Self::on_mouse_down))
+            .on_mouse_down(MouseButton::Middle, cx.listener(Self::on_middle_click))
             .on_mouse_up(MouseButton::Left, cx.listener(Self::on_mouse_up))
             .on_mouse_up_out(MouseButton::Left, cx.listener(Self::on_mouse_up))
             .on_mouse_move(cx.listener(Self::on_mouse_move))
@@ -640,8 +884,8 @@ impl Render for TextInput {
             .w_auto()
             .child(if self.variable.is_some() {
                 div()
-                    .line_height(px(12.))
-                    .text_size(px(12.))
+                    .line_height(text_px(12.))
+                    .text_size(text_px(12.))
                     .h(px(20. + 4. * 2.)) // 38px
                     .p(px(4.))
                     .px(px(7.))
@@ -656,8 +900,8 @@ impl Render for TextInput {
                     .child(TextElement { input: cx.entity() })
             } else {
                 div()
-                    .line_height(px(16.))
-                    .text_size(px(16.))
+                    .line_height(text_px(16.))
+                    .text_size(text_px(16.))
                     .h(px(30. + 4. * 2.)) // 38px
                     .p(px(4.))
                     .w_auto()
@@ -675,3 +919,29 @@ impl Focusable for TextInput {
         self.focus_handle.clone()
     }
 }
+
+#[test]
+fn test_word_boundaries() {
+    let text = "open  ~/.config/sherlock now";
+    assert_eq!(previous_word_boundary(text, text.len()), 25);
+    assert_eq!(previous_word_boundary(text, 25), 16);
+    assert_eq!(previous_word_boundary(text, 9), 0);
+    assert_eq!(next_word_boundary(text, 0), 4);
+    assert_eq!(next_word_boundary(text, 4), 15);
+    assert_eq!(next_word_boundary(text, 25), text.len());
+    assert_eq!(next_word_boundary("über straße", 0), "über".len());
+
+    assert_eq!(word_range_at(text, 18), 16..24);
+    assert_eq!(word_range_at(text, 5), 4..6);
+    assert_eq!(word_range_at(text, text.len()), 25..28);
+    assert_eq!(word_range_at("", 0), 0..0);
+}
+
+#[test]
+fn test_masked_offset() {
+    // Composed characters take two bytes but show as a single bullet
+    let content = "pässwörd";
+    assert_eq!(masked_offset(content, 0), 0);
+    assert_eq!(masked_offset(content, "pä".len()), 2 * "•".len());
+    assert_eq!(masked_offset(content, content.len()), 8 * "•".len());
+}
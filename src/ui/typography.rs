@@ -0,0 +1,70 @@
+use gpui::{Pixels, Rems, SharedString, px, rems};
+
+use crate::utils::config::ConfigGuard;
+
+/// Size of the root font that text sizes are relative to, at a `font_size_scale` of 1
+pub const BASE_FONT_SIZE: f32 = 16.;
+
+/// A text size given in pixels at a `font_size_scale` of 1, which grows and shrinks with the
+/// scale
+pub fn text_px(size: f32) -> Rems {
+    rems(size / BASE_FONT_SIZE)
+}
+
+/// The fonts of the `appearance` config
+#[derive(Clone, Debug)]
+pub struct Typography {
+    /// Family of every text, the system default if unset
+    pub family: Option<SharedString>,
+    pub scale: f32,
+}
+impl Typography {
+    pub fn new(family: Option<&str>, scale: f32) -> Self {
+        Self {
+            family: family
+                .map(str::trim)
+                .filter(|family| !family.is_empty())
+                .map(|family| SharedString::from(family.to_string())),
+            // Sizes far off the default make the tiles unreadable
+            scale: if scale.is_finite() {
+                scale.clamp(0.5, 3.0)
+            } else {
+                1.0
+            },
+        }
+    }
+    pub fn from_config() -> Self {
+        match ConfigGuard::read() {
+            Ok(config) => Self::new(
+                config.appearance.font_family.as_deref(),
+                config.appearance.font_size_scale,
+            ),
+            Err(_) => Self::new(None, 1.0),
+        }
+    }
+    /// Size of the root font, which every [`text_px`] size is relative to
+    pub fn rem_size(&self) -> Pixels {
+        px(BASE_FONT_SIZE * self.scale)
+    }
+}
+
+/// Family of calculator results and command output, `None` unless `monospace_tiles` is set
+pub fn monospace_family() -> Option<SharedString> {
+    let config = ConfigGuard::read().ok()?;
+    config
+        .appearance
+        .monospace_tiles
+        .then(|| SharedString::from(config.appearance.monospace_family.clone()))
+}
+
+#[test]
+fn test_typography() {
+    let typography = Typography::new(Some(" Inter "), 1.25);
+    assert_eq!(typography.family, Some(SharedString::from("Inter")));
+    assert_eq!(typography.rem_size(), px(20.));
+
+    let fallback = Typography::new(Some(""), f32::NAN);
+    assert_eq!(fallback.family, None);
+    assert_eq!(fallback.scale, 1.0);
+    assert_eq!(Typography::new(None, 10.).scale, 3.0);
+}
@@ -4,15 +4,46 @@ use bincode;
 use serde::{Serialize, de::DeserializeOwned};
 
 use crate::{
-    sherlock_error,
+    sher_log, sherlock_error,
     utils::errors::{SherlockError, SherlockErrorType},
 };
 
+/// Identifies files written by [`BinaryCache`]
+const MAGIC: &[u8; 4] = b"SHLK";
+/// Has to be bumped whenever the layout of a cached type changes
+pub const SCHEMA_VERSION: u32 = 7;
+/// Magic, schema version, config hash and checksum
+const HEADER_LEN: usize = 4 + 4 + 8 + 4;
+
+/// # Cache Layout
+/// A fixed header followed by the zstd-compressed bincode payload:
+/// - **magic:** `SHLK`
+/// - **version:** [`SCHEMA_VERSION`] the file was written with
+/// - **config hash:** hash of the settings the content depends on, `0` if it depends on none
+/// - **checksum:** crc32 of the compressed payload
+///
+/// Caches with a different version or config hash, corrupted ones and those without the header
+/// are removed on read and reported as [`SherlockErrorType::CacheInvalidError`], so the caller regenerates
+/// them.
 pub struct BinaryCache;
 impl BinaryCache {
     pub fn write<T: Serialize + Debug, P: AsRef<Path>>(
         path: P,
         data: &T,
+    ) -> Result<(), SherlockError> {
+        Self::write_keyed(path, data, 0)
+    }
+    pub fn read<T: DeserializeOwned + Default + Clone + Debug, P: AsRef<Path>>(
+        path: P,
+    ) -> Result<T, SherlockError> {
+        Self::read_keyed(path, 0)
+    }
+
+    /// Writes the cache for the given config hash
+    pub fn write_keyed<T: Serialize + Debug, P: AsRef<Path>>(
+        path: P,
+        data: &T,
+        config_hash: u64,
     ) -> Result<(), SherlockError> {
         let cache = path.as_ref();
 
@@ -20,18 +51,35 @@ impl BinaryCache {
         let cfg = bincode::config::standard().with_fixed_int_encoding();
         let encoded = bincode::serde::encode_to_vec(&data, cfg)
             .map_err(|e| sherlock_error!(SherlockErrorType::SerializationError, e.to_string()))?;
+        let compressed = zstd::encode_all(encoded.as_slice(), 3)
+            .map_err(|e| sherlock_error!(SherlockErrorType::SerializationError, e.to_string()))?;
 
-        std::fs::write(&cache, encoded).map_err(|e| {
-            sherlock_error!(
-                SherlockErrorType::FileWriteError(cache.to_path_buf()),
-                e.to_string()
-            )
-        })?;
+        let mut bytes = Vec::with_capacity(HEADER_LEN + compressed.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&SCHEMA_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&config_hash.to_le_bytes());
+        bytes.extend_from_slice(&crc32fast::hash(&compressed).to_le_bytes());
+        bytes.extend_from_slice(&compressed);
+
+        // Replaced atomically, so a concurrent reader never sees a half-written cache
+        let tmp = cache.with_extension(format!("tmp-{}", std::process::id()));
+        std::fs::write(&tmp, bytes)
+            .and_then(|_| std::fs::rename(&tmp, cache))
+            .map_err(|e| {
+                let _ = fs::remove_file(&tmp);
+                sherlock_error!(
+                    SherlockErrorType::FileWriteError(cache.to_path_buf()),
+                    e.to_string()
+                )
+            })?;
 
         Ok(())
     }
-    pub fn read<T: DeserializeOwned + Default + Clone + Debug, P: AsRef<Path>>(
+
+    /// Reads a cache that has been written for the given config hash
+    pub fn read_keyed<T: DeserializeOwned + Default + Clone + Debug, P: AsRef<Path>>(
         path: P,
+        config_hash: u64,
     ) -> Result<T, SherlockError> {
         let cache = path.as_ref();
 
@@ -42,14 +90,131 @@ impl BinaryCache {
             )
         })?;
 
-        // Decode binary
+        let decoded = Self::decode(&bytes, Some(config_hash));
+        if let Err(reason) = &decoded {
+            let _ = sher_log!(format!(
+                r#"Cache "{}" is regenerated: {}"#,
+                cache.display(),
+                reason
+            ));
+            let _ = fs::remove_file(cache);
+        }
+        decoded.map_err(|reason| {
+            sherlock_error!(
+                SherlockErrorType::CacheInvalidError(cache.to_path_buf()),
+                reason
+            )
+        })
+    }
+
+    /// Reads a cache whatever schema version and config hash it was written with, leaving the
+    /// file in place. Only meant for taking over user state that older releases kept in caches.
+    pub fn read_unchecked<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Option<T> {
+        let bytes = fs::read(path).ok()?;
+        Self::decode(&bytes, None).ok()
+    }
+
+    /// Reads a cache written before the header existed, which nothing can be checked for. Only
+    /// meant for taking over the usage counts of those releases.
+    pub fn read_legacy<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Option<T> {
+        let bytes = fs::read(path).ok()?;
+        if bytes.starts_with(MAGIC) {
+            return None;
+        }
+        let cfg = bincode::config::standard().with_fixed_int_encoding();
+        bincode::serde::decode_from_slice::<T, _>(&bytes, cfg)
+            .ok()
+            .map(|(decoded, _)| decoded)
+    }
+
+    /// Decodes the cache, checking its version and config hash unless `config_hash` is `None`
+    fn decode<T: DeserializeOwned>(bytes: &[u8], config_hash: Option<u64>) -> Result<T, String> {
         let cfg = bincode::config::standard().with_fixed_int_encoding();
-        match bincode::serde::decode_from_slice::<T, _>(&bytes, cfg) {
-            Ok(decoded) => Ok(decoded.0),
-            Err(_) => {
-                let _ = fs::remove_file(path);
-                Ok(T::default())
+
+        let Some(header) = bytes.get(..HEADER_LEN).filter(|h| h.starts_with(MAGIC)) else {
+            return Err("unversioned cache".to_string());
+        };
+
+        let field = |range: std::ops::Range<usize>| &header[range];
+        let version = u32::from_le_bytes(field(4..8).try_into().unwrap_or_default());
+        let hash = u64::from_le_bytes(field(8..16).try_into().unwrap_or_default());
+        let checksum = u32::from_le_bytes(field(16..20).try_into().unwrap_or_default());
+        let payload = &bytes[HEADER_LEN..];
+
+        if let Some(config_hash) = config_hash {
+            if version != SCHEMA_VERSION {
+                return Err(format!(
+                    "schema version {version} does not match {SCHEMA_VERSION}"
+                ));
             }
+            if hash != config_hash {
+                return Err("configuration changed".to_string());
+            }
+        }
+        if crc32fast::hash(payload) != checksum {
+            return Err("checksum mismatch".to_string());
         }
+
+        let decompressed = zstd::decode_all(payload).map_err(|e| e.to_string())?;
+        bincode::serde::decode_from_slice::<T, _>(&decompressed, cfg)
+            .map(|(decoded, _)| decoded)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn temp_cache(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("sherlock-{}-{name}.bin", std::process::id()))
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let path = temp_cache("roundtrip");
+        let counts = HashMap::from([("firefox".to_string(), 3u32)]);
+        BinaryCache::write_keyed(&path, &counts, 42).unwrap();
+
+        let read: HashMap<String, u32> = BinaryCache::read_keyed(&path, 42).unwrap();
+        assert_eq!(read, counts);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_invalidation() {
+        let path = temp_cache("invalidation");
+        BinaryCache::write_keyed(&path, &vec![1u32, 2, 3], 1).unwrap();
+
+        // A different config hash removes the cache
+        assert!(BinaryCache::read_keyed::<Vec<u32>, _>(&path, 2).is_err());
+        assert!(!path.exists());
+
+        // So does a corrupted payload
+        BinaryCache::write(&path, &vec![1u32, 2, 3]).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+        assert!(BinaryCache::read::<Vec<u32>, _>(&path).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_unversioned_cache() {
+        let path = temp_cache("unversioned");
+        let cfg = bincode::config::standard().with_fixed_int_encoding();
+        let legacy = bincode::serde::encode_to_vec(vec![7u32], cfg).unwrap();
+        fs::write(&path, legacy).unwrap();
+
+        // Only the explicit legacy read takes it, a regular read treats it as a miss
+        assert_eq!(
+            BinaryCache::read_legacy::<Vec<u32>, _>(&path),
+            Some(vec![7])
+        );
+        assert!(BinaryCache::read_unchecked::<Vec<u32>, _>(&path).is_none());
+        assert!(BinaryCache::read::<Vec<u32>, _>(&path).is_err());
+        assert!(!path.exists());
     }
 }
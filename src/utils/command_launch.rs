@@ -1,19 +1,49 @@
 use std::{
+    collections::HashMap,
+    io::Read,
     os::unix::process::CommandExt,
+    path::Path,
     process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError, Sender},
+    },
+    time::{Duration, Instant},
 };
 
 use gpui::SharedString;
 use regex::{Captures, Regex};
 
 use crate::{
+    loader::utils::ChainStep,
     sherlock_error,
     utils::{
-        config::{ConfigGuard, SherlockConfig},
+        config::{ConfigGuard, LaunchScope, SherlockConfig},
         errors::{SherlockError, SherlockErrorType},
+        files::find_executable,
+        startup_notification::x11_startup_id,
+        watch::StopToken,
     },
 };
 
+/// Values for the field codes of a desktop entry's `Exec` key, e.g. `%u` or `%i`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FieldCodes<'a> {
+    /// File or url the application is launched with. Only browser commands have one, apps are
+    /// started without a file, so their `%f`/`%u` codes are removed.
+    pub target: Option<&'a str>,
+    pub icon: Option<&'a Path>,
+    pub name: Option<&'a str>,
+    pub desktop_file: Option<&'a Path>,
+}
+
+/// Working directory and additional environment variables of a spawned command
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProcessEnv<'a> {
+    pub cwd: Option<&'a Path>,
+    pub vars: Option<&'a HashMap<String, String>>,
+}
+
 /// Spawnes a command completely detatched from the current process.
 ///
 /// This function uses a "double-fork" strategy to ensure that the spawned process is adopted by
@@ -28,17 +58,36 @@ use crate::{
 ///
 /// # Arguments
 /// * `cmd` -  A string containing the program name followed by its arguments (e.g, `foot -e`).
+/// * `codes` - Values the field codes in `cmd` are expanded to, for desktop entries. Codes without
+///   a value are removed. Other commands only lose standalone codes like `%U`, so a literal `%`
+///   stays untouched.
 pub fn spawn_detached(
     cmd: &str,
     keyword: &str,
     variables: &[(SharedString, SharedString)],
+    codes: Option<&FieldCodes>,
+) -> Result<(), SherlockError> {
+    spawn_detached_in(cmd, keyword, variables, codes, &ProcessEnv::default())
+}
+
+/// [`spawn_detached`] in the working directory and with the environment variables of `env`
+pub fn spawn_detached_in(
+    cmd: &str,
+    keyword: &str,
+    variables: &[(SharedString, SharedString)],
+    codes: Option<&FieldCodes>,
+    env: &ProcessEnv,
 ) -> Result<(), SherlockError> {
     let config = ConfigGuard::read().unwrap();
     let cmd = parse_variables(cmd, keyword, variables, &config);
+    let scope = config.behavior.launch_scope;
 
     drop(config);
 
-    let parts = split_as_command(&cmd);
+    let parts = match codes {
+        Some(codes) => expand_field_codes(split_as_command(&cmd), codes),
+        None => strip_field_codes(split_as_command(&cmd)),
+    };
     if parts.is_empty() {
         return Ok(());
     }
@@ -46,42 +95,369 @@ pub fn spawn_detached(
     let program = &parts[0];
     let args = &parts[1..];
 
-    let mut command = Command::new(program);
+    let mut command = scoped_command(scope, program);
     command.args(args);
 
     command
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null());
+    if let Some(cwd) = env.cwd {
+        command.current_dir(cwd);
+    }
+    if let Some(vars) = env.vars {
+        command.envs(vars);
+    }
+    apply_startup_notification(&mut command, program);
 
-    unsafe {
-        command.pre_exec(|| {
-            // Fork again inside the child
-            match libc::fork() {
-                -1 => return Err(std::io::Error::last_os_error()),
-                0 => {
-                    // detatch grandchild
-                    libc::setsid();
-                    Ok(())
+    // Scoped programs are placed in a unit of their own already, and `systemd-run --scope` has
+    // to stay the parent of the program it places
+    let detach = scope == LaunchScope::None;
+    if detach {
+        unsafe {
+            command.pre_exec(|| {
+                // Fork again inside the child
+                match libc::fork() {
+                    -1 => return Err(std::io::Error::last_os_error()),
+                    0 => {
+                        // detatch grandchild
+                        libc::setsid();
+                        Ok(())
+                    }
+                    _ => {
+                        // exit child immediately
+                        // this orphans the grandchild, will get adopted by PID 1.
+                        libc::_exit(0);
+                    }
                 }
-                _ => {
-                    // exit child immediately
-                    // this orphans the grandchild, will get adopted by PID 1.
-                    libc::_exit(0);
+            });
+        }
+    }
+
+    let mut child = command.spawn().map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::CommandExecutionError(cmd.to_string()),
+            e.to_string()
+        )
+    })?;
+    if detach {
+        let _ = child.wait();
+    } else {
+        // The unit manager runs as long as the program, which is reaped once it exits
+        std::thread::spawn(move || child.wait());
+    }
+
+    Ok(())
+}
+
+/// Starts `program` in a unit of its own, so it gets proper cgroup placement and outlives a
+/// restart of the compositor. The unit runs the program itself, which keeps its environment.
+fn scoped_command(scope: LaunchScope, program: &str) -> Command {
+    let mut command = match scope {
+        LaunchScope::None => return Command::new(program),
+        LaunchScope::Systemd => {
+            static SCOPES: AtomicUsize = AtomicUsize::new(0);
+            let name: String = program
+                .rsplit('/')
+                .next()
+                .unwrap_or(program)
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '_' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            let mut command = Command::new("systemd-run");
+            command
+                .args(["--user", "--scope", "--collect", "--quiet"])
+                .arg(format!(
+                    "--unit=app-sherlock-{name}-{}-{}.scope",
+                    std::process::id(),
+                    SCOPES.fetch_add(1, Ordering::Relaxed)
+                ));
+            command
+        }
+        LaunchScope::Uwsm => {
+            let mut command = Command::new("uwsm");
+            command.arg("app");
+            command
+        }
+    };
+    command.arg("--").arg(program);
+    command
+}
+
+/// Runs the steps of a `chain` one after another in the background, so pauses between them do
+/// not hold up the window
+pub fn run_chain(
+    steps: &[ChainStep],
+    keyword: &str,
+    variables: &[(SharedString, SharedString)],
+    env: &ProcessEnv,
+) -> Result<(), SherlockError> {
+    let steps: Vec<ChainStep> = {
+        let config = ConfigGuard::read()?;
+        steps
+            .iter()
+            .map(|step| match step {
+                ChainStep::Exec(cmd) => {
+                    ChainStep::Exec(parse_variables(cmd, keyword, variables, &config))
                 }
+                ChainStep::Sleep { sleep } => ChainStep::Sleep { sleep: *sleep },
+            })
+            .collect()
+    };
+    let cwd = env.cwd.map(Path::to_path_buf);
+    let vars = env.vars.cloned();
+    std::thread::spawn(move || {
+        let env = ProcessEnv {
+            cwd: cwd.as_deref(),
+            vars: vars.as_ref(),
+        };
+        if let Err(e) = run_steps(&steps, &env) {
+            eprintln!("{e}");
+        }
+    });
+    Ok(())
+}
+
+fn run_steps(steps: &[ChainStep], env: &ProcessEnv) -> Result<(), SherlockError> {
+    for step in steps {
+        let cmd = match step {
+            ChainStep::Exec(cmd) => cmd,
+            ChainStep::Sleep { sleep } => {
+                std::thread::sleep(Duration::from_millis(*sleep));
+                continue;
             }
-        });
+        };
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(cmd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if let Some(cwd) = env.cwd {
+            command.current_dir(cwd);
+        }
+        if let Some(vars) = env.vars {
+            command.envs(vars);
+        }
+        let status = command.status().map_err(|e| {
+            sherlock_error!(
+                SherlockErrorType::CommandExecutionError(cmd.clone()),
+                e.to_string()
+            )
+        })?;
+        if !status.success() {
+            return Err(sherlock_error!(
+                SherlockErrorType::CommandExecutionError(cmd.clone()),
+                format!("Stopped the chain, the step exited with {status}")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Time the previously focused window gets to take the focus back before typing starts
+const TYPE_DELAY: Duration = Duration::from_millis(250);
+
+/// Types the text into the window that has the focus once Sherlock closed, through `wtype` on
+/// Wayland and `ydotool` where `wtype` is missing or the compositor lacks virtual keyboards
+pub fn type_text(
+    text: &str,
+    keyword: &str,
+    variables: &[(SharedString, SharedString)],
+) -> Result<(), SherlockError> {
+    let text = {
+        let config = ConfigGuard::read()?;
+        parse_variables(text, keyword, variables, &config)
+    };
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let program = ["wtype", "ydotool"]
+        .into_iter()
+        .filter(|program| wayland || *program != "wtype")
+        .find(|program| find_executable(program).is_some())
+        .ok_or_else(|| {
+            sherlock_error!(
+                SherlockErrorType::CommandExecutionError(String::from("type")),
+                "Typing text needs wtype or ydotool"
+            )
+        })?;
+
+    let mut command = typing_command(program, &text);
+    std::thread::spawn(move || {
+        std::thread::sleep(TYPE_DELAY);
+        match command.status() {
+            Ok(status) if !status.success() => eprintln!("{program} exited with {status}"),
+            Err(e) => eprintln!("{program}: {e}"),
+            _ => {}
+        }
+    });
+    Ok(())
+}
+
+fn typing_command(program: &str, text: &str) -> Command {
+    let mut command = Command::new(program);
+    if program == "ydotool" {
+        command.arg("type");
     }
+    command
+        .arg("--")
+        .arg(text)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    command
+}
+
+/// Output beyond this many bytes is cut off, tiles are not meant for whole log files
+const MAX_OUTPUT: usize = 64 * 1024;
+/// Commands whose output is captured are killed once they ran this long
+const OUTPUT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often a captured command is checked for having exited, timed out or been stopped
+const OUTPUT_POLL: Duration = Duration::from_millis(100);
 
+/// Runs the command through `sh` and waits for it, returning what it printed to stdout followed
+/// by what it printed to stderr. Reading stops after [`MAX_OUTPUT`] bytes, after
+/// [`OUTPUT_TIMEOUT`] or once `stop` is stopped, which kills the command along with whatever it
+/// started.
+pub fn capture_output(
+    cmd: &str,
+    env: &ProcessEnv,
+    stop: &StopToken,
+) -> Result<String, SherlockError> {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(cmd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // A group of its own, so the processes it starts can be killed along with it
+        .process_group(0);
+    if let Some(cwd) = env.cwd {
+        command.current_dir(cwd);
+    }
+    if let Some(vars) = env.vars {
+        command.envs(vars);
+    }
     let mut child = command.spawn().map_err(|e| {
         sherlock_error!(
             SherlockErrorType::CommandExecutionError(cmd.to_string()),
             e.to_string()
         )
     })?;
+
+    let (tx, rx) = mpsc::channel();
+    forward_output(child.stdout.take(), false, tx.clone());
+    forward_output(child.stderr.take(), true, tx);
+    let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+    let deadline = Instant::now() + OUTPUT_TIMEOUT;
+    let (mut open, mut exited) = (true, false);
+    let note = loop {
+        if stop.is_stopped() {
+            break None;
+        }
+        if Instant::now() >= deadline {
+            break Some(format!(
+                "\n… stopped after {} seconds",
+                OUTPUT_TIMEOUT.as_secs()
+            ));
+        }
+        if stdout.len() + stderr.len() > MAX_OUTPUT {
+            break Some(String::from("\n…"));
+        }
+        if !open {
+            // The pipes are closed, but the command might not have exited yet
+            match child.try_wait() {
+                Ok(Some(_)) | Err(_) => {
+                    exited = true;
+                    break None;
+                }
+                Ok(None) => std::thread::sleep(OUTPUT_POLL),
+            }
+            continue;
+        }
+        match rx.recv_timeout(OUTPUT_POLL) {
+            Ok((false, chunk)) => stdout.extend(chunk),
+            Ok((true, chunk)) => stderr.extend(chunk),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => open = false,
+        }
+    };
+    if !exited {
+        // SAFETY: signals the group the child leads, created by `process_group(0)`. The child
+        // is only reaped below, so its id still names the group.
+        unsafe {
+            libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+        }
+    }
     let _ = child.wait();
 
-    Ok(())
+    let mut text = String::from_utf8_lossy(&stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&stderr));
+    if text.len() > MAX_OUTPUT {
+        let end = (0..=MAX_OUTPUT)
+            .rev()
+            .find(|i| text.is_char_boundary(*i))
+            .unwrap_or(0);
+        text.truncate(end);
+    }
+    let mut text = text.trim_end().to_string();
+    text.extend(note);
+    Ok(text)
+}
+
+/// Sends what the pipe yields to the capturing command, marked as stderr if `is_stderr` is set
+fn forward_output<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    is_stderr: bool,
+    tx: Sender<(bool, Vec<u8>)>,
+) {
+    let Some(mut pipe) = pipe else {
+        return;
+    };
+    std::thread::spawn(move || {
+        let mut buf = [0; 8192];
+        while let Ok(read @ 1..) = pipe.read(&mut buf) {
+            if tx.send((is_stderr, buf[..read].to_vec())).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Environment that moves rendering to the discrete GPU on hybrid graphics systems. Mesa reads
+/// `DRI_PRIME` for OpenGL and Vulkan, the proprietary NVIDIA driver needs PRIME render offload.
+pub fn discrete_gpu_env() -> &'static str {
+    if Path::new("/proc/driver/nvidia/version").exists() {
+        "__NV_PRIME_RENDER_OFFLOAD=1 __GLX_VENDOR_LIBRARY_NAME=nvidia \
+        __VK_LAYER_NV_optimus=NVIDIA_only DRI_PRIME=1"
+    } else {
+        "DRI_PRIME=1"
+    }
+}
+
+/// Tokens Sherlock has been started with are already used up and must not leak into children.
+/// On X11, the launch is announced to the window manager, which focuses the application once it
+/// maps.
+fn apply_startup_notification(command: &mut Command, program: &str) {
+    command
+        .env_remove("XDG_ACTIVATION_TOKEN")
+        .env_remove("DESKTOP_STARTUP_ID");
+
+    // Wayland tokens can only be requested through the compositor connection, which gpui does
+    // not expose yet
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() || std::env::var_os("DISPLAY").is_none() {
+        return;
+    }
+    if let Some(id) = x11_startup_id(program) {
+        command.env("DESKTOP_STARTUP_ID", id);
+    }
 }
 
 pub fn split_as_command(cmd: &str) -> Vec<String> {
@@ -125,7 +501,87 @@ pub fn split_as_command(cmd: &str) -> Vec<String> {
         parts.push(current);
     }
 
-    parts.retain(|s| !s.starts_with('%'));
+    parts
+}
+
+/// Arguments a terminal expects in front of the command it should run
+pub fn terminal_exec_args(terminal: &str) -> &'static str {
+    let program = terminal.split_whitespace().next().unwrap_or_default();
+    let name = program.rsplit('/').next().unwrap_or(program);
+    match name {
+        "gnome-terminal" | "kgx" | "ptyxis" | "kitty" => "--",
+        "wezterm" => "start --",
+        "xfce4-terminal" | "mate-terminal" | "terminator" | "sakura" => "-x",
+        _ => "-e",
+    }
+}
+
+/// Runs the command in the terminal and keeps the terminal open after the command exited, until
+/// Enter is pressed. A leading `{terminal}` of the command is taken over.
+pub fn hold_in_terminal(cmd: &str) -> String {
+    let cmd = cmd.trim();
+    let cmd = cmd.strip_prefix("{terminal}").unwrap_or(cmd).trim_start();
+    let script = format!(r#"{cmd}; echo; echo "Press Enter to close"; read _"#);
+    format!("{{terminal}} sh -c '{}'", script.replace('\'', r"'\''"))
+}
+
+/// Expands the field codes of the Desktop Entry spec. `%F`, `%U` and `%i` may only appear as
+/// standalone arguments, deprecated codes are removed. The file and url codes expand to
+/// [`FieldCodes::target`] and are removed without one.
+pub fn expand_field_codes(parts: Vec<String>, codes: &FieldCodes) -> Vec<String> {
+    let file = codes
+        .target
+        .map(|target| target.strip_prefix("file://").unwrap_or(target));
+    let mut expanded = Vec::with_capacity(parts.len());
+    for part in parts {
+        match part.as_str() {
+            "%f" | "%F" => expanded.extend(file.map(str::to_string)),
+            "%u" | "%U" => expanded.extend(codes.target.map(str::to_string)),
+            "%i" => {
+                if let Some(icon) = codes.icon {
+                    expanded.push(String::from("--icon"));
+                    expanded.push(icon.to_string_lossy().into_owned());
+                }
+            }
+            _ if part.contains('%') => {
+                let mut arg = String::with_capacity(part.len());
+                let mut chars = part.chars();
+                while let Some(c) = chars.next() {
+                    if c != '%' {
+                        arg.push(c);
+                        continue;
+                    }
+                    match chars.next() {
+                        Some('%') => arg.push('%'),
+                        Some('f' | 'F') => arg.push_str(file.unwrap_or_default()),
+                        Some('u' | 'U') => arg.push_str(codes.target.unwrap_or_default()),
+                        Some('c') => arg.push_str(codes.name.unwrap_or_default()),
+                        Some('k') => {
+                            if let Some(desktop_file) = codes.desktop_file {
+                                arg.push_str(&desktop_file.to_string_lossy());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                if !arg.is_empty() {
+                    expanded.push(arg);
+                }
+            }
+            _ => expanded.push(part),
+        }
+    }
+    expanded
+}
+
+/// Removes standalone field codes like `%U` from a command that is not a desktop entry
+pub fn strip_field_codes(mut parts: Vec<String>) -> Vec<String> {
+    parts.retain(|part| {
+        let mut chars = part.chars();
+        !(chars.next() == Some('%')
+            && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.next().is_none())
+    });
     parts
 }
 
@@ -147,7 +603,15 @@ pub fn parse_variables<'a>(
             let value = caps.get(2).map(|m| m.as_str());
 
             match key {
-                "terminal" => format!("{} -e", config.default_apps.terminal),
+                "terminal" => {
+                    let terminal = &config.default_apps.terminal;
+                    let args = config
+                        .default_apps
+                        .terminal_args
+                        .as_deref()
+                        .unwrap_or_else(|| terminal_exec_args(terminal));
+                    format!("{terminal} {args}").trim_end().to_string()
+                }
                 "keyword" => keyword.to_string(),
                 "variable" => variables
                     .iter()
@@ -183,3 +647,179 @@ pub fn parse_variables<'a>(
 
     exec
 }
+
+#[test]
+fn test_terminal_exec_args() {
+    assert_eq!(terminal_exec_args("gnome-terminal"), "--");
+    assert_eq!(terminal_exec_args("/usr/bin/wezterm"), "start --");
+    assert_eq!(terminal_exec_args("kitty --single-instance"), "--");
+    assert_eq!(terminal_exec_args("foot"), "-e");
+}
+
+#[test]
+fn test_scoped_command() {
+    let args = |command: &Command| {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    let plain = scoped_command(LaunchScope::None, "firefox");
+    assert_eq!(plain.get_program(), "firefox");
+    assert!(args(&plain).is_empty());
+
+    let uwsm = scoped_command(LaunchScope::Uwsm, "firefox");
+    assert_eq!(uwsm.get_program(), "uwsm");
+    assert_eq!(args(&uwsm), ["app", "--", "firefox"]);
+
+    let systemd = scoped_command(LaunchScope::Systemd, "/usr/bin/org.gnome.Nautilus");
+    let args = args(&systemd);
+    assert_eq!(systemd.get_program(), "systemd-run");
+    assert_eq!(args[..2], ["--user", "--scope"]);
+    assert!(args[4].starts_with("--unit=app-sherlock-org_gnome_Nautilus-"));
+    assert!(args[4].ends_with(".scope"));
+    assert_eq!(args[5..], ["--", "/usr/bin/org.gnome.Nautilus"]);
+}
+
+#[test]
+fn test_typing_command() {
+    let args = |command: &Command| {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        args(&typing_command("wtype", "-hunter2")),
+        ["--", "-hunter2"]
+    );
+    assert_eq!(
+        args(&typing_command("ydotool", "hello world")),
+        ["type", "--", "hello world"]
+    );
+}
+
+#[test]
+fn test_hold_in_terminal() {
+    let held = hold_in_terminal("{terminal} uname -a");
+    assert_eq!(
+        held,
+        r#"{terminal} sh -c 'uname -a; echo; echo "Press Enter to close"; read _'"#
+    );
+    assert_eq!(
+        split_as_command(&hold_in_terminal("echo 'it''s'"))[3],
+        r#"echo 'it''s'; echo; echo "Press Enter to close"; read _"#
+    );
+}
+
+#[test]
+fn test_run_steps() {
+    let dir = std::env::temp_dir().join(format!("sherlock-{}-chain", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let env = ProcessEnv {
+        cwd: Some(&dir),
+        vars: None,
+    };
+    let steps = [
+        ChainStep::Exec(String::from("echo first > chain.txt")),
+        ChainStep::Sleep { sleep: 10 },
+        ChainStep::Exec(String::from("echo second >> chain.txt")),
+        ChainStep::Exec(String::from("false")),
+        ChainStep::Exec(String::from("echo third >> chain.txt")),
+    ];
+    assert!(run_steps(&steps, &env).is_err());
+    let written = std::fs::read_to_string(dir.join("chain.txt")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(written, "first\nsecond\n");
+}
+
+#[test]
+fn test_capture_output() {
+    let vars = HashMap::from([(String::from("SHERLOCK_GREETING"), String::from("hello"))]);
+    let env = ProcessEnv {
+        cwd: Some(Path::new("/")),
+        vars: Some(&vars),
+    };
+    let stop = StopToken::default();
+    let output = capture_output("echo $SHERLOCK_GREETING; pwd; echo oops >&2", &env, &stop);
+    assert_eq!(output.unwrap(), "hello\n/\noops");
+
+    // Endless output is cut off instead of being read into memory
+    let output = capture_output("yes", &ProcessEnv::default(), &stop).unwrap();
+    assert!(output.ends_with("y\n…") && output.len() <= MAX_OUTPUT + 4);
+
+    // So are commands that were stopped before they finished
+    stop.stop();
+    let started = Instant::now();
+    assert_eq!(
+        capture_output("sleep 10", &ProcessEnv::default(), &stop).unwrap(),
+        ""
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_expand_field_codes() {
+    let codes = FieldCodes {
+        target: Some("file:///home/user/notes.md"),
+        icon: Some(Path::new("/usr/share/icons/editor.png")),
+        name: Some("Text Editor"),
+        desktop_file: Some(Path::new("/usr/share/applications/editor.desktop")),
+    };
+
+    assert_eq!(
+        expand_field_codes(split_as_command("editor %F %i --class=%c"), &codes),
+        vec![
+            "editor",
+            "/home/user/notes.md",
+            "--icon",
+            "/usr/share/icons/editor.png",
+            "--class=Text Editor",
+        ]
+    );
+    assert_eq!(
+        expand_field_codes(split_as_command("browser --url=%u 100%% %k"), &codes),
+        vec![
+            "browser",
+            "--url=file:///home/user/notes.md",
+            "100%",
+            "/usr/share/applications/editor.desktop",
+        ]
+    );
+
+    // Launching without a target drops the codes, just like deprecated ones
+    assert_eq!(
+        expand_field_codes(split_as_command("firefox %U %d"), &FieldCodes::default()),
+        vec!["firefox"]
+    );
+    // Targets are inserted as they are, percent-encoding included
+    let search = FieldCodes {
+        target: Some("https://example.org/?q=a%20b"),
+        ..Default::default()
+    };
+    assert_eq!(
+        expand_field_codes(split_as_command("browser %u"), &search),
+        vec!["browser", "https://example.org/?q=a%20b"]
+    );
+
+    // Commands that are not desktop entries keep a literal `%`
+    assert_eq!(
+        strip_field_codes(split_as_command(
+            "pactl set-sink-volume @DEFAULT_SINK@ +5% %U"
+        )),
+        vec!["pactl", "set-sink-volume", "@DEFAULT_SINK@", "+5%"]
+    );
+    assert_eq!(
+        strip_field_codes(split_as_command(
+            r#"sh -c "date +%H:%M" firefox "https://example.org/?q=a%20b""#
+        )),
+        vec![
+            "sh",
+            "-c",
+            "date +%H:%M",
+            "firefox",
+            "https://example.org/?q=a%20b"
+        ]
+    );
+}
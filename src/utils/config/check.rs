@@ -0,0 +1,416 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    loader::{
+        LauncherTables, fallback_file, launcher_problems,
+        utils::{ActionTable, AliasKey, AliasTable, DeclaredAction, IgnoreList, RawLauncher},
+    },
+    utils::config::{SherlockConfig, SherlockFlags},
+};
+
+/// A problem found by `sherlock --check-config`
+#[derive(Debug, PartialEq)]
+pub struct ConfigIssue {
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+    /// Warnings, like missing optional files, do not fail the check
+    pub is_error: bool,
+}
+
+/// Collects the issues of one file
+struct FileCheck<'a> {
+    file: &'a Path,
+    issues: Vec<ConfigIssue>,
+}
+impl<'a> FileCheck<'a> {
+    fn new(file: &'a Path) -> Self {
+        Self {
+            file,
+            issues: Vec::new(),
+        }
+    }
+    fn error(&mut self, line: Option<usize>, message: impl Into<String>) {
+        self.push(line, message.into(), true);
+    }
+    fn warning(&mut self, line: Option<usize>, message: impl Into<String>) {
+        self.push(line, message.into(), false);
+    }
+    fn push(&mut self, line: Option<usize>, message: String, is_error: bool) {
+        self.issues.push(ConfigIssue {
+            file: self.file.to_path_buf(),
+            line,
+            message,
+            is_error,
+        });
+    }
+    /// Reads the file, missing files are reported as warnings
+    fn read(&mut self) -> Option<String> {
+        match fs::read_to_string(self.file) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.warning(None, "file does not exist");
+                None
+            }
+            Err(e) => {
+                self.error(None, e.to_string());
+                None
+            }
+        }
+    }
+    /// Parses a json file into `T`, reporting syntax and type errors with their line
+    fn parse_json<T: DeserializeOwned>(&mut self, content: &str) -> Option<(Value, T)> {
+        let parsed = serde_json::from_str::<Value>(content)
+            .map(|value| (T::deserialize(&value).ok(), value));
+        match parsed {
+            Ok((Some(typed), value)) => Some((value, typed)),
+            Ok((None, _)) => {
+                // Deserializing the text again gives the position of the type error
+                if let Err(e) = serde_json::from_str::<T>(content) {
+                    self.error(Some(e.line()), e.to_string());
+                }
+                None
+            }
+            Err(e) => {
+                self.error(Some(e.line()), e.to_string());
+                None
+            }
+        }
+    }
+}
+
+/// Checks config.toml, fallback.json, the alias, actions and ignore files without opening a
+/// window. Prints every issue and returns the exit code: `1` if any errors were found.
+pub fn check_config(flags: &SherlockFlags) -> i32 {
+    let issues = collect_issues(flags);
+    for issue in &issues {
+        let (symbol, kind) = if issue.is_error {
+            ("\x1b[31m✗\x1b[0m", "error")
+        } else {
+            ("\x1b[33m!\x1b[0m", "warning")
+        };
+        let location = match issue.line {
+            Some(line) => format!("{}:{line}", issue.file.display()),
+            None => issue.file.display().to_string(),
+        };
+        println!("{symbol} {kind}: {location}: {}", issue.message);
+    }
+
+    let errors = issues.iter().filter(|issue| issue.is_error).count();
+    let warnings = issues.len() - errors;
+    if errors == 0 {
+        println!("✓ Configuration is valid ({warnings} warnings)");
+        0
+    } else {
+        println!("✗ Found {errors} errors and {warnings} warnings");
+        1
+    }
+}
+
+fn collect_issues(flags: &SherlockFlags) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+
+    // The other files may be moved by the config, so it is read first
+    let config = match flags.clone().config_file() {
+        Ok((path, filetype)) => {
+            let mut check = FileCheck::new(&path);
+            if path.exists() {
+                if let Some(content) = check.read() {
+                    check_main_config(&mut check, &content, filetype == "toml");
+                }
+            } else {
+                check.warning(None, "no config file found, the defaults are used");
+            }
+            issues.append(&mut check.issues);
+            flags.clone().to_config().ok().map(|(config, _)| config)
+        }
+        Err(e) => {
+            issues.push(ConfigIssue {
+                file: PathBuf::from("config.toml"),
+                line: None,
+                message: e.to_string(),
+                is_error: true,
+            });
+            None
+        }
+    };
+    let config = config.unwrap_or_else(|| {
+        SherlockConfig::apply_flags(&mut flags.clone(), SherlockConfig::default())
+    });
+    let files = &config.files;
+
+    let fallback = fallback_file(&files.fallback).unwrap_or_else(|| files.fallback.clone());
+    let mut check = FileCheck::new(&fallback);
+    if let Some(content) = check.read() {
+        let is_toml = fallback.extension().is_some_and(|ext| ext == "toml");
+        check_fallback(&mut check, &content, is_toml);
+    }
+    issues.append(&mut check.issues);
+
+    let mut check = FileCheck::new(&files.alias);
+    if let Some(content) = check.read() {
+        check.parse_json::<AliasTable>(&content);
+    }
+    issues.append(&mut check.issues);
+
+    let mut check = FileCheck::new(&files.actions);
+    if let Some(content) = check.read()
+        && let Some((_, declared)) = check.parse_json::<Vec<DeclaredAction>>(&content)
+        && let Err(e) = ActionTable::new(declared)
+    {
+        check.error(None, e);
+    }
+    issues.append(&mut check.issues);
+
+    let mut check = FileCheck::new(&files.ignore);
+    if let Some(content) = check.read() {
+        check_ignore(&mut check, &content);
+    }
+    issues.append(&mut check.issues);
+
+    issues
+}
+
+fn check_main_config(check: &mut FileCheck, content: &str, is_toml: bool) {
+    let value: Value = if is_toml {
+        let table = match toml::from_str::<toml::Table>(content) {
+            Ok(table) => table,
+            Err(e) => {
+                check.error(toml_line(content, &e), e.message());
+                return;
+            }
+        };
+        if let Err(e) = toml::from_str::<SherlockConfig>(content) {
+            check.error(toml_line(content, &e), e.message());
+            return;
+        }
+        serde_json::to_value(table).unwrap_or_default()
+    } else {
+        match check.parse_json::<SherlockConfig>(content) {
+            Some((value, _)) => value,
+            None => return,
+        }
+    };
+
+    let patterns = value
+        .pointer("/behavior/run_as_root")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str);
+    for pattern in patterns {
+        if let Err(e) = AliasKey::parse(pattern) {
+            check.error(None, format!("behavior.run_as_root: {e}"));
+        }
+    }
+    let patterns = value
+        .pointer("/behavior/uncounted")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str);
+    for pattern in patterns {
+        if let Some(Err(e)) = IgnoreList::parse_line(pattern) {
+            check.error(
+                None,
+                format!(r#"behavior.uncounted: invalid pattern "{pattern}": {e}"#),
+            );
+        }
+    }
+
+    let known = serde_json::to_value(SherlockConfig::default()).unwrap_or_default();
+    let mut unknown = Vec::new();
+    unknown_keys(&value, &known, &mut Vec::new(), &mut unknown);
+    // Profiles and conditional sections are laid over the config, so they take the same keys
+    if let Some(profiles) = value.get("profile").and_then(Value::as_object) {
+        for (name, overlay) in profiles {
+            let mut path = vec![String::from("profile"), name.clone()];
+            unknown_keys(overlay, &known, &mut path, &mut unknown);
+        }
+    }
+    if let Some(conditions) = value.get("when").and_then(Value::as_object) {
+        for (condition, sections) in conditions {
+            for (target, overlay) in sections.as_object().into_iter().flatten() {
+                let mut path = vec![String::from("when"), condition.clone(), target.clone()];
+                unknown_keys(overlay, &known, &mut path, &mut unknown);
+            }
+        }
+    }
+    for path in unknown {
+        // Nested config files are merged in through `[[source]]`
+        if path == ["source"] || path == ["profile"] || path == ["launcher"] || path == ["when"] {
+            continue;
+        }
+        let line = if is_toml {
+            toml_key_line(content, &path)
+        } else {
+            json_key_line(content, &path)
+        };
+        check.error(line, format!(r#"unknown key "{}""#, path.join(".")));
+    }
+}
+
+fn check_fallback(check: &mut FileCheck, content: &str, is_toml: bool) {
+    let parsed = if is_toml {
+        parse_toml_launchers(check, content)
+    } else {
+        check.parse_json::<Vec<RawLauncher>>(content)
+    };
+    let Some((value, launchers)) = parsed else {
+        return;
+    };
+    // Lines of the `type` keys, one per launcher
+    let type_lines: Vec<usize> = if is_toml {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| {
+                split_toml_key(line.split('=').next().unwrap_or_default()) == ["type"]
+            })
+            .map(|(i, _)| i + 1)
+            .collect()
+    } else {
+        content
+            .match_indices(r#""type""#)
+            .map(|(pos, _)| content[..pos].lines().count())
+            .collect()
+    };
+    let raw = value.as_array().cloned().unwrap_or_default();
+    for (i, (launcher, raw)) in launchers.iter().zip(raw).enumerate() {
+        let name = launcher
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("#{}", i + 1));
+        let line = type_lines.get(i).copied();
+        for problem in launcher_problems(launcher) {
+            check.error(line, format!(r#"launcher "{name}": {problem}"#));
+        }
+
+        let known = serde_json::to_value(launcher).unwrap_or_default();
+        let mut unknown = Vec::new();
+        unknown_keys(&raw, &known, &mut Vec::new(), &mut unknown);
+        for path in unknown.into_iter().filter(|path| path.len() == 1) {
+            check.error(
+                line,
+                format!(r#"launcher "{name}" has the unknown key "{}""#, path[0]),
+            );
+        }
+    }
+}
+
+/// Parses the `[[launcher]]` tables of a fallback.toml
+fn parse_toml_launchers(check: &mut FileCheck, content: &str) -> Option<(Value, Vec<RawLauncher>)> {
+    let tables = match toml::from_str::<LauncherTables>(content) {
+        Ok(tables) => tables,
+        Err(e) => {
+            check.error(toml_line(content, &e), e.message());
+            return None;
+        }
+    };
+    let table = toml::from_str::<toml::Table>(content).ok()?;
+    let value = serde_json::to_value(table.get("launcher")?).ok()?;
+    Some((value, tables.launcher))
+}
+
+fn check_ignore(check: &mut FileCheck, content: &str) {
+    for (i, line) in content.lines().enumerate() {
+        if let Some(Err(e)) = IgnoreList::parse_line(line) {
+            check.error(Some(i + 1), format!(r#"invalid pattern "{line}": {e}"#));
+        }
+    }
+}
+
+/// Collects the keys of `value` that `known` does not have. Empty objects in `known` stand for
+/// maps with arbitrary keys, like `bangs`.
+fn unknown_keys(value: &Value, known: &Value, path: &mut Vec<String>, out: &mut Vec<Vec<String>>) {
+    let (Value::Object(value), Value::Object(known)) = (value, known) else {
+        return;
+    };
+    if known.is_empty() {
+        return;
+    }
+    for (key, child) in value {
+        path.push(key.clone());
+        match known.get(key) {
+            Some(known_child) => unknown_keys(child, known_child, path, out),
+            None => out.push(path.clone()),
+        }
+        path.pop();
+    }
+}
+
+fn toml_line(content: &str, e: &toml::de::Error) -> Option<usize> {
+    e.span()
+        .map(|span| content[..span.start].lines().count().max(1))
+}
+
+/// Line of a key, following `[table]` headers as well as dotted keys
+fn toml_key_line(content: &str, path: &[String]) -> Option<usize> {
+    let mut table: Vec<String> = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_start_matches('[').split(']').next()?;
+            table = split_toml_key(header);
+            continue;
+        }
+        let Some((key, _)) = line.split_once('=') else {
+            continue;
+        };
+        let mut full = table.clone();
+        full.extend(split_toml_key(key));
+        if full == path {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+fn split_toml_key(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').to_string())
+        .collect()
+}
+
+/// Line of the innermost key of the path, good enough for the shallow json configs
+fn json_key_line(content: &str, path: &[String]) -> Option<usize> {
+    let key = format!(r#""{}""#, path.last()?);
+    let pos = content.find(&key)?;
+    Some(content[..pos].lines().count().max(1))
+}
+
+#[test]
+fn test_unknown_keys() {
+    let content = "[appearance]\nwidht = 900\n\n[behavior]\nanimate = false\n\n[bangs]\ngh = \"https://github.com/search?q={keyword}\"\n\n[profile.work.appearance]\nwidth = 1200\nhieght = 600\n";
+    let mut check = FileCheck::new(Path::new("config.toml"));
+    check_main_config(&mut check, content, true);
+    assert_eq!(check.issues.len(), 2);
+    assert_eq!(check.issues[0].line, Some(2));
+    assert_eq!(check.issues[0].message, r#"unknown key "appearance.widht""#);
+    assert_eq!(check.issues[1].line, Some(12));
+    assert_eq!(
+        check.issues[1].message,
+        r#"unknown key "profile.work.appearance.hieght""#
+    );
+
+    let mut check = FileCheck::new(Path::new("config.toml"));
+    check_main_config(&mut check, "[behavior]\nanimate = \"yes\"\n", true);
+    assert_eq!(check.issues.len(), 1);
+    assert_eq!(check.issues[0].line, Some(2));
+
+    let content = "[[launcher]]\nname = \"Apps\"\ntype = \"app_launcher\"\npriority = 1\n\n[[launcher]]\nname = \"Docker\"\ntype = \"dockr\"\npriority = 2\n";
+    let mut check = FileCheck::new(Path::new("fallback.toml"));
+    check_fallback(&mut check, content, true);
+    assert_eq!(check.issues.len(), 1);
+    assert_eq!(check.issues[0].line, Some(8));
+
+    let mut check = FileCheck::new(Path::new("sherlockignore"));
+    check_ignore(&mut check, "# comment\nsteam*\n[unclosed\n");
+    assert_eq!(check.issues.len(), 1);
+    assert_eq!(check.issues[0].line, Some(3));
+}
@@ -4,6 +4,7 @@ use std::{
 };
 
 use crate::{
+    loader::DEFAULT_FALLBACK,
     sherlock_error,
     utils::{
         config::{ConfigAppearance, ConfigFiles, SherlockConfig, SherlockFlags, imp::WithRoot},
@@ -91,7 +92,7 @@ impl SherlockConfig {
         write_file("sherlockignore", "");
         write_file("sherlock_actions.json", "[]");
         write_file("sherlock_alias.json", "{}");
-        write_file("fallback.json", "{}");
+        write_file("fallback.json", DEFAULT_FALLBACK);
         write_file("main.css", "");
 
         if let Some(loc) = loc.to_str() {
@@ -120,6 +120,9 @@ impl ConstantDefaults {
             "teams-for-linux --enable-features=UseOzonePlatform --ozone-platform=wayland --url {meeting_url}",
         )
     }
+    pub fn elevation() -> String {
+        String::from("pkexec")
+    }
     pub fn calendar_client() -> String {
         String::from("thunderbird")
     }
@@ -138,6 +141,9 @@ impl ConstantDefaults {
     pub fn currency() -> String {
         String::from("eur")
     }
+    pub fn monospace_family() -> String {
+        String::from("monospace")
+    }
 }
 
 pub struct BindDefaults {}
@@ -226,6 +232,12 @@ impl OtherDefaults {
     pub fn five() -> u8 {
         5
     }
+    pub fn n_clicks() -> Option<u8> {
+        Some(2)
+    }
+    pub fn font_size_scale() -> f32 {
+        1.0
+    }
     pub fn backdrop_opacity() -> f64 {
         0.6
     }
@@ -244,4 +256,22 @@ impl OtherDefaults {
     pub fn placeholder() -> String {
         String::from("Search:")
     }
+    /// Milliseconds to wait for further keystrokes before filtering
+    pub fn history_size() -> usize {
+        100
+    }
+    pub fn search_debounce() -> u64 {
+        15
+    }
+    /// Maximum number of typos tolerated by the matcher
+    pub fn typo_tolerance() -> usize {
+        1
+    }
+    /// Days after which launch counts are halved
+    pub fn usage_half_life() -> f32 {
+        30.0
+    }
+    pub fn icon_memory() -> usize {
+        64
+    }
 }
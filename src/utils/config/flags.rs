@@ -3,10 +3,11 @@ use crate::{
     utils::{
         config::{ConfigSourceFiles, SherlockConfig},
         errors::{SherlockError, SherlockErrorType},
-        files::{expand_path, home_dir},
+        files::{env_matches, expand_path, home_dir, hostname},
         paths,
     },
 };
+use serde_json::Value;
 use std::{fs::read_to_string, path::PathBuf};
 
 #[derive(Clone, Debug, Default)]
@@ -28,10 +29,13 @@ pub struct SherlockFlags {
     pub photo_mode: bool,
     pub input: Option<bool>,
     pub placeholder: Option<String>,
+    /// Name of the `[profile.<name>]` section that is laid over the config
+    pub profile: Option<String>,
 }
 
 impl SherlockFlags {
-    pub fn to_config(&mut self) -> Result<(SherlockConfig, Vec<SherlockError>), SherlockError> {
+    /// Location of the config file and whether it is `json` or `toml`
+    pub fn config_file(&mut self) -> Result<(PathBuf, String), SherlockError> {
         // Get location of config file
         let config_dir = self.config_dir.take().unwrap_or(paths::get_config_dir()?);
         let home = home_dir()?;
@@ -72,10 +76,41 @@ impl SherlockFlags {
                 )
             ));
         }
+        Ok((path, filetype))
+    }
+    /// Builds the config from the files the flags point to, falling back to the defaults if the
+    /// config file cannot be parsed. The flags are left untouched so the config can be rebuilt.
+    pub fn load_config(&self) -> SherlockConfig {
+        let mut flags = self.clone();
+        flags.to_config().map_or_else(
+            |e| {
+                eprintln!("{e}");
+                let defaults = SherlockConfig::default();
+                SherlockConfig::apply_flags(&mut self.clone(), defaults)
+            },
+            |(cfg, non_crit)| {
+                if !non_crit.is_empty() {
+                    eprintln!("{:?}", non_crit);
+                }
+                cfg
+            },
+        )
+    }
+    pub fn to_config(&mut self) -> Result<(SherlockConfig, Vec<SherlockError>), SherlockError> {
+        let (path, filetype) = self.config_file()?;
+        let home = home_dir()?;
+        let mut non_crit = Vec::new();
 
         match std::fs::read_to_string(&path) {
             Ok(mut config_str) => {
+                let parse_error =
+                    |e: String| sherlock_error!(SherlockErrorType::FileParseError(path.clone()), e);
                 let config_res: Result<SherlockConfig, SherlockError> = match filetype.as_str() {
+                    "json" if self.has_overlays(&config_str) => {
+                        serde_json::from_str::<Value>(&config_str)
+                            .map_err(|e| parse_error(e.to_string()))
+                            .and_then(|value| self.with_overlays(value, &mut non_crit))
+                    }
                     "json" => {
                         let mut bytes = config_str.into_bytes();
                         simd_json::from_slice(&mut bytes).map_err(|e| {
@@ -107,12 +142,17 @@ impl SherlockFlags {
                                     });
                             }
                         }
-                        toml::de::from_str(&config_str).map_err(|e| {
-                            sherlock_error!(
-                                SherlockErrorType::FileParseError(path.clone()),
-                                e.to_string()
-                            )
-                        })
+                        if self.has_overlays(&config_str) {
+                            toml::de::from_str::<toml::Table>(&config_str)
+                                .map_err(|e| parse_error(e.to_string()))
+                                .and_then(|table| {
+                                    serde_json::to_value(table)
+                                        .map_err(|e| parse_error(e.to_string()))
+                                })
+                                .and_then(|value| self.with_overlays(value, &mut non_crit))
+                        } else {
+                            toml::de::from_str(&config_str).map_err(|e| parse_error(e.to_string()))
+                        }
                     }
                     _ => {
                         return Err(sherlock_error!(
@@ -127,7 +167,7 @@ impl SherlockFlags {
                 match config_res {
                     Ok(mut config) => {
                         config = SherlockConfig::apply_flags(self, config);
-                        Ok((config, vec![]))
+                        Ok((config, non_crit))
                     }
                     Err(e) => {
                         let mut config = SherlockConfig::default();
@@ -145,4 +185,146 @@ impl SherlockFlags {
             }
         }
     }
+    /// Whether the config might contain sections that are laid over the rest of it. Configs
+    /// without them are parsed directly, which keeps the locations in parse errors.
+    fn has_overlays(&self, config_str: &str) -> bool {
+        self.profile.is_some() || config_str.contains("when")
+    }
+    /// Lays the `[when.<condition>."<value>"]` sections that hold on this machine over the rest
+    /// of the config, followed by the selected `[profile.<name>]` section. Conditions are
+    /// `hostname` and `env`, the latter taking `NAME` or `NAME=value`, e.g.
+    /// `[when.env."XDG_CURRENT_DESKTOP=Hyprland"]`. Unknown conditions and profiles are reported
+    /// and leave the base config as it is.
+    fn with_overlays(
+        &self,
+        mut value: Value,
+        non_crit: &mut Vec<SherlockError>,
+    ) -> Result<SherlockConfig, SherlockError> {
+        let profiles = value
+            .as_object_mut()
+            .and_then(|config| config.remove("profile"));
+        let conditions = value
+            .as_object_mut()
+            .and_then(|config| config.remove("when"));
+
+        if let Some(Value::Object(conditions)) = conditions {
+            let host = hostname();
+            for (condition, sections) in conditions {
+                let Value::Object(sections) = sections else {
+                    continue;
+                };
+                for (target, overlay) in sections {
+                    let holds = match condition.as_str() {
+                        "hostname" => host.as_deref() == Some(target.as_str()),
+                        "env" => env_matches(&target),
+                        _ => {
+                            non_crit.push(sherlock_error!(
+                                SherlockErrorType::ConfigError(None),
+                                format!(
+                                    "Unknown condition \"when.{condition}\", expected \"hostname\" or \"env\""
+                                )
+                            ));
+                            break;
+                        }
+                    };
+                    if holds {
+                        merge_values(&mut value, overlay);
+                    }
+                }
+            }
+        }
+        if let Some(name) = &self.profile {
+            match profiles.and_then(|mut profiles| profiles.get_mut(name).map(Value::take)) {
+                Some(overlay) => merge_values(&mut value, overlay),
+                None => non_crit.push(sherlock_error!(
+                    SherlockErrorType::ConfigError(None),
+                    format!("The profile \"{name}\" is not defined in the config")
+                )),
+            }
+        }
+        serde_json::from_value(value)
+            .map_err(|e| sherlock_error!(SherlockErrorType::ConfigError(None), e.to_string()))
+    }
+}
+
+/// Merges `overlay` into `base`. Tables are merged key by key, everything else is replaced.
+fn merge_values(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[test]
+fn test_profile_overlay() {
+    let config = r#"
+[appearance]
+width = 900
+height = 600
+
+[profile.work.appearance]
+width = 1200
+
+[profile.work.files]
+fallback = "~/.config/sherlock/work.json"
+"#;
+    let value = serde_json::to_value(toml::from_str::<toml::Table>(config).unwrap()).unwrap();
+    let flags = SherlockFlags {
+        profile: Some(String::from("work")),
+        ..Default::default()
+    };
+    let mut non_crit = Vec::new();
+    let config = flags.with_overlays(value.clone(), &mut non_crit).unwrap();
+    assert!(non_crit.is_empty());
+    assert_eq!(config.appearance.width, 1200);
+    assert_eq!(config.appearance.height, 600);
+    assert_eq!(
+        config.files.fallback,
+        PathBuf::from("~/.config/sherlock/work.json")
+    );
+
+    let flags = SherlockFlags {
+        profile: Some(String::from("home")),
+        ..Default::default()
+    };
+    let config = flags.with_overlays(value, &mut non_crit).unwrap();
+    assert_eq!(non_crit.len(), 1);
+    assert_eq!(config.appearance.width, 900);
+}
+
+#[test]
+fn test_conditional_sections() {
+    let config = r#"
+[appearance]
+width = 900
+
+[when.env."PATH"]
+appearance = { height = 400 }
+
+[when.env."PATH=sherlock-missing-value".appearance]
+width = 1200
+
+[when.hostname."sherlock-missing-host".appearance]
+width = 1400
+
+[when.user.root.appearance]
+width = 1600
+"#;
+    let value = serde_json::to_value(toml::from_str::<toml::Table>(config).unwrap()).unwrap();
+    let mut non_crit = Vec::new();
+    let config = SherlockFlags::default()
+        .with_overlays(value, &mut non_crit)
+        .unwrap();
+    assert_eq!(config.appearance.width, 900);
+    assert_eq!(config.appearance.height, 400);
+    assert_eq!(non_crit.len(), 1);
 }
@@ -6,7 +6,8 @@ use std::{
 use crate::utils::{
     config::{
         ConfigAppearance, ConfigBackdrop, ConfigBehavior, ConfigBinds, ConfigCaching, ConfigDebug,
-        ConfigDefaultApps, ConfigExpand, ConfigFiles, ConfigUnits, SearchBarIcon, StatusBar,
+        ConfigDefaultApps, ConfigExpand, ConfigFiles, ConfigUnits, LaunchScope, SearchBarIcon,
+        StatusBar, WindowPosition,
         defaults::{BindDefaults, ConstantDefaults, FileDefaults, OtherDefaults},
     },
     files::home_dir,
@@ -18,8 +19,10 @@ impl Default for ConfigDefaultApps {
             teams: ConstantDefaults::teams(),
             calendar_client: ConstantDefaults::calendar_client(),
             terminal: ConstantDefaults::get_terminal().unwrap_or_default(), // Should never get to this...
+            terminal_args: None,
             browser: ConstantDefaults::browser().ok(),
             mpris: None,
+            elevation: ConstantDefaults::elevation(),
         }
     }
 }
@@ -31,6 +34,7 @@ impl Default for ConfigUnits {
             weights: ConstantDefaults::weights(),
             volumes: ConstantDefaults::volumes(),
             temperatures: ConstantDefaults::temperatures(),
+            wind_speeds: None,
             currency: ConstantDefaults::currency(),
         }
     }
@@ -52,6 +56,7 @@ impl Default for ConfigAppearance {
             width: 900,
             height: 593, // 617 with, 593 without notification bar
             margins: (0, 0, 0, 0),
+            position: WindowPosition::Center,
             anchor: String::from(""),
             gsk_renderer: String::from("cairo"),
             icon_paths: FileDefaults::icon_paths(),
@@ -59,10 +64,16 @@ impl Default for ConfigAppearance {
             use_base_css: true,
             use_system_theme: false,
             opacity: 1.0,
+            blur: true,
             mod_key_ascii: BindDefaults::modkey_ascii(),
             shortcut_mod: BindDefaults::shortcut_mod(),
             num_shortcuts: 5,
             placeholder: OtherDefaults::placeholder(),
+            placeholder_hints: false,
+            font_family: None,
+            font_size_scale: OtherDefaults::font_size_scale(),
+            monospace_tiles: false,
+            monospace_family: ConstantDefaults::monospace_family(),
         }
     }
 }
@@ -75,8 +86,18 @@ impl Default for ConfigBehavior {
             global_prefix: None,
             global_flags: None,
             use_lr_nav: false,
+            use_readline: false,
             remember_query: false,
-            n_clicks: Some(2),
+            history_size: OtherDefaults::history_size(),
+            n_clicks: OtherDefaults::n_clicks(),
+            search_debounce: OtherDefaults::search_debounce(),
+            typo_tolerance: OtherDefaults::typo_tolerance(),
+            switch_to_window: true,
+            usage_half_life: OtherDefaults::usage_half_life(),
+            home_order: Vec::new(),
+            run_as_root: Vec::new(),
+            uncounted: Vec::new(),
+            launch_scope: LaunchScope::None,
         }
     }
 }
@@ -113,6 +134,7 @@ impl Default for ConfigCaching {
         Self {
             enable: true,
             cache: FileDefaults::cache(),
+            icon_memory: OtherDefaults::icon_memory(),
         }
     }
 }
@@ -4,15 +4,18 @@ use std::{
     path::PathBuf,
 };
 
+use crate::loader::utils::RawLauncher;
 use crate::ui::UIFunction;
 use crate::utils::config::defaults::FileDefaults;
 
+mod check;
 mod config_impl;
 mod defaults;
 mod flags;
 mod guard;
 mod imp;
 
+pub use check::check_config;
 pub use defaults::{BindDefaults, ConstantDefaults, OtherDefaults};
 pub use flags::SherlockFlags;
 pub use guard::ConfigGuard;
@@ -43,7 +46,8 @@ pub struct SherlockConfig {
     #[serde(default)]
     pub binds: ConfigBinds,
 
-    /// Custom key or action bindings (supplementing defaults)
+    /// Keys bound to UI functions, e.g. `"ctrl-n" = "item_down"`. Keys that are bound by default
+    /// are taken over by the function.
     #[serde(default)]
     pub keybinds: HashMap<String, UIFunction>,
 
@@ -74,6 +78,14 @@ pub struct SherlockConfig {
     /// Configures search bar icons
     #[serde(default)]
     pub search_bar_icon: SearchBarIcon,
+
+    /// Custom search bangs mapped to an engine or url template (supplementing defaults)
+    #[serde(default)]
+    pub bangs: HashMap<String, String>,
+
+    /// Launchers defined in `[[launcher]]` tables, loaded after the ones of the fallback file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub launcher: Vec<RawLauncher>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -84,10 +96,17 @@ pub struct ConfigDefaultApps {
     pub calendar_client: String,
     #[serde(default = "ConstantDefaults::terminal")]
     pub terminal: String,
+    /// Arguments placed between the terminal and the command, e.g. `--` for gnome-terminal.
+    /// Derived from the terminal if unset.
+    #[serde(default)]
+    pub terminal_args: Option<String>,
     #[serde(default)]
     pub browser: Option<String>,
     #[serde(default)]
     pub mpris: Option<String>,
+    /// Helper the "Run as root" action runs commands through
+    #[serde(default = "ConstantDefaults::elevation")]
+    pub elevation: String,
 }
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ConfigUnits {
@@ -99,6 +118,9 @@ pub struct ConfigUnits {
     pub volumes: String,
     #[serde(default = "ConstantDefaults::temperatures")]
     pub temperatures: String,
+    /// `km/h`, `mph`, `m/s` or `kn`. Follows `lengths` if unset.
+    #[serde(default)]
+    pub wind_speeds: Option<String>,
     #[serde(default = "ConstantDefaults::currency")]
     pub currency: String,
 }
@@ -119,8 +141,13 @@ pub struct ConfigAppearance {
     pub width: i32,
     #[serde(default)]
     pub height: i32,
+    /// Margins to the edges of the screen as top, right, bottom and left. The top margin is the
+    /// offset of `top_center` windows.
     #[serde(default)]
     pub margins: (i32, i32, i32, i32),
+    /// Where the window opens on the screen
+    #[serde(default)]
+    pub position: WindowPosition,
     #[serde(default)]
     pub anchor: String,
     #[serde(default)]
@@ -133,8 +160,12 @@ pub struct ConfigAppearance {
     pub use_base_css: bool,
     #[serde(default)]
     pub use_system_theme: bool,
+    /// Opacity of the window background, from 0 to 1
     #[serde(default = "OtherDefaults::one")]
     pub opacity: f64,
+    /// Blurs what lies behind the window, where the compositor supports it
+    #[serde(default = "OtherDefaults::bool_true")]
+    pub blur: bool,
     #[serde(default = "BindDefaults::modkey_ascii")]
     pub mod_key_ascii: Vec<String>,
     #[serde(default = "BindDefaults::shortcut_mod")]
@@ -143,6 +174,21 @@ pub struct ConfigAppearance {
     pub num_shortcuts: u8,
     #[serde(default = "OtherDefaults::placeholder")]
     pub placeholder: String,
+    /// Alternates the placeholder with hints on the loaded modes and launchers, such as
+    /// "Type 'pm ' for Power Menu"
+    #[serde(default)]
+    pub placeholder_hints: bool,
+    /// Family of every text, e.g. `Inter`. Uses the system default if unset.
+    #[serde(default)]
+    pub font_family: Option<String>,
+    /// Factor all text sizes are multiplied with
+    #[serde(default = "OtherDefaults::font_size_scale")]
+    pub font_size_scale: f32,
+    /// Shows calculator results and command output in `monospace_family`
+    #[serde(default)]
+    pub monospace_tiles: bool,
+    #[serde(default = "ConstantDefaults::monospace_family")]
+    pub monospace_family: String,
 }
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ConfigBehavior {
@@ -154,12 +200,47 @@ pub struct ConfigBehavior {
     pub global_prefix: Option<String>,
     #[serde(default)]
     pub global_flags: Option<String>,
-    #[serde(default = "OtherDefaults::bool_true")]
+    /// Moves the selection with the left and right arrows instead of the text cursor
+    #[serde(default)]
     pub use_lr_nav: bool,
+    /// Binds ctrl-a/ctrl-e to home/end and ctrl-u/ctrl-k to deleting up to either end, in
+    /// place of ctrl-a selecting everything
     #[serde(default)]
+    pub use_readline: bool,
+    /// Clicks on a result that run it, `0` only selects it
+    #[serde(default = "OtherDefaults::n_clicks")]
     pub n_clicks: Option<u8>,
     #[serde(default)]
     pub remember_query: bool,
+    /// Queries kept for ctrl-p/ctrl-n, `0` keeps none
+    #[serde(default = "OtherDefaults::history_size")]
+    pub history_size: usize,
+    #[serde(default = "OtherDefaults::search_debounce")]
+    pub search_debounce: u64,
+    #[serde(default = "OtherDefaults::typo_tolerance")]
+    pub typo_tolerance: usize,
+    /// Focuses the open window of an app instead of launching another instance
+    #[serde(default = "OtherDefaults::bool_true")]
+    pub switch_to_window: bool,
+    /// Days after which launch counts are halved, so apps that are no longer used sink again.
+    /// `0` keeps the counts forever.
+    #[serde(default = "OtherDefaults::usage_half_life")]
+    pub usage_half_life: f32,
+    /// Names or commands of items that lead the home view in this order, right after the
+    /// pinned ones
+    #[serde(default)]
+    pub home_order: Vec<String>,
+    /// Names or commands of the apps and commands that offer "Run as root", as globs or regexes
+    /// like the keys of sherlock_alias.json
+    #[serde(default)]
+    pub run_as_root: Vec<String>,
+    /// Items whose launches are not counted, so one-off commands do not climb the ranking.
+    /// Patterns in the format of the sherlockignore file, e.g. `command:shutdown*`.
+    #[serde(default)]
+    pub uncounted: Vec<String>,
+    /// Unit launched applications are placed in, so they get a cgroup of their own
+    #[serde(default)]
+    pub launch_scope: LaunchScope,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -224,6 +305,9 @@ pub struct ConfigCaching {
     pub enable: bool,
     #[serde(default = "FileDefaults::cache")]
     pub cache: PathBuf,
+    /// Memory budget for decoded icons in MiB
+    #[serde(default = "OtherDefaults::icon_memory")]
+    pub icon_memory: usize,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -276,6 +360,32 @@ pub struct ConfigSource {
     pub file: PathBuf,
 }
 
+/// Placement of the window on the screen
+#[derive(Debug, Copy, Clone, Deserialize, PartialEq, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowPosition {
+    /// In the middle of the screen, like rofi
+    #[default]
+    Center,
+    /// Docked to the top edge, like wofi
+    TopCenter,
+    /// Centered on the pointer where the compositor reports it, in the middle otherwise
+    Cursor,
+}
+
+/// How launched applications are started on systemd setups
+#[derive(Debug, Copy, Clone, Deserialize, PartialEq, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchScope {
+    /// Applications stay in the cgroup of Sherlock
+    #[default]
+    None,
+    /// A transient scope through `systemd-run --user --scope`
+    Systemd,
+    /// A unit through `uwsm app`, for sessions managed by uwsm
+    Uwsm,
+}
+
 #[derive(Debug, Copy, Clone, Deserialize, PartialEq, Serialize, Default)]
 pub enum HomeType {
     #[default]
@@ -59,6 +59,7 @@ pub enum SherlockErrorType {
     // Config & Flags
     ConfigError(Option<String>),
     FlagLoadError,
+    LauncherConfigError(String),
 
     // Resources
     ResourceParseError,
@@ -98,6 +99,7 @@ pub enum SherlockErrorType {
     // (De-) Serialization
     SerializationError,
     DeserializationError,
+    CacheInvalidError(PathBuf),
 
     // Apps
     UnsupportedBrowser(String),
@@ -166,6 +168,9 @@ impl SherlockErrorType {
                 }
             }
             SherlockErrorType::FlagLoadError => "Failed to load flags".into(),
+            SherlockErrorType::LauncherConfigError(name) => {
+                format!("Launcher \"{}\" is misconfigured", name)
+            }
 
             // Resources
             SherlockErrorType::ResourceParseError => "Failed to parse resources".into(),
@@ -225,6 +230,12 @@ impl SherlockErrorType {
             SherlockErrorType::DeserializationError => {
                 format!("Failed to deserialize content.")
             }
+            SherlockErrorType::CacheInvalidError(f) => {
+                format!(
+                    "Cache \"{}\" is outdated or corrupted and will be rebuilt.",
+                    f.to_string_lossy()
+                )
+            }
 
             // Apps
             SherlockErrorType::UnsupportedBrowser(browser) => {
@@ -0,0 +1,272 @@
+use futures::{StreamExt, stream};
+use gpui::{App, AsyncApp, Entity};
+use image::ImageFormat;
+use rusqlite::{Connection, OpenFlags, params};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::launcher::bookmark_launcher::BookmarkLauncher;
+use crate::launcher::children::RenderableChild;
+use crate::loader::render_svg_to_png;
+use crate::utils::paths::get_cache_dir;
+
+/// Where favicons of bookmarks and web tiles are taken from
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FaviconSource {
+    /// The browser's favicon database, without network requests
+    #[default]
+    Browser,
+    /// Request `/favicon.ico` from the site
+    Network,
+    #[serde(alias = "none")]
+    Disabled,
+}
+
+/// A page whose favicon should be shown on a tile
+pub struct FaviconPage {
+    pub host: String,
+    pub url: String,
+    /// Browser to read the favicon database of
+    pub browser: Option<String>,
+    /// Whether `/favicon.ico` may be requested from the site, which is opt-in
+    pub network: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum FaviconSchema {
+    Mozilla,
+    Chromium,
+}
+
+/// A (copied) favicon database of a browser
+#[derive(Clone, Debug)]
+pub struct FaviconDatabase {
+    pub path: PathBuf,
+    pub schema: FaviconSchema,
+}
+impl FaviconDatabase {
+    /// Reads the largest favicon stored for the page. Pages that have not been visited yet
+    /// borrow the favicon of another page on the same host.
+    pub fn lookup(&self, url: &str, host: &str) -> Option<Vec<u8>> {
+        let query = match self.schema {
+            FaviconSchema::Mozilla => {
+                "
+                SELECT i.data
+                FROM moz_icons i
+                JOIN moz_icons_to_pages ip ON ip.icon_id = i.id
+                JOIN moz_pages_w_icons p ON p.id = ip.page_id
+                WHERE p.page_url = ?1 OR p.page_url LIKE ?2
+                ORDER BY p.page_url = ?1 DESC, i.width DESC
+                LIMIT 1;
+                "
+            }
+            FaviconSchema::Chromium => {
+                "
+                SELECT b.image_data
+                FROM icon_mapping m
+                JOIN favicon_bitmaps b ON b.icon_id = m.icon_id
+                WHERE m.page_url = ?1 OR m.page_url LIKE ?2
+                ORDER BY m.page_url = ?1 DESC, b.width DESC
+                LIMIT 1;
+                "
+            }
+        };
+        let conn =
+            Connection::open_with_flags(&self.path, OpenFlags::SQLITE_OPEN_READ_ONLY).ok()?;
+        conn.query_row(query, params![url, format!("%://{host}/%")], |row| {
+            row.get(0)
+        })
+        .ok()
+    }
+}
+
+/// Extracts the lowercase host of an http(s) url, e.g. `github.com` for
+/// `https://github.com/Skxxtz/sherlock`
+pub fn url_host(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    if !matches!(scheme.to_ascii_lowercase().as_str(), "http" | "https") {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+fn favicon_path(host: &str) -> Option<PathBuf> {
+    Some(
+        get_cache_dir()
+            .ok()?
+            .join("favicons")
+            .join(format!("{host}.png")),
+    )
+}
+
+/// Looks up the cached favicon of a host. `Some(None)` marks hosts known to have no favicon.
+pub fn cached_favicon(host: &str) -> Option<Option<Arc<Path>>> {
+    let path = favicon_path(host)?;
+    let meta = fs::metadata(&path).ok()?;
+    Some((meta.len() > 0).then(|| Arc::from(path.into_boxed_path())))
+}
+
+/// Converts a favicon to png and caches it. Hosts without a usable favicon are remembered by an
+/// empty file, so they are not requested again on every launch.
+pub fn store_favicon(host: &str, data: Option<&[u8]>) -> Option<Arc<Path>> {
+    let path = favicon_path(host)?;
+    fs::create_dir_all(path.parent()?).ok()?;
+
+    let stored = data.is_some_and(|data| {
+        let head = String::from_utf8_lossy(&data[..data.len().min(256)]);
+        if head.contains("<svg") {
+            render_svg_to_png(data, &path).is_some()
+        } else {
+            image::load_from_memory(data)
+                .and_then(|icon| icon.save_with_format(&path, ImageFormat::Png))
+                .is_ok()
+        }
+    });
+    if !stored {
+        let _ = fs::write(&path, []);
+        return None;
+    }
+    Some(Arc::from(path.into_boxed_path()))
+}
+
+/// Requests `/favicon.ico` of the host. Network failures are returned as errors, while missing
+/// favicons yield `None`.
+pub async fn fetch_favicon(host: &str) -> Result<Option<Vec<u8>>, reqwest::Error> {
+    let response = reqwest::get(format!("https://{host}/favicon.ico")).await?;
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+    Ok(Some(response.bytes().await?.to_vec()))
+}
+
+/// Number of favicons requested at the same time
+const MAX_FETCHES: usize = 8;
+
+/// Shows cached favicons right away and fetches the missing ones in the background, first from
+/// the browser's favicon database, then from the network if the launcher opted into it.
+pub fn resolve_favicons(cx: &mut App, data_handle: Entity<Arc<Vec<RenderableChild>>>) {
+    let mut cached: HashMap<String, Option<Arc<Path>>> = HashMap::new();
+    let mut missing: HashMap<String, FaviconPage> = HashMap::new();
+    for page in data_handle
+        .read(cx)
+        .iter()
+        .filter_map(RenderableChild::favicon_page)
+    {
+        if cached.contains_key(&page.host) || missing.contains_key(&page.host) {
+            continue;
+        }
+        match cached_favicon(&page.host) {
+            Some(icon) => {
+                cached.insert(page.host, icon);
+            }
+            None => {
+                missing.insert(page.host.clone(), page);
+            }
+        }
+    }
+
+    if cached.values().any(Option::is_some) {
+        data_handle.update(cx, |items_arc, cx| {
+            let items = Arc::make_mut(items_arc);
+            for item in items.iter_mut() {
+                item.apply_favicons(&cached);
+            }
+            cx.notify();
+        });
+    }
+    if missing.is_empty() {
+        return;
+    }
+
+    cx.spawn(|cx: &mut AsyncApp| {
+        let cx = cx.clone();
+        async move {
+            // Browser databases work offline, so they are asked first
+            let (mut resolved, remaining) = cx
+                .background_executor()
+                .spawn(async move {
+                    let mut databases: HashMap<String, Option<FaviconDatabase>> = HashMap::new();
+                    let mut resolved = HashMap::new();
+                    let mut remaining = Vec::new();
+                    for (host, page) in missing {
+                        let data = page.browser.and_then(|browser| {
+                            databases
+                                .entry(browser)
+                                .or_insert_with_key(|b| BookmarkLauncher::favicon_database(b))
+                                .as_ref()?
+                                .lookup(&page.url, &host)
+                        });
+                        match data {
+                            Some(data) => {
+                                let icon = store_favicon(&host, Some(&data));
+                                resolved.insert(host, icon);
+                            }
+                            None if page.network => remaining.push(host),
+                            None => {}
+                        }
+                    }
+                    (resolved, remaining)
+                })
+                .await;
+
+            let fetched: Vec<_> = stream::iter(remaining)
+                .map(|host| async move {
+                    let data = fetch_favicon(&host).await;
+                    (host, data)
+                })
+                .buffer_unordered(MAX_FETCHES)
+                .collect()
+                .await;
+            let stored: Vec<(String, Option<Arc<Path>>)> = cx
+                .background_executor()
+                .spawn(async move {
+                    fetched
+                        .into_iter()
+                        // Unreachable hosts are retried on the next launch
+                        .filter_map(|(host, data)| {
+                            let icon = store_favicon(&host, data.ok()?.as_deref());
+                            Some((host, icon))
+                        })
+                        .collect()
+                })
+                .await;
+            resolved.extend(stored);
+
+            let _ = cx.update(|cx| {
+                data_handle.update(cx, |items_arc, cx| {
+                    let items = Arc::make_mut(items_arc);
+                    for item in items.iter_mut() {
+                        item.apply_favicons(&resolved);
+                    }
+                    cx.notify();
+                });
+            });
+        }
+    })
+    .detach();
+}
+
+#[test]
+fn test_url_host() {
+    assert_eq!(
+        url_host("https://github.com/Skxxtz/sherlock").as_deref(),
+        Some("github.com")
+    );
+    assert_eq!(
+        url_host("HTTP://user:pw@Example.org:8080/?q=1").as_deref(),
+        Some("example.org")
+    );
+    assert_eq!(url_host("https://docs.rs#top").as_deref(), Some("docs.rs"));
+    assert_eq!(url_host("file:///home/user/index.html"), None);
+    assert_eq!(url_host("javascript:alert(1)"), None);
+    assert_eq!(url_host("{keyword}"), None);
+}
@@ -26,6 +26,38 @@ pub fn expand_path<T: AsRef<Path>>(path: T, home: &Path) -> PathBuf {
     }
     path.to_path_buf()
 }
+/// Resolves a program name against `$PATH`. Absolute paths are only checked for existence.
+pub fn find_executable(program: &str) -> Option<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let is_executable = |path: &Path| {
+        path.metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    };
+    let path = Path::new(program);
+    if path.is_absolute() {
+        return is_executable(path).then(|| path.to_path_buf());
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+/// Checks an environment variable, `NAME=value` for an exact value and `NAME` for any value
+pub fn env_matches(spec: &str) -> bool {
+    match spec.split_once('=') {
+        Some((name, value)) => env::var(name).is_ok_and(|v| v == value),
+        None => env::var(spec).is_ok_and(|v| !v.is_empty()),
+    }
+}
+/// Name of this machine
+pub fn hostname() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| env::var("HOSTNAME").ok())
+}
 pub fn home_dir() -> Result<PathBuf, SherlockError> {
     env::var("HOME")
         .map_err(|e| {
@@ -2,8 +2,14 @@ pub mod cache;
 pub mod command_launch;
 pub mod config;
 pub mod errors;
+pub mod favicon;
 pub mod files;
 pub mod intent;
 pub mod logging;
 pub mod paths;
+pub mod startup_notification;
+pub mod thumbnails;
+pub mod user_state;
+pub mod watch;
 pub mod websearch;
+pub mod windows;
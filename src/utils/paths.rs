@@ -1,5 +1,8 @@
 use crate::utils::files;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 fn get_xdg_dirs() -> xdg::BaseDirectories {
     xdg::BaseDirectories::with_prefix("sherlock")
@@ -36,21 +39,16 @@ pub fn get_config_dir() -> Result<PathBuf, crate::utils::errors::SherlockError>
     Ok(dir)
 }
 
-/// Returns the data directory.
+/// Returns the state directory.
 ///
-/// It first checks for the legacy `~/.sherlock` directory. If it exists, it returns that path.
-/// Otherwise, it returns the XDG standard data path, `$XDG_DATA_HOME/sherlock`.
-/// If the directory does not exist, it will be created.
-pub fn get_data_dir() -> Result<PathBuf, crate::utils::errors::SherlockError> {
-    let legacy_path = legacy_path()?;
-    if legacy_path.exists() {
-        return Ok(legacy_path);
-    }
+/// This function returns the XDG standard state path, `$XDG_STATE_HOME/sherlock`, which holds
+/// mutable state like usage counts. If the directory does not exist, it will be created.
+pub fn get_state_dir() -> Result<PathBuf, crate::utils::errors::SherlockError> {
     let xdg_dirs = get_xdg_dirs();
-    let dir = xdg_dirs.get_data_home().ok_or_else(|| {
+    let dir = xdg_dirs.get_state_home().ok_or_else(|| {
         crate::sherlock_error!(
             crate::utils::errors::SherlockErrorType::DirReadError(
-                "Could not find data directory".to_string()
+                "Could not find state directory".to_string()
             ),
             ""
         )
@@ -58,7 +56,7 @@ pub fn get_data_dir() -> Result<PathBuf, crate::utils::errors::SherlockError> {
     fs::create_dir_all(&dir).map_err(|_| {
         crate::sherlock_error!(
             crate::utils::errors::SherlockErrorType::DirCreateError(
-                "Could not create data directory".to_string()
+                "Could not create state directory".to_string()
             ),
             ""
         )
@@ -66,6 +64,35 @@ pub fn get_data_dir() -> Result<PathBuf, crate::utils::errors::SherlockError> {
     Ok(dir)
 }
 
+/// Returns the path of a file in the state directory.
+///
+/// Older versions kept state in the data directory or in `~/.sherlock`. The first time a file
+/// is asked for, it is moved over from there.
+pub fn get_state_file(name: &str) -> Result<PathBuf, crate::utils::errors::SherlockError> {
+    let path = get_state_dir()?.join(name);
+    if !path.exists() {
+        let old = [legacy_path().ok(), get_xdg_dirs().get_data_home()]
+            .into_iter()
+            .flatten()
+            .map(|dir| dir.join(name))
+            .find(|old| old.is_file());
+        if let Some(old) = old {
+            migrate(&old, &path);
+        }
+    }
+    Ok(path)
+}
+
+fn migrate(from: &Path, to: &Path) {
+    if fs::rename(from, to).is_ok() {
+        return;
+    }
+    // Renaming fails across file systems
+    if fs::copy(from, to).is_ok() {
+        let _ = fs::remove_file(from);
+    }
+}
+
 /// Returns the cache directory.
 ///
 /// This function returns the XDG standard cache path, `$XDG_CACHE_HOME/sherlock`.
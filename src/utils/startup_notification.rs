@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        Event,
+        xproto::{
+            AtomEnum, ClientMessageEvent, ConnectionExt, CreateWindowAux, EventMask, PropMode,
+            Timestamp, Window, WindowClass,
+        },
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+};
+
+use crate::utils::watch::wait_readable;
+
+/// How long the X server may take to report its time before the launch goes unannounced
+const SERVER_TIME_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Announces the launch of `program` to the X11 window manager and returns the startup id for
+/// its `DESKTOP_STARTUP_ID`. The id carries the X server time of the launch, which the window
+/// manager compares against user input when deciding whether to focus the new window.
+pub fn x11_startup_id(program: &str) -> Option<String> {
+    static LAUNCHES: AtomicUsize = AtomicUsize::new(0);
+
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen)?.root;
+    // Sends the messages and receives the timestamp, it is destroyed with the connection
+    let window = conn.generate_id().ok()?;
+    conn.create_window(
+        x11rb::COPY_DEPTH_FROM_PARENT,
+        window,
+        root,
+        -100,
+        -100,
+        1,
+        1,
+        0,
+        WindowClass::INPUT_OUTPUT,
+        x11rb::COPY_FROM_PARENT,
+        &CreateWindowAux::new()
+            .override_redirect(1)
+            .event_mask(EventMask::PROPERTY_CHANGE),
+    )
+    .ok()?;
+
+    let time = server_time(&conn, window)?;
+    let name = program.rsplit('/').next().unwrap_or(program);
+    let id = format!(
+        "sherlock-{}-{}-{name}_TIME{time}",
+        std::process::id(),
+        LAUNCHES.fetch_add(1, Ordering::Relaxed),
+    );
+    let message = format!(
+        "new: ID={} NAME={} BIN={} SCREEN={screen}",
+        quote(&id),
+        quote(name),
+        quote(name)
+    );
+
+    let atom = |name: &[u8]| Some(conn.intern_atom(false, name).ok()?.reply().ok()?.atom);
+    let begin = atom(b"_NET_STARTUP_INFO_BEGIN")?;
+    let more = atom(b"_NET_STARTUP_INFO")?;
+    for (i, chunk) in message_chunks(&message).into_iter().enumerate() {
+        let kind = if i == 0 { begin } else { more };
+        let event = ClientMessageEvent::new(8, window, kind, chunk);
+        conn.send_event(false, root, EventMask::PROPERTY_CHANGE, event)
+            .ok()?;
+    }
+    conn.flush().ok()?;
+    Some(id)
+}
+
+/// Current time of the X server. Appending nothing to a property still makes the server report
+/// a change, along with its time.
+fn server_time(conn: &RustConnection, window: Window) -> Option<Timestamp> {
+    let atom = conn
+        .intern_atom(false, b"_SHERLOCK_TIMESTAMP")
+        .ok()?
+        .reply()
+        .ok()?
+        .atom;
+    conn.change_property8(PropMode::APPEND, window, atom, AtomEnum::STRING, &[])
+        .ok()?;
+    conn.flush().ok()?;
+    // Launches are not held up by an unresponsive server
+    let deadline = Instant::now() + SERVER_TIME_TIMEOUT;
+    loop {
+        while let Some(event) = conn.poll_for_event().ok()? {
+            if let Event::PropertyNotify(event) = event
+                && event.window == window
+            {
+                return Some(event.time);
+            }
+        }
+        let left = deadline.checked_duration_since(Instant::now())?;
+        wait_readable(conn.stream(), left);
+    }
+}
+
+/// Quotes a value of a startup message, escaping quotes and backslashes
+fn quote(value: &str) -> String {
+    format!(r#""{}""#, value.replace('\\', r"\\").replace('"', r#"\""#))
+}
+
+/// The nul-terminated message split into the 20 bytes a client message carries
+fn message_chunks(message: &str) -> Vec<[u8; 20]> {
+    let mut bytes = message.as_bytes().to_vec();
+    bytes.push(0);
+    bytes
+        .chunks(20)
+        .map(|chunk| {
+            let mut data = [0; 20];
+            data[..chunk.len()].copy_from_slice(chunk);
+            data
+        })
+        .collect()
+}
+
+#[test]
+fn test_startup_message() {
+    assert_eq!(quote(r#"my "app"\1"#), r#""my \"app\"\\1""#);
+
+    let message = r#"new: ID="sherlock-1-0-foot_TIME42" NAME="foot" SCREEN=0"#;
+    let chunks = message_chunks(message);
+    assert_eq!(chunks.len(), message.len() / 20 + 1);
+    let joined: Vec<u8> = chunks.concat();
+    assert_eq!(&joined[..message.len()], message.as_bytes());
+    // Terminated by a nul byte, with the rest of the last chunk zeroed
+    assert!(joined[message.len()..].iter().all(|byte| *byte == 0));
+}
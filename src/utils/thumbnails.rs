@@ -0,0 +1,269 @@
+use image::{DynamicImage, ImageFormat};
+use md5::{Digest, Md5};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use crate::utils::files::find_executable;
+
+/// Edge length of the `large` thumbnails, which are generated. `normal` ones are only reused.
+const LARGE: u32 = 256;
+/// Files above this size are not thumbnailed, decoding them would take too long
+const MAX_SOURCE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Characters kept as they are in file uris, besides alphanumerics
+const URI_PATH: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// The uri of a file as the thumbnail spec hashes it, e.g. `file:///home/user/a%20b.png`
+pub fn file_uri(path: &Path) -> String {
+    format!(
+        "file://{}",
+        utf8_percent_encode(&path.to_string_lossy(), URI_PATH)
+    )
+}
+
+/// File name of the thumbnails of an uri, the md5 of the uri
+fn thumbnail_name(uri: &str) -> String {
+    let digest = Md5::digest(uri.as_bytes());
+    let hex: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("{hex}.png")
+}
+
+/// `$XDG_CACHE_HOME/thumbnails`, shared with file managers
+fn thumbnail_dir() -> Option<PathBuf> {
+    Some(
+        xdg::BaseDirectories::new()
+            .get_cache_home()?
+            .join("thumbnails"),
+    )
+}
+
+/// Returns the thumbnail of the file, generating it for images and PDFs if no file manager did
+/// so yet. Blocks while generating, so it is meant for background threads.
+pub fn thumbnail(path: &Path) -> Option<Arc<Path>> {
+    let meta = fs::metadata(path).ok()?;
+    let mtime = meta
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    let uri = file_uri(path);
+    let name = thumbnail_name(&uri);
+    let dir = thumbnail_dir()?;
+    // Thumbnails of files inside the cache are never looked up
+    if path.starts_with(&dir) {
+        return None;
+    }
+
+    let is_current = |thumbnail: &Path| {
+        let chunks = fs::read(thumbnail).map(|png| text_chunks(&png));
+        chunks.is_ok_and(|chunks| {
+            chunks.get("Thumb::MTime") == Some(&mtime.to_string())
+                && chunks.get("Thumb::URI").is_none_or(|stored| *stored == uri)
+        })
+    };
+    let existing = ["large", "x-large", "normal"]
+        .into_iter()
+        .map(|size| dir.join(size).join(&name))
+        .find(|thumbnail| is_current(thumbnail));
+    if let Some(existing) = existing {
+        return Some(Arc::from(existing.into_boxed_path()));
+    }
+
+    // Files that could not be thumbnailed before are not tried again until they change
+    let failed = dir.join("fail").join("sherlock").join(&name);
+    let kind = kind(path)?;
+    if is_current(&failed) || meta.len() > MAX_SOURCE_SIZE {
+        return None;
+    }
+    // PDFs are rendered by poppler, which might be installed later on
+    if kind == SourceKind::Pdf && find_executable("pdftoppm").is_none() {
+        return None;
+    }
+    let text = [
+        ("Thumb::URI", uri.as_str()),
+        ("Thumb::MTime", &mtime.to_string()),
+        ("Software", "Sherlock"),
+    ];
+    match render(path, kind) {
+        Some(image) => {
+            let thumbnail = dir.join("large").join(&name);
+            store(&image, &text, &thumbnail)?;
+            Some(Arc::from(thumbnail.into_boxed_path()))
+        }
+        None => {
+            store(&DynamicImage::new_rgba8(1, 1), &text, &failed);
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SourceKind {
+    Image,
+    Pdf,
+}
+
+/// The kinds of files thumbnails are generated for, by their extension
+fn kind(path: &Path) -> Option<SourceKind> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "tif" | "tiff" | "ico" | "tga"
+        | "qoi" => Some(SourceKind::Image),
+        "pdf" => Some(SourceKind::Pdf),
+        _ => None,
+    }
+}
+
+/// Renders the image or the first page of the PDF at the size of `large` thumbnails
+fn render(path: &Path, kind: SourceKind) -> Option<DynamicImage> {
+    let image = match kind {
+        SourceKind::Image => image::open(path).ok()?,
+        SourceKind::Pdf => {
+            let output = Command::new("pdftoppm")
+                .args(["-png", "-singlefile", "-f", "1", "-scale-to"])
+                .arg(LARGE.to_string())
+                .arg(path)
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            image::load_from_memory_with_format(&output.stdout, ImageFormat::Png).ok()?
+        }
+    };
+    // Smaller images are not scaled up
+    if image.width() <= LARGE && image.height() <= LARGE {
+        return Some(image);
+    }
+    Some(image.thumbnail(LARGE, LARGE))
+}
+
+/// Writes the thumbnail with its text chunks, through a temporary file so other programs never
+/// see half of it
+fn store(image: &DynamicImage, text: &[(&str, &str)], path: &Path) -> Option<()> {
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .ok()?;
+    let png = with_text_chunks(png, text)?;
+
+    let dir = path.parent()?;
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)
+        .ok()?;
+    let temporary = dir.join(format!(
+        ".{}.sherlock-{}",
+        path.file_name()?.to_string_lossy(),
+        std::process::id()
+    ));
+    let written = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&temporary)
+        .and_then(|mut file| file.write_all(&png));
+    if written.and_then(|_| fs::rename(&temporary, path)).is_err() {
+        let _ = fs::remove_file(&temporary);
+        return None;
+    }
+    Some(())
+}
+
+/// Inserts `tEXt` chunks right after the header of an encoded png
+fn with_text_chunks(png: Vec<u8>, text: &[(&str, &str)]) -> Option<Vec<u8>> {
+    // Signature, then the header chunk: length, type, 13 bytes of data and the checksum
+    const HEADER_END: usize = 8 + 4 + 4 + 13 + 4;
+    if png.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let mut out = Vec::with_capacity(png.len() + 128);
+    out.extend_from_slice(&png[..HEADER_END]);
+    for (keyword, value) in text {
+        let mut chunk = b"tEXt".to_vec();
+        chunk.extend_from_slice(keyword.as_bytes());
+        chunk.push(0);
+        chunk.extend_from_slice(value.as_bytes());
+        out.extend_from_slice(&((chunk.len() - 4) as u32).to_be_bytes());
+        out.extend_from_slice(&chunk);
+        out.extend_from_slice(&crc32fast::hash(&chunk).to_be_bytes());
+    }
+    out.extend_from_slice(&png[HEADER_END..]);
+    Some(out)
+}
+
+/// Keywords and values of the `tEXt` chunks of a png
+fn text_chunks(png: &[u8]) -> HashMap<String, String> {
+    let mut chunks = HashMap::new();
+    let mut offset = 8;
+    while let Some(header) = png.get(offset..offset + 8) {
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let Some(data) = png.get(offset + 8..offset + 8 + length) else {
+            break;
+        };
+        match &header[4..8] {
+            b"tEXt" => {
+                if let Some((keyword, value)) = data
+                    .iter()
+                    .position(|byte| *byte == 0)
+                    .map(|nul| (&data[..nul], &data[nul + 1..]))
+                {
+                    chunks.insert(
+                        String::from_utf8_lossy(keyword).into_owned(),
+                        String::from_utf8_lossy(value).into_owned(),
+                    );
+                }
+            }
+            b"IEND" => break,
+            _ => {}
+        }
+        // Length, type, data and checksum
+        offset += 12 + length;
+    }
+    chunks
+}
+
+#[test]
+fn test_thumbnail_names() {
+    // The example of the thumbnail spec
+    let uri = file_uri(Path::new("/home/jens/photos/me.png"));
+    assert_eq!(uri, "file:///home/jens/photos/me.png");
+    assert_eq!(thumbnail_name(&uri), "c6ee772d9e49320e97ec29a7eb5b1697.png");
+    assert_eq!(
+        file_uri(Path::new("/tmp/Scan 1 (final).pdf")),
+        "file:///tmp/Scan%201%20%28final%29.pdf"
+    );
+
+    let mut png = Vec::new();
+    DynamicImage::new_rgba8(2, 2)
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .unwrap();
+    let png = with_text_chunks(png, &[("Thumb::MTime", "1700000000"), ("Software", "x")]).unwrap();
+    let chunks = text_chunks(&png);
+    assert_eq!(
+        chunks.get("Thumb::MTime").map(String::as_str),
+        Some("1700000000")
+    );
+    assert_eq!(chunks.len(), 2);
+    // The chunks keep the png readable
+    assert!(image::load_from_memory_with_format(&png, ImageFormat::Png).is_ok());
+    assert_eq!(kind(Path::new("/tmp/Scan.PDF")), Some(SourceKind::Pdf));
+    assert_eq!(kind(Path::new("/tmp/notes.txt")), None);
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{fs, io::ErrorKind, path::Path};
+
+use crate::{
+    sherlock_error,
+    utils::errors::{SherlockError, SherlockErrorType},
+};
+
+/// Data the user built up over time, like launch counts. Unlike caches, it cannot be
+/// regenerated, so it is kept in a [`StateFile`] instead of the
+/// [`BinaryCache`](super::cache::BinaryCache), which drops its files whenever
+/// [`SCHEMA_VERSION`](super::cache::SCHEMA_VERSION) changes.
+pub trait UserState: Serialize + DeserializeOwned {
+    /// Has to be bumped whenever the layout of the type changes, along with a step in
+    /// [`Self::migrate`] that converts the previous layout
+    const VERSION: u32;
+    /// Converts the data of a file written with an older version
+    fn migrate(version: u32, data: serde_json::Value) -> Result<Self, String>;
+}
+
+/// # State Layout
+/// A JSON document holding the data along with the version of their layout:
+/// `{ "version": 1, "data": ... }`. Files of older versions are migrated once they are read,
+/// while files written by a newer release or damaged ones are reported as errors and left as
+/// they are.
+pub struct StateFile;
+impl StateFile {
+    /// Reads the state, `None` if the file does not exist yet
+    pub fn read<T: UserState, P: AsRef<Path>>(path: P) -> Result<Option<T>, SherlockError> {
+        let path = path.as_ref();
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(sherlock_error!(
+                    SherlockErrorType::FileReadError(path.to_path_buf()),
+                    e.to_string()
+                ));
+            }
+        };
+        Self::decode(&bytes).map(Some).map_err(|reason| {
+            sherlock_error!(
+                SherlockErrorType::FileParseError(path.to_path_buf()),
+                reason
+            )
+        })
+    }
+
+    pub fn write<T: UserState, P: AsRef<Path>>(path: P, data: &T) -> Result<(), SherlockError> {
+        let path = path.as_ref();
+        let bytes = serde_json::to_vec(&Stored {
+            version: T::VERSION,
+            data,
+        })
+        .map_err(|e| sherlock_error!(SherlockErrorType::SerializationError, e.to_string()))?;
+
+        // Replaced atomically, so a crash never leaves half of the state behind
+        let tmp = path.with_extension(format!("tmp-{}", std::process::id()));
+        fs::write(&tmp, bytes)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&tmp);
+                sherlock_error!(
+                    SherlockErrorType::FileWriteError(path.to_path_buf()),
+                    e.to_string()
+                )
+            })
+    }
+
+    fn decode<T: UserState>(bytes: &[u8]) -> Result<T, String> {
+        let stored: Stored<serde_json::Value> =
+            serde_json::from_slice(bytes).map_err(|e| e.to_string())?;
+        match stored.version {
+            version if version == T::VERSION => {
+                serde_json::from_value(stored.data).map_err(|e| e.to_string())
+            }
+            version if version < T::VERSION => T::migrate(version, stored.data),
+            version => Err(format!(
+                "version {version} was written by a newer release, this one reads up to {}",
+                T::VERSION
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Stored<T> {
+    version: u32,
+    data: T,
+}
+
+#[test]
+fn test_state_file() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Pins {
+        keys: Vec<String>,
+    }
+    impl UserState for Pins {
+        const VERSION: u32 = 2;
+        fn migrate(version: u32, data: serde_json::Value) -> Result<Self, String> {
+            match version {
+                // The first version was a plain list
+                1 => Ok(Self {
+                    keys: serde_json::from_value(data).map_err(|e| e.to_string())?,
+                }),
+                _ => Err(format!("unknown version {version}")),
+            }
+        }
+    }
+
+    let path = std::env::temp_dir().join(format!("sherlock-{}-state.json", std::process::id()));
+    assert_eq!(StateFile::read::<Pins, _>(&path).unwrap(), None);
+
+    fs::write(&path, r#"{ "version": 1, "data": ["kitty"] }"#).unwrap();
+    let pins: Pins = StateFile::read(&path).unwrap().unwrap();
+    assert_eq!(pins.keys, vec!["kitty"]);
+    StateFile::write(&path, &pins).unwrap();
+    assert_eq!(StateFile::read::<Pins, _>(&path).unwrap(), Some(pins));
+
+    // Newer files are neither read nor removed
+    fs::write(&path, r#"{ "version": 3, "data": {} }"#).unwrap();
+    assert!(StateFile::read::<Pins, _>(&path).is_err());
+    assert!(path.exists());
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,91 @@
+use std::fmt::Debug;
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long blocking watchers wait for an event before they check whether they were stopped
+pub const STOP_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tells the threads and tasks of a watcher to end, which check [`Self::is_stopped`] regularly
+#[derive(Clone, Default)]
+pub struct StopToken(Arc<AtomicBool>);
+impl StopToken {
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+impl Debug for StopToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("StopToken")
+            .field(&self.is_stopped())
+            .finish()
+    }
+}
+
+/// The watchers a launcher started for its tiles. Loading the launcher again stops the previous
+/// ones, and they are stopped as well once a config reload replaced the launcher. Clones start
+/// without watchers, since they belong to a launcher of their own.
+#[derive(Default)]
+pub struct Watchers(Mutex<Option<StopToken>>);
+impl Watchers {
+    /// Stops the watchers of the previous load and returns the token for the new ones
+    pub fn start(&self) -> StopToken {
+        let token = StopToken::default();
+        let previous = self.0.lock().unwrap().replace(token.clone());
+        if let Some(previous) = previous {
+            previous.stop();
+        }
+        token
+    }
+    pub fn stop(&self) {
+        let current = self.0.lock().unwrap().take();
+        if let Some(current) = current {
+            current.stop();
+        }
+    }
+}
+impl Clone for Watchers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+impl Debug for Watchers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Watchers")
+            .field(&*self.0.lock().unwrap())
+            .finish()
+    }
+}
+
+/// Waits until the file descriptor is readable or the timeout passed. Returns whether it is
+/// readable.
+pub fn wait_readable(fd: &impl AsRawFd, timeout: Duration) -> bool {
+    let fd: RawFd = fd.as_raw_fd();
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+    // SAFETY: `pollfd` is a valid array of one entry for the duration of the call
+    unsafe { libc::poll(&mut pollfd, 1, timeout) > 0 }
+}
+
+#[test]
+fn test_watchers() {
+    let watchers = Watchers::default();
+    let first = watchers.start();
+    // Loading again stops the previous watchers
+    let second = watchers.start();
+    assert!(first.is_stopped() && !second.is_stopped());
+
+    // Clones belong to other launchers
+    assert!(!watchers.clone().start().is_stopped());
+    assert!(!second.is_stopped());
+    watchers.stop();
+    assert!(second.is_stopped());
+}
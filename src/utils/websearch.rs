@@ -1,64 +1,312 @@
-use std::collections::HashMap;
-
 use gpui::SharedString;
+use once_cell::sync::Lazy;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
-use super::command_launch::spawn_detached;
-use crate::utils::{
-    config::{ConfigGuard, ConstantDefaults},
-    errors::SherlockError,
+use super::command_launch::{FieldCodes, spawn_detached, split_as_command, strip_field_codes};
+use crate::{
+    sherlock_error,
+    utils::{
+        config::{ConfigGuard, ConstantDefaults},
+        errors::{SherlockError, SherlockErrorType},
+        favicon::{cached_favicon, url_host},
+    },
 };
 
+/// Placeholders of url templates, see [`expand_url_template`]
+static URL_PLACEHOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{(keyword_raw|keyword|variable:([^}]*))\}").unwrap());
+
+/// Browser profile and Firefox container a search is opened in
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BrowserContext<'a> {
+    pub profile: Option<&'a str>,
+    pub container: Option<&'a str>,
+}
+
 pub fn websearch(
-    mut engine: &str,
+    engine: &str,
     query: &str,
     browser: Option<&str>,
+    context: BrowserContext,
     variables: &[(SharedString, SharedString)],
 ) -> Result<(), SherlockError> {
-    if is_url(query) {
-        engine = "plain";
+    let bang = {
+        let c = ConfigGuard::read()?;
+        resolve_bang(query, &c.bangs)
+    };
+    let (engine, query) = match &bang {
+        Some((engine, query)) => (engine.as_str(), query.as_str()),
+        None if is_url(query) => ("plain", query),
+        None => (engine, query),
+    };
+    let url_template = engine_url(engine);
+
+    let mut browser = match browser {
+        Some(b) => b.to_string(),
+        None => default_browser()?,
+    };
+
+    let mut url = expand_url_template(url_template, query, variables);
+    if let Some(container) = context.container {
+        url = in_container(&url, container);
     }
-    let engines: HashMap<&str, &str> = HashMap::from([
-        ("google", "https://www.google.com/search?q={keyword}"),
-        ("bing", "https://www.bing.com/search?q={keyword}"),
-        ("duckduckgo", "https://duckduckgo.com/?q={keyword}"),
-        ("yahoo", "https://search.yahoo.com/search?p={keyword}"),
-        ("baidu", "https://www.baidu.com/s?wd={keyword}"),
-        ("yandex", "https://yandex.com/search/?text={keyword}"),
-        ("ask", "https://www.ask.com/web?q={keyword}"),
-        ("ecosia", "https://www.ecosia.org/search?q={keyword}"),
-        ("qwant", "https://www.qwant.com/?q={keyword}"),
-        (
-            "startpage",
-            "https://www.startpage.com/sp/search?q={keyword}",
-        ),
-        ("plain", "{keyword}"),
-    ]);
-    let url_template = if let Some(url) = engines.get(engine) {
-        url
+    if let Some(profile) = context.profile {
+        browser = with_profile(&browser, profile);
+    }
+    let has_target_code = ["%u", "%U", "%f", "%F"]
+        .iter()
+        .any(|code| browser.contains(code));
+    // Browsers with a field code get the url in its place. Appended urls are left alone, as
+    // they might be percent-encoded.
+    if !has_target_code {
+        browser.push_str(&format!(r#" "{}""#, url));
+        return spawn_detached(&browser, query, variables, None);
+    }
+
+    let codes = FieldCodes {
+        target: Some(&url),
+        ..Default::default()
+    };
+    spawn_detached(&browser, query, variables, Some(&codes))
+}
+
+fn default_browser() -> Result<String, SherlockError> {
+    let c = ConfigGuard::read()?;
+    c.default_apps
+        .browser
+        .clone()
+        .map_or_else(ConstantDefaults::browser, Ok)
+}
+
+/// Whether the program of a browser command is Mozilla based. Everything else is assumed to be
+/// Chromium based.
+fn is_mozilla(program: &str) -> bool {
+    let name = program.rsplit('/').next().unwrap_or(program).to_lowercase();
+    ["firefox", "zen", "librewolf", "waterfox", "floorp"]
+        .iter()
+        .any(|mozilla| name.contains(mozilla))
+}
+
+/// Adds the flag that selects the profile to a browser command. Mozilla based browsers take
+/// `-P`, everything else is assumed to be Chromium based.
+fn with_profile(browser: &str, profile: &str) -> String {
+    let (program, args) = browser.split_once(' ').unwrap_or((browser, ""));
+    let flag = if is_mozilla(program) {
+        format!(r#"-P "{profile}""#)
     } else {
-        engine
+        format!(r#"--profile-directory="{profile}""#)
     };
+    format!("{program} {flag} {args}").trim_end().to_string()
+}
 
-    let mut browser = match browser {
+/// Wraps the url so that Firefox opens it in the given container tab. Requires the "Open
+/// external links in a container" extension, which handles the `ext+container:` scheme.
+fn in_container(url: &str, container: &str) -> String {
+    let encode = |value: &str| utf8_percent_encode(value, URL_COMPONENT).to_string();
+    format!(
+        "ext+container:name={}&url={}",
+        encode(container),
+        encode(url)
+    )
+}
+
+/// Installs the url as web app by writing a desktop entry that opens it in a window of its own.
+/// Chromium based browsers get an app window through `--app`, other browsers a new window.
+pub fn install_web_app(query: &str, browser: Option<&str>) -> Result<PathBuf, SherlockError> {
+    let query = query.trim();
+    let url = if query.contains("://") {
+        query.to_string()
+    } else {
+        format!("https://{query}")
+    };
+    let host = url_host(&url).filter(|_| is_url(query)).ok_or_else(|| {
+        sherlock_error!(
+            SherlockErrorType::CommandExecutionError(String::from("install web app")),
+            format!(r#""{query}" is not a url"#)
+        )
+    })?;
+    let browser = match browser {
         Some(b) => b.to_string(),
-        None => {
-            let c = ConfigGuard::read()?;
-            c.default_apps
-                .browser
-                .clone()
-                .unwrap_or(ConstantDefaults::browser()?)
-        }
+        None => default_browser()?,
     };
+    let icon = cached_favicon(&host)
+        .flatten()
+        .map(|icon| icon.to_string_lossy().into_owned());
+    let entry = web_app_entry(&url, &host, &browser, icon.as_deref());
 
-    let url = url_template.replace("{keyword}", &query.replace(" ", "+"));
-    let command = if browser.contains("%u") {
-        browser.replace("%u", &format!(r#" "{}""#, url))
+    let dir = xdg::BaseDirectories::new()
+        .get_data_home()
+        .map(|data| data.join("applications"))
+        .ok_or_else(|| {
+            sherlock_error!(
+                SherlockErrorType::DirReadError(String::from("applications")),
+                "Could not find data directory"
+            )
+        })?;
+    std::fs::create_dir_all(&dir).map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::DirCreateError(dir.display().to_string()),
+            e.to_string()
+        )
+    })?;
+    let path = dir.join(format!("sherlock-webapp-{host}.desktop"));
+    std::fs::write(&path, entry).map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::FileWriteError(path.clone()),
+            e.to_string()
+        )
+    })?;
+    Ok(path)
+}
+
+/// Desktop entry of a web app, named after the host of the url
+fn web_app_entry(url: &str, host: &str, browser: &str, icon: Option<&str>) -> String {
+    // Field codes of the configured browser would otherwise be filled with nothing
+    let mut args = strip_field_codes(split_as_command(browser));
+    if args.first().is_some_and(|program| is_mozilla(program)) {
+        args.extend([String::from("--new-window"), url.to_string()]);
     } else {
-        browser.push_str(&format!(r#" "{}""#, url));
-        browser
+        args.push(format!("--app={url}"));
+    }
+    let exec: Vec<String> = args.iter().map(|arg| exec_arg(arg)).collect();
+    let exec = exec.join(" ");
+    format!(
+        "[Desktop Entry]\n\
+        Type=Application\n\
+        Name={host}\n\
+        Comment=Web app for {host}\n\
+        Exec={exec}\n\
+        Icon={}\n\
+        Categories=Network;WebBrowser;\n\
+        Terminal=false\n",
+        icon.unwrap_or("web-browser")
+    )
+}
+
+/// Quotes an argument of an `Exec` key as the Desktop Entry spec asks. Arguments with reserved
+/// characters are put in double quotes, in which `"`, `` ` ``, `$` and `\` are escaped, and `%`
+/// is doubled so it is not read as a field code. Backslashes are escaped once more, as the key is
+/// a string value.
+fn exec_arg(arg: &str) -> String {
+    const RESERVED: &[char] = &[
+        ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(',
+        ')', '`',
+    ];
+    let arg = arg.replace('%', "%%");
+    let quoted = if arg.contains(RESERVED) {
+        let mut quoted = String::from('"');
+        for c in arg.chars() {
+            if matches!(c, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    } else {
+        arg
+    };
+    quoted
+        .replace('\\', r"\\")
+        .replace('\n', r"\n")
+        .replace('\t', r"\t")
+}
+
+/// Url template of a search engine. Unknown engines are used as templates themselves.
+pub fn engine_url(engine: &str) -> &str {
+    match engine {
+        "google" => "https://www.google.com/search?q={keyword}",
+        "bing" => "https://www.bing.com/search?q={keyword}",
+        "duckduckgo" => "https://duckduckgo.com/?q={keyword}",
+        "yahoo" => "https://search.yahoo.com/search?p={keyword}",
+        "baidu" => "https://www.baidu.com/s?wd={keyword}",
+        "yandex" => "https://yandex.com/search/?text={keyword}",
+        "ask" => "https://www.ask.com/web?q={keyword}",
+        "ecosia" => "https://www.ecosia.org/search?q={keyword}",
+        "qwant" => "https://www.qwant.com/?q={keyword}",
+        "startpage" => "https://www.startpage.com/sp/search?q={keyword}",
+        "plain" => "{keyword_raw}",
+        _ => engine,
+    }
+}
+
+/// Characters that are left as they are when a value is inserted into a url
+const URL_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Fills in the placeholders of a url template:
+/// - **`{keyword}`:** the percent-encoded query
+/// - **`{keyword_raw}`:** the query as it is, e.g. for templates that take a whole url
+/// - **`{variable:name}`:** the percent-encoded content of the variable bar `name`
+///
+/// Unknown placeholders are kept.
+pub fn expand_url_template(
+    template: &str,
+    query: &str,
+    variables: &[(SharedString, SharedString)],
+) -> String {
+    let encode = |value: &str| utf8_percent_encode(value, URL_COMPONENT).to_string();
+    URL_PLACEHOLDER
+        .replace_all(template, |caps: &Captures| match &caps[1] {
+            "keyword_raw" => query.to_string(),
+            "keyword" => encode(query),
+            _ => variables
+                .iter()
+                .find(|(name, _)| Some(name.as_ref()) == caps.get(2).map(|m| m.as_str()))
+                .map(|(_, value)| encode(value))
+                .unwrap_or_else(|| caps[0].to_string()),
+        })
+        .into_owned()
+}
+
+/// Engines of the built-in bangs, e.g. `!yt lofi` searches YouTube for `lofi`
+fn builtin_bang(bang: &str) -> Option<&'static str> {
+    let engine = match bang {
+        "g" | "google" => "google",
+        "b" | "bing" => "bing",
+        "d" | "ddg" => "duckduckgo",
+        "sp" => "startpage",
+        "eco" => "ecosia",
+        "yt" => "https://www.youtube.com/results?search_query={keyword}",
+        "w" | "wiki" => "https://en.wikipedia.org/w/index.php?search={keyword}",
+        "aw" => "https://wiki.archlinux.org/index.php?search={keyword}",
+        "aur" => "https://aur.archlinux.org/packages?K={keyword}",
+        "gh" => "https://github.com/search?q={keyword}",
+        "so" => "https://stackoverflow.com/search?q={keyword}",
+        "r" => "https://www.reddit.com/search/?q={keyword}",
+        "crates" => "https://crates.io/search?q={keyword}",
+        "rs" => "https://docs.rs/releases/search?query={keyword}",
+        "maps" => "https://www.google.com/maps/search/{keyword}",
+        _ => return None,
+    };
+    Some(engine)
+}
+
+/// Splits a bang off the start or end of the query. Returns the engine it stands for together
+/// with the remaining query. Bangs of the config take precedence over the built-in ones.
+pub fn resolve_bang(query: &str, bangs: &HashMap<String, String>) -> Option<(String, String)> {
+    let query = query.trim();
+    let (bang, rest) = match query.split_once(char::is_whitespace) {
+        Some((first, rest)) if first.starts_with('!') => (first, rest),
+        _ => match query.rsplit_once(char::is_whitespace) {
+            Some((rest, last)) if last.starts_with('!') => (last, rest),
+            _ => (query, ""),
+        },
     };
+    let name = bang.strip_prefix('!').filter(|name| !name.is_empty())?;
+    let name = name.to_lowercase();
 
-    spawn_detached(&command, query, variables)
+    let engine = bangs
+        .get(&name)
+        .map(String::as_str)
+        .or_else(|| builtin_bang(&name))?;
+    Some((engine.to_string(), rest.trim().to_string()))
 }
 
 fn is_url(input: &str) -> bool {
@@ -131,6 +379,117 @@ fn is_url(input: &str) -> bool {
     false
 }
 
+#[test]
+fn test_expand_url_template() {
+    let variables = [(SharedString::from("lang"), SharedString::from("de & en"))];
+    assert_eq!(
+        expand_url_template(engine_url("google"), "c++ lifetimes?", &[]),
+        "https://www.google.com/search?q=c%2B%2B%20lifetimes%3F"
+    );
+    assert_eq!(
+        expand_url_template(
+            "https://example.org/{variable:lang}/search?q={keyword}&v={variable:missing}",
+            "ä",
+            &variables
+        ),
+        "https://example.org/de%20%26%20en/search?q=%C3%A4&v={variable:missing}"
+    );
+    assert_eq!(
+        expand_url_template(engine_url("plain"), "https://docs.rs/?q=a b", &[]),
+        "https://docs.rs/?q=a b"
+    );
+}
+
+#[test]
+fn test_browser_context() {
+    assert_eq!(
+        with_profile("/usr/lib/firefox/firefox %u", "work"),
+        r#"/usr/lib/firefox/firefox -P "work" %u"#
+    );
+    assert_eq!(
+        with_profile("chromium", "Profile 1"),
+        r#"chromium --profile-directory="Profile 1""#
+    );
+    assert_eq!(
+        in_container("https://example.org/?q=a", "Work"),
+        "ext+container:name=Work&url=https%3A%2F%2Fexample.org%2F%3Fq%3Da"
+    );
+}
+
+#[test]
+fn test_web_app_entry() {
+    use crate::loader::application_loader::unescape_string;
+
+    let entry = web_app_entry(
+        "https://music.youtube.com",
+        "music.youtube.com",
+        "chromium %U",
+        None,
+    );
+    assert!(entry.starts_with("[Desktop Entry]\n"));
+    assert!(entry.contains("Name=music.youtube.com\n"));
+    assert!(entry.contains("Exec=chromium --app=https://music.youtube.com\n"));
+    assert!(entry.contains("Icon=web-browser\n"));
+
+    let entry = web_app_entry(
+        "https://docs.rs/?q=a%20b",
+        "docs.rs",
+        "firefox",
+        Some("/tmp/docs.png"),
+    );
+    assert!(entry.contains(r#"Exec=firefox --new-window "https://docs.rs/?q=a%%20b""#));
+    assert!(entry.contains("Icon=/tmp/docs.png\n"));
+
+    // Quoted arguments escape `$` and friends, whose backslashes are escaped for the string
+    assert_eq!(
+        exec_arg(r#"https://example.org/?a=$b&c="d"\e"#),
+        r#""https://example.org/?a=\\$b&c=\\"d\\"\\\\e""#
+    );
+    // The loader undoes the string escapes, after which the arguments split as they were
+    let exec = unescape_string(&format!("firefox {}", exec_arg("https://x.org/?a=$b c")));
+    assert_eq!(
+        split_as_command(&exec),
+        vec!["firefox", "https://x.org/?a=$b c"]
+    );
+}
+
+#[test]
+fn test_resolve_bang() {
+    let bangs = HashMap::from([(
+        String::from("nix"),
+        String::from("https://search.nixos.org/packages?query={keyword}"),
+    )]);
+    let resolve = |query: &str| resolve_bang(query, &bangs);
+
+    assert_eq!(
+        resolve("!g rust lifetimes"),
+        Some((String::from("google"), String::from("rust lifetimes")))
+    );
+    assert_eq!(
+        resolve("lofi !YT"),
+        Some((
+            String::from("https://www.youtube.com/results?search_query={keyword}"),
+            String::from("lofi")
+        ))
+    );
+    assert_eq!(
+        resolve("!nix ripgrep").map(|(engine, _)| engine),
+        Some(String::from(
+            "https://search.nixos.org/packages?query={keyword}"
+        ))
+    );
+    assert_eq!(
+        resolve("!aw"),
+        Some((
+            String::from("https://wiki.archlinux.org/index.php?search={keyword}"),
+            String::new()
+        ))
+    );
+    assert_eq!(resolve("!unknown query"), None);
+    assert_eq!(resolve("what is 5!"), None);
+    assert_eq!(resolve("rust lifetimes"), None);
+}
+
 #[test]
 fn test_url_detector() {
     assert!(is_url("google.com"));
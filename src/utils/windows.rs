@@ -0,0 +1,235 @@
+use serde_json::Value;
+use std::process::Command;
+
+use crate::{
+    sherlock_error,
+    utils::errors::{SherlockError, SherlockErrorType},
+};
+
+/// A toplevel window as reported by the compositor
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpenWindow {
+    /// Compositor specific handle used to focus the window
+    pub id: String,
+    /// Wayland `app_id` or, for XWayland clients, the `WM_CLASS`
+    pub app_id: String,
+}
+
+/// Compositors whose IPC exposes the open windows
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Compositor {
+    Hyprland,
+    Sway,
+    Niri,
+}
+impl Compositor {
+    fn detect() -> Option<Self> {
+        let is_set = |var: &str| std::env::var_os(var).is_some();
+        if is_set("HYPRLAND_INSTANCE_SIGNATURE") {
+            Some(Self::Hyprland)
+        } else if is_set("NIRI_SOCKET") {
+            Some(Self::Niri)
+        } else if is_set("SWAYSOCK") {
+            Some(Self::Sway)
+        } else {
+            None
+        }
+    }
+
+    fn list_command(self) -> Command {
+        let (program, args): (&str, &[&str]) = match self {
+            Self::Hyprland => ("hyprctl", &["clients", "-j"]),
+            Self::Sway => ("swaymsg", &["-t", "get_tree", "-r"]),
+            Self::Niri => ("niri", &["msg", "--json", "windows"]),
+        };
+        let mut command = Command::new(program);
+        command.args(args);
+        command
+    }
+
+    fn focus_command(self, window: &OpenWindow) -> Command {
+        let (program, args) = match self {
+            Self::Hyprland => (
+                "hyprctl",
+                vec![
+                    "dispatch".to_string(),
+                    "focuswindow".to_string(),
+                    format!("address:{}", window.id),
+                ],
+            ),
+            Self::Sway => (
+                "swaymsg",
+                vec![format!("[con_id={}]", window.id), "focus".to_string()],
+            ),
+            Self::Niri => (
+                "niri",
+                ["msg", "action", "focus-window", "--id", &window.id]
+                    .map(str::to_string)
+                    .to_vec(),
+            ),
+        };
+        let mut command = Command::new(program);
+        command.args(args);
+        command
+    }
+
+    fn parse(self, json: &Value) -> Vec<OpenWindow> {
+        let mut windows = Vec::new();
+        match self {
+            Self::Hyprland => {
+                windows.extend(json.as_array().into_iter().flatten().filter_map(|client| {
+                    Some(OpenWindow {
+                        id: client["address"].as_str()?.to_string(),
+                        app_id: client["class"].as_str()?.to_string(),
+                    })
+                }))
+            }
+            Self::Niri => {
+                windows.extend(json.as_array().into_iter().flatten().filter_map(|window| {
+                    Some(OpenWindow {
+                        id: window["id"].as_u64()?.to_string(),
+                        app_id: window["app_id"].as_str()?.to_string(),
+                    })
+                }))
+            }
+            Self::Sway => collect_sway_windows(json, &mut windows),
+        }
+        windows
+    }
+}
+
+/// Walks the sway layout tree, windows are the nodes carrying an `app_id` or `window_properties`
+fn collect_sway_windows(node: &Value, windows: &mut Vec<OpenWindow>) {
+    let app_id = node["app_id"]
+        .as_str()
+        .or_else(|| node["window_properties"]["class"].as_str());
+    if let (Some(id), Some(app_id)) = (node["id"].as_u64(), app_id) {
+        windows.push(OpenWindow {
+            id: id.to_string(),
+            app_id: app_id.to_string(),
+        });
+    }
+    for key in ["nodes", "floating_nodes"] {
+        for child in node[key].as_array().into_iter().flatten() {
+            collect_sway_windows(child, windows);
+        }
+    }
+}
+
+/// Lists the open windows of the running compositor. Compositors without a supported IPC yield
+/// no windows, in which case applications are always launched anew.
+pub fn open_windows() -> Vec<OpenWindow> {
+    let Some(compositor) = Compositor::detect() else {
+        return Vec::new();
+    };
+    compositor
+        .list_command()
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| serde_json::from_slice::<Value>(&out.stdout).ok())
+        .map(|json| compositor.parse(&json))
+        .unwrap_or_default()
+}
+
+/// Position of the pointer on the focused output, only Hyprland exposes it
+pub fn cursor_position() -> Option<(f32, f32)> {
+    if Compositor::detect()? != Compositor::Hyprland {
+        return None;
+    }
+    let query = |args: &[&str]| {
+        Command::new("hyprctl")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .and_then(|out| serde_json::from_slice::<Value>(&out.stdout).ok())
+    };
+    let cursor = query(&["cursorpos", "-j"])?;
+    let monitors = query(&["monitors", "-j"])?;
+    cursor_on_monitor(&cursor, &monitors)
+}
+
+/// The global position of `hyprctl cursorpos` relative to the focused monitor
+fn cursor_on_monitor(cursor: &Value, monitors: &Value) -> Option<(f32, f32)> {
+    let monitor = monitors
+        .as_array()?
+        .iter()
+        .find(|monitor| monitor["focused"].as_bool() == Some(true))?;
+    let x = cursor["x"].as_f64()? - monitor["x"].as_f64().unwrap_or(0.0);
+    let y = cursor["y"].as_f64()? - monitor["y"].as_f64().unwrap_or(0.0);
+    Some((x as f32, y as f32))
+}
+
+/// Finds the window of an application by its `StartupWMClass` or desktop file id
+pub fn find_window<'a>(windows: &'a [OpenWindow], class: &str) -> Option<&'a OpenWindow> {
+    windows
+        .iter()
+        .find(|window| window.app_id.eq_ignore_ascii_case(class))
+}
+
+/// Asks the compositor to focus the window
+pub fn focus_window(window: &OpenWindow) -> Result<(), SherlockError> {
+    let compositor = Compositor::detect().ok_or_else(|| {
+        sherlock_error!(
+            SherlockErrorType::CommandExecutionError(String::from("focus window")),
+            "No supported compositor detected"
+        )
+    })?;
+    let mut command = compositor.focus_command(window);
+    let status = command.status().map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::CommandExecutionError(format!("{command:?}")),
+            e.to_string()
+        )
+    })?;
+    if !status.success() {
+        return Err(sherlock_error!(
+            SherlockErrorType::CommandExecutionError(format!("{command:?}")),
+            format!("Exited with {status}")
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_windows() {
+    let tree = serde_json::json!({
+        "id": 1,
+        "nodes": [{
+            "id": 4,
+            "nodes": [
+                { "id": 7, "app_id": "org.gnome.Nautilus", "nodes": [] },
+                { "id": 9, "app_id": null, "window_properties": { "class": "Steam" } }
+            ],
+            "floating_nodes": [{ "id": 12, "app_id": "kitty" }]
+        }]
+    });
+    let windows = Compositor::Sway.parse(&tree);
+    let ids: Vec<(&str, &str)> = windows
+        .iter()
+        .map(|w| (w.id.as_str(), w.app_id.as_str()))
+        .collect();
+    assert_eq!(
+        ids,
+        vec![("7", "org.gnome.Nautilus"), ("9", "Steam"), ("12", "kitty")]
+    );
+    assert_eq!(
+        find_window(&windows, "steam").map(|w| w.id.as_str()),
+        Some("9")
+    );
+
+    let clients = serde_json::json!([{ "address": "0x5632", "class": "firefox" }]);
+    let windows = Compositor::Hyprland.parse(&clients);
+    assert_eq!(windows[0].id, "0x5632");
+}
+
+#[test]
+fn test_cursor_on_monitor() {
+    let monitors = serde_json::json!([
+        { "x": 0, "y": 0, "focused": false },
+        { "x": 1920, "y": 0, "focused": true }
+    ]);
+    let cursor = serde_json::json!({ "x": 2420, "y": 300 });
+    assert_eq!(cursor_on_monitor(&cursor, &monitors), Some((500.0, 300.0)));
+}